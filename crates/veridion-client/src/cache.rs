@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::client::Verdict;
+use crate::models::TransferContext;
+
+/// What a decision depends on: destination, partner and data categories. Per-request fields
+/// (IPs, path, size, user agent) are left out so repeated calls to the same destination hit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    country: String,
+    partner: String,
+    categories: Vec<String>,
+}
+
+impl CacheKey {
+    pub fn for_context(ctx: &TransferContext) -> Self {
+        let mut categories: Vec<String> = ctx
+            .data_categories
+            .iter()
+            .flatten()
+            .map(|c| c.trim().to_lowercase())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        CacheKey {
+            country: ctx.destination_country_code.as_deref().unwrap_or("").trim().to_uppercase(),
+            partner: ctx.partner_name.as_deref().unwrap_or("").trim().to_lowercase(),
+            categories,
+        }
+    }
+}
+
+/// Bounded TTL cache of API verdicts.
+pub struct DecisionCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<CacheKey, (Verdict, Instant)>>,
+}
+
+impl DecisionCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        DecisionCache { ttl, max_entries, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_entries > 0
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Verdict> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(key)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(verdict, _)| verdict.clone())
+    }
+
+    pub fn insert(&self, key: CacheKey, verdict: Verdict) {
+        if !self.enabled() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries {
+            let ttl = self.ttl;
+            entries.retain(|_, (_, at)| at.elapsed() < ttl);
+        }
+        if entries.len() >= self.max_entries {
+            if let Some(oldest) = entries.iter().min_by_key(|(_, (_, at))| *at).map(|(k, _)| k.clone()) {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (verdict, Instant::now()));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::VerdictSource;
+    use crate::models::Decision;
+
+    fn verdict(decision: Decision) -> Verdict {
+        Verdict {
+            decision,
+            allowed: decision == Decision::ALLOW,
+            reason: "test".into(),
+            evidence_id: Some("ev-1".into()),
+            review_id: None,
+            source: VerdictSource::Api,
+        }
+    }
+
+    fn key(country: &str) -> CacheKey {
+        CacheKey::for_context(&TransferContext { destination_country_code: Some(country.into()), ..Default::default() })
+    }
+
+    #[test]
+    fn key_ignores_per_request_fields_and_normalises() {
+        let a = TransferContext {
+            destination_country_code: Some("us".into()),
+            partner_name: Some(" Stripe ".into()),
+            data_categories: Some(vec!["Email".into(), "email".into(), "payment_card".into()]),
+            dest_ip: Some("1.2.3.4".into()),
+            request_path: Some("/a".into()),
+            ..Default::default()
+        };
+        let b = TransferContext {
+            destination_country_code: Some("US".into()),
+            partner_name: Some("stripe".into()),
+            data_categories: Some(vec!["payment_card".into(), "email".into()]),
+            dest_ip: Some("5.6.7.8".into()),
+            request_path: Some("/b".into()),
+            ..Default::default()
+        };
+        assert_eq!(CacheKey::for_context(&a), CacheKey::for_context(&b));
+        assert_ne!(CacheKey::for_context(&a), key("US"));
+    }
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let cache = DecisionCache::new(Duration::from_millis(50), 10);
+        cache.insert(key("US"), verdict(Decision::ALLOW));
+        assert_eq!(cache.get(&key("US")).map(|v| v.decision), Some(Decision::ALLOW));
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(cache.get(&key("US")).is_none());
+    }
+
+    #[test]
+    fn zero_ttl_or_capacity_disables_the_cache() {
+        for cache in [DecisionCache::new(Duration::ZERO, 10), DecisionCache::new(Duration::from_secs(60), 0)] {
+            assert!(!cache.enabled());
+            cache.insert(key("US"), verdict(Decision::ALLOW));
+            assert!(cache.get(&key("US")).is_none());
+        }
+    }
+
+    #[test]
+    fn full_cache_evicts_the_oldest_entry() {
+        let cache = DecisionCache::new(Duration::from_secs(60), 2);
+        cache.insert(key("US"), verdict(Decision::ALLOW));
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert(key("CN"), verdict(Decision::BLOCK));
+        std::thread::sleep(Duration::from_millis(2));
+        cache.insert(key("IN"), verdict(Decision::REVIEW));
+        assert!(cache.get(&key("US")).is_none());
+        assert!(cache.get(&key("CN")).is_some());
+        assert!(cache.get(&key("IN")).is_some());
+    }
+}
@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use crate::cache::{CacheKey, DecisionCache};
+use crate::error::Error;
+use crate::models::{ApiErrorBody, Decision, EvaluateResponse, TransferContext};
+
+/// What to do when the API cannot be reached and no cached decision is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailMode {
+    /// Let the transfer through.
+    Open,
+    /// Refuse the transfer (default).
+    Closed,
+}
+
+/// How a REVIEW decision is enforced while the review is pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewAction {
+    Allow,
+    /// Default.
+    Deny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerdictSource {
+    Api,
+    Cache,
+    FailOpen,
+    FailClosed,
+}
+
+/// Enforcement outcome of a pre-transfer check.
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub decision: Decision,
+    /// Whether the caller should proceed, after applying the review action and fail mode.
+    pub allowed: bool,
+    pub reason: String,
+    /// Set only when this call wrote evidence; cached and fail-mode verdicts have none.
+    pub evidence_id: Option<String>,
+    pub review_id: Option<String>,
+    pub source: VerdictSource,
+}
+
+pub struct ShieldClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+    cache_ttl: Duration,
+    max_cache_entries: usize,
+    fail_mode: FailMode,
+    review_action: ReviewAction,
+    http: Option<reqwest::Client>,
+}
+
+impl ShieldClientBuilder {
+    /// Sent as `Authorization: Bearer <key>`.
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Per-call timeout for evaluate requests (default 2s).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long a decision is reused for the same destination, partner and categories (default 60s).
+    /// Cache hits do not write evidence; use `Duration::ZERO` to evaluate every transfer.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    pub fn max_cache_entries(mut self, max: usize) -> Self {
+        self.max_cache_entries = max;
+        self
+    }
+
+    pub fn fail_mode(mut self, mode: FailMode) -> Self {
+        self.fail_mode = mode;
+        self
+    }
+
+    pub fn review_action(mut self, action: ReviewAction) -> Self {
+        self.review_action = action;
+        self
+    }
+
+    /// Use an existing client for API calls instead of building one.
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    pub fn build(self) -> Result<ShieldClient, Error> {
+        let http = match self.http {
+            Some(http) => http,
+            None => reqwest::Client::builder().build()?,
+        };
+        Ok(ShieldClient {
+            http,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            api_key: self.api_key,
+            timeout: self.timeout,
+            cache: DecisionCache::new(self.cache_ttl, self.max_cache_entries),
+            fail_mode: self.fail_mode,
+            review_action: self.review_action,
+        })
+    }
+}
+
+/// Client for the Sovereign Shield evaluate API.
+pub struct ShieldClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    timeout: Duration,
+    cache: DecisionCache,
+    fail_mode: FailMode,
+    review_action: ReviewAction,
+}
+
+impl ShieldClient {
+    /// `base_url` is the API root, e.g. `https://shield.example.com`.
+    pub fn builder(base_url: impl Into<String>) -> ShieldClientBuilder {
+        ShieldClientBuilder {
+            base_url: base_url.into(),
+            api_key: None,
+            timeout: Duration::from_secs(2),
+            cache_ttl: Duration::from_secs(60),
+            max_cache_entries: 10_000,
+            fail_mode: FailMode::Closed,
+            review_action: ReviewAction::Deny,
+            http: None,
+        }
+    }
+
+    /// Evaluate one transfer and record its evidence. With an idempotency key, a retry
+    /// returns the original result instead of writing a second event.
+    pub async fn evaluate(
+        &self,
+        ctx: &TransferContext,
+        idempotency_key: Option<&str>,
+    ) -> Result<EvaluateResponse, Error> {
+        let mut req = self
+            .http
+            .post(format!("{}/api/v1/shield/evaluate", self.base_url))
+            .timeout(self.timeout)
+            .json(ctx);
+        if let Some(key) = &self.api_key {
+            req = req.bearer_auth(key);
+        }
+        if let Some(key) = idempotency_key {
+            req = req.header("Idempotency-Key", key);
+        }
+
+        let resp = req.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.json::<ApiErrorBody>().await.ok();
+            return Err(Error::Api {
+                status: status.as_u16(),
+                code: body.as_ref().map(|b| b.error.clone()).unwrap_or_default(),
+                message: body.map(|b| b.message).unwrap_or_else(|| status.to_string()),
+            });
+        }
+        Ok(resp.json::<EvaluateResponse>().await?)
+    }
+
+    fn allows(&self, decision: Decision) -> bool {
+        match decision {
+            Decision::ALLOW => true,
+            Decision::REVIEW => self.review_action == ReviewAction::Allow,
+            Decision::BLOCK => false,
+        }
+    }
+
+    /// Pre-transfer check: cached decision if fresh, otherwise the API, otherwise the fail mode.
+    /// Never returns an error; `Verdict::source` says how the verdict was reached.
+    pub async fn check(&self, ctx: &TransferContext) -> Verdict {
+        let key = CacheKey::for_context(ctx);
+        if let Some(mut cached) = self.cache.get(&key) {
+            cached.source = VerdictSource::Cache;
+            cached.evidence_id = None;
+            cached.review_id = None;
+            return cached;
+        }
+
+        match self.evaluate(ctx, None).await {
+            Ok(resp) => {
+                let verdict = Verdict {
+                    decision: resp.decision,
+                    allowed: self.allows(resp.decision),
+                    reason: resp.reason,
+                    evidence_id: Some(resp.evidence_id),
+                    review_id: resp.review_id,
+                    source: VerdictSource::Api,
+                };
+                self.cache.insert(key, verdict.clone());
+                verdict
+            }
+            Err(e) => {
+                let open = self.fail_mode == FailMode::Open;
+                Verdict {
+                    decision: if open { Decision::ALLOW } else { Decision::BLOCK },
+                    allowed: open,
+                    reason: format!("Shield API unavailable: {}", e),
+                    evidence_id: None,
+                    review_id: None,
+                    source: if open { VerdictSource::FailOpen } else { VerdictSource::FailClosed },
+                }
+            }
+        }
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minimal evaluate endpoint answering every request with `decision`; returns its URL and hit count.
+    async fn stub_api(decision: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read the headers, then the body they announce
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .and_then(|v| v.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if n == 0 || request.len() >= end + 4 + length {
+                            break;
+                        }
+                    } else if n == 0 {
+                        break;
+                    }
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                let body = format!(
+                    r#"{{"decision":"{}","reason":"stub","severity":"LOW","country_status":"eu_eea","evidence_id":"ev-{}","review_id":null,"timestamp":null}}"#,
+                    decision,
+                    counter.load(Ordering::SeqCst)
+                );
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, hits)
+    }
+
+    /// A local address nothing listens on.
+    async fn unreachable_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn ctx() -> TransferContext {
+        TransferContext {
+            destination_country_code: Some("DE".into()),
+            data_categories: Some(vec!["email".into()]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn api_verdict_is_cached_without_evidence() {
+        let (url, hits) = stub_api("ALLOW").await;
+        let client = ShieldClient::builder(url).build().unwrap();
+
+        let first = client.check(&ctx()).await;
+        assert_eq!(first.source, VerdictSource::Api);
+        assert!(first.allowed);
+        assert_eq!(first.evidence_id.as_deref(), Some("ev-1"));
+
+        let second = client.check(&ctx()).await;
+        assert_eq!(second.source, VerdictSource::Cache);
+        assert!(second.allowed);
+        assert!(second.evidence_id.is_none());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        client.clear_cache();
+        assert_eq!(client.check(&ctx()).await.source, VerdictSource::Api);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn zero_ttl_evaluates_every_transfer() {
+        let (url, hits) = stub_api("ALLOW").await;
+        let client = ShieldClient::builder(url).cache_ttl(Duration::ZERO).build().unwrap();
+        for _ in 0..3 {
+            assert_eq!(client.check(&ctx()).await.source, VerdictSource::Api);
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn review_is_denied_unless_review_action_allows() {
+        let (url, _) = stub_api("REVIEW").await;
+        let deny = ShieldClient::builder(url.clone()).build().unwrap();
+        assert!(!deny.check(&ctx()).await.allowed);
+        let allow = ShieldClient::builder(url).review_action(ReviewAction::Allow).build().unwrap();
+        assert!(allow.check(&ctx()).await.allowed);
+    }
+
+    #[tokio::test]
+    async fn unreachable_api_fails_closed_by_default() {
+        let client = ShieldClient::builder(unreachable_url().await).build().unwrap();
+        let verdict = client.check(&ctx()).await;
+        assert_eq!(verdict.source, VerdictSource::FailClosed);
+        assert_eq!(verdict.decision, Decision::BLOCK);
+        assert!(!verdict.allowed);
+    }
+
+    #[tokio::test]
+    async fn unreachable_api_fails_open_when_configured() {
+        let client = ShieldClient::builder(unreachable_url().await).fail_mode(FailMode::Open).build().unwrap();
+        let verdict = client.check(&ctx()).await;
+        assert_eq!(verdict.source, VerdictSource::FailOpen);
+        assert!(verdict.allowed);
+        // Fail-mode verdicts are not cached: the next call tries the API again
+        assert_eq!(client.check(&ctx()).await.source, VerdictSource::FailOpen);
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+use crate::models::Decision;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The API could not be reached or the response could not be read.
+    Http(reqwest::Error),
+    /// The API answered with a non-success status.
+    Api { status: u16, code: String, message: String },
+    /// A pre-transfer check refused the outbound request.
+    TransferDenied {
+        decision: Decision,
+        reason: String,
+        host: String,
+        evidence_id: Option<String>,
+        review_id: Option<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "Shield API request failed: {}", e),
+            Error::Api { status, code, message } => write!(f, "Shield API returned {} {}: {}", status, code, message),
+            Error::TransferDenied { decision, reason, host, .. } => {
+                write!(f, "Transfer to {} denied ({}): {}", host, decision, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
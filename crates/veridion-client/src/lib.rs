@@ -0,0 +1,26 @@
+//! Client for the Veridion Sovereign Shield API.
+//!
+//! `models` holds the request and response types shared with the API server and is always available.
+//! The `client` feature (default) adds [`ShieldClient`], with a TTL decision cache and fail-open /
+//! fail-closed handling; the `middleware` feature adds [`ShieldMiddleware`] for `reqwest-middleware`,
+//! which checks every outbound request by host before it is sent.
+
+pub mod models;
+
+#[cfg(feature = "client")]
+mod cache;
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+mod error;
+#[cfg(feature = "middleware")]
+mod middleware;
+
+pub use models::{Decision, EvaluateResponse, TransferContext, TransferDecision};
+
+#[cfg(feature = "client")]
+pub use client::{FailMode, ReviewAction, ShieldClient, ShieldClientBuilder, Verdict, VerdictSource};
+#[cfg(feature = "client")]
+pub use error::Error;
+#[cfg(feature = "middleware")]
+pub use middleware::{HostTarget, ShieldMiddleware, TransferHints};
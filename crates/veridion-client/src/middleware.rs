@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+use crate::client::ShieldClient;
+use crate::error::Error;
+use crate::models::TransferContext;
+
+/// Per-request overrides, attached with `RequestBuilder::with_extension(TransferHints { .. })`.
+#[derive(Debug, Clone, Default)]
+pub struct TransferHints {
+    pub destination_country_code: Option<String>,
+    pub partner_name: Option<String>,
+    pub data_categories: Option<Vec<String>>,
+    pub data_size: Option<u64>,
+}
+
+/// Where a host sends data: the country and, optionally, the partner the API should check SCCs for.
+#[derive(Debug, Clone)]
+pub struct HostTarget {
+    pub country: String,
+    pub partner: Option<String>,
+}
+
+/// reqwest-middleware layer that evaluates every outbound request by host before sending it.
+/// Denied requests fail with `Error::TransferDenied` wrapped in `reqwest_middleware::Error::Middleware`.
+pub struct ShieldMiddleware {
+    client: Arc<ShieldClient>,
+    hosts: HashMap<String, HostTarget>,
+    default_data_categories: Vec<String>,
+}
+
+impl ShieldMiddleware {
+    pub fn new(client: Arc<ShieldClient>) -> Self {
+        ShieldMiddleware { client, hosts: HashMap::new(), default_data_categories: Vec::new() }
+    }
+
+    /// Map a host (`api.example.com`) or wildcard (`*.example.com`) to a destination. Hosts that match
+    /// no pattern are left for the API to place from `dest_ip`: its host map, else the address the
+    /// host resolves to, else its handling of an unknown country.
+    pub fn host(mut self, pattern: impl Into<String>, country: impl Into<String>, partner: Option<String>) -> Self {
+        self.hosts.insert(
+            pattern.into().to_lowercase(),
+            HostTarget { country: country.into().to_uppercase(), partner },
+        );
+        self
+    }
+
+    /// Categories assumed for requests that do not carry `TransferHints`.
+    pub fn default_data_categories(mut self, categories: Vec<String>) -> Self {
+        self.default_data_categories = categories;
+        self
+    }
+
+    fn target_for(&self, host: &str) -> (Option<String>, Option<String>) {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let mut best: Option<(&str, &HostTarget)> = None;
+        for (pattern, target) in &self.hosts {
+            let matches = match pattern.strip_prefix("*.") {
+                Some(suffix) => host.ends_with(&format!(".{}", suffix)) || host == suffix,
+                None => host == *pattern,
+            };
+            // Prefer the most specific pattern
+            if matches && best.map(|(p, _)| pattern.len() > p.len()).unwrap_or(true) {
+                best = Some((pattern, target));
+            }
+        }
+        match best {
+            Some((_, t)) => (Some(t.country.clone()), t.partner.clone().or(Some(host))),
+            None => (None, Some(host)),
+        }
+    }
+
+    fn context_for(&self, req: &Request, hints: Option<&TransferHints>) -> TransferContext {
+        let host = req.url().host_str().unwrap_or_default();
+        let (country, partner) = self.target_for(host);
+        let hints = hints.cloned().unwrap_or_default();
+        let categories = hints
+            .data_categories
+            .or_else(|| (!self.default_data_categories.is_empty()).then(|| self.default_data_categories.clone()));
+
+        TransferContext {
+            destination_country_code: hints.destination_country_code.or(country),
+            data_categories: categories,
+            partner_name: hints.partner_name.or(partner),
+            dest_ip: Some(host.to_string()),
+            data_size: hints.data_size,
+            protocol: Some(req.url().scheme().to_string()),
+            user_agent: req
+                .headers()
+                .get(reqwest::header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+            request_path: Some(req.url().path().to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for ShieldMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let ctx = self.context_for(&req, extensions.get::<TransferHints>());
+        let verdict = self.client.check(&ctx).await;
+        if !verdict.allowed {
+            return Err(reqwest_middleware::Error::Middleware(anyhow::Error::new(Error::TransferDenied {
+                decision: verdict.decision,
+                reason: verdict.reason,
+                host: req.url().host_str().unwrap_or_default().to_string(),
+                evidence_id: verdict.evidence_id,
+                review_id: verdict.review_id,
+            })));
+        }
+        extensions.insert(verdict);
+        next.run(req, extensions).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn middleware() -> ShieldMiddleware {
+        let client = Arc::new(ShieldClient::builder("http://127.0.0.1:1").build().unwrap());
+        ShieldMiddleware::new(client)
+            .host("*.stripe.com", "us", Some("Stripe".into()))
+            .host("files.stripe.com", "ie", None)
+    }
+
+    #[test]
+    fn most_specific_pattern_wins() {
+        let m = middleware();
+        assert_eq!(m.target_for("api.stripe.com"), (Some("US".into()), Some("Stripe".into())));
+        assert_eq!(m.target_for("stripe.com."), (Some("US".into()), Some("Stripe".into())));
+        assert_eq!(m.target_for("FILES.stripe.com"), (Some("IE".into()), Some("files.stripe.com".into())));
+    }
+
+    #[test]
+    fn unmapped_hosts_are_left_for_the_api() {
+        let m = middleware();
+        assert_eq!(m.target_for("api.example.de"), (None, Some("api.example.de".into())));
+        let req = Request::new(reqwest::Method::POST, "https://api.example.de/v1/x".parse().unwrap());
+        let ctx = m.context_for(&req, None);
+        assert_eq!(ctx.destination_country_code, None);
+        assert_eq!(ctx.dest_ip.as_deref(), Some("api.example.de"));
+        assert_eq!(ctx.request_path.as_deref(), Some("/v1/x"));
+    }
+}
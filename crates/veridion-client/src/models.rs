@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a transfer evaluation. Variant names are the wire format.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Decision {
+    ALLOW,
+    BLOCK,
+    REVIEW,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Decision::ALLOW => write!(f, "ALLOW"),
+            Decision::BLOCK => write!(f, "BLOCK"),
+            Decision::REVIEW => write!(f, "REVIEW"),
+        }
+    }
+}
+
+/// Body of `POST /api/v1/shield/evaluate`. Fields are camelCase on the wire; snake_case is accepted too.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TransferContext {
+    #[serde(alias = "destination_country_code")]
+    pub destination_country_code: Option<String>,
+    #[serde(alias = "destination_country")]
+    pub destination_country: Option<String>,
+    #[serde(alias = "data_categories")]
+    pub data_categories: Option<Vec<String>>,
+    #[serde(alias = "partner_name")]
+    pub partner_name: Option<String>,
+    #[serde(alias = "source_ip")]
+    pub source_ip: Option<String>,
+    #[serde(alias = "dest_ip")]
+    pub dest_ip: Option<String>,
+    #[serde(alias = "data_size")]
+    pub data_size: Option<u64>,
+    pub protocol: Option<String>,
+    #[serde(alias = "user_agent")]
+    pub user_agent: Option<String>,
+    #[serde(alias = "request_path")]
+    pub request_path: Option<String>,
+    /// Service or system the data leaves from, e.g. the OTLP `service.name` of a traced caller.
+    #[serde(alias = "source_system")]
+    pub source_system: Option<String>,
+    /// Why the data is sent; checked against the purpose of the declared flow it matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+}
+
+/// The policy engine's verdict for one transfer, before evidence is recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TransferDecision {
+    pub decision: Decision,
+    pub reason: String,
+    pub severity: String,
+    pub articles: Vec<String>,
+    pub event_type: String,
+    pub country_status: String,
+}
+
+/// Response of `POST /api/v1/shield/evaluate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EvaluateResponse {
+    pub decision: Decision,
+    pub reason: String,
+    pub severity: String,
+    #[serde(default)]
+    pub articles: Vec<String>,
+    pub country_status: String,
+    pub evidence_id: String,
+    pub review_id: Option<String>,
+    /// True when an `Idempotency-Key` retry returned the original result.
+    #[serde(default)]
+    pub replayed: bool,
+    /// Signed transfer permit (compact JWS, EdDSA) for an ALLOW requested with `?permit=true`.
+    /// Verify it against `/api/v1/shield/permits/jwks.json` before sending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permit_expires_at: Option<String>,
+    /// `DECLARED` or `UNDECLARED_FLOW` once any flow is declared in the data-flow register.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow_status: Option<String>,
+    /// Declared flow covering this transfer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_flow_id: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+/// Error body returned by the API on failure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiErrorBody {
+    pub error: String,
+    pub message: String,
+}
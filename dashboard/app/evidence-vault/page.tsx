@@ -241,7 +241,7 @@ export default function EvidenceVaultPage() {
 
   function getEventDecision(e: EvidenceEvent): string {
     const et = (e.eventType || '').toUpperCase();
-    if (e.eventType === 'DATA_TRANSFER_BLOCKED' || e.verificationStatus === 'BLOCK' || et.includes('HUMAN_OVERSIGHT_REJECTED')) return 'BLOCKED';
+    if (e.eventType === 'DATA_TRANSFER_BLOCKED' || e.eventType === 'DATA_TRANSFER_SANCTIONS_BLOCKED' || e.verificationStatus === 'BLOCK' || et.includes('HUMAN_OVERSIGHT_REJECTED')) return 'BLOCKED';
     if (e.eventType === 'DATA_TRANSFER_REVIEW' || e.verificationStatus === 'REVIEW') return 'REVIEW';
     if (et.includes('HUMAN_OVERSIGHT_APPROVED')) return 'ALLOWED';
     return e.verificationStatus || e.payload?.decision || 'ALLOWED';
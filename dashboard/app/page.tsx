@@ -202,7 +202,8 @@ export default function SovereignShieldPage() {
   // BLOCKED (24H): policy blocks + human rejections (REGULUS: human REJECT = sealed block decision, GDPR Art. 30 / EU AI Act Art. 14)
   const blocked = last24HoursEvents.filter((e) => 
     e.verificationStatus === 'BLOCK' || e.severity === 'BLOCK' || 
-    e.eventType === 'DATA_TRANSFER_BLOCKED' || e.eventType === 'HUMAN_OVERSIGHT_REJECTED'
+    e.eventType === 'DATA_TRANSFER_BLOCKED' || e.eventType === 'DATA_TRANSFER_SANCTIONS_BLOCKED' ||
+    e.eventType === 'HUMAN_OVERSIGHT_REJECTED'
   ).length;
   const allowed = last24HoursEvents.filter((e) => 
     e.verificationStatus === 'ALLOW' || e.severity === 'ALLOW' ||
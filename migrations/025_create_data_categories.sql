@@ -0,0 +1,47 @@
+-- Data category taxonomy: canonical categories, synonyms and sensitivity tiers
+-- Used by Sovereign Shield to normalise data_categories and escalate Art. 9 / Art. 10 transfers
+
+CREATE TABLE IF NOT EXISTS data_categories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code VARCHAR(100) NOT NULL UNIQUE,
+    label VARCHAR(255) NOT NULL,
+    description TEXT,
+    synonyms TEXT[] NOT NULL DEFAULT '{}',
+    sensitivity_tier VARCHAR(20) NOT NULL DEFAULT 'standard'
+        CHECK (sensitivity_tier IN ('none', 'standard', 'high', 'special')),
+    special_category BOOLEAN NOT NULL DEFAULT FALSE,
+    criminal_data BOOLEAN NOT NULL DEFAULT FALSE,
+    anonymised BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_categories_synonyms ON data_categories USING GIN (synonyms);
+
+DROP TRIGGER IF EXISTS update_data_categories_updated_at ON data_categories;
+CREATE TRIGGER update_data_categories_updated_at
+    BEFORE UPDATE ON data_categories
+    FOR EACH ROW EXECUTE PROCEDURE update_updated_at_column();
+
+INSERT INTO data_categories (code, label, synonyms, sensitivity_tier, special_category, criminal_data, anonymised) VALUES
+    ('name', 'Name', '{full_name,first_name,last_name,surname,given_name}', 'standard', FALSE, FALSE, FALSE),
+    ('email', 'Email address', '{email_address,e_mail,contact_email,mail}', 'standard', FALSE, FALSE, FALSE),
+    ('phone', 'Phone number', '{phone_number,telephone,mobile,msisdn}', 'standard', FALSE, FALSE, FALSE),
+    ('postal_address', 'Postal address', '{address,home_address,street_address}', 'standard', FALSE, FALSE, FALSE),
+    ('online_identifier', 'Online identifier', '{ip,ip_address,cookie_id,device_id,user_id,customer_id}', 'standard', FALSE, FALSE, FALSE),
+    ('usage_data', 'Usage and behavioural data', '{behavioural,analytics,clickstream,telemetry}', 'standard', FALSE, FALSE, FALSE),
+    ('employment', 'Employment data', '{hr,employee_data,payroll}', 'standard', FALSE, FALSE, FALSE),
+    ('location', 'Location data', '{geolocation,gps,geo}', 'high', FALSE, FALSE, FALSE),
+    ('financial', 'Financial data', '{bank_account,iban,payment,payment_card,credit_card,transaction}', 'high', FALSE, FALSE, FALSE),
+    ('government_id', 'Government identifier', '{passport,national_id,ssn,tax_id,id_number}', 'high', FALSE, FALSE, FALSE),
+    ('health', 'Health data', '{medical,health_data,diagnosis}', 'special', TRUE, FALSE, FALSE),
+    ('genetic', 'Genetic data', '{dna,genome}', 'special', TRUE, FALSE, FALSE),
+    ('biometric', 'Biometric data', '{fingerprint,face_id,facial_recognition}', 'special', TRUE, FALSE, FALSE),
+    ('racial_ethnic_origin', 'Racial or ethnic origin', '{race,ethnicity}', 'special', TRUE, FALSE, FALSE),
+    ('political_opinions', 'Political opinions', '{political}', 'special', TRUE, FALSE, FALSE),
+    ('religious_beliefs', 'Religious or philosophical beliefs', '{religion,philosophical_beliefs}', 'special', TRUE, FALSE, FALSE),
+    ('trade_union_membership', 'Trade union membership', '{trade_union}', 'special', TRUE, FALSE, FALSE),
+    ('sex_life_orientation', 'Sex life or sexual orientation', '{sexual_orientation,sex_life}', 'special', TRUE, FALSE, FALSE),
+    ('criminal_records', 'Criminal convictions and offences', '{criminal,criminal_convictions,offences}', 'special', FALSE, TRUE, FALSE),
+    ('anonymised', 'Anonymised data', '{anonymized,aggregated,statistics,non_personal}', 'none', FALSE, FALSE, TRUE)
+ON CONFLICT (code) DO NOTHING;
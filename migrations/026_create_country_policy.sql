@@ -0,0 +1,44 @@
+-- Temporal country policy: scheduled classification changes (adequacy sunsets, invalidations)
+-- and the overrides they produce on top of the built-in classification in shield.rs
+
+CREATE TABLE IF NOT EXISTS country_policy_overrides (
+    country_code VARCHAR(2) PRIMARY KEY,
+    status VARCHAR(50) NOT NULL
+        CHECK (status IN ('eu_eea', 'adequate_protection', 'scc_required', 'blocked', 'unknown')),
+    reason TEXT,
+    source_change_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS country_policy_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    country_code VARCHAR(2) NOT NULL,
+    new_status VARCHAR(50) NOT NULL
+        CHECK (new_status IN ('eu_eea', 'adequate_protection', 'scc_required', 'blocked', 'unknown')),
+    effective_at TIMESTAMPTZ NOT NULL,
+    reason TEXT,
+    legal_reference TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'scheduled'
+        CHECK (status IN ('scheduled', 'applied', 'cancelled')),
+    created_by VARCHAR(255),
+    notified_at TIMESTAMPTZ,
+    applied_at TIMESTAMPTZ,
+    previous_status VARCHAR(50),
+    evidence_event_id VARCHAR(64),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_country_policy_changes_due ON country_policy_changes(effective_at) WHERE status = 'scheduled';
+CREATE INDEX IF NOT EXISTS idx_country_policy_changes_country ON country_policy_changes(country_code);
+
+DROP TRIGGER IF EXISTS update_country_policy_overrides_updated_at ON country_policy_overrides;
+CREATE TRIGGER update_country_policy_overrides_updated_at
+    BEFORE UPDATE ON country_policy_overrides
+    FOR EACH ROW EXECUTE PROCEDURE update_updated_at_column();
+
+DROP TRIGGER IF EXISTS update_country_policy_changes_updated_at ON country_policy_changes;
+CREATE TRIGGER update_country_policy_changes_updated_at
+    BEFORE UPDATE ON country_policy_changes
+    FOR EACH ROW EXECUTE PROCEDURE update_updated_at_column();
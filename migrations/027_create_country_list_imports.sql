@@ -0,0 +1,26 @@
+-- Imported adequacy / blocked-country lists awaiting review before they override the classification
+
+CREATE TABLE IF NOT EXISTS country_list_imports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source_name VARCHAR(255) NOT NULL,
+    list_type VARCHAR(50) NOT NULL
+        CHECK (list_type IN ('adequate', 'blocked', 'scc_required', 'classifications')),
+    format VARCHAR(10) NOT NULL CHECK (format IN ('json', 'csv')),
+    content_hash VARCHAR(64) NOT NULL,
+    diff JSONB NOT NULL DEFAULT '[]'::jsonb,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'applied', 'rejected', 'no_changes')),
+    seal_id VARCHAR(255),
+    evidence_event_id VARCHAR(64),
+    created_by VARCHAR(255),
+    decided_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_country_list_imports_seal_id ON country_list_imports(seal_id) WHERE seal_id IS NOT NULL;
+
+DROP TRIGGER IF EXISTS update_country_list_imports_updated_at ON country_list_imports;
+CREATE TRIGGER update_country_list_imports_updated_at
+    BEFORE UPDATE ON country_list_imports
+    FOR EACH ROW EXECUTE PROCEDURE update_updated_at_column();
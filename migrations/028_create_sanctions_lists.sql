@@ -0,0 +1,31 @@
+-- Sanctions screening: locally loaded consolidated lists (e.g. EU financial sanctions XML/CSV)
+-- Transfers whose partner_name matches an active entry are blocked by Sovereign Shield
+
+CREATE TABLE IF NOT EXISTS sanctions_lists (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source_name VARCHAR(255) NOT NULL,
+    format VARCHAR(10) NOT NULL CHECK (format IN ('xml', 'csv')),
+    content_hash VARCHAR(64) NOT NULL,
+    entry_count INTEGER NOT NULL DEFAULT 0,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    loaded_by VARCHAR(255),
+    evidence_event_id VARCHAR(64),
+    loaded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS sanctions_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    list_id UUID NOT NULL REFERENCES sanctions_lists(id) ON DELETE CASCADE,
+    entity_ref VARCHAR(255) NOT NULL,
+    name TEXT NOT NULL,
+    normalised_name TEXT NOT NULL,
+    name_keys TEXT[] NOT NULL DEFAULT '{}',
+    subject_type VARCHAR(50),
+    programme VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sanctions_entries_list_id ON sanctions_entries(list_id);
+CREATE INDEX IF NOT EXISTS idx_sanctions_entries_normalised_name ON sanctions_entries(normalised_name);
+CREATE INDEX IF NOT EXISTS idx_sanctions_entries_name_keys ON sanctions_entries USING GIN (name_keys);
+CREATE INDEX IF NOT EXISTS idx_sanctions_lists_active ON sanctions_lists(active) WHERE active = TRUE;
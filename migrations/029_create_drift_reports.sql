@@ -0,0 +1,20 @@
+-- Compliance drift reports: past DATA_TRANSFER evidence replayed through the current Shield policy
+-- Reports are derived data only; the original evidence_events rows are never modified
+
+CREATE TABLE IF NOT EXISTS drift_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed')),
+    since TIMESTAMPTZ,
+    until TIMESTAMPTZ,
+    requested_by VARCHAR(255),
+    evaluated_count BIGINT NOT NULL DEFAULT 0,
+    drift_count BIGINT NOT NULL DEFAULT 0,
+    report JSONB NOT NULL DEFAULT '{}'::jsonb,
+    error_message TEXT,
+    evidence_event_id VARCHAR(64),
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_drift_reports_started_at ON drift_reports(started_at DESC);
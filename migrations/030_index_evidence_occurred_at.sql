@@ -0,0 +1,4 @@
+-- Stats and date filters read occurred_at (the source time of ingested logs), not created_at
+
+CREATE INDEX IF NOT EXISTS idx_evidence_events_occurred_at ON evidence_events(occurred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_evidence_events_source_occurred ON evidence_events(source_system, occurred_at DESC);
@@ -0,0 +1,8 @@
+-- Idempotency keys for Shield evaluate / ingest: retries within the replay window return the original event
+-- Not unique: a key may be reused once the window has passed
+
+ALTER TABLE evidence_events ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_evidence_events_idempotency_key
+    ON evidence_events(source_system, idempotency_key, recorded_at DESC)
+    WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,109 @@
+-- Live stream feed: evidence events, review creations and review decisions appended by triggers
+-- Each insert is announced on the veridion_live channel (NOTIFY fires on commit) so every API
+-- instance can push it to its SSE clients; the BIGSERIAL id is the SSE event ID used for resume
+
+CREATE TABLE IF NOT EXISTS live_events (
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(30) NOT NULL,
+    source_system VARCHAR(255),
+    event_type VARCHAR(100),
+    severity VARCHAR(20),
+    data JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_live_events_created_at ON live_events(created_at);
+
+CREATE OR REPLACE FUNCTION live_events_notify()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('veridion_live', NEW.id::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_live_events_notify ON live_events;
+CREATE TRIGGER trg_live_events_notify
+    AFTER INSERT ON live_events
+    FOR EACH ROW EXECUTE PROCEDURE live_events_notify();
+
+CREATE OR REPLACE FUNCTION live_events_from_evidence()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO live_events (kind, source_system, event_type, severity, data)
+    VALUES ('evidence', NEW.source_system, NEW.event_type, NEW.severity, jsonb_build_object(
+        'eventId', NEW.event_id,
+        'eventType', NEW.event_type,
+        'severity', NEW.severity,
+        'sourceSystem', NEW.source_system,
+        'occurredAt', NEW.occurred_at,
+        'recordedAt', NEW.recorded_at,
+        'decision', NEW.payload->>'decision',
+        'reason', NEW.payload->>'reason',
+        'destinationCountryCode', NEW.payload->>'destination_country_code',
+        'partnerName', NEW.payload->>'partner_name'
+    ));
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_live_events_evidence ON evidence_events;
+CREATE TRIGGER trg_live_events_evidence
+    AFTER INSERT ON evidence_events
+    FOR EACH ROW EXECUTE PROCEDURE live_events_from_evidence();
+
+CREATE OR REPLACE FUNCTION live_events_from_review()
+RETURNS TRIGGER AS $$
+DECLARE
+    ev RECORD;
+BEGIN
+    SELECT source_system, event_type, severity INTO ev
+    FROM evidence_events WHERE event_id = NEW.evidence_event_id LIMIT 1;
+
+    INSERT INTO live_events (kind, source_system, event_type, severity, data)
+    VALUES ('review.created', ev.source_system, ev.event_type, ev.severity, jsonb_build_object(
+        'sealId', NEW.seal_id,
+        'agentId', NEW.agent_id,
+        'action', NEW.action_summary,
+        'status', NEW.status,
+        'evidenceEventId', NEW.evidence_event_id,
+        'createdAt', NEW.created_at
+    ));
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_live_events_review ON compliance_records;
+CREATE TRIGGER trg_live_events_review
+    AFTER INSERT ON compliance_records
+    FOR EACH ROW EXECUTE PROCEDURE live_events_from_review();
+
+CREATE OR REPLACE FUNCTION live_events_from_oversight()
+RETURNS TRIGGER AS $$
+DECLARE
+    rec RECORD;
+BEGIN
+    SELECT cr.evidence_event_id, ev.source_system, ev.event_type, ev.severity INTO rec
+    FROM compliance_records cr
+    LEFT JOIN evidence_events ev ON ev.event_id = cr.evidence_event_id
+    WHERE cr.seal_id = NEW.seal_id LIMIT 1;
+
+    INSERT INTO live_events (kind, source_system, event_type, severity, data)
+    VALUES ('review.decided', rec.source_system, rec.event_type, rec.severity, jsonb_build_object(
+        'sealId', NEW.seal_id,
+        'status', NEW.status,
+        'reviewerId', NEW.reviewer_id,
+        'decidedAt', NEW.decided_at,
+        'comments', NEW.comments,
+        'evidenceEventId', rec.evidence_event_id
+    ));
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_live_events_oversight ON human_oversight;
+CREATE TRIGGER trg_live_events_oversight
+    AFTER UPDATE OF status ON human_oversight
+    FOR EACH ROW
+    WHEN (OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE PROCEDURE live_events_from_oversight();
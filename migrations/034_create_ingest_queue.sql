@@ -0,0 +1,40 @@
+-- Durable ingest queue: POST /api/v1/shield/ingest-logs stores each entry as a job and returns 202;
+-- background workers evaluate jobs and record evidence, retrying transient failures with backoff
+
+CREATE TABLE IF NOT EXISTS ingest_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Idempotency-Key header of the submission; resubmitting returns this batch
+    idempotency_key VARCHAR(255),
+    total_entries INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'completed', 'completed_with_errors')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ingest_batches_idempotency_key ON ingest_batches(idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS ingest_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    batch_id UUID NOT NULL REFERENCES ingest_batches(id) ON DELETE CASCADE,
+    entry_index INTEGER NOT NULL,
+    -- The submitted IngestLogEntry
+    payload JSONB NOT NULL,
+    idempotency_key VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'processing', 'done', 'failed')),
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- IngestEntryResult once the job is done or has failed permanently
+    result JSONB,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (batch_id, entry_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ingest_jobs_due ON ingest_jobs(next_attempt_at)
+    WHERE status IN ('pending', 'processing');
+CREATE INDEX IF NOT EXISTS idx_ingest_jobs_batch_status ON ingest_jobs(batch_id, status);
@@ -0,0 +1,21 @@
+-- Local IP-to-country data (e.g. a DB-IP / IP2Location lite CSV) used to resolve the destination
+-- country of ingested network logs that only carry addresses. One dataset is active at a time.
+
+CREATE TABLE IF NOT EXISTS ip_country_datasets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source_name VARCHAR(255) NOT NULL,
+    content_hash VARCHAR(64) NOT NULL,
+    range_count INTEGER NOT NULL DEFAULT 0,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    loaded_by VARCHAR(255),
+    loaded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS ip_country_ranges (
+    dataset_id UUID NOT NULL REFERENCES ip_country_datasets(id) ON DELETE CASCADE,
+    start_ip INET NOT NULL,
+    end_ip INET NOT NULL,
+    country_code CHAR(2) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ip_country_ranges_lookup ON ip_country_ranges(dataset_id, start_ip DESC);
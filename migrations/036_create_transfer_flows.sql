@@ -0,0 +1,45 @@
+-- Flow aggregation: with FLOW_AGGREGATION_WINDOW_SECS set, ingested ALLOW / REVIEW transfers are counted
+-- per flow (source, partner, destination, category set, decision) and time window instead of each writing
+-- evidence; one sealed evidence event (and at most one review) is written per flow once its window closes
+
+CREATE TABLE IF NOT EXISTS transfer_flows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- SHA-256 of the flow's identifying fields
+    flow_key VARCHAR(64) NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    window_end TIMESTAMPTZ NOT NULL,
+    source_ip VARCHAR(64),
+    partner_name VARCHAR(500),
+    destination_country_code VARCHAR(2),
+    data_categories JSONB NOT NULL DEFAULT '[]',
+    decision VARCHAR(20) NOT NULL,
+    transfer_count BIGINT NOT NULL DEFAULT 0,
+    total_bytes BIGINT NOT NULL DEFAULT 0,
+    -- Source timestamps of the earliest and latest counted transfer
+    first_seen_at TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    -- First transfer's TransferContext, TransferDecision and evidence payload, the basis of the sealed event
+    context JSONB NOT NULL,
+    decision_detail JSONB NOT NULL,
+    payload JSONB NOT NULL,
+    evidence_event_id VARCHAR(255),
+    review_id VARCHAR(255),
+    sealed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One open row per flow and window; transfers arriving after a window was sealed open a follow-up row
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transfer_flows_open ON transfer_flows(flow_key, window_start)
+    WHERE sealed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_transfer_flows_due ON transfer_flows(window_end) WHERE sealed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_transfer_flows_window ON transfer_flows(window_start DESC);
+
+-- Idempotency keys of aggregated transfers, so a retried entry is not counted twice
+CREATE TABLE IF NOT EXISTS transfer_flow_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    flow_id UUID REFERENCES transfer_flows(id) ON DELETE CASCADE,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transfer_flow_keys_recorded_at ON transfer_flow_keys(recorded_at);
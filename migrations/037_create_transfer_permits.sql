@@ -0,0 +1,35 @@
+-- Transfer permits: `POST /api/v1/shield/evaluate?permit=true` returns a short-lived EdDSA-signed JWS
+-- for ALLOW decisions that downstream systems verify offline against the published JWKS
+
+CREATE TABLE IF NOT EXISTS permit_signing_keys (
+    -- RFC 7638 JWK thumbprint of the public key
+    kid VARCHAR(64) PRIMARY KEY,
+    algorithm VARCHAR(20) NOT NULL DEFAULT 'EdDSA',
+    public_key BYTEA NOT NULL,
+    -- PKCS#8 private key, AES-256-GCM wrapped with VERIDION_MASTER_KEY (nonce prepended)
+    wrapped_private_key BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Retired keys sign nothing new but stay published until their last permits expire
+    retired_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_permit_signing_keys_active ON permit_signing_keys((TRUE))
+    WHERE retired_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS transfer_permits (
+    jti UUID PRIMARY KEY,
+    kid VARCHAR(64) NOT NULL REFERENCES permit_signing_keys(kid),
+    evidence_event_id VARCHAR(255) NOT NULL,
+    destination_country_code VARCHAR(2),
+    partner_name VARCHAR(500),
+    -- Country status the ALLOW relied on: `adequate`, or `scc_required` with an active SCC for the partner
+    country_status VARCHAR(30) NOT NULL,
+    claims JSONB NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revocation_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_transfer_permits_unexpired ON transfer_permits(destination_country_code, expires_at);
+CREATE INDEX IF NOT EXISTS idx_transfer_permits_revoked ON transfer_permits(revoked_at) WHERE revoked_at IS NOT NULL;
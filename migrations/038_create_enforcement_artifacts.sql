@@ -0,0 +1,32 @@
+-- Network-layer enforcement artifacts generated from the blocked-country policy: nftables sets,
+-- ipset restore files and DNS RPZ zones. Each distinct output is a numbered version per format whose
+-- SHA-256 is sealed into evidence; deployments record which version a firewall or resolver loaded.
+
+CREATE TABLE IF NOT EXISTS enforcement_artifacts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    format VARCHAR(20) NOT NULL,
+    version INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    content_hash VARCHAR(64) NOT NULL,
+    -- Hash of what the content was built from; regenerating from unchanged inputs returns the same version
+    input_hash VARCHAR(64) NOT NULL,
+    blocked_countries TEXT[] NOT NULL DEFAULT '{}',
+    ip_dataset_id UUID REFERENCES ip_country_datasets(id) ON DELETE SET NULL,
+    network_count INTEGER NOT NULL DEFAULT 0,
+    host_count INTEGER NOT NULL DEFAULT 0,
+    evidence_event_id VARCHAR(255),
+    generated_by VARCHAR(255),
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (format, version)
+);
+
+CREATE TABLE IF NOT EXISTS enforcement_deployments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    artifact_id UUID NOT NULL REFERENCES enforcement_artifacts(id) ON DELETE CASCADE,
+    -- Firewall, gateway or resolver that loaded the artifact
+    target VARCHAR(255) NOT NULL,
+    evidence_event_id VARCHAR(255),
+    deployed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_enforcement_deployments_artifact ON enforcement_deployments(artifact_id, deployed_at DESC);
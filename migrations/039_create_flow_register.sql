@@ -0,0 +1,55 @@
+-- Data-flow register: teams declare the transfers they expect (source system, partner, destination,
+-- categories, purpose). Once any flow is declared, every ingested transfer is matched against the
+-- register; transfers that match nothing are marked UNDECLARED_FLOW and grouped here for DPO triage.
+
+CREATE TABLE IF NOT EXISTS declared_flows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    -- NULL matches any value; at least one of the three is set
+    source_system VARCHAR(255),
+    partner_name VARCHAR(500),
+    destination_country_code VARCHAR(2),
+    -- Canonical category codes the flow may carry; empty allows any
+    data_categories JSONB NOT NULL DEFAULT '[]',
+    purpose TEXT NOT NULL,
+    owner VARCHAR(255),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (source_system IS NOT NULL OR partner_name IS NOT NULL OR destination_country_code IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_declared_flows_active ON declared_flows(active);
+
+CREATE TABLE IF NOT EXISTS undeclared_flows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- SHA-256 of source system, partner, destination and category set
+    group_key VARCHAR(64) NOT NULL,
+    source_system VARCHAR(255),
+    partner_name VARCHAR(500),
+    destination_country_code VARCHAR(2),
+    data_categories JSONB NOT NULL DEFAULT '[]',
+    transfer_count BIGINT NOT NULL DEFAULT 0,
+    first_seen_at TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    -- Decision and evidence (or aggregated flow) of the latest transfer
+    last_decision VARCHAR(20),
+    last_evidence_id VARCHAR(255),
+    last_flow_id UUID,
+    -- open, declared (a declared flow now covers it) or dismissed
+    status VARCHAR(20) NOT NULL DEFAULT 'open',
+    declared_flow_id UUID REFERENCES declared_flows(id) ON DELETE SET NULL,
+    resolved_by VARCHAR(255),
+    resolution_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    -- UNDECLARED_FLOW_DETECTED evidence written when the group was opened
+    evidence_event_id VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One live group per key: dismissed groups keep counting; once a group is declared, a transfer that
+-- is undeclared again (its declaration retired) opens a new one
+CREATE UNIQUE INDEX IF NOT EXISTS idx_undeclared_flows_live ON undeclared_flows(group_key) WHERE status <> 'declared';
+CREATE INDEX IF NOT EXISTS idx_undeclared_flows_status ON undeclared_flows(status, last_seen_at DESC);
@@ -0,0 +1,34 @@
+-- Version counter for everything a policy snapshot is built from: the data-category taxonomy,
+-- country overrides and the SCC registry. Statement triggers bump it on any change, so API
+-- instances keep evaluating against a cached snapshot until the version moves.
+
+CREATE TABLE IF NOT EXISTS policy_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO policy_version (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION bump_policy_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE policy_version SET version = version + 1, updated_at = NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_policy_version_data_categories ON data_categories;
+CREATE TRIGGER trg_policy_version_data_categories
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON data_categories
+    FOR EACH STATEMENT EXECUTE PROCEDURE bump_policy_version();
+
+DROP TRIGGER IF EXISTS trg_policy_version_country_overrides ON country_policy_overrides;
+CREATE TRIGGER trg_policy_version_country_overrides
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON country_policy_overrides
+    FOR EACH STATEMENT EXECUTE PROCEDURE bump_policy_version();
+
+DROP TRIGGER IF EXISTS trg_policy_version_scc_registries ON scc_registries;
+CREATE TRIGGER trg_policy_version_scc_registries
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON scc_registries
+    FOR EACH STATEMENT EXECUTE PROCEDURE bump_policy_version();
@@ -0,0 +1,18 @@
+-- One claim per (source_system, idempotency_key), inserted in the same transaction as the evidence event,
+-- so concurrent retries cannot both write. A claim older than the replay window is taken over on reuse.
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    source_system VARCHAR(255) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source_system, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_recorded_at ON idempotency_keys(recorded_at);
+
+INSERT INTO idempotency_keys (source_system, idempotency_key, recorded_at)
+SELECT source_system, idempotency_key, MAX(recorded_at)
+FROM evidence_events
+WHERE idempotency_key IS NOT NULL
+GROUP BY source_system, idempotency_key
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,5 @@
+-- Webhook signing secrets are stored wrapped with VERIDION_MASTER_KEY (AES-256-GCM, nonce prepended),
+-- like permit signing keys. Plaintext secrets from earlier versions are wrapped and cleared at startup.
+
+ALTER TABLE webhook_subscriptions ADD COLUMN IF NOT EXISTS wrapped_secret BYTEA;
+ALTER TABLE webhook_subscriptions ALTER COLUMN secret DROP NOT NULL;
@@ -0,0 +1,11 @@
+-- Live stream: notify with a constant payload so Postgres collapses the notifications of one
+-- transaction into a single NOTIFY (a bulk ingest used to queue one per evidence row). The
+-- listener reads everything past its cursor on each wake-up instead of fetching one ID.
+
+CREATE OR REPLACE FUNCTION live_events_notify()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('veridion_live', '');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
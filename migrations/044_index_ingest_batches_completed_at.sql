@@ -0,0 +1,5 @@
+-- Retention: completed ingest batches (and, by cascade, their jobs and payloads) are deleted
+-- INGEST_RETENTION_DAYS after completion
+
+CREATE INDEX IF NOT EXISTS idx_ingest_batches_completed_at ON ingest_batches(completed_at)
+    WHERE completed_at IS NOT NULL;
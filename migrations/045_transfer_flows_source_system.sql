@@ -0,0 +1,9 @@
+-- Flows are keyed by the reporting source system (source IP only when none is given), and a flow
+-- that cannot be sealed is marked failed instead of being claimed again after every lease
+
+ALTER TABLE transfer_flows ADD COLUMN IF NOT EXISTS source_system VARCHAR(255);
+ALTER TABLE transfer_flows ADD COLUMN IF NOT EXISTS seal_error TEXT;
+
+DROP INDEX IF EXISTS idx_transfer_flows_due;
+CREATE INDEX IF NOT EXISTS idx_transfer_flows_due ON transfer_flows(window_end)
+    WHERE evidence_event_id IS NULL AND seal_error IS NULL;
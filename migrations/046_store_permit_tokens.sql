@@ -0,0 +1,9 @@
+-- Transfer permits: keep the signed token so an idempotent replay of `evaluate?permit=true` returns
+-- the permit already issued for the evidence event instead of minting another
+
+ALTER TABLE transfer_permits ADD COLUMN IF NOT EXISTS token TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_transfer_permits_evidence ON transfer_permits(evidence_event_id);
+
+COMMENT ON COLUMN transfer_permits.country_status IS
+    'Country status the ALLOW relied on: eu_eea, adequate_protection, or scc_required with an active SCC for the partner';
@@ -0,0 +1,169 @@
+use actix_web::error::{JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Request field, in its wire name (e.g. `destinationCountryCode`).
+    pub field: String,
+    pub message: String,
+}
+
+/// Body of every error response. `error` is a stable code clients can match on;
+/// `message` is for people and may change.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    #[schema(example = "VALIDATION_FAILED")]
+    pub error: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+    /// Present on internal errors; quote it to find the matching server log line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// Error returned by route handlers. Internal errors are logged with their detail and a reference;
+/// the response only carries the code and reference, never database or driver text.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 400 `VALIDATION_FAILED`, one entry per invalid field.
+    Validation(Vec<FieldError>),
+    /// 400 with a specific code (`INVALID_JSON`, `INVALID_ID`, `INVALID_IDEMPOTENCY_KEY`, ...).
+    BadRequest { code: &'static str, message: String },
+    /// 401 `UNAUTHORIZED`.
+    Unauthorized(&'static str),
+    /// 404 `NOT_FOUND`; the value names the resource.
+    NotFound(&'static str),
+    /// 409 with a specific code, for requests that conflict with current state.
+    Conflict { code: &'static str, message: String },
+    /// 429 `RATE_LIMITED`; the value is the `Retry-After` in seconds.
+    RateLimited(u64),
+    /// 500 with a specific code (`QUERY_FAILED`, `EVIDENCE_CREATION_FAILED`, ...).
+    Internal { code: &'static str, reference: Uuid },
+}
+
+impl ApiError {
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::BadRequest { code, message: message.into() }
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        ApiError::Conflict { code, message: message.into() }
+    }
+
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        ApiError::Validation(vec![FieldError { field: field.into(), message: message.into() }])
+    }
+
+    /// Log `detail` server-side and return an error that only exposes `code` and a reference.
+    pub fn internal(code: &'static str, detail: impl fmt::Display) -> Self {
+        let reference = Uuid::new_v4();
+        log::error!("{} [ref {}]: {}", code, reference, detail);
+        ApiError::Internal { code, reference }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation(_) => "VALIDATION_FAILED",
+            ApiError::BadRequest { code, .. } => code,
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::Conflict { code, .. } => code,
+            ApiError::RateLimited(_) => "RATE_LIMITED",
+            ApiError::Internal { code, .. } => code,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::Validation(fields) if fields.len() == 1 => {
+                format!("{}: {}", fields[0].field, fields[0].message)
+            }
+            ApiError::Validation(fields) => format!("{} fields are invalid", fields.len()),
+            ApiError::BadRequest { message, .. } | ApiError::Conflict { message, .. } => message.clone(),
+            ApiError::Unauthorized(message) => message.to_string(),
+            ApiError::NotFound(resource) => format!("{} not found", resource),
+            ApiError::RateLimited(secs) => format!("Too many requests; retry in {}s", secs),
+            ApiError::Internal { .. } => "Internal server error".into(),
+        }
+    }
+
+    pub fn body(&self) -> ErrorBody {
+        ErrorBody {
+            error: self.code().into(),
+            message: self.message(),
+            fields: match self {
+                ApiError::Validation(fields) => fields.clone(),
+                _ => Vec::new(),
+            },
+            reference: match self {
+                ApiError::Internal { reference, .. } => Some(reference.to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation(_) | ApiError::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict { .. } => StatusCode::CONFLICT,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::RateLimited(secs) = self {
+            response.insert_header(("Retry-After", secs.to_string()));
+        }
+        response.json(self.body())
+    }
+}
+
+/// `JsonConfig` error handler: malformed bodies get the standard error shape.
+pub fn json_error(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let message = match &err {
+        JsonPayloadError::Deserialize(e) => e.to_string(),
+        JsonPayloadError::ContentType => "Content-Type must be application/json".into(),
+        JsonPayloadError::Overflow { limit } => format!("Body exceeds {} bytes", limit),
+        JsonPayloadError::OverflowKnownLength { length, limit } => {
+            format!("Body of {} bytes exceeds {} bytes", length, limit)
+        }
+        _ => "Request body could not be read".into(),
+    };
+    ApiError::bad_request("INVALID_JSON", message).into()
+}
+
+/// `QueryConfig` error handler.
+pub fn query_error(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let message = match &err {
+        QueryPayloadError::Deserialize(e) => e.to_string(),
+        _ => "Invalid query string".into(),
+    };
+    ApiError::bad_request("INVALID_QUERY", message).into()
+}
+
+/// `PathConfig` error handler; path parameters are IDs.
+pub fn path_error(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    let message = match &err {
+        PathError::Deserialize(e) => e.to_string(),
+        _ => "Invalid path parameter".into(),
+    };
+    ApiError::bad_request("INVALID_ID", message).into()
+}
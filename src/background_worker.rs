@@ -0,0 +1,195 @@
+use sqlx::PgPool;
+use std::env;
+use std::time::Duration;
+
+use crate::country_import;
+use crate::country_policy;
+use crate::idempotency;
+use crate::ingest_queue::{self, RetryPolicy};
+use crate::transfer_flows;
+use crate::webhooks;
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Periodically apply due country policy changes, raise advance notices for upcoming ones and finish
+/// country list imports whose review was decided but not applied.
+/// Interval: `POLICY_SCHEDULER_INTERVAL_SECS` (default 300). Notice window: `POLICY_CHANGE_NOTICE_DAYS` (default 30).
+pub fn spawn_country_policy_scheduler(pool: PgPool) {
+    let interval_secs = env_u64("POLICY_SCHEDULER_INTERVAL_SECS", 300).max(1);
+    let notice_days = env_u64("POLICY_CHANGE_NOTICE_DAYS", 30) as i64;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            ticker.tick().await;
+            match country_policy::notify_upcoming(&pool, notice_days).await {
+                Ok(n) if n > 0 => log::info!("Country policy scheduler: {} advance notice(s) raised", n),
+                Ok(_) => {}
+                Err(e) => log::error!("Country policy scheduler notice error: {}", e),
+            }
+            match country_policy::apply_due_changes(&pool).await {
+                Ok(n) if n > 0 => log::info!("Country policy scheduler: {} change(s) applied", n),
+                Ok(_) => {}
+                Err(e) => log::error!("Country policy scheduler apply error: {}", e),
+            }
+            match country_import::apply_decided(&pool).await {
+                Ok(n) if n > 0 => log::info!("Country policy scheduler: {} decided country list import(s) settled", n),
+                Ok(_) => {}
+                Err(e) => log::error!("Country policy scheduler import error: {}", e),
+            }
+        }
+    });
+}
+
+/// Deliver queued webhooks. Interval: `WEBHOOK_DISPATCH_INTERVAL_SECS` (default 5).
+/// A full batch is followed immediately by another so a backlog drains without waiting for the next tick.
+pub fn spawn_webhook_dispatcher(pool: PgPool) {
+    let interval_secs = env_u64("WEBHOOK_DISPATCH_INTERVAL_SECS", 5).max(1);
+    // Redirects are not followed: a receiver could otherwise bounce deliveries to an internal address
+    let http = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("HTTP client builds with static settings");
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            ticker.tick().await;
+            loop {
+                match webhooks::dispatch_due(&pool, &http).await {
+                    Ok(n) if n >= webhooks::DISPATCH_BATCH as usize => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        log::error!("Webhook dispatcher error: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Process queued ingest jobs with `INGEST_WORKERS` concurrent workers (default 2), each polling every
+/// `INGEST_POLL_INTERVAL_SECS` (default 2). Failed jobs are retried up to `INGEST_MAX_ATTEMPTS` times
+/// (default 5), backing off from `INGEST_RETRY_BASE_SECS` (default 10).
+pub fn spawn_ingest_workers(pool: PgPool) {
+    let workers = env_u64("INGEST_WORKERS", 2).max(1);
+    let interval_secs = env_u64("INGEST_POLL_INTERVAL_SECS", 2).max(1);
+    let policy = RetryPolicy {
+        max_attempts: env_u64("INGEST_MAX_ATTEMPTS", 5).max(1) as i32,
+        base_backoff_secs: env_u64("INGEST_RETRY_BASE_SECS", 10).max(1) as i64,
+    };
+
+    for _ in 0..workers {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+            loop {
+                ticker.tick().await;
+                loop {
+                    match ingest_queue::process_due(&pool, policy).await {
+                        Ok(n) if n >= ingest_queue::CLAIM_BATCH as usize => continue,
+                        Ok(_) => break,
+                        Err(e) => {
+                            log::error!("Ingest worker error: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Seal aggregated transfer flows once their window has closed, writing one evidence event per flow.
+/// Interval: `FLOW_SEAL_INTERVAL_SECS` (default 10). Grace for late entries: `FLOW_AGGREGATION_GRACE_SECS` (default 30).
+/// Runs only when `FLOW_AGGREGATION_WINDOW_SECS` is set.
+pub fn spawn_flow_sealer(pool: PgPool) {
+    if transfer_flows::window_secs() == 0 {
+        return;
+    }
+    let interval_secs = env_u64("FLOW_SEAL_INTERVAL_SECS", 10).max(1);
+    let grace_secs = env_u64("FLOW_AGGREGATION_GRACE_SECS", 30) as i64;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            ticker.tick().await;
+            loop {
+                match transfer_flows::seal_due(&pool, grace_secs).await {
+                    Ok(n) if n >= transfer_flows::SEAL_BATCH as usize => continue,
+                    Ok(n) => {
+                        if n > 0 {
+                            log::info!("Flow sealer: {} flow(s) sealed", n);
+                        }
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("Flow sealer error: {}", e);
+                        break;
+                    }
+                }
+            }
+            if let Err(e) = transfer_flows::prune_keys(&pool, idempotency::window_secs()).await {
+                log::error!("Flow sealer error: {}", e);
+            }
+        }
+    });
+}
+
+/// Trim the live stream feed; clients resuming from older IDs get whatever is still retained.
+/// Retention: `LIVE_STREAM_RETENTION_HOURS` (default 24).
+pub fn spawn_live_events_pruner(pool: PgPool) {
+    let retention_hours = env_u64("LIVE_STREAM_RETENTION_HOURS", 24).max(1) as i32;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            ticker.tick().await;
+            let result = sqlx::query("DELETE FROM live_events WHERE created_at < NOW() - make_interval(hours => $1)")
+                .bind(retention_hours)
+                .execute(&pool)
+                .await;
+            match result {
+                Ok(r) if r.rows_affected() > 0 => log::info!("Live stream: pruned {} event(s)", r.rows_affected()),
+                Ok(_) => {}
+                Err(e) => log::error!("Live stream pruning error: {}", e),
+            }
+        }
+    });
+}
+
+/// Delete finished ingest batches with their jobs; payloads are kept only for status lookups.
+/// Retention: `INGEST_RETENTION_DAYS` after completion (default 7).
+pub fn spawn_ingest_pruner(pool: PgPool) {
+    let retention_days = env_u64("INGEST_RETENTION_DAYS", 7).max(1) as i32;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            ticker.tick().await;
+            match ingest_queue::prune(&pool, retention_days).await {
+                Ok(n) if n > 0 => log::info!("Ingest queue: pruned {} completed batch(es)", n),
+                Ok(_) => {}
+                Err(e) => log::error!("Ingest queue pruning error: {}", e),
+            }
+        }
+    });
+}
+
+/// Hourly, forget idempotency claims that have left the replay window.
+pub fn spawn_idempotency_pruner(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(3600));
+        loop {
+            ticker.tick().await;
+            match idempotency::prune(&pool).await {
+                Ok(n) if n > 0 => log::info!("Idempotency: pruned {} expired key(s)", n),
+                Ok(_) => {}
+                Err(e) => log::error!("Idempotency pruning error: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,241 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Proxy configuration, read from `EGRESS_PROXY_CONFIG` (default `./configs/egress-proxy.json`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyConfig {
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    /// Where the last good policy snapshot is written, so a restart while the API is down still has one.
+    #[serde(default = "default_policy_cache_path")]
+    pub policy_cache_path: String,
+    #[serde(default = "default_policy_refresh_secs")]
+    pub policy_refresh_secs: u64,
+    /// How old the country snapshot may be and still decide connections while the API is unreachable.
+    #[serde(default = "default_policy_max_age_secs")]
+    pub policy_max_age_secs: u64,
+    /// Evaluations made while the API was unreachable, kept on disk until their evidence is written.
+    #[serde(default = "default_spool_dir")]
+    pub spool_dir: String,
+    /// Connections from one client to the same destination within this window share one idempotency key,
+    /// so client retries replay the first decision instead of recording new evidence. 0 records every connection.
+    #[serde(default = "default_evidence_window_secs")]
+    pub evidence_window_secs: u64,
+    /// How long a cached decision may be reused while the API is unreachable.
+    #[serde(default = "default_max_stale_secs")]
+    pub max_stale_secs: u64,
+    #[serde(default = "default_api_timeout_ms")]
+    pub api_timeout_ms: u64,
+    /// `closed` (default) blocks connections that cannot be decided; `open` lets them through.
+    #[serde(default = "default_fail_mode")]
+    pub fail_mode: String,
+    /// `deny` (default) or `allow` for REVIEW decisions.
+    #[serde(default = "default_review_action")]
+    pub review_action: String,
+    /// Categories assumed for clients that match no profile.
+    #[serde(default)]
+    pub default_data_categories: Vec<String>,
+    /// Host patterns (`api.example.com`, `*.example.com`) to destination country and partner.
+    #[serde(default)]
+    pub hosts: HashMap<String, HostTarget>,
+    #[serde(default)]
+    pub clients: Vec<ClientProfile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostTarget {
+    pub country: String,
+    pub partner: Option<String>,
+}
+
+/// Per-client defaults, matched on the connecting address.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientProfile {
+    pub name: String,
+    pub cidrs: Vec<String>,
+    pub data_categories: Vec<String>,
+}
+
+fn default_listen() -> String { "0.0.0.0:3128".into() }
+fn default_api_url() -> String { "http://127.0.0.1:8080".into() }
+fn default_policy_cache_path() -> String { "./egress-proxy-policy.json".into() }
+fn default_policy_refresh_secs() -> u64 { 60 }
+fn default_policy_max_age_secs() -> u64 { 86_400 }
+fn default_spool_dir() -> String { "./egress-proxy-spool".into() }
+fn default_evidence_window_secs() -> u64 { 60 }
+fn default_max_stale_secs() -> u64 { 900 }
+fn default_api_timeout_ms() -> u64 { 2000 }
+fn default_fail_mode() -> String { "closed".into() }
+fn default_review_action() -> String { "deny".into() }
+
+impl ProxyConfig {
+    pub fn load() -> Result<Self, String> {
+        let path = std::env::var("EGRESS_PROXY_CONFIG").unwrap_or_else(|_| "./configs/egress-proxy.json".into());
+        let raw = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut config: ProxyConfig = serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path, e))?;
+        if let Ok(url) = std::env::var("VERIDION_API_URL") {
+            config.api_url = url;
+        }
+        config.api_url = config.api_url.trim_end_matches('/').to_string();
+        for client in &config.clients {
+            for cidr in &client.cidrs {
+                parse_cidr(cidr).ok_or_else(|| format!("Invalid CIDR {} for client {}", cidr, client.name))?;
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn fail_open(&self) -> bool {
+        self.fail_mode.eq_ignore_ascii_case("open")
+    }
+
+    /// Profile for a connecting address; the first matching profile wins.
+    pub fn client_for(&self, addr: IpAddr) -> Option<&ClientProfile> {
+        self.clients.iter().find(|c| {
+            c.cidrs.iter().filter_map(|cidr| parse_cidr(cidr)).any(|(net, len)| cidr_contains(net, len, addr))
+        })
+    }
+
+    /// Destination country and partner for a host from the configured patterns. Unmapped hosts get no
+    /// country here; the API places them by the address they resolve to (`dest_ip`).
+    pub fn target_for(&self, host: &str) -> (Option<String>, Option<String>) {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let mut best: Option<(&str, &HostTarget)> = None;
+        for (pattern, target) in &self.hosts {
+            let pattern = pattern.as_str();
+            let matches = match pattern.strip_prefix("*.") {
+                Some(suffix) => host.ends_with(&format!(".{}", suffix)) || host == suffix,
+                None => host == pattern,
+            };
+            // Prefer the most specific pattern
+            if matches && best.map(|(p, _)| pattern.len() > p.len()).unwrap_or(true) {
+                best = Some((pattern, target));
+            }
+        }
+        match best {
+            Some((_, t)) => (Some(t.country.to_uppercase()), t.partner.clone().or(Some(host))),
+            None => (None, Some(host)),
+        }
+    }
+}
+
+fn parse_cidr(cidr: &str) -> Option<(IpAddr, u8)> {
+    let (addr, len) = match cidr.split_once('/') {
+        Some((a, l)) => (a.trim().parse::<IpAddr>().ok()?, l.trim().parse::<u8>().ok()?),
+        None => {
+            let a = cidr.trim().parse::<IpAddr>().ok()?;
+            (a, if a.is_ipv4() { 32 } else { 128 })
+        }
+    };
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    (len <= max).then_some((addr, len))
+}
+
+fn cidr_contains(net: IpAddr, len: u8, addr: IpAddr) -> bool {
+    match (net, addr) {
+        (IpAddr::V4(n), IpAddr::V4(a)) => {
+            let mask = if len == 0 { 0 } else { u32::MAX << (32 - len) };
+            u32::from(n) & mask == u32::from(a) & mask
+        }
+        (IpAddr::V6(n), IpAddr::V6(a)) => {
+            let mask = if len == 0 { 0 } else { u128::MAX << (128 - len) };
+            u128::from(n) & mask == u128::from(a) & mask
+        }
+        (IpAddr::V4(n), IpAddr::V6(a)) => match a.to_ipv4_mapped() {
+            Some(a4) => cidr_contains(IpAddr::V4(n), len, IpAddr::V4(a4)),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> ProxyConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn cidrs_parse() {
+        let cases = [
+            ("10.20.0.0/24", Some((ip("10.20.0.0"), 24))),
+            (" 10.20.0.7 ", Some((ip("10.20.0.7"), 32))),
+            ("0.0.0.0/0", Some((ip("0.0.0.0"), 0))),
+            ("2001:db8::/32", Some((ip("2001:db8::"), 32))),
+            ("2001:db8::1", Some((ip("2001:db8::1"), 128))),
+            ("10.0.0.0/33", None),
+            ("2001:db8::/129", None),
+            ("10.0.0/8", None),
+            ("10.0.0.0/x", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_cidr(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn cidr_membership() {
+        let cases = [
+            ("10.20.0.0/24", "10.20.0.255", true),
+            ("10.20.0.0/24", "10.20.1.0", false),
+            ("0.0.0.0/0", "203.0.113.9", true),
+            ("10.20.0.7/32", "10.20.0.7", true),
+            ("10.20.0.7/32", "10.20.0.8", false),
+            ("2001:db8::/32", "2001:db8:ffff::1", true),
+            ("2001:db8::/32", "2001:db9::1", false),
+            ("::/0", "2001:db8::1", true),
+            // IPv4-mapped IPv6 peers match IPv4 ranges, but not the other way round
+            ("10.20.0.0/24", "::ffff:10.20.0.5", true),
+            ("::/0", "10.20.0.5", false),
+        ];
+        for (cidr, addr, expected) in cases {
+            let (net, len) = parse_cidr(cidr).unwrap();
+            assert_eq!(cidr_contains(net, len, ip(addr)), expected, "{} in {}", addr, cidr);
+        }
+    }
+
+    #[test]
+    fn client_profiles_match_first() {
+        let config = config(r#"{"clients": [
+            {"name": "payroll", "cidrs": ["10.20.0.0/24"], "dataCategories": ["financial"]},
+            {"name": "everyone", "cidrs": ["0.0.0.0/0"], "dataCategories": []}
+        ]}"#);
+        assert_eq!(config.client_for(ip("10.20.0.9")).unwrap().name, "payroll");
+        assert_eq!(config.client_for(ip("192.0.2.1")).unwrap().name, "everyone");
+        assert!(config.client_for(ip("2001:db8::1")).is_none());
+    }
+
+    #[test]
+    fn hosts_map_to_the_most_specific_target() {
+        let config = config(r#"{"hosts": {
+            "*.amazonaws.com": {"country": "us", "partner": "AWS"},
+            "*.eu-central-1.amazonaws.com": {"country": "DE"},
+            "api.stripe.com": {"country": "US", "partner": "Stripe"}
+        }}"#);
+        let cases = [
+            ("s3.amazonaws.com", (Some("US"), Some("AWS"))),
+            ("amazonaws.com", (Some("US"), Some("AWS"))),
+            ("s3.eu-central-1.amazonaws.com", (Some("DE"), Some("s3.eu-central-1.amazonaws.com"))),
+            ("API.Stripe.com.", (Some("US"), Some("Stripe"))),
+            ("stripe.com", (None, Some("stripe.com"))),
+            ("notamazonaws.com", (None, Some("notamazonaws.com"))),
+        ];
+        for (host, (country, partner)) in cases {
+            let (c, p) = config.target_for(host);
+            assert_eq!((c.as_deref(), p.as_deref()), (country, partner), "{}", host);
+        }
+    }
+}
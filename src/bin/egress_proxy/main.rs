@@ -0,0 +1,67 @@
+//! Egress forward proxy that enforces Sovereign Shield decisions for clients that cannot call the API
+//! themselves. Point `HTTP_PROXY` / `HTTPS_PROXY` at it; every connection is evaluated (and recorded as
+//! evidence) through `POST /api/v1/shield/evaluate`, with a local policy cache for API outages.
+
+mod config;
+mod policy;
+mod proxy;
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use config::ProxyConfig;
+use policy::PolicyClient;
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    dotenv::dotenv().ok();
+    env_logger::init();
+
+    let config = match ProxyConfig::load() {
+        Ok(c) => Arc::new(c),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let policy = match PolicyClient::new((*config).clone()) {
+        Ok(p) => Arc::new(p),
+        Err(e) => {
+            eprintln!("Failed to create API client: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let refresh = Duration::from_secs(config.policy_refresh_secs.max(1));
+    let background = policy.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(refresh);
+        loop {
+            ticker.tick().await;
+            match background.refresh_countries().await {
+                Ok(n) => log::debug!("Policy snapshot refreshed: {} countries", n),
+                Err(e) => log::warn!("Policy snapshot refresh failed, keeping cached copy: {}", e),
+            }
+            background.flush_spool().await;
+        }
+    });
+
+    let listener = TcpListener::bind(&config.listen).await?;
+    println!("Veridion egress proxy listening on {} (API {})", config.listen, config.api_url);
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Accept failed: {}", e);
+                continue;
+            }
+        };
+        let policy = policy.clone();
+        let config = config.clone();
+        tokio::spawn(async move {
+            proxy::handle(stream, peer, policy, config).await;
+        });
+    }
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::RwLock;
+
+use crate::config::ProxyConfig;
+
+/// Evaluations that could not reach the API are kept (bounded) and replayed for evidence.
+const MAX_SPOOLED: usize = 10_000;
+
+/// Body of `POST /api/v1/shield/evaluate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateBody {
+    pub destination_country_code: Option<String>,
+    pub data_categories: Option<Vec<String>>,
+    pub partner_name: Option<String>,
+    pub source_ip: Option<String>,
+    pub dest_ip: Option<String>,
+    pub protocol: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EvaluateResponse {
+    decision: String,
+    reason: String,
+    evidence_id: Option<String>,
+    review_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CountryClassification {
+    code: String,
+    status: String,
+}
+
+/// What the proxy does with one connection, and why.
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    pub allow: bool,
+    pub decision: String,
+    pub reason: String,
+    pub evidence_id: Option<String>,
+    pub review_id: Option<String>,
+    /// `api`, `cache`, `policy-snapshot` or `fail-open` / `fail-closed`.
+    pub source: &'static str,
+}
+
+/// Country classifications as last fetched, and when; a snapshot loaded from disk dates from its write.
+struct CountrySnapshot {
+    statuses: HashMap<String, String>,
+    fetched_at: SystemTime,
+}
+
+impl CountrySnapshot {
+    /// Status of a country, or None once the snapshot is older than `max_age`.
+    fn status(&self, code: &str, max_age: Duration) -> Option<&str> {
+        // A fetch time in the future (clock stepped back) counts as fresh
+        let age = self.fetched_at.elapsed().unwrap_or_default();
+        if age > max_age {
+            return None;
+        }
+        self.statuses.get(code).map(String::as_str)
+    }
+}
+
+struct CachedDecision {
+    decision: String,
+    reason: String,
+    at: Instant,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Spooled {
+    idempotency_key: String,
+    body: EvaluateBody,
+}
+
+/// Talks to the Shield API and keeps enough policy locally to ride out short outages:
+/// the last decision per (client, host) and the last country classification snapshot.
+pub struct PolicyClient {
+    config: ProxyConfig,
+    http: reqwest::Client,
+    countries: RwLock<CountrySnapshot>,
+    decisions: Mutex<HashMap<(String, String), CachedDecision>>,
+    spool: Mutex<Spool>,
+}
+
+impl PolicyClient {
+    pub fn new(config: ProxyConfig) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.api_timeout_ms))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let countries = load_cached_countries(&config.policy_cache_path);
+        let spool = Spool::open(&config.spool_dir)?;
+        Ok(PolicyClient {
+            config,
+            http,
+            countries: RwLock::new(countries),
+            decisions: Mutex::new(HashMap::new()),
+            spool: Mutex::new(spool),
+        })
+    }
+
+    fn allows(&self, decision: &str) -> bool {
+        decision == "ALLOW" || (decision == "REVIEW" && self.config.review_action.eq_ignore_ascii_case("allow"))
+    }
+
+    /// Evaluate through the API (which writes the evidence). When the API is unreachable fall back to
+    /// a recent cached decision, then to the country snapshot while it is younger than `policyMaxAgeSecs`,
+    /// then to the configured fail mode;
+    /// the request is spooled to disk so evidence is still written once the API is back, even across a restart.
+    /// Hosts without a configured country can only be placed by the API, so they skip the country snapshot.
+    pub async fn decide(&self, client: &str, host: &str, idempotency_key: String, body: EvaluateBody) -> Verdict {
+        match self.evaluate(&idempotency_key, &body).await {
+            Ok(resp) => {
+                self.decisions.lock().unwrap().insert(
+                    (client.to_string(), host.to_string()),
+                    CachedDecision { decision: resp.decision.clone(), reason: resp.reason.clone(), at: Instant::now() },
+                );
+                return Verdict {
+                    allow: self.allows(&resp.decision),
+                    decision: resp.decision,
+                    reason: resp.reason,
+                    evidence_id: resp.evidence_id,
+                    review_id: resp.review_id,
+                    source: "api",
+                };
+            }
+            Err(e) => log::warn!("Shield API unavailable, using local policy: {}", e),
+        }
+
+        let country = body.destination_country_code.clone();
+        self.spool_evaluation(idempotency_key, body);
+
+        let max_stale = Duration::from_secs(self.config.max_stale_secs);
+        if let Some(cached) = self.decisions.lock().unwrap().get(&(client.to_string(), host.to_string())) {
+            if cached.at.elapsed() <= max_stale {
+                return Verdict {
+                    allow: self.allows(&cached.decision),
+                    decision: cached.decision.clone(),
+                    reason: format!("{} (cached)", cached.reason),
+                    evidence_id: None,
+                    review_id: None,
+                    source: "cache",
+                };
+            }
+        }
+
+        let max_age = Duration::from_secs(self.config.policy_max_age_secs);
+        let status = match &country {
+            Some(code) => self.countries.read().await.status(code, max_age).map(str::to_string),
+            None => None,
+        };
+        match status.as_deref() {
+            Some("eu_eea") | Some("adequate_protection") => Verdict {
+                allow: true,
+                decision: "ALLOW".into(),
+                reason: format!("{} is {} (policy snapshot)", country.unwrap_or_default(), status.unwrap_or_default()),
+                evidence_id: None,
+                review_id: None,
+                source: "policy-snapshot",
+            },
+            Some("blocked") => Verdict {
+                allow: false,
+                decision: "BLOCK".into(),
+                reason: format!("{} is blocked (policy snapshot)", country.unwrap_or_default()),
+                evidence_id: None,
+                review_id: None,
+                source: "policy-snapshot",
+            },
+            _ => {
+                let open = self.config.fail_open();
+                Verdict {
+                    allow: open,
+                    decision: if open { "ALLOW".into() } else { "BLOCK".into() },
+                    reason: "Shield API unavailable and no current cached policy for this destination".into(),
+                    evidence_id: None,
+                    review_id: None,
+                    source: if open { "fail-open" } else { "fail-closed" },
+                }
+            }
+        }
+    }
+
+    async fn evaluate(&self, idempotency_key: &str, body: &EvaluateBody) -> Result<EvaluateResponse, String> {
+        let resp = self.http
+            .post(format!("{}/api/v1/shield/evaluate", self.config.api_url))
+            .header("Idempotency-Key", idempotency_key)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(format!("evaluate returned {}", resp.status()));
+        }
+        resp.json::<EvaluateResponse>().await.map_err(|e| e.to_string())
+    }
+
+    fn spool_evaluation(&self, idempotency_key: String, body: EvaluateBody) {
+        if let Err(e) = self.spool.lock().unwrap().write(&Spooled { idempotency_key, body }) {
+            log::error!("Evidence for this connection will not be recorded: {}", e);
+        }
+    }
+
+    /// Replay spooled evaluations, oldest first; the idempotency key makes a retried replay harmless.
+    pub async fn flush_spool(&self) {
+        let pending = self.spool.lock().unwrap().files();
+        for path in pending {
+            let item = match Spool::read(&path) {
+                Ok(item) => item,
+                Err(e) => {
+                    log::error!("Dropping unreadable spool file {}: {}", path.display(), e);
+                    self.spool.lock().unwrap().remove(&path);
+                    continue;
+                }
+            };
+            if let Err(e) = self.evaluate(&item.idempotency_key, &item.body).await {
+                log::warn!("Evidence replay deferred: {}", e);
+                break;
+            }
+            self.spool.lock().unwrap().remove(&path);
+        }
+    }
+
+    /// Fetch the effective country classifications and persist them for restarts during an outage.
+    pub async fn refresh_countries(&self) -> Result<usize, String> {
+        let resp = self.http
+            .get(format!("{}/api/v1/lenses/sovereign-shield/countries", self.config.api_url))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(format!("countries returned {}", resp.status()));
+        }
+        let list: Vec<CountryClassification> = resp.json().await.map_err(|e| e.to_string())?;
+        if let Ok(json) = serde_json::to_string(&list) {
+            if let Err(e) = std::fs::write(&self.config.policy_cache_path, json) {
+                log::warn!("Failed to write policy cache {}: {}", self.config.policy_cache_path, e);
+            }
+        }
+        let statuses: HashMap<String, String> = list.into_iter().map(|c| (c.code.to_uppercase(), c.status)).collect();
+        let count = statuses.len();
+        *self.countries.write().await = CountrySnapshot { statuses, fetched_at: SystemTime::now() };
+        Ok(count)
+    }
+}
+
+fn load_cached_countries(path: &str) -> CountrySnapshot {
+    let statuses = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Vec<CountryClassification>>(&raw).ok())
+        .map(|list| list.into_iter().map(|c| (c.code.to_uppercase(), c.status)).collect())
+        .unwrap_or_default();
+    let fetched_at = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    CountrySnapshot { statuses, fetched_at }
+}
+
+/// Spooled evaluations, one JSON file each, named so that lexical order is spool order.
+struct Spool {
+    dir: PathBuf,
+    seq: u64,
+    pending: usize,
+}
+
+impl Spool {
+    fn open(dir: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create spool dir {}: {}", dir, e))?;
+        let mut spool = Spool { dir: PathBuf::from(dir), seq: 0, pending: 0 };
+        spool.pending = spool.files().len();
+        if spool.pending > 0 {
+            log::info!("{} spooled evaluation(s) waiting in {}", spool.pending, dir);
+        }
+        Ok(spool)
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().map(|x| x == "json").unwrap_or(false))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files
+    }
+
+    fn read(path: &Path) -> Result<Spooled, String> {
+        let raw = std::fs::read(path).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&raw).map_err(|e| e.to_string())
+    }
+
+    fn remove(&mut self, path: &Path) {
+        match std::fs::remove_file(path) {
+            Ok(()) => self.pending = self.pending.saturating_sub(1),
+            Err(e) => log::warn!("Failed to remove spool file {}: {}", path.display(), e),
+        }
+    }
+
+    /// Written to a temporary name and renamed, so a crash never leaves a half-written evaluation.
+    fn write(&mut self, item: &Spooled) -> Result<(), String> {
+        let json = serde_json::to_vec(item).map_err(|e| e.to_string())?;
+        self.seq += 1;
+        let name = format!("{:020}-{:06}", chrono::Utc::now().timestamp_micros(), self.seq % 1_000_000);
+        let tmp = self.dir.join(format!("{}.tmp", name));
+        let path = self.dir.join(format!("{}.json", name));
+        std::fs::write(&tmp, &json)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to spool evaluation to {}: {}", path.display(), e))?;
+        self.pending += 1;
+        if self.pending > MAX_SPOOLED {
+            let files = self.files();
+            self.pending = files.len();
+            for path in files.iter().take(files.len().saturating_sub(MAX_SPOOLED)) {
+                log::warn!("Evidence spool full, dropping oldest pending evaluation {}", path.display());
+                self.remove(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_expires_after_max_age() {
+        let statuses = HashMap::from([("JP".to_string(), "adequate_protection".to_string())]);
+        let max_age = Duration::from_secs(3600);
+        let fresh = CountrySnapshot { statuses: statuses.clone(), fetched_at: SystemTime::now() - Duration::from_secs(60) };
+        assert_eq!(fresh.status("JP", max_age), Some("adequate_protection"));
+        assert_eq!(fresh.status("US", max_age), None);
+
+        let expired = CountrySnapshot { statuses: statuses.clone(), fetched_at: SystemTime::now() - Duration::from_secs(7200) };
+        assert_eq!(expired.status("JP", max_age), None);
+
+        let future = CountrySnapshot { statuses, fetched_at: SystemTime::now() + Duration::from_secs(60) };
+        assert_eq!(future.status("JP", max_age), Some("adequate_protection"));
+    }
+
+    #[test]
+    fn missing_cache_file_is_expired() {
+        let snapshot = load_cached_countries("/nonexistent/egress-proxy-policy.json");
+        assert!(snapshot.statuses.is_empty());
+        assert_eq!(snapshot.fetched_at, SystemTime::UNIX_EPOCH);
+    }
+}
@@ -0,0 +1,284 @@
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use crate::config::ProxyConfig;
+use crate::policy::{EvaluateBody, PolicyClient, Verdict};
+
+/// Largest request head accepted before the connection is rejected.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const DNS_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct RequestHead {
+    method: String,
+    target: String,
+    version: String,
+    headers: Vec<(String, String)>,
+    /// Bytes read past the end of the head (start of the body), forwarded upstream as-is.
+    rest: Vec<u8>,
+}
+
+impl RequestHead {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+async fn read_head(stream: &mut TcpStream) -> Result<RequestHead, String> {
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+    let end = loop {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("connection closed before request head".into());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err("request head too large".into());
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().ok_or("missing method")?.to_string();
+    let target = request_line.next().ok_or("missing request target")?.to_string();
+    let version = request_line.next().unwrap_or("HTTP/1.1").to_string();
+    let headers = lines
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| l.split_once(':').map(|(k, v)| (k.trim().to_string(), v.trim().to_string())))
+        .collect();
+
+    Ok(RequestHead { method, target, version, headers, rest: buf[end..].to_vec() })
+}
+
+/// Split `host:port` (with optional `[v6]` brackets), defaulting the port.
+fn split_host_port(authority: &str, default_port: u16) -> (String, u16) {
+    if let Some(rest) = authority.strip_prefix('[') {
+        if let Some((host, tail)) = rest.split_once(']') {
+            let port = tail.strip_prefix(':').and_then(|p| p.parse().ok()).unwrap_or(default_port);
+            return (host.to_string(), port);
+        }
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host.to_string(), port.parse().unwrap_or(default_port)),
+        _ => (authority.to_string(), default_port),
+    }
+}
+
+/// First address the destination resolves to.
+async fn resolve(host: &str, port: u16) -> Result<SocketAddr, String> {
+    let lookup = tokio::time::timeout(DNS_TIMEOUT, tokio::net::lookup_host((host, port))).await;
+    match lookup {
+        Ok(Ok(mut addrs)) => addrs.next().ok_or_else(|| format!("{} has no address", host)),
+        Ok(Err(e)) => Err(format!("Failed to resolve {}: {}", host, e)),
+        Err(_) => Err(format!("Timed out resolving {}", host)),
+    }
+}
+
+/// Stable for one client, destination and request within `evidenceWindowSecs`, so retries replay the
+/// first evaluation (and a spooled evaluation replays harmlessly); unique per connection when the window is 0.
+fn idempotency_key(config: &ProxyConfig, peer: SocketAddr, host: &str, port: u16, protocol: &str, path: Option<&str>) -> String {
+    if config.evidence_window_secs == 0 {
+        return format!("egress-proxy:{}", Uuid::new_v4());
+    }
+    let window = chrono::Utc::now().timestamp() as u64 / config.evidence_window_secs;
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}|{}|{}|{}|{}|{}", peer.ip(), host, port, protocol, path.unwrap_or(""), window).as_bytes());
+    format!("egress-proxy:{:x}", hasher.finalize())
+}
+
+async fn respond_denied(stream: &mut TcpStream, verdict: &Verdict) {
+    let body = serde_json::json!({
+        "decision": verdict.decision,
+        "reason": verdict.reason,
+        "evidence_id": verdict.evidence_id,
+        "review_id": verdict.review_id,
+        "source": verdict.source,
+    })
+    .to_string();
+    let reason: String = verdict.reason.chars().map(|c| if c.is_ascii_graphic() || c == ' ' { c } else { '-' }).collect();
+    let mut response = format!(
+        "HTTP/1.1 403 Forbidden\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\nx-shield-decision: {}\r\nx-shield-reason: {}\r\n",
+        body.len(),
+        verdict.decision,
+        reason,
+    );
+    if let Some(id) = &verdict.evidence_id {
+        response.push_str(&format!("x-shield-evidence-id: {}\r\n", id));
+    }
+    if let Some(id) = &verdict.review_id {
+        response.push_str(&format!("x-shield-review-id: {}\r\n", id));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+async fn respond_error(stream: &mut TcpStream, status: &str, message: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        message.len(),
+        message,
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Handle one client connection: `CONNECT host:port` tunnels (HTTPS) or absolute-form HTTP requests.
+/// Plain HTTP is forced to `Connection: close` so every request on the socket is evaluated.
+pub async fn handle(mut client: TcpStream, peer: SocketAddr, policy: Arc<PolicyClient>, config: Arc<ProxyConfig>) {
+    let head = match read_head(&mut client).await {
+        Ok(h) => h,
+        Err(e) => {
+            log::debug!("{}: {}", peer, e);
+            respond_error(&mut client, "400 Bad Request", &e).await;
+            return;
+        }
+    };
+
+    let is_connect = head.method.eq_ignore_ascii_case("CONNECT");
+    let (host, port, path) = if is_connect {
+        let (h, p) = split_host_port(&head.target, 443);
+        (h, p, None)
+    } else {
+        let Some(rest) = head.target.strip_prefix("http://") else {
+            respond_error(&mut client, "400 Bad Request", "Absolute-form http:// target or CONNECT required").await;
+            return;
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        let (h, p) = split_host_port(authority, 80);
+        (h, p, Some(path))
+    };
+
+    let profile = config.client_for(peer.ip());
+    let client_name = profile.map(|p| p.name.clone()).unwrap_or_else(|| "default".into());
+    let categories = profile
+        .map(|p| p.data_categories.clone())
+        .unwrap_or_else(|| config.default_data_categories.clone());
+    let (country, partner) = config.target_for(&host);
+
+    // Evaluate the address actually connected to, so the API can place hosts the config does not map
+    let upstream_addr = match resolve(&host, port).await {
+        Ok(addr) => addr,
+        Err(e) => {
+            respond_error(&mut client, "502 Bad Gateway", &e).await;
+            return;
+        }
+    };
+    let protocol = if is_connect { "https-connect" } else { "http" };
+    let idempotency_key = idempotency_key(&config, peer, &host, port, protocol, path.as_deref());
+
+    let body = EvaluateBody {
+        destination_country_code: country,
+        data_categories: Some(categories),
+        partner_name: partner,
+        source_ip: Some(peer.ip().to_string()),
+        dest_ip: Some(upstream_addr.ip().to_string()),
+        protocol: Some(protocol.into()),
+        user_agent: head.header("user-agent").map(String::from),
+        request_path: path.clone(),
+    };
+    let verdict = policy.decide(&client_name, &host, idempotency_key, body).await;
+    log::info!(
+        "{} [{}] {} {}:{} -> {} ({}, {})",
+        peer, client_name, head.method, host, port, verdict.decision, verdict.source, verdict.reason
+    );
+
+    if !verdict.allow {
+        respond_denied(&mut client, &verdict).await;
+        return;
+    }
+
+    let mut upstream = match TcpStream::connect(upstream_addr).await {
+        Ok(s) => s,
+        Err(e) => {
+            respond_error(&mut client, "502 Bad Gateway", &format!("Upstream connect failed: {}", e)).await;
+            return;
+        }
+    };
+
+    if is_connect {
+        if client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n").await.is_err() {
+            return;
+        }
+        if !head.rest.is_empty() && upstream.write_all(&head.rest).await.is_err() {
+            return;
+        }
+    } else {
+        let mut forwarded = format!("{} {} {}\r\n", head.method, path.unwrap_or_else(|| "/".into()), head.version);
+        for (k, v) in &head.headers {
+            if k.eq_ignore_ascii_case("proxy-connection")
+                || k.eq_ignore_ascii_case("proxy-authorization")
+                || k.eq_ignore_ascii_case("connection")
+            {
+                continue;
+            }
+            forwarded.push_str(&format!("{}: {}\r\n", k, v));
+        }
+        forwarded.push_str("Connection: close\r\n\r\n");
+        if upstream.write_all(forwarded.as_bytes()).await.is_err() || upstream.write_all(&head.rest).await.is_err() {
+            return;
+        }
+    }
+
+    if let Err(e) = tokio::io::copy_bidirectional(&mut client, &mut upstream).await {
+        log::debug!("{} tunnel to {}:{} closed: {}", peer, host, port, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn windowed(evidence_window_secs: u64) -> ProxyConfig {
+        serde_json::from_value(serde_json::json!({ "evidenceWindowSecs": evidence_window_secs })).unwrap()
+    }
+
+    #[test]
+    fn host_and_port_split() {
+        let cases = [
+            ("api.stripe.com:443", ("api.stripe.com", 443)),
+            ("api.stripe.com", ("api.stripe.com", 80)),
+            ("api.stripe.com:notaport", ("api.stripe.com", 80)),
+            ("10.0.0.1:8443", ("10.0.0.1", 8443)),
+            ("[2001:db8::1]:443", ("2001:db8::1", 443)),
+            ("[2001:db8::1]", ("2001:db8::1", 80)),
+            ("[::1]:", ("::1", 80)),
+            // A bare IPv6 address has no port
+            ("2001:db8::1", ("2001:db8::1", 80)),
+        ];
+        for (authority, (host, port)) in cases {
+            assert_eq!(split_host_port(authority, 80), (host.to_string(), port), "{}", authority);
+        }
+    }
+
+    #[test]
+    fn idempotency_keys_are_stable_within_the_window() {
+        let peer: SocketAddr = "10.20.0.5:51000".parse().unwrap();
+        let other_port: SocketAddr = "10.20.0.5:51001".parse().unwrap();
+        let config = windowed(1_000_000_000);
+        let key = idempotency_key(&config, peer, "api.stripe.com", 443, "https", None);
+        assert!(key.starts_with("egress-proxy:"));
+        // Retries from a new source port share the key; another destination or path does not
+        assert_eq!(key, idempotency_key(&config, other_port, "api.stripe.com", 443, "https", None));
+        assert_ne!(key, idempotency_key(&config, peer, "api.stripe.com", 8443, "https", None));
+        assert_ne!(key, idempotency_key(&config, peer, "api.stripe.com", 443, "https", Some("/v1/charges")));
+
+        let unique = windowed(0);
+        assert_ne!(
+            idempotency_key(&unique, peer, "api.stripe.com", 443, "https", None),
+            idempotency_key(&unique, peer, "api.stripe.com", 443, "https", None),
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Agent configuration, read from `LOG_AGENT_CONFIG` (default `./configs/log-agent.json`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentConfig {
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    /// Committed read offset per source, rewritten after every batch is sent or spooled.
+    #[serde(default = "default_state_path")]
+    pub state_path: String,
+    /// Batches that could not be delivered wait here until the API is reachable again.
+    #[serde(default = "default_spool_dir")]
+    pub spool_dir: String,
+    /// Oldest spooled batches are dropped once the spool grows past this.
+    #[serde(default = "default_max_spool_bytes")]
+    pub max_spool_bytes: u64,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// A partial batch is sent once it has waited this long.
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default = "default_api_timeout_ms")]
+    pub api_timeout_ms: u64,
+    /// Delay after the first failed send, doubling per failure up to `maxBackoffSecs`.
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+    pub sources: Vec<SourceConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceConfig {
+    /// Stable name; part of each entry's `external_id` and the key for its saved offset.
+    pub name: String,
+    pub path: String,
+    /// `json`, `regex` or `kv`.
+    pub parser: String,
+    /// `regex` only: named groups are entry fields (`destIp`, `partnerName`, ...).
+    pub pattern: Option<String>,
+    /// Entry field to the JSON key or `kv` key it is read from; unmapped fields use their own name.
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+    /// Values for fields the line does not provide, e.g. `dataCategories` or `partnerName`.
+    #[serde(default)]
+    pub defaults: HashMap<String, serde_json::Value>,
+    /// `end` (default) or `beginning`: where to start a file with no saved offset.
+    #[serde(default = "default_start_at")]
+    pub start_at: String,
+}
+
+fn default_api_url() -> String { "http://127.0.0.1:8080".into() }
+fn default_state_path() -> String { "./log-agent-state.json".into() }
+fn default_spool_dir() -> String { "./log-agent-spool".into() }
+fn default_max_spool_bytes() -> u64 { 256 * 1024 * 1024 }
+fn default_batch_size() -> usize { 500 }
+fn default_flush_interval_ms() -> u64 { 2000 }
+fn default_poll_interval_ms() -> u64 { 500 }
+fn default_api_timeout_ms() -> u64 { 30_000 }
+fn default_initial_backoff_ms() -> u64 { 1000 }
+fn default_max_backoff_secs() -> u64 { 300 }
+fn default_start_at() -> String { "end".into() }
+
+impl AgentConfig {
+    pub fn load() -> Result<Self, String> {
+        let path = std::env::var("LOG_AGENT_CONFIG").unwrap_or_else(|_| "./configs/log-agent.json".into());
+        let raw = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut config: AgentConfig = serde_json::from_str(&raw).map_err(|e| format!("Invalid {}: {}", path, e))?;
+        if let Ok(url) = std::env::var("VERIDION_API_URL") {
+            config.api_url = url;
+        }
+        config.api_url = config.api_url.trim_end_matches('/').to_string();
+        config.batch_size = config.batch_size.max(1);
+
+        let mut names = std::collections::HashSet::new();
+        for source in &config.sources {
+            if !names.insert(source.name.as_str()) {
+                return Err(format!("Duplicate source name {}", source.name));
+            }
+            if !matches!(source.start_at.as_str(), "end" | "beginning") {
+                return Err(format!("Source {}: startAt must be `end` or `beginning`", source.name));
+            }
+        }
+        if config.sources.is_empty() {
+            return Err(format!("{} configures no sources", path));
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,144 @@
+//! Log-tailing agent for sources that only write files. Each configured file is followed across
+//! rotation, its lines parsed into ingest entries and shipped in batches to
+//! `POST /api/v1/shield/ingest-logs`. Offsets are saved once a batch is delivered or spooled to disk,
+//! and every entry carries an `external_id` derived from its file and offset, so re-reading after a
+//! crash never writes duplicate evidence.
+
+mod config;
+mod parse;
+mod sender;
+mod tail;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use config::AgentConfig;
+use parse::{LineParser, LogEntry};
+use sender::Sender;
+use tail::{Position, Tailer};
+
+struct Source {
+    name: String,
+    parser: LineParser,
+    tailer: Tailer,
+}
+
+fn load_state(path: &str) -> HashMap<String, Position> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn positions(sources: &[Source]) -> HashMap<String, Position> {
+    sources
+        .iter()
+        .filter_map(|s| s.tailer.position().map(|p| (s.name.clone(), p)))
+        .collect()
+}
+
+fn save_state(path: &str, state: &HashMap<String, Position>) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(&state).map_err(|e| e.to_string())?;
+    let tmp = format!("{}.tmp", path);
+    std::fs::write(&tmp, json)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to save offsets to {}: {}", path, e))
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    env_logger::init();
+
+    let config = match AgentConfig::load() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let state = load_state(&config.state_path);
+    let mut sources = Vec::with_capacity(config.sources.len());
+    for source in &config.sources {
+        let parser = match LineParser::new(source) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        };
+        let tailer = Tailer::open(&source.path, state.get(&source.name).copied(), source.start_at == "beginning");
+        sources.push(Source { name: source.name.clone(), parser, tailer });
+    }
+    let mut sender = match Sender::new(&config) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    println!("Veridion log agent shipping {} source(s) to {}", sources.len(), config.api_url);
+
+    let poll = Duration::from_millis(config.poll_interval_ms.max(10));
+    let flush_after = Duration::from_millis(config.flush_interval_ms);
+    let mut pending: Vec<LogEntry> = Vec::new();
+    let mut oldest_pending = Instant::now();
+    let mut saved = state;
+    loop {
+        // While batches cannot even be spooled, stop reading rather than buffer without bound
+        let sources_to_read = if pending.len() < config.batch_size * 4 { sources.as_mut_slice() } else { &mut [] };
+        for source in sources_to_read {
+            let lines = match source.tailer.read_lines() {
+                Ok(lines) => lines,
+                Err(e) => {
+                    log::warn!("Source {}: read failed: {}", source.name, e);
+                    continue;
+                }
+            };
+            for line in lines {
+                match source.parser.parse(&line.text) {
+                    Ok(mut entry) => {
+                        entry.external_id = Some(format!("log-agent:{}:{}:{}", source.name, line.file_id, line.offset));
+                        if pending.is_empty() {
+                            oldest_pending = Instant::now();
+                        }
+                        pending.push(entry);
+                    }
+                    Err(e) => log::warn!("Source {}: skipped line at offset {}: {}", source.name, line.offset, e),
+                }
+            }
+        }
+
+        if !pending.is_empty() && (pending.len() >= config.batch_size || oldest_pending.elapsed() >= flush_after) {
+            let mut delivered = true;
+            for batch in pending.chunks(config.batch_size) {
+                if let Err(e) = sender.submit(batch.to_vec()).await {
+                    log::error!("{}", e);
+                    delivered = false;
+                    break;
+                }
+            }
+            if !delivered {
+                // Neither sent nor spooled: keep the batch in memory and leave offsets uncommitted
+                tokio::time::sleep(poll).await;
+                continue;
+            }
+            pending.clear();
+        }
+
+        // With nothing in flight, every line read so far is accounted for (sent, spooled or unparseable)
+        if pending.is_empty() {
+            let current = positions(&sources);
+            if current != saved {
+                match save_state(&config.state_path, &current) {
+                    Ok(()) => saved = current,
+                    Err(e) => log::error!("{}", e),
+                }
+            }
+        }
+
+        sender.drain_spool().await;
+        tokio::time::sleep(poll).await;
+    }
+}
@@ -0,0 +1,249 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::config::SourceConfig;
+
+/// One entry of `POST /api/v1/shield/ingest-logs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub source_ip: Option<String>,
+    pub dest_ip: Option<String>,
+    pub protocol: Option<String>,
+    pub data_size: Option<u64>,
+    pub timestamp: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_path: Option<String>,
+    pub destination_country_code: Option<String>,
+    pub destination_country: Option<String>,
+    pub data_categories: Option<Vec<String>>,
+    pub partner_name: Option<String>,
+    pub external_id: Option<String>,
+}
+
+const FIELDS: &[&str] = &[
+    "source_ip", "dest_ip", "protocol", "data_size", "timestamp", "user_agent", "request_path",
+    "destination_country_code", "destination_country", "data_categories", "partner_name",
+];
+
+enum Format {
+    Json,
+    Regex(Regex),
+    KeyValue,
+}
+
+/// Turns one log line into a `LogEntry` according to a source's parser settings.
+pub struct LineParser {
+    format: Format,
+    /// Entry field (snake_case) to the key it is read from.
+    fields: HashMap<String, String>,
+    defaults: Map<String, Value>,
+}
+
+/// `destIp` and `dest_ip` both name the `dest_ip` field.
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn known_field(source: &str, name: &str) -> Result<String, String> {
+    let field = snake_case(name);
+    if FIELDS.contains(&field.as_str()) {
+        Ok(field)
+    } else {
+        Err(format!("Source {}: unknown entry field {}", source, name))
+    }
+}
+
+impl LineParser {
+    pub fn new(source: &SourceConfig) -> Result<Self, String> {
+        let format = match source.parser.as_str() {
+            "json" => Format::Json,
+            "kv" => Format::KeyValue,
+            "regex" => {
+                let pattern = source.pattern.as_deref()
+                    .ok_or_else(|| format!("Source {}: the regex parser needs a pattern", source.name))?;
+                let re = Regex::new(pattern).map_err(|e| format!("Source {}: invalid pattern: {}", source.name, e))?;
+                for name in re.capture_names().flatten() {
+                    known_field(&source.name, name)?;
+                }
+                Format::Regex(re)
+            }
+            other => return Err(format!("Source {}: unknown parser {} (json, regex or kv)", source.name, other)),
+        };
+        let mut fields = HashMap::new();
+        for (field, key) in &source.fields {
+            fields.insert(known_field(&source.name, field)?, key.clone());
+        }
+        let mut defaults = Map::new();
+        for (field, value) in &source.defaults {
+            defaults.insert(known_field(&source.name, field)?, value.clone());
+        }
+        Ok(LineParser { format, fields, defaults })
+    }
+
+    pub fn parse(&self, line: &str) -> Result<LogEntry, String> {
+        let raw: Map<String, Value> = match &self.format {
+            Format::Json => match serde_json::from_str::<Value>(line) {
+                Ok(Value::Object(obj)) => obj,
+                Ok(_) => return Err("not a JSON object".into()),
+                Err(e) => return Err(format!("invalid JSON: {}", e)),
+            },
+            Format::KeyValue => parse_key_values(line).into_iter().map(|(k, v)| (k, Value::String(v))).collect(),
+            Format::Regex(re) => {
+                let caps = re.captures(line).ok_or("line does not match the pattern")?;
+                re.capture_names()
+                    .flatten()
+                    .filter_map(|name| caps.name(name).map(|m| (name.to_string(), Value::String(m.as_str().to_string()))))
+                    .collect()
+            }
+        };
+
+        let mut entry = Map::new();
+        for field in FIELDS {
+            let value = match self.fields.get(*field) {
+                Some(key) => raw.get(key),
+                None => raw.iter().find(|(k, _)| snake_case(k) == *field).map(|(_, v)| v),
+            };
+            let value = match value {
+                Some(Value::Null) | None => self.defaults.get(*field),
+                Some(Value::String(s)) if s.is_empty() || s == "-" => self.defaults.get(*field),
+                Some(v) => Some(v),
+            };
+            if let Some(value) = value {
+                entry.insert(field.to_string(), coerce(field, value)?);
+            }
+        }
+        serde_json::from_value(Value::Object(entry)).map_err(|e| e.to_string())
+    }
+}
+
+/// Line values are strings; sizes become numbers and category lists split on commas.
+fn coerce(field: &str, value: &Value) -> Result<Value, String> {
+    match (field, value) {
+        ("data_size", Value::String(s)) => s.trim().parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| format!("data_size {} is not a byte count", s)),
+        ("data_categories", Value::String(s)) => Ok(Value::from(
+            s.split(',').map(|c| c.trim()).filter(|c| !c.is_empty()).collect::<Vec<_>>(),
+        )),
+        (_, Value::Number(n)) if field != "data_size" => Ok(Value::String(n.to_string())),
+        _ => Ok(value.clone()),
+    }
+}
+
+/// `key=value` pairs separated by whitespace; values may be double-quoted.
+fn parse_key_values(line: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let Some(eq) = rest.find('=') else { break };
+        let key = rest[..eq].rsplit(char::is_whitespace).next().unwrap_or("").to_string();
+        rest = &rest[eq + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let value = quoted[..end].to_string();
+            rest = quoted.get(end + 1..).unwrap_or("");
+            value
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+        if !key.is_empty() {
+            pairs.push((key, value));
+        }
+        rest = rest.trim_start();
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(parser: &str, pattern: Option<&str>, fields: &[(&str, &str)], defaults: Value) -> SourceConfig {
+        SourceConfig {
+            name: "test".into(),
+            path: "/var/log/test.log".into(),
+            parser: parser.into(),
+            pattern: pattern.map(String::from),
+            fields: fields.iter().map(|(f, k)| (f.to_string(), k.to_string())).collect(),
+            defaults: serde_json::from_value(defaults).unwrap(),
+            start_at: "end".into(),
+        }
+    }
+
+    #[test]
+    fn key_value_pairs() {
+        let pairs = parse_key_values(r#"  src=10.0.0.5 dst=203.0.113.9 ua="curl/8.0 (linux)" empty= path=/a=b tail"#);
+        let expected = [("src", "10.0.0.5"), ("dst", "203.0.113.9"), ("ua", "curl/8.0 (linux)"), ("empty", ""), ("path", "/a=b")];
+        assert_eq!(pairs, expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>());
+        assert_eq!(parse_key_values(r#"msg="unterminated value"#), vec![("msg".to_string(), "unterminated value".to_string())]);
+        assert!(parse_key_values("no pairs here").is_empty());
+        assert!(parse_key_values("").is_empty());
+    }
+
+    #[test]
+    fn coerced_values() {
+        assert_eq!(coerce("data_size", &Value::from(" 4096 ")).unwrap(), Value::from(4096u64));
+        assert!(coerce("data_size", &Value::from("4 KB")).unwrap_err().contains("not a byte count"));
+        assert_eq!(coerce("data_size", &Value::from(512)).unwrap(), Value::from(512));
+        assert_eq!(coerce("data_categories", &Value::from("email, name,,")).unwrap(), serde_json::json!(["email", "name"]));
+        assert_eq!(coerce("data_categories", &serde_json::json!(["health"])).unwrap(), serde_json::json!(["health"]));
+        assert_eq!(coerce("dest_ip", &Value::from(42)).unwrap(), Value::from("42"));
+        assert_eq!(coerce("partner_name", &Value::from("Acme")).unwrap(), Value::from("Acme"));
+    }
+
+    #[test]
+    fn regex_field_extraction() {
+        let parser = LineParser::new(&source(
+            "regex",
+            Some(r"^(?P<sourceIp>\S+) -> (?P<dest_ip>\S+) (?P<dataSize>\S+) (?P<partnerName>.*)$"),
+            &[],
+            serde_json::json!({"dataCategories": "email,name", "partnerName": "Unknown"}),
+        )).unwrap();
+        let entry = parser.parse("10.0.0.5 -> 203.0.113.9 2048 Acme Ltd").unwrap();
+        assert_eq!(entry.source_ip.as_deref(), Some("10.0.0.5"));
+        assert_eq!(entry.dest_ip.as_deref(), Some("203.0.113.9"));
+        assert_eq!(entry.data_size, Some(2048));
+        assert_eq!(entry.partner_name.as_deref(), Some("Acme Ltd"));
+        assert_eq!(entry.data_categories, Some(vec!["email".to_string(), "name".to_string()]));
+
+        // `-` and empty captures fall back to the defaults
+        let entry = parser.parse("10.0.0.5 -> 203.0.113.9 - ").unwrap();
+        assert_eq!((entry.data_size, entry.partner_name.as_deref()), (None, Some("Unknown")));
+
+        assert_eq!(parser.parse("garbage").unwrap_err(), "line does not match the pattern");
+        assert!(parser.parse("10.0.0.5 -> 203.0.113.9 big Acme").unwrap_err().contains("not a byte count"));
+    }
+
+    #[test]
+    fn parser_settings_are_checked() {
+        let err = |s: SourceConfig| LineParser::new(&s).err().unwrap();
+        assert!(err(source("regex", None, &[], serde_json::json!({}))).contains("needs a pattern"));
+        assert!(err(source("regex", Some("(?P<bogus>.*)"), &[], serde_json::json!({}))).contains("unknown entry field bogus"));
+        assert!(err(source("regex", Some("(unclosed"), &[], serde_json::json!({}))).contains("invalid pattern"));
+        assert!(err(source("csv", None, &[], serde_json::json!({}))).contains("unknown parser csv"));
+        assert!(err(source("kv", None, &[("destHost", "dst")], serde_json::json!({}))).contains("unknown entry field destHost"));
+    }
+
+    #[test]
+    fn mapped_key_value_fields() {
+        let parser = LineParser::new(&source("kv", None, &[("destIp", "dst"), ("dataSize", "bytes")], serde_json::json!({}))).unwrap();
+        let entry = parser.parse(r#"dst=198.51.100.7 bytes=10 user_agent="Mozilla/5.0 (X11)""#).unwrap();
+        assert_eq!(entry.dest_ip.as_deref(), Some("198.51.100.7"));
+        assert_eq!(entry.data_size, Some(10));
+        assert_eq!(entry.user_agent.as_deref(), Some("Mozilla/5.0 (X11)"));
+    }
+}
@@ -0,0 +1,297 @@
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::AgentConfig;
+use crate::parse::LogEntry;
+
+enum SendError {
+    /// Network error, timeout or any reply other than a validation failure (a wrong `apiUrl`, a proxy
+    /// error page, 413): the batch is kept and retried, since dropping it would lose the entries.
+    Retry(String),
+    /// The API rejected these entry indices; the rest of the batch can be resent without them.
+    Rejected(Vec<(usize, String)>),
+    /// `VALIDATION_FAILED` for the batch as a whole; resending it unchanged cannot succeed.
+    Invalid(String),
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+    message: String,
+    #[serde(default)]
+    fields: Vec<FieldError>,
+}
+
+#[derive(Deserialize)]
+struct FieldError {
+    field: String,
+    message: String,
+}
+
+/// Exponential backoff with jitter between failed sends; while it runs, new batches go straight to the spool.
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl Backoff {
+    fn ready(&self) -> bool {
+        self.next_attempt.map(|at| Instant::now() >= at).unwrap_or(true)
+    }
+
+    fn failed(&mut self) -> Duration {
+        let delay = self.initial.saturating_mul(1 << self.failures.min(16)).min(self.max);
+        let delay = delay.mul_f64(rand::thread_rng().gen_range(0.8..1.2));
+        self.failures += 1;
+        self.next_attempt = Some(Instant::now() + delay);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.next_attempt = None;
+    }
+}
+
+/// Delivers batches to `POST /api/v1/shield/ingest-logs`, spooling them to disk when the API is unreachable.
+pub struct Sender {
+    http: reqwest::Client,
+    url: String,
+    spool: Spool,
+    backoff: Backoff,
+}
+
+impl Sender {
+    pub fn new(config: &AgentConfig) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.api_timeout_ms))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Sender {
+            http,
+            url: format!("{}/api/v1/shield/ingest-logs", config.api_url),
+            spool: Spool::open(&config.spool_dir, config.max_spool_bytes)?,
+            backoff: Backoff {
+                initial: Duration::from_millis(config.initial_backoff_ms.max(1)),
+                max: Duration::from_secs(config.max_backoff_secs.max(1)),
+                failures: 0,
+                next_attempt: None,
+            },
+        })
+    }
+
+    /// Deliver or spool a batch. Once this returns Ok the batch is durable and its offsets may be committed.
+    /// Older spooled batches go first so the API sees entries roughly in order.
+    pub async fn submit(&mut self, batch: Vec<LogEntry>) -> Result<(), String> {
+        if self.spool.is_empty() && self.backoff.ready() && self.deliver(batch.clone()).await {
+            return Ok(());
+        }
+        self.spool.write(&batch)
+    }
+
+    /// Resend spooled batches, oldest first, until one fails or the spool is empty.
+    pub async fn drain_spool(&mut self) {
+        while self.backoff.ready() {
+            let Some(path) = self.spool.oldest() else { break };
+            let batch = match self.spool.read(&path) {
+                Ok(b) => b,
+                Err(e) => {
+                    log::error!("Dropping unreadable spool file {}: {}", path.display(), e);
+                    self.spool.remove(&path);
+                    continue;
+                }
+            };
+            if !self.deliver(batch).await {
+                break;
+            }
+            self.spool.remove(&path);
+        }
+    }
+
+    /// True when the batch is done with: accepted, or rejected in a way that resending cannot fix.
+    async fn deliver(&mut self, mut batch: Vec<LogEntry>) -> bool {
+        loop {
+            match self.send(&batch).await {
+                Ok(()) => {
+                    self.backoff.reset();
+                    return true;
+                }
+                Err(SendError::Rejected(rejected)) => {
+                    let indices: HashSet<usize> = rejected.iter().map(|(i, _)| *i).collect();
+                    for (index, message) in rejected {
+                        let id = batch.get(index).and_then(|e| e.external_id.as_deref()).unwrap_or("?");
+                        log::error!("API rejected entry {}: {}", id, message);
+                    }
+                    batch = batch.into_iter().enumerate().filter(|(i, _)| !indices.contains(i)).map(|(_, e)| e).collect();
+                    if batch.is_empty() {
+                        return true;
+                    }
+                }
+                Err(SendError::Invalid(e)) => {
+                    log::error!("API rejected a batch of {} entries, dropping it: {}", batch.len(), e);
+                    return true;
+                }
+                Err(SendError::Retry(e)) => {
+                    let delay = self.backoff.failed();
+                    log::warn!("Ingest API unavailable ({}), spooling and retrying in {:?}", e, delay);
+                    return false;
+                }
+            }
+        }
+    }
+
+    async fn send(&self, batch: &[LogEntry]) -> Result<(), SendError> {
+        let resp = self.http
+            .post(&self.url)
+            .json(batch)
+            .send()
+            .await
+            .map_err(|e| SendError::Retry(e.to_string()))?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = resp.bytes().await.unwrap_or_default();
+        Err(classify(status, &body))
+    }
+}
+
+/// Only a `400 VALIDATION_FAILED` body is a rejection; any other failure is retried.
+fn classify(status: reqwest::StatusCode, body: &[u8]) -> SendError {
+    let body = serde_json::from_slice::<ErrorBody>(body).ok()
+        .filter(|b| status == reqwest::StatusCode::BAD_REQUEST && b.error == "VALIDATION_FAILED");
+    let Some(body) = body else {
+        return SendError::Retry(format!("ingest-logs returned {}", status));
+    };
+    let rejected: Vec<(usize, String)> = body.fields.iter()
+        .filter_map(|f| {
+            let index = f.field.strip_prefix('[')?.split_once(']')?.0.parse().ok()?;
+            Some((index, format!("{}: {}", f.field, f.message)))
+        })
+        .collect();
+    if rejected.is_empty() {
+        SendError::Invalid(format!("{}: {}", body.error, body.message))
+    } else {
+        SendError::Rejected(rejected)
+    }
+}
+
+/// Undelivered batches, one JSON file each, named so that lexical order is submission order.
+struct Spool {
+    dir: PathBuf,
+    max_bytes: u64,
+    seq: u64,
+}
+
+impl Spool {
+    fn open(dir: &str, max_bytes: u64) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create spool dir {}: {}", dir, e))?;
+        let spool = Spool { dir: PathBuf::from(dir), max_bytes, seq: 0 };
+        let pending = spool.files().len();
+        if pending > 0 {
+            log::info!("{} spooled batch(es) waiting in {}", pending, dir);
+        }
+        Ok(spool)
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().map(|x| x == "json").unwrap_or(false))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files
+    }
+
+    fn is_empty(&self) -> bool {
+        self.files().is_empty()
+    }
+
+    fn oldest(&self) -> Option<PathBuf> {
+        self.files().into_iter().next()
+    }
+
+    fn read(&self, path: &Path) -> Result<Vec<LogEntry>, String> {
+        let raw = std::fs::read(path).map_err(|e| e.to_string())?;
+        serde_json::from_slice(&raw).map_err(|e| e.to_string())
+    }
+
+    fn remove(&self, path: &Path) {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove spool file {}: {}", path.display(), e);
+        }
+    }
+
+    /// Written to a temporary name and renamed, so a crash never leaves a half-written batch.
+    fn write(&mut self, batch: &[LogEntry]) -> Result<(), String> {
+        let json = serde_json::to_vec(batch).map_err(|e| e.to_string())?;
+        self.seq += 1;
+        let name = format!("{:020}-{:06}", chrono::Utc::now().timestamp_micros(), self.seq % 1_000_000);
+        let tmp = self.dir.join(format!("{}.tmp", name));
+        let path = self.dir.join(format!("{}.json", name));
+        std::fs::write(&tmp, &json)
+            .and_then(|_| std::fs::rename(&tmp, &path))
+            .map_err(|e| format!("Failed to spool batch to {}: {}", path.display(), e))?;
+        self.enforce_limit();
+        Ok(())
+    }
+
+    fn enforce_limit(&self) {
+        let files = self.files();
+        let sizes: Vec<u64> = files.iter().map(|p| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0)).collect();
+        let mut total: u64 = sizes.iter().sum();
+        for (path, size) in files.iter().zip(sizes) {
+            if total <= self.max_bytes {
+                break;
+            }
+            log::warn!("Spool over {} bytes, dropping oldest batch {}", self.max_bytes, path.display());
+            self.remove(path);
+            total -= size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn only_validation_failures_drop_entries() {
+        let body = br#"{"error":"VALIDATION_FAILED","message":"Invalid request","fields":[{"field":"[3].destination_country_code","message":"must be a 2-letter country code"},{"field":"body","message":"x"}]}"#;
+        match classify(StatusCode::BAD_REQUEST, body) {
+            SendError::Rejected(rejected) => assert_eq!(rejected, vec![(3, "[3].destination_country_code: must be a 2-letter country code".to_string())]),
+            _ => panic!("expected the entry to be rejected"),
+        }
+        let whole = br#"{"error":"VALIDATION_FAILED","message":"At least one entry is required"}"#;
+        assert!(matches!(classify(StatusCode::BAD_REQUEST, whole), SendError::Invalid(_)));
+    }
+
+    #[test]
+    fn other_failures_are_retried() {
+        let cases: &[(u16, &[u8])] = &[
+            (404, b""),
+            (404, br#"{"error":"NOT_FOUND","message":"No route"}"#),
+            (413, b"<html><body>413 Request Entity Too Large</body></html>"),
+            (502, b"<html>Bad Gateway</html>"),
+            (429, br#"{"error":"RATE_LIMITED","message":"slow down"}"#),
+            (401, br#"{"error":"UNAUTHORIZED","message":"Missing token"}"#),
+            (400, br#"{"error":"INVALID_JSON","message":"expected an array"}"#),
+            // A proxy answering with the API's error code but a different status is not the API
+            (422, br#"{"error":"VALIDATION_FAILED","message":"?"}"#),
+        ];
+        for (status, body) in cases {
+            let status = StatusCode::from_u16(*status).unwrap();
+            assert!(matches!(classify(status, body), SendError::Retry(_)), "{}", status);
+        }
+    }
+}
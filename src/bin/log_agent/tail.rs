@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::fs::Metadata;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Bytes read from one file per poll, so a large backlog is shipped in steps.
+const MAX_READ_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Identity of the file behind a path: the inode on unix.
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+/// Identity of the file behind a path: elsewhere there is no stable inode in std, so the creation
+/// time stands in; rotation recreates the file, which changes it.
+#[cfg(not(unix))]
+fn file_id(meta: &Metadata) -> u64 {
+    meta.created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Where reading stopped in a file, identified by inode so a rotated file is not mistaken for its successor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub file_id: u64,
+    pub offset: u64,
+}
+
+pub struct Line {
+    pub text: String,
+    pub file_id: u64,
+    /// Byte offset of the line's first character; with `file_id` it identifies the line.
+    pub offset: u64,
+}
+
+/// Follows one path across rotation (rename + recreate) and truncation (copytruncate).
+/// Only complete lines are returned; a trailing partial line is read again on the next poll.
+pub struct Tailer {
+    path: PathBuf,
+    file: Option<File>,
+    file_id: u64,
+    offset: u64,
+}
+
+impl Tailer {
+    /// Resume from `saved` when it still refers to the file at `path`. A different file means it was
+    /// rotated while the agent was stopped, so the new one is read from the start; with no saved
+    /// position the file is read from the start or the end per `from_beginning`.
+    pub fn open(path: &str, saved: Option<Position>, from_beginning: bool) -> Self {
+        let mut tailer = Tailer { path: PathBuf::from(path), file: None, file_id: 0, offset: 0 };
+        if let Ok(file) = File::open(&tailer.path) {
+            if let Ok(meta) = file.metadata() {
+                tailer.file_id = file_id(&meta);
+                tailer.offset = match saved {
+                    Some(p) if p.file_id == tailer.file_id && p.offset <= meta.len() => p.offset,
+                    Some(_) => 0,
+                    None if from_beginning => 0,
+                    None => meta.len(),
+                };
+                tailer.file = Some(file);
+            }
+        }
+        tailer
+    }
+
+    pub fn position(&self) -> Option<Position> {
+        self.file.as_ref().map(|_| Position { file_id: self.file_id, offset: self.offset })
+    }
+
+    pub fn read_lines(&mut self) -> io::Result<Vec<Line>> {
+        let mut lines = Vec::new();
+        let read = self.read_complete(&mut lines, false)?;
+
+        // A missing path is a rotation in progress: keep the current handle until the new file appears
+        let Ok(meta) = std::fs::metadata(&self.path) else { return Ok(lines) };
+        if self.file.is_none() || file_id(&meta) != self.file_id {
+            if self.file.is_some() {
+                // Rotated: finish the old file before switching. A backlog larger than one read takes
+                // several polls; the position stays on the old file until it reaches EOF.
+                if read == MAX_READ_BYTES {
+                    return Ok(lines);
+                }
+                // Whatever was appended since, including an unterminated last line
+                while self.read_complete(&mut lines, true)? == MAX_READ_BYTES {}
+                log::info!("{} rotated, following the new file", self.path.display());
+            }
+            self.file = Some(File::open(&self.path)?);
+            self.file_id = file_id(&meta);
+            self.offset = 0;
+            self.read_complete(&mut lines, false)?;
+        } else if meta.len() < self.offset {
+            log::info!("{} truncated, reading from the start", self.path.display());
+            self.offset = 0;
+            self.read_complete(&mut lines, false)?;
+        }
+        Ok(lines)
+    }
+
+    /// Read up to `MAX_READ_BYTES` from the current offset and return how many bytes were read
+    /// (not all of them are consumed when the last line is incomplete).
+    fn read_complete(&mut self, lines: &mut Vec<Line>, include_partial: bool) -> io::Result<u64> {
+        let Some(file) = self.file.as_mut() else { return Ok(0) };
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take(MAX_READ_BYTES).read_to_end(&mut buf)?;
+        let read = buf.len() as u64;
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let end = match buf.iter().rposition(|&b| b == b'\n') {
+            _ if include_partial && read < MAX_READ_BYTES => buf.len(),
+            Some(i) => i + 1,
+            // An oversized line with no newline in reach is shipped in pieces rather than stalling the file
+            None if read == MAX_READ_BYTES => buf.len(),
+            None => return Ok(read),
+        };
+
+        let mut start = 0;
+        for chunk in buf[..end].split_inclusive(|&b| b == b'\n') {
+            let text = String::from_utf8_lossy(chunk).trim_end_matches(['\n', '\r']).to_string();
+            if !text.trim().is_empty() {
+                lines.push(Line { text, file_id: self.file_id, offset: self.offset + start as u64 });
+            }
+            start += chunk.len();
+        }
+        self.offset += end as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A fresh directory per test under the system temp dir.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("veridion-log-agent-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn append(path: &std::path::Path, text: &str) {
+        std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap().write_all(text.as_bytes()).unwrap();
+    }
+
+    fn texts(lines: &[Line]) -> Vec<&str> {
+        lines.iter().map(|l| l.text.as_str()).collect()
+    }
+
+    #[test]
+    fn partial_lines_wait_for_their_newline() {
+        let dir = scratch("partial");
+        let path = dir.join("app.log");
+        append(&path, "one\ntw");
+        let mut tailer = Tailer::open(path.to_str().unwrap(), None, true);
+        let lines = tailer.read_lines().unwrap();
+        assert_eq!(texts(&lines), vec!["one"]);
+        assert_eq!(tailer.position().unwrap().offset, 4);
+
+        append(&path, "o\r\n\nthree\n");
+        let lines = tailer.read_lines().unwrap();
+        assert_eq!(texts(&lines), vec!["two", "three"]);
+        assert_eq!(lines.iter().map(|l| l.offset).collect::<Vec<_>>(), vec![4, 10]);
+    }
+
+    #[test]
+    fn start_position() {
+        let dir = scratch("start");
+        let path = dir.join("app.log");
+        append(&path, "old\n");
+        let p = path.to_str().unwrap();
+        assert!(Tailer::open(p, None, false).read_lines().unwrap().is_empty());
+        assert_eq!(texts(&Tailer::open(p, None, true).read_lines().unwrap()), vec!["old"]);
+
+        let id = Tailer::open(p, None, false).position().unwrap().file_id;
+        let resumed = Tailer::open(p, Some(Position { file_id: id, offset: 4 }), true).position().unwrap();
+        assert_eq!(resumed.offset, 4);
+        // Rotated while the agent was stopped: the saved position belongs to another file
+        let moved = Tailer::open(p, Some(Position { file_id: id.wrapping_add(1), offset: 4 }), false).position().unwrap();
+        assert_eq!(moved.offset, 0);
+    }
+
+    #[test]
+    fn rotation_finishes_the_old_file_first() {
+        let dir = scratch("rotate");
+        let path = dir.join("app.log");
+        append(&path, "a1\n");
+        let mut tailer = Tailer::open(path.to_str().unwrap(), None, true);
+        assert_eq!(texts(&tailer.read_lines().unwrap()), vec!["a1"]);
+        let old_id = tailer.position().unwrap().file_id;
+
+        // Written after the last poll, then rotated; the unterminated last line is still shipped
+        append(&path, "a2\na3");
+        std::fs::rename(&path, dir.join("app.log.1")).unwrap();
+        append(&path, "b1\n");
+
+        let lines = tailer.read_lines().unwrap();
+        assert_eq!(texts(&lines), vec!["a2", "a3", "b1"]);
+        assert_eq!(lines[0].file_id, old_id);
+        let position = tailer.position().unwrap();
+        assert_ne!(position.file_id, old_id);
+        assert_eq!((lines[2].file_id, position.offset), (position.file_id, 3));
+
+        // Between the rename and the new file, the current handle is kept
+        std::fs::rename(&path, dir.join("app.log.2")).unwrap();
+        assert!(tailer.read_lines().unwrap().is_empty());
+        assert_eq!(tailer.position(), Some(position));
+    }
+
+    #[test]
+    fn truncation_restarts_from_the_beginning() {
+        let dir = scratch("truncate");
+        let path = dir.join("app.log");
+        append(&path, "first line\nsecond line\n");
+        let mut tailer = Tailer::open(path.to_str().unwrap(), None, true);
+        assert_eq!(tailer.read_lines().unwrap().len(), 2);
+        let id = tailer.position().unwrap().file_id;
+
+        // copytruncate keeps the inode
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(0).unwrap();
+        append(&path, "new\n");
+        let lines = tailer.read_lines().unwrap();
+        assert_eq!(texts(&lines), vec!["new"]);
+        assert_eq!(lines[0].offset, 0);
+        assert_eq!(tailer.position().unwrap(), Position { file_id: id, offset: 4 });
+    }
+}
@@ -0,0 +1,634 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Digest};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::country_policy;
+use crate::evidence::{self, CreateEventParams};
+use crate::review_queue;
+use crate::shield::{classify_country, country_name};
+
+const SOURCE_SYSTEM: &str = "country-policy";
+
+pub const LIST_TYPES: &[&str] = &["adequate", "blocked", "scc_required", "classifications"];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryListImportRow {
+    pub id: Uuid,
+    pub source_name: String,
+    pub list_type: String,
+    pub format: String,
+    pub content_hash: String,
+    pub diff: serde_json::Value,
+    pub status: String,
+    pub seal_id: Option<String>,
+    pub evidence_event_id: Option<String>,
+    pub created_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedEntry {
+    pub country_code: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffEntry {
+    pub country_code: String,
+    pub country_name: String,
+    pub current_status: String,
+    pub proposed_status: String,
+    pub change: String,
+}
+
+/// Map the status names used in official lists and internal policy files onto classification statuses.
+pub fn normalise_status(input: &str) -> Option<&'static str> {
+    match input.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+        "eu" | "eea" | "eu_eea" => Some("eu_eea"),
+        "adequate" | "adequacy" | "adequate_protection" => Some("adequate_protection"),
+        "scc" | "scc_required" => Some("scc_required"),
+        "blocked" | "sanctioned" | "prohibited" => Some("blocked"),
+        "unknown" => Some("unknown"),
+        _ => None,
+    }
+}
+
+fn list_status(list_type: &str) -> Option<&'static str> {
+    match list_type {
+        "adequate" => Some("adequate_protection"),
+        "blocked" => Some("blocked"),
+        "scc_required" => Some("scc_required"),
+        _ => None,
+    }
+}
+
+/// Status given to a country that drops off a list (e.g. losing adequacy falls back to SCCs).
+fn removal_status(list_type: &str) -> &'static str {
+    match list_type {
+        "adequate" => "scc_required",
+        _ => "unknown",
+    }
+}
+
+fn valid_code(code: &str) -> Option<String> {
+    let c = code.trim().trim_matches('"').to_uppercase();
+    (c.len() == 2 && c.chars().all(|ch| ch.is_ascii_alphabetic())).then_some(c)
+}
+
+fn entry_from(code: &str, status: Option<&str>, default_status: Option<&str>, errors: &mut Vec<String>) -> Option<ImportedEntry> {
+    let Some(country_code) = valid_code(code) else {
+        errors.push(format!("Invalid country code: {}", code));
+        return None;
+    };
+    let status = match status.filter(|s| !s.trim().is_empty()) {
+        Some(s) => match normalise_status(s) {
+            Some(st) => st,
+            None => {
+                errors.push(format!("{}: unknown status '{}'", country_code, s));
+                return None;
+            }
+        },
+        None => match default_status {
+            Some(st) => st,
+            None => {
+                errors.push(format!("{}: status required for a classifications import", country_code));
+                return None;
+            }
+        },
+    };
+    Some(ImportedEntry { country_code, status: status.to_string() })
+}
+
+fn parse_json(content: &str, default_status: Option<&str>, errors: &mut Vec<String>) -> Result<Vec<ImportedEntry>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let mut entries = Vec::new();
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                match item {
+                    serde_json::Value::String(code) => {
+                        entries.extend(entry_from(&code, None, default_status, errors));
+                    }
+                    serde_json::Value::Object(obj) => {
+                        let code = ["code", "country_code", "countryCode"].iter()
+                            .find_map(|k| obj.get(*k).and_then(|v| v.as_str()))
+                            .unwrap_or("");
+                        let status = obj.get("status").and_then(|v| v.as_str());
+                        entries.extend(entry_from(code, status, default_status, errors));
+                    }
+                    other => errors.push(format!("Unsupported entry: {}", other)),
+                }
+            }
+        }
+        // {"adequate": ["JP", ...], "blocked": [...]} keyed by status
+        serde_json::Value::Object(obj) => {
+            for (status, codes) in obj {
+                let Some(codes) = codes.as_array() else {
+                    errors.push(format!("'{}' must be an array of country codes", status));
+                    continue;
+                };
+                for code in codes.iter().filter_map(|c| c.as_str()) {
+                    entries.extend(entry_from(code, Some(&status), default_status, errors));
+                }
+            }
+        }
+        _ => return Err("JSON must be an array or an object keyed by status".into()),
+    }
+    Ok(entries)
+}
+
+const CODE_COLUMNS: &[&str] = &["code", "country_code", "iso2", "iso_code"];
+const STATUS_COLUMNS: &[&str] = &["status", "classification"];
+
+fn parse_csv(content: &str, default_status: Option<&str>, errors: &mut Vec<String>) -> Vec<ImportedEntry> {
+    let mut entries = Vec::new();
+    let mut code_idx = 0;
+    let mut status_idx: Option<usize> = None;
+    let mut first_row = true;
+
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split([',', ';']).map(|c| c.trim().trim_matches('"')).collect();
+        // Only a row naming a known column is a header; anything else is data and reported if invalid
+        let is_header = first_row && cols.iter().any(|c| {
+            let c = c.to_lowercase();
+            CODE_COLUMNS.contains(&c.as_str()) || STATUS_COLUMNS.contains(&c.as_str())
+        });
+        first_row = false;
+        if is_header {
+            // Header row: locate the code and status columns
+            for (i, col) in cols.iter().enumerate() {
+                let col = col.to_lowercase();
+                if CODE_COLUMNS.contains(&col.as_str()) {
+                    code_idx = i;
+                } else if STATUS_COLUMNS.contains(&col.as_str()) {
+                    status_idx = Some(i);
+                }
+            }
+            continue;
+        }
+        let code = cols.get(code_idx).copied().unwrap_or("");
+        let status = status_idx.and_then(|i| cols.get(i).copied());
+        if let Some(entry) = entry_from(code, status, default_status, errors) {
+            entries.push(entry);
+        } else if let Some(last) = errors.last_mut() {
+            *last = format!("line {}: {}", line_no + 1, last);
+        }
+    }
+    entries
+}
+
+/// Parse a JSON or CSV country list. For `adequate` / `blocked` / `scc_required` lists a bare list of
+/// ISO codes is enough; `classifications` files must carry a status per country.
+pub fn parse_list(content: &str, format: &str, list_type: &str) -> Result<(Vec<ImportedEntry>, Vec<String>), String> {
+    if !LIST_TYPES.contains(&list_type) {
+        return Err(format!("Invalid list type: {}", list_type));
+    }
+    let default_status = list_status(list_type);
+    let mut errors = Vec::new();
+    let entries = match format {
+        "json" => parse_json(content, default_status, &mut errors)?,
+        "csv" => parse_csv(content, default_status, &mut errors),
+        other => return Err(format!("Unsupported format: {}", other)),
+    };
+    Ok((entries, errors))
+}
+
+/// Diff imported entries against the classification currently in force.
+pub async fn compute_diff(pool: &PgPool, list_type: &str, entries: &[ImportedEntry]) -> Vec<DiffEntry> {
+    let current: HashMap<String, String> = country_policy::effective_classifications(pool).await
+        .into_iter()
+        .filter_map(|c| {
+            let code = c.get("code")?.as_str()?.to_string();
+            let status = c.get("status")?.as_str()?.to_string();
+            Some((code, status))
+        })
+        .collect();
+    let current_of = |code: &str| current.get(code).cloned().unwrap_or_else(|| "unknown".into());
+
+    let mut proposed: BTreeMap<String, (String, &'static str)> = BTreeMap::new();
+    for entry in entries {
+        proposed.insert(entry.country_code.clone(), (entry.status.clone(), "reclassified"));
+    }
+
+    // List imports are authoritative for their status: anything no longer listed drops off.
+    if let Some(status) = list_status(list_type) {
+        for (code, current_status) in &current {
+            if current_status == status && !proposed.contains_key(code) {
+                proposed.insert(code.clone(), (removal_status(list_type).to_string(), "removed"));
+            }
+        }
+    }
+
+    proposed.into_iter()
+        .filter_map(|(code, (status, change))| {
+            let current_status = current_of(&code);
+            if current_status == status {
+                return None;
+            }
+            // EU/EEA membership is not something an adequacy or sanctions list can change.
+            if current_status == "eu_eea" && list_type != "classifications" {
+                return None;
+            }
+            Some(DiffEntry {
+                country_name: country_name(&code),
+                country_code: code,
+                current_status,
+                proposed_status: status,
+                change: change.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub struct CreateImportParams {
+    pub source_name: String,
+    pub list_type: String,
+    pub format: String,
+    pub content: String,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub import: CountryListImportRow,
+    pub diff: Vec<DiffEntry>,
+    pub errors: Vec<String>,
+}
+
+/// Parse and diff a country list, then open a review item; nothing is applied until the review is approved.
+/// The import, its evidence and its review commit together, so a pending import always has a review.
+pub async fn create_import(pool: &PgPool, params: CreateImportParams) -> Result<ImportSummary, String> {
+    let format = params.format.to_lowercase();
+    let (entries, errors) = parse_list(&params.content, &format, &params.list_type)?;
+    if entries.is_empty() {
+        return Err(format!("No valid entries found ({} error(s))", errors.len()));
+    }
+    let diff = compute_diff(pool, &params.list_type, &entries).await;
+
+    let mut hasher = Sha256::new();
+    hasher.update(params.content.as_bytes());
+    let content_hash = format!("{:x}", hasher.finalize());
+    let diff_json = serde_json::to_value(&diff).unwrap_or_default();
+    let status = if diff.is_empty() { "no_changes" } else { "pending" };
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut row = sqlx::query_as::<_, CountryListImportRow>(
+        r#"INSERT INTO country_list_imports
+            (source_name, list_type, format, content_hash, diff, status, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING *"#
+    )
+    .bind(&params.source_name)
+    .bind(&params.list_type)
+    .bind(&format)
+    .bind(&content_hash)
+    .bind(&diff_json)
+    .bind(status)
+    .bind(&params.created_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store country list import: {}", e))?;
+
+    if diff.is_empty() {
+        tx.commit().await.map_err(|e| format!("Failed to commit country list import: {}", e))?;
+        return Ok(ImportSummary { import: row, diff, errors });
+    }
+
+    let event = evidence::create_event_in_tx(&mut tx, CreateEventParams {
+        event_type: "COUNTRY_LIST_IMPORT_PROPOSED".into(),
+        severity: "L2".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 45".into()],
+        payload: serde_json::json!({
+            "import_id": row.id.to_string(),
+            "source_name": row.source_name,
+            "list_type": row.list_type,
+            "content_hash": content_hash,
+            "diff": diff_json,
+        }),
+        correlation_id: Some(row.id.to_string()),
+        causation_id: None,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await?;
+
+    let action = format!("apply_country_list_import: {} change(s) from {}", diff.len(), row.source_name);
+    let seal_id = review_queue::create_review_in_tx(
+        &mut tx,
+        "country-import",
+        &action,
+        SOURCE_SYSTEM,
+        &serde_json::json!({
+            "import_id": row.id.to_string(),
+            "list_type": row.list_type,
+            "diff": diff_json,
+        }),
+        &event.event_id,
+    ).await?;
+
+    sqlx::query("UPDATE country_list_imports SET seal_id = $1, evidence_event_id = $2 WHERE id = $3")
+        .bind(&seal_id)
+        .bind(&event.event_id)
+        .bind(row.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to link import review: {}", e))?;
+    tx.commit().await.map_err(|e| format!("Failed to commit country list import: {}", e))?;
+    row.seal_id = Some(seal_id);
+    row.evidence_event_id = Some(event.event_id);
+
+    Ok(ImportSummary { import: row, diff, errors })
+}
+
+/// Called when a review item is decided: approving an import's review applies its diff. The import's
+/// status, its overrides and the evidence event commit together, so a failure leaves it pending for
+/// `apply_decided` to retry.
+/// Entries are checked against the classification in force at approval: one whose country changed
+/// since the diff was reviewed is skipped (and listed in the evidence) rather than applied blind.
+pub async fn on_review_decided(pool: &PgPool, seal_id: &str, ho_status: &str) -> Result<(), String> {
+    let new_status = if ho_status == "APPROVED" { "applied" } else { "rejected" };
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let row = sqlx::query_as::<_, CountryListImportRow>(
+        r#"UPDATE country_list_imports SET status = $1, decided_at = NOW()
+           WHERE seal_id = $2 AND status = 'pending'
+           RETURNING *"#
+    )
+    .bind(new_status)
+    .bind(seal_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update country list import: {}", e))?;
+
+    let Some(row) = row else {
+        return Ok(());
+    };
+    if new_status != "applied" {
+        tx.commit().await.map_err(|e| format!("Failed to reject country list import: {}", e))?;
+        return Ok(());
+    }
+
+    let diff: Vec<DiffEntry> = serde_json::from_value(row.diff.clone()).unwrap_or_default();
+    let codes: Vec<String> = diff.iter().map(|d| d.country_code.clone()).collect();
+    // Locked so a scheduled change cannot land between this check and the write
+    let overrides: HashMap<String, String> = sqlx::query_as::<_, (String, String)>(
+        "SELECT country_code, status FROM country_policy_overrides WHERE country_code = ANY($1) FOR UPDATE"
+    )
+    .bind(&codes)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to read country overrides: {}", e))?
+    .into_iter()
+    .collect();
+
+    let reason = format!("Imported from {} ({})", row.source_name, row.list_type);
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in &diff {
+        let current = overrides.get(&entry.country_code)
+            .cloned()
+            .unwrap_or_else(|| classify_country(&entry.country_code).to_string());
+        if current != entry.current_status {
+            skipped.push(serde_json::json!({
+                "country_code": entry.country_code,
+                "reviewed_status": entry.current_status,
+                "current_status": current,
+                "proposed_status": entry.proposed_status,
+            }));
+            continue;
+        }
+        country_policy::set_override(&mut tx, &entry.country_code, &entry.proposed_status, Some(&reason), None).await?;
+        applied.push(entry);
+    }
+
+    evidence::create_event_in_tx(&mut tx, CreateEventParams {
+        event_type: "COUNTRY_LIST_IMPORT_APPLIED".into(),
+        severity: "L2".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 45".into()],
+        payload: serde_json::json!({
+            "import_id": row.id.to_string(),
+            "source_name": row.source_name,
+            "list_type": row.list_type,
+            "content_hash": row.content_hash,
+            "seal_id": seal_id,
+            "diff": row.diff,
+            "applied": applied,
+            "skipped_stale": skipped,
+        }),
+        correlation_id: Some(row.id.to_string()),
+        causation_id: row.evidence_event_id.clone(),
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await?;
+
+    tx.commit().await.map_err(|e| format!("Failed to commit country list import: {}", e))?;
+    for entry in &applied {
+        country_policy::revoke_permits(pool, &entry.country_code, &entry.proposed_status).await;
+    }
+
+    if !skipped.is_empty() {
+        log::warn!(
+            "Country list import {}: {} change(s) skipped because the country was reclassified since review",
+            row.id, skipped.len()
+        );
+    }
+    log::info!("Country list import {} applied: {} change(s)", row.id, applied.len());
+    Ok(())
+}
+
+/// Finish imports still `pending` although their review was decided (applying failed when the decision
+/// was made). Run by the country policy scheduler; returns how many were settled.
+pub async fn apply_decided(pool: &PgPool) -> Result<usize, String> {
+    let decided: Vec<(String, String)> = sqlx::query_as(
+        r#"SELECT ci.seal_id, ho.status FROM country_list_imports ci
+           JOIN human_oversight ho ON ho.seal_id = ci.seal_id
+           WHERE ci.status = 'pending' AND ho.status IN ('APPROVED', 'REJECTED')
+           ORDER BY ci.created_at"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to find decided country list imports: {}", e))?;
+
+    let mut settled = 0;
+    for (seal_id, ho_status) in decided {
+        match on_review_decided(pool, &seal_id, &ho_status).await {
+            Ok(()) => settled += 1,
+            Err(e) => log::error!("Failed to process country list import for review {}: {}", seal_id, e),
+        }
+    }
+    Ok(settled)
+}
+
+pub async fn list_imports(pool: &PgPool) -> Result<Vec<CountryListImportRow>, String> {
+    sqlx::query_as::<_, CountryListImportRow>("SELECT * FROM country_list_imports ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn get_import(pool: &PgPool, id: Uuid) -> Result<Option<CountryListImportRow>, String> {
+    sqlx::query_as::<_, CountryListImportRow>("SELECT * FROM country_list_imports WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// `veridion-api import-country-list <file> --list <adequate|blocked|scc_required|classifications> [--format json|csv] [--source NAME]`
+pub async fn run_cli(pool: &PgPool, args: &[String]) -> std::io::Result<()> {
+    let mut path: Option<&str> = None;
+    let mut list_type = "classifications".to_string();
+    let mut format: Option<String> = None;
+    let mut source: Option<String> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--list" => list_type = iter.next().cloned().unwrap_or_default(),
+            "--format" => format = iter.next().cloned(),
+            "--source" => source = iter.next().cloned(),
+            other => path = Some(other),
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("Usage: veridion-api import-country-list <file> --list <{}> [--format json|csv] [--source NAME]", LIST_TYPES.join("|"));
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing file"));
+    };
+    let content = std::fs::read_to_string(path)?;
+    let format = format.unwrap_or_else(|| {
+        if path.to_lowercase().ends_with(".csv") { "csv".into() } else { "json".into() }
+    });
+
+    let params = CreateImportParams {
+        source_name: source.unwrap_or_else(|| path.to_string()),
+        list_type,
+        format,
+        content,
+        created_by: Some("cli".into()),
+    };
+
+    match create_import(pool, params).await {
+        Ok(summary) => {
+            for err in &summary.errors {
+                println!("  skipped: {}", err);
+            }
+            if summary.diff.is_empty() {
+                println!("No changes against current classification.");
+            }
+            for d in &summary.diff {
+                println!("  {} {:<20} {} -> {} ({})", d.country_code, d.country_name, d.current_status, d.proposed_status, d.change);
+            }
+            if let Some(seal_id) = &summary.import.seal_id {
+                println!("Import {} awaiting review: {}", summary.import.id, seal_id);
+            }
+            Ok(())
+        }
+        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(entries: &[ImportedEntry]) -> Vec<(&str, &str)> {
+        entries.iter().map(|e| (e.country_code.as_str(), e.status.as_str())).collect()
+    }
+
+    #[test]
+    fn status_aliases() {
+        let cases = [
+            ("EU", Some("eu_eea")),
+            ("eu-eea", Some("eu_eea")),
+            (" Adequacy ", Some("adequate_protection")),
+            ("adequate protection", Some("adequate_protection")),
+            ("SCC", Some("scc_required")),
+            ("sanctioned", Some("blocked")),
+            ("Prohibited", Some("blocked")),
+            ("unknown", Some("unknown")),
+            ("whitelisted", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalise_status(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn json_array_of_codes_and_objects() {
+        let mut errors = Vec::new();
+        let content = r#"["jp", {"code": "KR"}, {"countryCode": "CH", "status": "EEA"}, {"country_code": "USA"}, 7]"#;
+        let entries = parse_json(content, Some("adequate_protection"), &mut errors).unwrap();
+        assert_eq!(pairs(&entries), [("JP", "adequate_protection"), ("KR", "adequate_protection"), ("CH", "eu_eea")]);
+        assert_eq!(errors, ["Invalid country code: USA", "Unsupported entry: 7"]);
+    }
+
+    #[test]
+    fn json_object_keyed_by_status() {
+        let mut errors = Vec::new();
+        let content = r#"{"adequate": ["JP"], "sanctioned": ["RU", "KP"], "friendly": ["FR"], "blocked": "IR"}"#;
+        let mut entries = parse_json(content, None, &mut errors).unwrap();
+        entries.sort_by(|a, b| a.country_code.cmp(&b.country_code));
+        assert_eq!(pairs(&entries), [("JP", "adequate_protection"), ("KP", "blocked"), ("RU", "blocked")]);
+        errors.sort();
+        assert_eq!(errors, ["'blocked' must be an array of country codes", "FR: unknown status 'friendly'"]);
+    }
+
+    #[test]
+    fn json_rejects_other_shapes() {
+        let mut errors = Vec::new();
+        assert!(parse_json(r#""JP""#, None, &mut errors).is_err());
+        assert!(parse_json("[JP", None, &mut errors).unwrap_err().starts_with("Invalid JSON"));
+        // Classifications need a status per country
+        let entries = parse_json(r#"["JP"]"#, None, &mut errors).unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(errors, ["JP: status required for a classifications import"]);
+    }
+
+    #[test]
+    fn csv_with_header() {
+        let mut errors = Vec::new();
+        let content = "# EU adequacy decisions\nname,ISO2,Classification\nJapan,jp,adequate\n\n\"Korea\",\"KR\",\"adequacy\"\nNowhere,XX1,adequate\nRussia,RU,friendly\n";
+        let entries = parse_csv(content, None, &mut errors);
+        assert_eq!(pairs(&entries), [("JP", "adequate_protection"), ("KR", "adequate_protection")]);
+        assert_eq!(errors, ["line 6: Invalid country code: XX1", "line 7: RU: unknown status 'friendly'"]);
+    }
+
+    #[test]
+    fn csv_semicolons_without_header() {
+        let mut errors = Vec::new();
+        let entries = parse_csv("JP;ignored\n \"CH\" ; x\nJapan\n", Some("blocked"), &mut errors);
+        assert_eq!(pairs(&entries), [("JP", "blocked"), ("CH", "blocked")]);
+        // Without a header the first column is the code and every row is data
+        assert_eq!(errors, ["line 3: Invalid country code: Japan"]);
+    }
+
+    #[test]
+    fn parse_list_checks_type_and_format() {
+        assert!(parse_list("JP", "csv", "friendly").is_err());
+        assert!(parse_list("JP", "xml", "adequate").is_err());
+        let (entries, errors) = parse_list("code\nJP\n", "csv", "adequate").unwrap();
+        assert_eq!(pairs(&entries), [("JP", "adequate_protection")]);
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,366 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::evidence::{self, CreateEventParams};
+use crate::shield::{all_country_classifications, classify_country, country_name};
+use crate::transfer_permits;
+
+pub const COUNTRY_STATUSES: &[&str] = &["eu_eea", "adequate_protection", "scc_required", "blocked", "unknown"];
+/// Statuses under which a transfer needs no safeguard of its own (EU/EEA or an adequacy decision).
+pub const UNRESTRICTED_STATUSES: &[&str] = &["eu_eea", "adequate_protection"];
+
+const SOURCE_SYSTEM: &str = "country-policy";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryPolicyChangeRow {
+    pub id: Uuid,
+    pub country_code: String,
+    pub new_status: String,
+    pub effective_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub legal_reference: Option<String>,
+    pub status: String,
+    pub created_by: Option<String>,
+    pub notified_at: Option<DateTime<Utc>>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub previous_status: Option<String>,
+    pub evidence_event_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingChange {
+    pub id: Uuid,
+    pub country_code: String,
+    pub country_name: String,
+    pub current_status: String,
+    pub new_status: String,
+    pub effective_at: String,
+    pub days_until: i64,
+    pub reason: Option<String>,
+    pub legal_reference: Option<String>,
+}
+
+/// Overrides applied by scheduled changes or approved imports, keyed by upper-case country code.
+pub async fn load_overrides(pool: &PgPool) -> Result<HashMap<String, String>, String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT country_code, status FROM country_policy_overrides"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load country overrides: {}", e))?;
+    Ok(rows.into_iter().map(|(c, s)| (c.to_uppercase(), s)).collect())
+}
+
+/// Classification currently in force for a country: DB override if present, otherwise the built-in list.
+pub async fn effective_status(pool: &PgPool, code: &str) -> String {
+    let upper = code.to_uppercase();
+    let row: Result<Option<String>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT status FROM country_policy_overrides WHERE country_code = $1"
+    )
+    .bind(&upper)
+    .fetch_optional(pool)
+    .await;
+    match row {
+        Ok(Some(status)) => status,
+        Ok(None) => classify_country(&upper).to_string(),
+        Err(e) => {
+            log::error!("Country override lookup error: {}", e);
+            classify_country(&upper).to_string()
+        }
+    }
+}
+
+/// All country classifications with overrides applied (countries only known via overrides are appended).
+pub async fn effective_classifications(pool: &PgPool) -> Vec<serde_json::Value> {
+    let overrides = load_overrides(pool).await.unwrap_or_else(|e| {
+        log::error!("{}", e);
+        HashMap::new()
+    });
+    let mut countries = all_country_classifications();
+    for country in &mut countries {
+        let code = country.get("code").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        if let Some(status) = overrides.get(&code) {
+            country["status"] = serde_json::json!(status);
+        }
+    }
+    let mut extra: Vec<&String> = overrides.keys()
+        .filter(|code| !countries.iter().any(|c| c.get("code").and_then(|v| v.as_str()) == Some(code.as_str())))
+        .collect();
+    extra.sort();
+    for code in extra {
+        countries.push(serde_json::json!({
+            "code": code,
+            "name": country_name(code),
+            "status": overrides[code],
+        }));
+    }
+    countries
+}
+
+/// Write (or replace) the override for a country inside the caller's transaction. Once it commits,
+/// call `revoke_permits` so permits issued under the old status stop verifying.
+pub async fn set_override(
+    tx: &mut Transaction<'_, Postgres>,
+    country_code: &str,
+    status: &str,
+    reason: Option<&str>,
+    source_change_id: Option<Uuid>,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"INSERT INTO country_policy_overrides (country_code, status, reason, source_change_id)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT (country_code) DO UPDATE SET
+             status = EXCLUDED.status,
+             reason = EXCLUDED.reason,
+             source_change_id = EXCLUDED.source_change_id"#
+    )
+    .bind(country_code.to_uppercase())
+    .bind(status)
+    .bind(reason)
+    .bind(source_change_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to set country override: {}", e))?;
+    Ok(())
+}
+
+/// Revoke the still-valid permits a committed status change invalidates; failures are logged.
+pub async fn revoke_permits(pool: &PgPool, country_code: &str, status: &str) {
+    let reason = format!("{} is now {}", country_code.to_uppercase(), status);
+    match transfer_permits::revoke_for_country(pool, country_code, status, &reason).await {
+        Ok(n) if n > 0 => log::info!("{}: {} transfer permit(s) revoked", reason, n),
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to revoke transfer permits for {}: {}", country_code, e),
+    }
+}
+
+pub struct ScheduleChangeParams {
+    pub country_code: String,
+    pub new_status: String,
+    pub effective_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub legal_reference: Option<String>,
+    pub created_by: Option<String>,
+}
+
+pub async fn schedule_change(pool: &PgPool, params: ScheduleChangeParams) -> Result<CountryPolicyChangeRow, String> {
+    let code = params.country_code.trim().to_uppercase();
+    if code.len() != 2 {
+        return Err(format!("Invalid country code: {}", params.country_code));
+    }
+    if !COUNTRY_STATUSES.contains(&params.new_status.as_str()) {
+        return Err(format!("Invalid country status: {}", params.new_status));
+    }
+
+    sqlx::query_as::<_, CountryPolicyChangeRow>(
+        r#"INSERT INTO country_policy_changes
+            (country_code, new_status, effective_at, reason, legal_reference, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           RETURNING *"#
+    )
+    .bind(&code)
+    .bind(&params.new_status)
+    .bind(params.effective_at)
+    .bind(&params.reason)
+    .bind(&params.legal_reference)
+    .bind(&params.created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to schedule country policy change: {}", e))
+}
+
+pub async fn list_changes(pool: &PgPool, status: Option<&str>) -> Result<Vec<CountryPolicyChangeRow>, String> {
+    let query = match status {
+        Some(s) => sqlx::query_as::<_, CountryPolicyChangeRow>(
+            "SELECT * FROM country_policy_changes WHERE status = $1 ORDER BY effective_at ASC"
+        ).bind(s.to_string()),
+        None => sqlx::query_as::<_, CountryPolicyChangeRow>(
+            "SELECT * FROM country_policy_changes ORDER BY effective_at ASC"
+        ),
+    };
+    query.fetch_all(pool).await.map_err(|e| e.to_string())
+}
+
+pub async fn cancel_change(pool: &PgPool, id: Uuid) -> Result<bool, String> {
+    let result = sqlx::query(
+        "UPDATE country_policy_changes SET status = 'cancelled' WHERE id = $1 AND status = 'scheduled'"
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to cancel country policy change: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Scheduled changes taking effect within the next `days` days, with the status they will replace.
+pub async fn upcoming_changes(pool: &PgPool, days: i64) -> Result<Vec<UpcomingChange>, String> {
+    let now = Utc::now();
+    let rows = sqlx::query_as::<_, CountryPolicyChangeRow>(
+        r#"SELECT * FROM country_policy_changes
+           WHERE status = 'scheduled' AND effective_at <= $1
+           ORDER BY effective_at ASC"#
+    )
+    .bind(now + Duration::days(days))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        items.push(UpcomingChange {
+            id: row.id,
+            country_name: country_name(&row.country_code),
+            current_status: effective_status(pool, &row.country_code).await,
+            days_until: (row.effective_at - now).num_days().max(0),
+            effective_at: row.effective_at.to_rfc3339(),
+            country_code: row.country_code,
+            new_status: row.new_status,
+            reason: row.reason,
+            legal_reference: row.legal_reference,
+        });
+    }
+    Ok(items)
+}
+
+/// Apply every scheduled change whose effective date has passed and seal the transition in evidence.
+/// A change that fails stays scheduled and is retried on the next run.
+pub async fn apply_due_changes(pool: &PgPool) -> Result<usize, String> {
+    let due = sqlx::query_as::<_, CountryPolicyChangeRow>(
+        r#"SELECT * FROM country_policy_changes
+           WHERE status = 'scheduled' AND effective_at <= NOW()
+           ORDER BY effective_at ASC"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load due country policy changes: {}", e))?;
+
+    let mut applied = 0;
+    for change in due {
+        match apply_change(pool, &change).await {
+            Ok(true) => applied += 1,
+            Ok(false) => {}
+            Err(e) => log::error!("Failed to apply country policy change {}: {}", change.id, e),
+        }
+    }
+    Ok(applied)
+}
+
+/// Claim, apply and seal one change in a single transaction, so it is never reported applied without
+/// its override and evidence. The claim is a conditional UPDATE, so concurrent API instances never
+/// apply it twice. False when another instance got there first.
+async fn apply_change(pool: &PgPool, change: &CountryPolicyChangeRow) -> Result<bool, String> {
+    let previous_status = effective_status(pool, &change.country_code).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let claimed = sqlx::query(
+        r#"UPDATE country_policy_changes
+           SET status = 'applied', applied_at = NOW(), previous_status = $2
+           WHERE id = $1 AND status = 'scheduled'"#
+    )
+    .bind(change.id)
+    .bind(&previous_status)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to claim country policy change: {}", e))?;
+    if claimed.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    set_override(&mut tx, &change.country_code, &change.new_status, change.reason.as_deref(), Some(change.id)).await?;
+
+    let event = evidence::create_event_in_tx(&mut tx, CreateEventParams {
+        event_type: "COUNTRY_POLICY_CHANGED".into(),
+        severity: "L2".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 45".into()],
+        payload: serde_json::json!({
+            "change_id": change.id.to_string(),
+            "country_code": change.country_code,
+            "country_name": country_name(&change.country_code),
+            "previous_status": previous_status,
+            "new_status": change.new_status,
+            "effective_at": change.effective_at.to_rfc3339(),
+            "reason": change.reason,
+            "legal_reference": change.legal_reference,
+        }),
+        correlation_id: Some(change.id.to_string()),
+        causation_id: None,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await?;
+    sqlx::query("UPDATE country_policy_changes SET evidence_event_id = $1 WHERE id = $2")
+        .bind(&event.event_id)
+        .bind(change.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to link country policy change evidence: {}", e))?;
+
+    tx.commit().await.map_err(|e| format!("Failed to commit country policy change: {}", e))?;
+    revoke_permits(pool, &change.country_code, &change.new_status).await;
+
+    log::info!(
+        "Country policy change applied: {} {} -> {}",
+        change.country_code, previous_status, change.new_status
+    );
+    Ok(true)
+}
+
+/// Raise a one-off advance notice for changes taking effect within `notice_days`.
+pub async fn notify_upcoming(pool: &PgPool, notice_days: i64) -> Result<usize, String> {
+    let rows = sqlx::query_as::<_, CountryPolicyChangeRow>(
+        r#"UPDATE country_policy_changes SET notified_at = NOW()
+           WHERE status = 'scheduled' AND notified_at IS NULL AND effective_at <= $1
+           RETURNING *"#
+    )
+    .bind(Utc::now() + Duration::days(notice_days))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to mark upcoming country policy changes: {}", e))?;
+
+    for change in &rows {
+        let current_status = effective_status(pool, &change.country_code).await;
+        log::warn!(
+            "Upcoming country policy change: {} {} -> {} on {}",
+            change.country_code, current_status, change.new_status, change.effective_at.to_rfc3339()
+        );
+
+        let params = CreateEventParams {
+            event_type: "COUNTRY_POLICY_CHANGE_UPCOMING".into(),
+            severity: "L1".into(),
+            source_system: SOURCE_SYSTEM.into(),
+            regulatory_tags: vec!["GDPR".into()],
+            articles: vec!["GDPR Art. 45".into()],
+            payload: serde_json::json!({
+                "change_id": change.id.to_string(),
+                "country_code": change.country_code,
+                "country_name": country_name(&change.country_code),
+                "current_status": current_status,
+                "new_status": change.new_status,
+                "effective_at": change.effective_at.to_rfc3339(),
+                "reason": change.reason,
+                "legal_reference": change.legal_reference,
+            }),
+            correlation_id: Some(change.id.to_string()),
+            causation_id: None,
+            source_ip: None,
+            source_user_agent: None,
+            occurred_at: None,
+            idempotency_key: None,
+        };
+        if let Err(e) = evidence::create_event(pool, params).await {
+            log::error!("Failed to create evidence for upcoming change {}: {}", change.id, e);
+        }
+    }
+    Ok(rows.len())
+}
@@ -21,6 +21,28 @@ fn random_bytes(n: usize) -> Vec<u8> {
     buf
 }
 
+/// Encrypt a secret under the master key; the 12-byte nonce is prepended to the ciphertext.
+pub fn wrap_with_master_key(secret: &[u8]) -> Result<Vec<u8>, String> {
+    let master_cipher = Aes256Gcm::new_from_slice(&get_master_key())
+        .map_err(|e| format!("Master cipher init: {}", e))?;
+    let mut wrapped = random_bytes(12);
+    let ciphertext = master_cipher.encrypt(Nonce::from_slice(&wrapped), secret)
+        .map_err(|e| format!("Key wrapping failed: {}", e))?;
+    wrapped.extend_from_slice(&ciphertext);
+    Ok(wrapped)
+}
+
+pub fn unwrap_with_master_key(wrapped: &[u8]) -> Result<Vec<u8>, String> {
+    if wrapped.len() < 12 {
+        return Err("Wrapped key is too short".into());
+    }
+    let master_cipher = Aes256Gcm::new_from_slice(&get_master_key())
+        .map_err(|e| format!("Master cipher init: {}", e))?;
+    let (nonce, ciphertext) = wrapped.split_at(12);
+    master_cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Key unwrapping failed (was VERIDION_MASTER_KEY changed?)".to_string())
+}
+
 pub struct EncryptedLog {
     pub log_id: String,
     pub ciphertext: Vec<u8>,
@@ -0,0 +1,211 @@
+//! Browser-side transfers from Content Security Policy violation reports: the legacy `report-uri`
+//! body (`{"csp-report": {...}}`) and Reporting API batches (`application/reports+json`, entries
+//! of type `csp-violation`). Each report names a third-party origin that a page loaded (report-only
+//! policy) or tried to load (enforced policy); either way the page sends visitor data to that host.
+
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::log_formats::{host_of, path_of};
+use crate::host_map;
+use crate::routes_shield::IngestLogEntry;
+
+/// Categories every third-party request from a browser carries: the visitor's IP address, user agent
+/// and referring page.
+pub const DEFAULT_CATEGORIES: &[&str] = &["online_identifier", "usage_data"];
+
+/// Report requests are counted per client address over this window.
+const RATE_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+/// Beyond this many tracked clients, finished windows are forgotten.
+const RATE_TRACKED_CLIENTS: usize = 10_000;
+
+/// Client address → (window start, requests in the window).
+type RateWindows = HashMap<IpAddr, (Instant, u32)>;
+static RATE_WINDOWS: Mutex<Option<RateWindows>> = Mutex::new(None);
+
+/// One violation, normalised across both report formats.
+#[derive(Debug, Default)]
+pub struct Violation {
+    pub document_url: Option<String>,
+    pub blocked_url: Option<String>,
+    pub directive: Option<String>,
+    pub disposition: Option<String>,
+    pub user_agent: Option<String>,
+    /// Reporting API: milliseconds between the violation and the upload.
+    pub age_ms: Option<u64>,
+}
+
+fn text(obj: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|k| obj.get(*k)?.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Violations in a report body. Reporting API entries of other types (deprecation, network-error, ...)
+/// are counted as ignored.
+pub fn parse(body: &Value) -> Result<(Vec<Violation>, usize), String> {
+    if let Some(report) = body.get("csp-report") {
+        return Ok((vec![Violation {
+            document_url: text(report, &["document-uri"]),
+            blocked_url: text(report, &["blocked-uri"]),
+            directive: text(report, &["effective-directive", "violated-directive"]),
+            disposition: text(report, &["disposition"]),
+            ..Default::default()
+        }], 0));
+    }
+    let reports = match body {
+        Value::Array(reports) => reports.as_slice(),
+        Value::Object(_) => std::slice::from_ref(body),
+        _ => return Err("expected a csp-report object or a Reporting API array".into()),
+    };
+    let mut violations = Vec::new();
+    let mut ignored = 0;
+    for report in reports {
+        if report.get("type").and_then(Value::as_str) != Some("csp-violation") {
+            ignored += 1;
+            continue;
+        }
+        let Some(inner) = report.get("body") else {
+            ignored += 1;
+            continue;
+        };
+        violations.push(Violation {
+            document_url: text(inner, &["documentURL", "documentURI"]).or_else(|| text(report, &["url"])),
+            blocked_url: text(inner, &["blockedURL", "blockedURI"]),
+            directive: text(inner, &["effectiveDirective", "violatedDirective"]),
+            disposition: text(inner, &["disposition"]),
+            user_agent: text(report, &["user_agent"]),
+            age_ms: report.get("age").and_then(Value::as_u64),
+        });
+    }
+    Ok((violations, ignored))
+}
+
+/// `CSP_REPORT_WEBSITES`: comma-separated hosts (`*.example.com` for subdomains) whose reports are
+/// accepted and whose origins may post cross-origin. Unset or empty accepts none.
+fn website_patterns() -> Vec<String> {
+    std::env::var("CSP_REPORT_WEBSITES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// The receiver is off until at least one website is configured.
+pub fn enabled() -> bool {
+    !website_patterns().is_empty()
+}
+
+pub fn website_allowed(website: &str) -> bool {
+    website_patterns().iter().any(|p| match p.strip_prefix("*.") {
+        Some(suffix) => website == suffix || website.ends_with(&format!(".{}", suffix)),
+        None => website == p,
+    })
+}
+
+/// `CSP_REPORT_RATE_LIMIT`: report requests accepted per client address per minute (default 60).
+fn rate_limit() -> u32 {
+    std::env::var("CSP_REPORT_RATE_LIMIT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(60)
+        .max(1)
+}
+
+/// Count one request from `client`. Over the limit, returns the seconds until its window resets.
+pub fn check_rate(client: IpAddr) -> Result<(), u64> {
+    let now = Instant::now();
+    let limit = rate_limit();
+    let mut guard = RATE_WINDOWS.lock().unwrap_or_else(|e| e.into_inner());
+    let windows = guard.get_or_insert_with(HashMap::new);
+    if windows.len() >= RATE_TRACKED_CLIENTS && !windows.contains_key(&client) {
+        windows.retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
+    }
+    let (start, count) = windows.entry(client).or_insert((now, 0));
+    if now.duration_since(*start) >= RATE_WINDOW {
+        *start = now;
+        *count = 0;
+    }
+    if *count >= limit {
+        return Err((RATE_WINDOW - now.duration_since(*start)).as_secs().max(1));
+    }
+    *count += 1;
+    Ok(())
+}
+
+/// Registrable domain, approximately: the last two labels, or three under a two-letter country TLD
+/// with a short second level (`example.co.uk`).
+fn site_of(host: &str) -> String {
+    let labels: Vec<&str> = host.rsplit('.').collect();
+    let keep = match labels.as_slice() {
+        [tld, second, _, ..] if tld.len() == 2 && second.len() <= 3 => 3,
+        _ => 2,
+    };
+    let mut site: Vec<&str> = labels.into_iter().take(keep).collect();
+    site.reverse();
+    site.join(".")
+}
+
+/// Hosts on the website's own domain (`cdn.example.com` on `www.example.com`) are first party.
+fn is_first_party(host: &str, website: &str) -> bool {
+    host == website || (host.parse::<IpAddr>().is_err() && site_of(host) == site_of(website))
+}
+
+/// A third-party origin found in a report.
+pub struct ReportedOrigin {
+    pub host: String,
+    pub entry: IngestLogEntry,
+}
+
+/// Map a violation onto an ingest entry, or say why it is not a third-party transfer. `website`
+/// overrides the document's host as the tag recorded in `source_system`.
+pub fn origin(violation: &Violation, website: Option<&str>, categories: &[String]) -> Result<ReportedOrigin, String> {
+    let blocked = violation.blocked_url.as_deref().ok_or("no blocked URL")?;
+    // `inline`, `eval`, `data:`, `blob:`, extension schemes and the like load nothing from a remote host
+    let scheme = blocked.split_once("://").map(|(s, _)| s.to_lowercase())
+        .ok_or_else(|| format!("not a network load: {}", blocked))?;
+    if !["http", "https", "ws", "wss"].contains(&scheme.as_str()) {
+        return Err(format!("not a network load: {}", blocked));
+    }
+    let host = host_of(blocked).ok_or_else(|| format!("no host in {}", blocked))?;
+    let website = website
+        .map(|w| w.trim().to_lowercase())
+        .or_else(|| violation.document_url.as_deref().and_then(host_of))
+        .ok_or("no document URL and no website given")?;
+    if !website_allowed(&website) {
+        return Err(format!("website {} is not monitored", website));
+    }
+    if is_first_party(&host, &website) {
+        return Err(format!("first-party origin {}", host));
+    }
+
+    let mapped = host_map::lookup_env(&host);
+    let directive = violation.directive.clone().unwrap_or_default();
+    let disposition = violation.disposition.clone().unwrap_or_else(|| "enforce".into());
+    let occurred_at = Utc::now() - Duration::milliseconds(violation.age_ms.unwrap_or(0).min(86_400_000) as i64);
+    // Browsers repeat a report on every page view; one entry per origin and hour is enough evidence
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::json!([website, host, directive, disposition, occurred_at.format("%Y-%m-%dT%H").to_string()]).to_string());
+    let entry = IngestLogEntry {
+        dest_ip: host.parse::<IpAddr>().ok().map(|ip| ip.to_string()),
+        protocol: Some(scheme),
+        timestamp: Some(occurred_at.to_rfc3339()),
+        user_agent: violation.user_agent.clone(),
+        request_path: path_of(blocked),
+        destination_country_code: mapped.as_ref().map(|(code, _)| code.clone()),
+        data_categories: Some(categories.to_vec()),
+        partner_name: mapped.and_then(|(_, partner)| partner).or_else(|| Some(host.clone())),
+        source_system: Some(website),
+        external_id: Some(format!("csp:{:x}", hasher.finalize())),
+        ..Default::default()
+    };
+    Ok(ReportedOrigin { host, entry })
+}
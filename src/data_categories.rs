@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCategoryRow {
+    pub id: Uuid,
+    pub code: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub synonyms: Vec<String>,
+    pub sensitivity_tier: String,
+    pub special_category: bool,
+    pub criminal_data: bool,
+    pub anonymised: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Result of normalising free-form category strings against the taxonomy.
+/// Unrecognised inputs are kept (in normalised form) so they still count as personal data.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalisedCategories {
+    pub categories: Vec<String>,
+    pub unrecognised: Vec<String>,
+    pub special_category: bool,
+    pub criminal_data: bool,
+    pub all_anonymised: bool,
+    pub highest_tier: String,
+}
+
+impl NormalisedCategories {
+    /// Art. 9 special categories or Art. 10 criminal data present.
+    pub fn is_sensitive(&self) -> bool {
+        self.special_category || self.criminal_data
+    }
+
+    pub fn has_personal_data(&self) -> bool {
+        !self.categories.is_empty() && !self.all_anonymised
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Taxonomy {
+    entries: Vec<DataCategoryRow>,
+}
+
+fn tier_rank(tier: &str) -> u8 {
+    match tier {
+        "none" => 0,
+        "standard" => 1,
+        "high" => 2,
+        "special" => 3,
+        _ => 1,
+    }
+}
+
+/// Lowercase, trim and collapse separators so "Contact Email", "contact-email" and "contact_email" compare equal.
+pub fn normalise_key(input: &str) -> String {
+    input
+        .trim()
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+impl Taxonomy {
+    pub fn new(entries: Vec<DataCategoryRow>) -> Self {
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[DataCategoryRow] {
+        &self.entries
+    }
+
+    pub fn lookup(&self, input: &str) -> Option<&DataCategoryRow> {
+        let key = normalise_key(input);
+        self.entries.iter().find(|e| {
+            normalise_key(&e.code) == key || e.synonyms.iter().any(|s| normalise_key(s) == key)
+        })
+    }
+
+    /// Map raw category strings to canonical codes (GDPR Art. 9 / Art. 10 flags included).
+    /// An unknown category is treated as standard personal data — never as anonymised.
+    pub fn normalise(&self, raw: &[String]) -> NormalisedCategories {
+        let mut out = NormalisedCategories {
+            all_anonymised: !raw.is_empty(),
+            highest_tier: "none".into(),
+            ..Default::default()
+        };
+
+        for input in raw {
+            let key = normalise_key(input);
+            if key.is_empty() {
+                continue;
+            }
+            let (code, tier) = match self.lookup(&key) {
+                Some(entry) => {
+                    out.special_category |= entry.special_category;
+                    out.criminal_data |= entry.criminal_data;
+                    out.all_anonymised &= entry.anonymised;
+                    (entry.code.clone(), entry.sensitivity_tier.as_str())
+                }
+                None => {
+                    out.all_anonymised = false;
+                    if !out.unrecognised.contains(&key) {
+                        out.unrecognised.push(key.clone());
+                    }
+                    (key, "standard")
+                }
+            };
+            if tier_rank(tier) > tier_rank(&out.highest_tier) {
+                out.highest_tier = tier.to_string();
+            }
+            if !out.categories.contains(&code) {
+                out.categories.push(code);
+            }
+        }
+
+        if out.categories.is_empty() {
+            out.all_anonymised = false;
+        }
+        out
+    }
+}
+
+pub async fn list_categories(pool: &PgPool) -> Result<Vec<DataCategoryRow>, String> {
+    sqlx::query_as::<_, DataCategoryRow>("SELECT * FROM data_categories ORDER BY code ASC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list data categories: {}", e))
+}
+
+pub async fn load_taxonomy(pool: &PgPool) -> Result<Taxonomy, String> {
+    list_categories(pool).await.map(Taxonomy::new)
+}
+
+pub struct UpsertCategoryParams {
+    pub code: String,
+    pub label: String,
+    pub description: Option<String>,
+    pub synonyms: Vec<String>,
+    pub sensitivity_tier: String,
+    pub special_category: bool,
+    pub criminal_data: bool,
+    pub anonymised: bool,
+}
+
+pub async fn upsert_category(pool: &PgPool, params: UpsertCategoryParams) -> Result<DataCategoryRow, String> {
+    let code = normalise_key(&params.code);
+    if code.is_empty() {
+        return Err("Category code must not be empty".into());
+    }
+    if !matches!(params.sensitivity_tier.as_str(), "none" | "standard" | "high" | "special") {
+        return Err(format!("Invalid sensitivity tier: {}", params.sensitivity_tier));
+    }
+    let mut synonyms: Vec<String> = params.synonyms.iter()
+        .map(|s| normalise_key(s))
+        .filter(|s| !s.is_empty() && *s != code)
+        .collect();
+    synonyms.sort();
+    synonyms.dedup();
+
+    sqlx::query_as::<_, DataCategoryRow>(
+        r#"INSERT INTO data_categories
+            (code, label, description, synonyms, sensitivity_tier, special_category, criminal_data, anonymised)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           ON CONFLICT (code) DO UPDATE SET
+             label = EXCLUDED.label,
+             description = EXCLUDED.description,
+             synonyms = EXCLUDED.synonyms,
+             sensitivity_tier = EXCLUDED.sensitivity_tier,
+             special_category = EXCLUDED.special_category,
+             criminal_data = EXCLUDED.criminal_data,
+             anonymised = EXCLUDED.anonymised
+           RETURNING *"#
+    )
+    .bind(&code)
+    .bind(&params.label)
+    .bind(&params.description)
+    .bind(&synonyms)
+    .bind(&params.sensitivity_tier)
+    .bind(params.special_category)
+    .bind(params.criminal_data)
+    .bind(params.anonymised)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to save data category: {}", e))
+}
+
+pub async fn delete_category(pool: &PgPool, code: &str) -> Result<bool, String> {
+    let result = sqlx::query("DELETE FROM data_categories WHERE code = $1")
+        .bind(normalise_key(code))
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to delete data category: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(code: &str, synonyms: &[&str], tier: &str, special: bool, criminal: bool, anonymised: bool) -> DataCategoryRow {
+        DataCategoryRow {
+            id: Uuid::new_v4(),
+            code: code.into(),
+            label: code.into(),
+            description: None,
+            synonyms: synonyms.iter().map(|s| s.to_string()).collect(),
+            sensitivity_tier: tier.into(),
+            special_category: special,
+            criminal_data: criminal,
+            anonymised,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn taxonomy() -> Taxonomy {
+        Taxonomy::new(vec![
+            row("email", &["e_mail", "contact_email"], "standard", false, false, false),
+            row("health", &["medical_records"], "special", true, false, false),
+            row("criminal_record", &["convictions"], "high", false, true, false),
+            row("hashed_id", &[], "none", false, false, true),
+            row("aggregate_stats", &["statistics"], "none", false, false, true),
+        ])
+    }
+
+    fn normalise(raw: &[&str]) -> NormalisedCategories {
+        taxonomy().normalise(&raw.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn keys_normalise() {
+        for (input, expected) in [("Contact Email", "contact_email"), (" contact-email ", "contact_email"), ("E--Mail!", "e_mail"), ("  ", "")] {
+            assert_eq!(normalise_key(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn synonyms_map_to_one_code() {
+        let out = normalise(&["Contact Email", "E-Mail", "email", "Medical Records"]);
+        assert_eq!(out.categories, ["email", "health"]);
+        assert!(out.unrecognised.is_empty());
+        assert!(out.special_category && !out.criminal_data);
+        assert_eq!(out.highest_tier, "special");
+    }
+
+    #[test]
+    fn unknown_categories_count_as_personal_data() {
+        let out = normalise(&["Shoe Size", "shoe-size", "hashed id"]);
+        assert_eq!(out.categories, ["shoe_size", "hashed_id"]);
+        assert_eq!(out.unrecognised, ["shoe_size"]);
+        assert!(!out.all_anonymised);
+        assert!(out.has_personal_data());
+        assert_eq!(out.highest_tier, "standard");
+        assert!(!out.is_sensitive());
+    }
+
+    #[test]
+    fn anonymised_only_when_every_category_is() {
+        let out = normalise(&["Hashed ID", "statistics"]);
+        assert!(out.all_anonymised);
+        assert!(!out.has_personal_data());
+        assert_eq!(out.highest_tier, "none");
+
+        assert!(!normalise(&["hashed_id", "email"]).all_anonymised);
+        for empty in [&[][..], &["", "  "][..]] {
+            let out = normalise(empty);
+            assert!(out.categories.is_empty() && !out.all_anonymised && !out.has_personal_data());
+        }
+    }
+
+    #[test]
+    fn criminal_data_is_flagged() {
+        let out = normalise(&["Convictions", "email"]);
+        assert!(out.criminal_data && !out.special_category && out.is_sensitive());
+        assert_eq!(out.highest_tier, "high");
+    }
+}
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::evidence::{self, CreateEventParams};
+use crate::shield::{country_name, screen_partner, PolicySnapshot, TransferContext};
+
+const BATCH_SIZE: i64 = 500;
+const SAMPLE_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReportRow {
+    pub id: Uuid,
+    pub status: String,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub requested_by: Option<String>,
+    pub evaluated_count: i64,
+    pub drift_count: i64,
+    pub report: serde_json::Value,
+    pub error_message: Option<String>,
+    pub evidence_event_id: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftGroup {
+    pub partner_name: Option<String>,
+    pub destination_country_code: String,
+    pub destination_country: String,
+    pub previous_decision: String,
+    pub current_decision: String,
+    pub current_reason: String,
+    pub count: u64,
+    pub sample_event_ids: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredTransfer {
+    event_id: String,
+    sequence_number: i64,
+    payload: serde_json::Value,
+}
+
+fn payload_str(payload: &serde_json::Value, key: &str) -> Option<String> {
+    payload.get(key).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(String::from)
+}
+
+/// Rebuild the transfer context that was evaluated from a stored DATA_TRANSFER payload.
+pub fn context_from_payload(payload: &serde_json::Value) -> TransferContext {
+    TransferContext {
+        destination_country_code: payload_str(payload, "destination_country_code"),
+        destination_country: payload_str(payload, "destination_country"),
+        data_categories: payload.get("data_categories")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|c| c.as_str().map(String::from)).collect()),
+        partner_name: payload_str(payload, "partner_name"),
+        source_ip: payload_str(payload, "source_ip"),
+        dest_ip: payload_str(payload, "dest_ip"),
+        data_size: payload.get("data_size").and_then(|v| v.as_u64()),
+        protocol: payload_str(payload, "protocol"),
+        user_agent: payload_str(payload, "user_agent"),
+        request_path: payload_str(payload, "request_path"),
+        source_system: payload_str(payload, "source_system"),
+        purpose: payload_str(payload, "purpose"),
+    }
+}
+
+pub async fn start_report(
+    pool: &PgPool,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    requested_by: Option<&str>,
+) -> Result<DriftReportRow, String> {
+    sqlx::query_as::<_, DriftReportRow>(
+        "INSERT INTO drift_reports (since, until, requested_by) VALUES ($1, $2, $3) RETURNING *"
+    )
+    .bind(since)
+    .bind(until)
+    .bind(requested_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to create drift report: {}", e))
+}
+
+/// Replay stored Shield transfer decisions through the current engine and record which would now differ.
+/// Read-only with respect to evidence: no transfer evidence or review items are created by the replay.
+pub async fn run_report(pool: &PgPool, report_id: Uuid) -> Result<(), String> {
+    let report = sqlx::query_as::<_, DriftReportRow>("SELECT * FROM drift_reports WHERE id = $1")
+        .bind(report_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // One snapshot for the whole replay so every stored transfer is judged by the same policy
+    let snapshot = PolicySnapshot::load(pool).await?;
+    let mut groups: BTreeMap<(String, String, String, String), DriftGroup> = BTreeMap::new();
+    let mut evaluated: i64 = 0;
+    let mut drifted: i64 = 0;
+    let mut failed: i64 = 0;
+    let mut last_sequence: i64 = 0;
+
+    loop {
+        let batch: Vec<StoredTransfer> = sqlx::query_as(
+            r#"SELECT event_id, sequence_number, payload FROM evidence_events
+               WHERE source_system = 'sovereign-shield'
+                 AND event_type LIKE 'DATA_TRANSFER%'
+                 AND sequence_number > $1
+                 AND ($2::timestamptz IS NULL OR occurred_at >= $2)
+                 AND ($3::timestamptz IS NULL OR occurred_at < $3)
+               ORDER BY sequence_number ASC
+               LIMIT $4"#
+        )
+        .bind(last_sequence)
+        .bind(report.since)
+        .bind(report.until)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load stored transfers: {}", e))?;
+
+        if batch.is_empty() {
+            break;
+        }
+        last_sequence = batch.last().map(|r| r.sequence_number).unwrap_or(last_sequence);
+
+        for stored in batch {
+            let Some(previous) = payload_str(&stored.payload, "decision") else {
+                continue;
+            };
+            let mut ctx = context_from_payload(&stored.payload);
+            let categories = snapshot.normalise_context(&mut ctx);
+            let screening = screen_partner(pool, ctx.partner_name.as_deref()).await;
+            if let Err(e) = &screening {
+                log::error!("Drift replay screening failed for {}: {}", stored.event_id, e);
+                failed += 1;
+                continue;
+            }
+            let (current, _) = snapshot.evaluate_screened(&ctx, categories.as_ref(), screening);
+            evaluated += 1;
+
+            let current_decision = current.decision.to_string();
+            if current_decision == previous {
+                continue;
+            }
+            drifted += 1;
+
+            let code = ctx.destination_country_code.clone().unwrap_or_default().to_uppercase();
+            let key = (
+                ctx.partner_name.clone().unwrap_or_default(),
+                code.clone(),
+                previous.clone(),
+                current_decision.clone(),
+            );
+            let group = groups.entry(key).or_insert_with(|| DriftGroup {
+                partner_name: ctx.partner_name.clone(),
+                destination_country: if code.is_empty() { "Unknown".into() } else { country_name(&code) },
+                destination_country_code: code,
+                previous_decision: previous,
+                current_decision,
+                current_reason: current.reason.clone(),
+                count: 0,
+                sample_event_ids: Vec::new(),
+            });
+            group.count += 1;
+            if group.sample_event_ids.len() < SAMPLE_SIZE {
+                group.sample_event_ids.push(stored.event_id);
+            }
+        }
+    }
+
+    let mut groups: Vec<DriftGroup> = groups.into_values().collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+    let report_json = serde_json::json!({
+        "evaluated": evaluated,
+        "drifted": drifted,
+        "unchanged": evaluated - drifted,
+        "failed": failed,
+        "groups": groups,
+    });
+
+    let event_id = match evidence::create_event(pool, CreateEventParams {
+        event_type: "COMPLIANCE_DRIFT_REPORT".into(),
+        severity: if drifted > 0 { "L2".into() } else { "L1".into() },
+        source_system: "drift-report".into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 5(2)".into()],
+        payload: serde_json::json!({
+            "report_id": report_id.to_string(),
+            "since": report.since.map(|t| t.to_rfc3339()),
+            "until": report.until.map(|t| t.to_rfc3339()),
+            "evaluated": evaluated,
+            "drifted": drifted,
+            "groups": groups.len(),
+        }),
+        correlation_id: Some(report_id.to_string()),
+        causation_id: None,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await {
+        Ok(event) => Some(event.event_id),
+        Err(e) => {
+            log::error!("Failed to create evidence for drift report {}: {}", report_id, e);
+            None
+        }
+    };
+
+    sqlx::query(
+        r#"UPDATE drift_reports
+           SET status = 'completed', evaluated_count = $1, drift_count = $2, report = $3,
+               evidence_event_id = $4, completed_at = NOW()
+           WHERE id = $5"#
+    )
+    .bind(evaluated)
+    .bind(drifted)
+    .bind(&report_json)
+    .bind(&event_id)
+    .bind(report_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to store drift report: {}", e))?;
+
+    log::info!("Drift report {} completed: {} evaluated, {} drifted", report_id, evaluated, drifted);
+    Ok(())
+}
+
+/// Run the replay in the background; failures are recorded on the report row.
+pub fn spawn_report(pool: PgPool, report_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(e) = run_report(&pool, report_id).await {
+            log::error!("Drift report {} failed: {}", report_id, e);
+            let _ = sqlx::query(
+                "UPDATE drift_reports SET status = 'failed', error_message = $1, completed_at = NOW() WHERE id = $2"
+            )
+            .bind(&e)
+            .bind(report_id)
+            .execute(&pool)
+            .await;
+        }
+    });
+}
+
+pub async fn get_report(pool: &PgPool, id: Uuid) -> Result<Option<DriftReportRow>, String> {
+    sqlx::query_as::<_, DriftReportRow>("SELECT * FROM drift_reports WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub async fn list_reports(pool: &PgPool, limit: i64) -> Result<Vec<DriftReportRow>, String> {
+    sqlx::query_as::<_, DriftReportRow>("SELECT * FROM drift_reports ORDER BY started_at DESC LIMIT $1")
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,541 @@
+//! Network-layer enforcement of blocked jurisdictions. Countries classified `blocked` become nftables
+//! sets or an ipset restore file (address ranges from the active IP-to-country dataset), and provider
+//! hosts that the host maps place in a blocked country become a DNS RPZ zone. Each distinct output is
+//! stored as a numbered version with its SHA-256 sealed into evidence; deployments are recorded against
+//! a version, so the rules in force at any point can be proven.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::BTreeSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::country_policy;
+use crate::evidence::{self, CreateEventParams};
+
+pub const FORMATS: &[&str] = &["nftables", "ipset", "rpz"];
+
+const SOURCE_SYSTEM: &str = "network-enforcement";
+
+/// Host maps (`EXT_AUTHZ_HOST_MAP` syntax) whose entries place provider hosts in a country.
+const HOST_MAPS: &[&str] = &["EXT_AUTHZ_HOST_MAP", "MAIL_PROVIDER_MAP"];
+
+/// Name of the nftables table and prefix of the ipset sets.
+const SET_NAME: &str = "veridion_blocked";
+
+const ARTIFACT_COLUMNS: &str = "id, format, version, content_hash, input_hash, blocked_countries, ip_dataset_id, \
+    network_count, host_count, evidence_event_id, generated_by, generated_at";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnforcementArtifactRow {
+    pub id: Uuid,
+    pub format: String,
+    pub version: i32,
+    /// SHA-256 of the artifact content, as sealed in evidence.
+    pub content_hash: String,
+    pub input_hash: String,
+    pub blocked_countries: Vec<String>,
+    pub ip_dataset_id: Option<Uuid>,
+    pub network_count: i32,
+    pub host_count: i32,
+    pub evidence_event_id: Option<String>,
+    pub generated_by: Option<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnforcementDeploymentRow {
+    pub id: Uuid,
+    pub artifact_id: Uuid,
+    pub target: String,
+    pub evidence_event_id: Option<String>,
+    pub deployed_at: DateTime<Utc>,
+}
+
+/// An address block in CIDR form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Network {
+    start: u128,
+    prefix: u32,
+    v6: bool,
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.v6 {
+            write!(f, "{}/{}", Ipv6Addr::from(self.start), self.prefix)
+        } else {
+            write!(f, "{}/{}", Ipv4Addr::from(self.start as u32), self.prefix)
+        }
+    }
+}
+
+fn sha256_hex(input: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn to_number(ip: IpAddr) -> (u128, bool) {
+    match ip {
+        IpAddr::V4(a) => (u32::from(a) as u128, false),
+        IpAddr::V6(a) => (u128::from(a), true),
+    }
+}
+
+/// Smallest set of CIDR blocks covering `start..=end`.
+fn range_to_cidrs(mut start: u128, end: u128, v6: bool, out: &mut Vec<Network>) {
+    let bits = if v6 { 128 } else { 32 };
+    loop {
+        let align = if start == 0 { bits } else { start.trailing_zeros().min(bits) };
+        // Addresses left in the range, as a power of two; the whole address space overflows to 0
+        let span = (end - start).checked_add(1);
+        let fits = span.map(|s| 127 - s.leading_zeros()).unwrap_or(128).min(bits);
+        let size = align.min(fits);
+        out.push(Network { start, prefix: bits - size, v6 });
+        match start.checked_add(1u128.checked_shl(size).unwrap_or(0)) {
+            Some(next) if size < 128 && next <= end && next != 0 => start = next,
+            _ => break,
+        }
+    }
+}
+
+/// Merge overlapping and adjacent ranges, then express them as CIDR blocks.
+fn to_networks(mut ranges: Vec<(u128, u128, bool)>) -> Vec<Network> {
+    ranges.sort_by_key(|&(start, _, v6)| (v6, start));
+    let mut merged: Vec<(u128, u128, bool)> = Vec::with_capacity(ranges.len());
+    for (start, end, v6) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.2 == v6 && start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end, v6)),
+        }
+    }
+    let mut networks = Vec::new();
+    for (start, end, v6) in merged {
+        range_to_cidrs(start, end, v6, &mut networks);
+    }
+    networks
+}
+
+/// Country codes currently classified `blocked`, overrides applied.
+pub async fn blocked_countries(pool: &PgPool) -> Vec<String> {
+    let mut codes: Vec<String> = country_policy::effective_classifications(pool)
+        .await
+        .into_iter()
+        .filter(|c| c.get("status").and_then(|s| s.as_str()) == Some("blocked"))
+        .filter_map(|c| c.get("code").and_then(|s| s.as_str()).map(str::to_uppercase))
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+/// Active IP-to-country dataset (ID and content hash) and the blocks it assigns to `countries`.
+async fn blocked_networks(pool: &PgPool, countries: &[String]) -> Result<(Option<(Uuid, String)>, Vec<Network>), String> {
+    let dataset: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, content_hash FROM ip_country_datasets WHERE active ORDER BY loaded_at DESC LIMIT 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to read IP country dataset: {}", e))?;
+    let Some((dataset_id, _)) = &dataset else {
+        return Ok((None, Vec::new()));
+    };
+    if countries.is_empty() {
+        return Ok((dataset, Vec::new()));
+    }
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"SELECT host(start_ip), host(end_ip) FROM ip_country_ranges
+           WHERE dataset_id = $1 AND country_code::text = ANY($2)"#
+    )
+    .bind(dataset_id)
+    .bind(countries)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to read IP ranges: {}", e))?;
+
+    let ranges = rows.iter()
+        .filter_map(|(start, end)| {
+            let (start, v6) = to_number(start.parse().ok()?);
+            let (end, _) = to_number(end.parse().ok()?);
+            Some((start, end, v6))
+        })
+        .collect();
+    Ok((dataset, to_networks(ranges)))
+}
+
+/// Host patterns (`host` or `*.domain`) that the host maps place in one of `countries`.
+fn blocked_hosts(countries: &[String]) -> Vec<String> {
+    let mut hosts = BTreeSet::new();
+    for map in HOST_MAPS.iter().filter_map(|name| std::env::var(name).ok()) {
+        for entry in map.split(',') {
+            let Some((pattern, target)) = entry.split_once('=') else {
+                continue;
+            };
+            let code = target.split(':').next().unwrap_or("").trim().to_uppercase();
+            let pattern = pattern.trim().trim_end_matches('.').to_lowercase();
+            let name = pattern.strip_prefix("*.").unwrap_or(&pattern);
+            if countries.contains(&code) && name.contains('.') && name.parse::<IpAddr>().is_err() {
+                hosts.insert(pattern);
+            }
+        }
+    }
+    hosts.into_iter().collect()
+}
+
+fn header(comment: &str, format: &str, version: i32, generated_at: &DateTime<Utc>, countries: &[String], dataset: Option<&(Uuid, String)>) -> String {
+    let mut out = format!("{} Veridion blocked-jurisdiction {} artifact, version {}\n", comment, format, version);
+    out.push_str(&format!("{} Generated {}\n", comment, generated_at.to_rfc3339()));
+    out.push_str(&format!("{} Blocked countries: {}\n", comment, if countries.is_empty() { "none".to_string() } else { countries.join(", ") }));
+    if let Some((id, hash)) = dataset {
+        out.push_str(&format!("{} IP-to-country dataset {} (sha256 {})\n", comment, id, hash));
+    }
+    out
+}
+
+/// nftables script: replaces the `veridion_blocked` table with address sets and output/forward chains
+/// that reject traffic to them. Loaded atomically with `nft -f`.
+fn render_nftables(head: &str, networks: &[Network]) -> String {
+    let set = |v6: bool| {
+        let elements: Vec<String> = networks.iter().filter(|n| n.v6 == v6).map(Network::to_string).collect();
+        let kind = if v6 { "ipv6_addr" } else { "ipv4_addr" };
+        let name = if v6 { "blocked_v6" } else { "blocked_v4" };
+        if elements.is_empty() {
+            format!("    set {} {{\n        type {}\n        flags interval\n    }}\n", name, kind)
+        } else {
+            format!(
+                "    set {} {{\n        type {}\n        flags interval\n        elements = {{\n            {}\n        }}\n    }}\n",
+                name, kind, elements.join(",\n            "),
+            )
+        }
+    };
+    let chain = |name: &str, hook: &str| {
+        format!(
+            "    chain {} {{\n        type filter hook {} priority filter; policy accept;\n        ip daddr @blocked_v4 counter reject\n        ip6 daddr @blocked_v6 counter reject\n    }}\n",
+            name, hook,
+        )
+    };
+    format!(
+        "#!/usr/sbin/nft -f\n{}\ntable inet {t}\ndelete table inet {t}\n\ntable inet {t} {{\n{}\n{}\n{}\n{}}}\n",
+        head, set(false), set(true), chain("egress", "output"), chain("forward", "forward"), t = SET_NAME,
+    )
+}
+
+/// ipset restore file: fills temporary sets and swaps them in, so the live sets are never empty.
+/// Firewall rules reference `veridion_blocked_v4` / `veridion_blocked_v6`.
+fn render_ipset(head: &str, networks: &[Network]) -> String {
+    let mut out = head.to_string();
+    for (v6, family) in [(false, "inet"), (true, "inet6")] {
+        let members: Vec<&Network> = networks.iter().filter(|n| n.v6 == v6).collect();
+        let name = format!("{}_{}", SET_NAME, if v6 { "v6" } else { "v4" });
+        let maxelem = members.len().max(65536);
+        out.push_str(&format!("create {} hash:net family {} maxelem {} -exist\n", name, family, maxelem));
+        out.push_str(&format!("create {}_tmp hash:net family {} maxelem {} -exist\n", name, family, maxelem));
+        out.push_str(&format!("flush {}_tmp\n", name));
+        for network in members {
+            out.push_str(&format!("add {}_tmp {}\n", name, network));
+        }
+        out.push_str(&format!("swap {}_tmp {}\n", name, name));
+        out.push_str(&format!("destroy {}_tmp\n", name));
+    }
+    out
+}
+
+/// RPZ zone: every listed host answers NXDOMAIN; `*.domain` covers subdomains only, as in the host
+/// maps. The serial is the artifact version.
+fn render_rpz(head: &str, version: i32, hosts: &[String]) -> String {
+    let mut out = head.to_string();
+    out.push_str("$TTL 300\n");
+    out.push_str(&format!("@ IN SOA localhost. hostmaster.localhost. ({} 3600 600 86400 300)\n", version));
+    out.push_str("  IN NS localhost.\n");
+    for host in hosts {
+        out.push_str(&format!("{} CNAME .\n", host));
+    }
+    out
+}
+
+/// Seal an artifact's content hash and inputs into evidence within the transaction that stores it,
+/// so no version is ever handed out without its evidence.
+async fn seal(tx: &mut Transaction<'_, Postgres>, artifact: &mut EnforcementArtifactRow, ip_dataset_hash: Option<&str>) -> Result<(), String> {
+    let event = evidence::create_event_in_tx(tx, CreateEventParams {
+        event_type: "ENFORCEMENT_ARTIFACT_GENERATED".into(),
+        severity: "L1".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 44".into()],
+        payload: serde_json::json!({
+            "artifact_id": artifact.id.to_string(),
+            "format": artifact.format,
+            "version": artifact.version,
+            "content_hash": artifact.content_hash,
+            "input_hash": artifact.input_hash,
+            "blocked_countries": artifact.blocked_countries,
+            "ip_dataset_id": artifact.ip_dataset_id.map(|id| id.to_string()),
+            "ip_dataset_hash": ip_dataset_hash,
+            "network_count": artifact.network_count,
+            "host_count": artifact.host_count,
+        }),
+        correlation_id: Some(artifact.id.to_string()),
+        causation_id: None,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: Some(artifact.generated_at),
+        idempotency_key: None,
+    })
+    .await
+    .map_err(|e| format!("Failed to create evidence for enforcement artifact {}: {}", artifact.id, e))?;
+    sqlx::query("UPDATE enforcement_artifacts SET evidence_event_id = $1 WHERE id = $2")
+        .bind(&event.event_id)
+        .bind(artifact.id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to link evidence to enforcement artifact {}: {}", artifact.id, e))?;
+    artifact.evidence_event_id = Some(event.event_id);
+    Ok(())
+}
+
+/// Generate the artifact for `format` from the current policy. When nothing it is built from has
+/// changed since the latest version, that version is returned (`false`) instead of a new one.
+pub async fn generate(pool: &PgPool, format: &str, generated_by: Option<&str>) -> Result<(EnforcementArtifactRow, bool), String> {
+    if !FORMATS.contains(&format) {
+        return Err(format!("Unknown format {} (expected one of {})", format, FORMATS.join(", ")));
+    }
+    let countries = blocked_countries(pool).await;
+    let (dataset, networks, hosts) = if format == "rpz" {
+        (None, Vec::new(), blocked_hosts(&countries))
+    } else {
+        let (dataset, networks) = blocked_networks(pool, &countries).await?;
+        if dataset.is_none() && !countries.is_empty() {
+            return Err("No IP-to-country dataset loaded (run load-ip-country-db); the sets would be empty".into());
+        }
+        (dataset, networks, Vec::new())
+    };
+    let network_list: Vec<String> = networks.iter().map(Network::to_string).collect();
+    let input_hash = sha256_hex(&serde_json::json!([
+        format, countries, dataset.as_ref().map(|(_, hash)| hash), network_list, hosts,
+    ]).to_string());
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(format!("enforcement-artifacts:{}", format))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let latest: Option<EnforcementArtifactRow> = sqlx::query_as(&format!(
+        "SELECT {} FROM enforcement_artifacts WHERE format = $1 ORDER BY version DESC LIMIT 1", ARTIFACT_COLUMNS
+    ))
+    .bind(format)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to read enforcement artifacts: {}", e))?;
+    let dataset_hash = dataset.as_ref().map(|(_, hash)| hash.as_str());
+    let previous_version = latest.as_ref().map(|a| a.version);
+    if let Some(mut latest) = latest.filter(|a| a.input_hash == input_hash) {
+        // Versions stored before generation and sealing shared a transaction may lack their evidence
+        if latest.evidence_event_id.is_none() {
+            seal(&mut tx, &mut latest, dataset_hash).await?;
+            tx.commit().await.map_err(|e| e.to_string())?;
+        }
+        return Ok((latest, false));
+    }
+
+    let version = previous_version.map(|v| v + 1).unwrap_or(1);
+    let generated_at = Utc::now();
+    let comment = if format == "rpz" { ";" } else { "#" };
+    let head = header(comment, format, version, &generated_at, &countries, dataset.as_ref());
+    let content = match format {
+        "nftables" => render_nftables(&head, &networks),
+        "ipset" => render_ipset(&head, &networks),
+        _ => render_rpz(&head, version, &hosts),
+    };
+    let content_hash = sha256_hex(&content);
+
+    let mut artifact: EnforcementArtifactRow = sqlx::query_as(&format!(
+        r#"INSERT INTO enforcement_artifacts
+               (format, version, content, content_hash, input_hash, blocked_countries, ip_dataset_id,
+                network_count, host_count, generated_by, generated_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+           RETURNING {}"#, ARTIFACT_COLUMNS
+    ))
+    .bind(format)
+    .bind(version)
+    .bind(&content)
+    .bind(&content_hash)
+    .bind(&input_hash)
+    .bind(&countries)
+    .bind(dataset.as_ref().map(|(id, _)| *id))
+    .bind(networks.len() as i32)
+    .bind(hosts.len() as i32)
+    .bind(generated_by)
+    .bind(generated_at)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store enforcement artifact: {}", e))?;
+    seal(&mut tx, &mut artifact, dataset_hash).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok((artifact, true))
+}
+
+pub async fn list(pool: &PgPool, format: Option<&str>, limit: i64) -> Result<Vec<EnforcementArtifactRow>, String> {
+    sqlx::query_as(&format!(
+        r#"SELECT {} FROM enforcement_artifacts
+           WHERE ($1::text IS NULL OR format = $1)
+           ORDER BY generated_at DESC, version DESC
+           LIMIT $2"#, ARTIFACT_COLUMNS
+    ))
+    .bind(format)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list enforcement artifacts: {}", e))
+}
+
+/// An artifact and its content: by ID, or the latest version of a format.
+pub async fn get(pool: &PgPool, id: Option<Uuid>, format: Option<&str>) -> Result<Option<(EnforcementArtifactRow, String)>, String> {
+    #[derive(sqlx::FromRow)]
+    struct WithContent {
+        #[sqlx(flatten)]
+        artifact: EnforcementArtifactRow,
+        content: String,
+    }
+    let row: Option<WithContent> = sqlx::query_as(&format!(
+        r#"SELECT {}, content FROM enforcement_artifacts
+           WHERE ($1::uuid IS NULL OR id = $1) AND ($2::text IS NULL OR format = $2)
+           ORDER BY version DESC
+           LIMIT 1"#, ARTIFACT_COLUMNS
+    ))
+    .bind(id)
+    .bind(format)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to read enforcement artifact: {}", e))?;
+    Ok(row.map(|r| (r.artifact, r.content)))
+}
+
+/// Record that `target` loaded `artifact`, with evidence linked to the artifact's generation event.
+pub async fn record_deployment(pool: &PgPool, artifact: &EnforcementArtifactRow, target: &str) -> Result<EnforcementDeploymentRow, String> {
+    let mut deployment: EnforcementDeploymentRow = sqlx::query_as(
+        "INSERT INTO enforcement_deployments (artifact_id, target) VALUES ($1, $2) RETURNING *"
+    )
+    .bind(artifact.id)
+    .bind(target)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to record deployment: {}", e))?;
+
+    let event = evidence::create_event(pool, CreateEventParams {
+        event_type: "ENFORCEMENT_ARTIFACT_DEPLOYED".into(),
+        severity: "L1".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 44".into()],
+        payload: serde_json::json!({
+            "deployment_id": deployment.id.to_string(),
+            "artifact_id": artifact.id.to_string(),
+            "format": artifact.format,
+            "version": artifact.version,
+            "content_hash": artifact.content_hash,
+            "target": target,
+        }),
+        correlation_id: Some(artifact.id.to_string()),
+        causation_id: artifact.evidence_event_id.clone(),
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: Some(deployment.deployed_at),
+        idempotency_key: None,
+    }).await?;
+    sqlx::query("UPDATE enforcement_deployments SET evidence_event_id = $1 WHERE id = $2")
+        .bind(&event.event_id)
+        .bind(deployment.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    deployment.evidence_event_id = Some(event.event_id);
+    Ok(deployment)
+}
+
+/// `export-enforcement <format> [file]`: generate (or reuse) the current version and write its content.
+pub async fn run_cli(pool: &PgPool, args: &[String]) -> std::io::Result<()> {
+    let Some(format) = args.first().filter(|f| FORMATS.contains(&f.as_str())) else {
+        eprintln!("Usage: veridion-api export-enforcement <{}> [file]", FORMATS.join("|"));
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing or unknown format"));
+    };
+    let (artifact, created) = generate(pool, format, Some("cli")).await.map_err(std::io::Error::other)?;
+    let (_, content) = get(pool, Some(artifact.id), None)
+        .await
+        .map_err(std::io::Error::other)?
+        .ok_or_else(|| std::io::Error::other("artifact disappeared"))?;
+    let summary = format!(
+        "{} {} version {} ({}; {} networks, {} hosts, sha256 {})",
+        if created { "Generated" } else { "Unchanged:" }, format, artifact.version,
+        if artifact.blocked_countries.is_empty() { "no blocked countries".to_string() } else { artifact.blocked_countries.join(", ") },
+        artifact.network_count, artifact.host_count, artifact.content_hash,
+    );
+    match args.get(1) {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            println!("{} -> {}", summary, path);
+        }
+        None => {
+            print!("{}", content);
+            eprintln!("{}", summary);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidrs(start: &str, end: &str) -> Vec<String> {
+        let (start, v6) = to_number(start.parse().unwrap());
+        let (end, _) = to_number(end.parse().unwrap());
+        let mut out = Vec::new();
+        range_to_cidrs(start, end, v6, &mut out);
+        out.iter().map(Network::to_string).collect()
+    }
+
+    #[test]
+    fn aligned_and_unaligned_ranges() {
+        assert_eq!(cidrs("203.0.113.0", "203.0.113.255"), vec!["203.0.113.0/24"]);
+        assert_eq!(cidrs("198.51.100.7", "198.51.100.7"), vec!["198.51.100.7/32"]);
+        assert_eq!(cidrs("10.0.0.1", "10.0.0.6"), vec!["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"]);
+        assert_eq!(cidrs("192.0.2.128", "192.0.3.63"), vec!["192.0.2.128/25", "192.0.3.0/26"]);
+        assert_eq!(cidrs("2001:db8::", "2001:db8::ffff"), vec!["2001:db8::/112"]);
+        assert_eq!(cidrs("2001:db8::1", "2001:db8::2"), vec!["2001:db8::1/128", "2001:db8::2/128"]);
+    }
+
+    #[test]
+    fn address_space_edges() {
+        assert_eq!(cidrs("0.0.0.0", "255.255.255.255"), vec!["0.0.0.0/0"]);
+        assert_eq!(cidrs("0.0.0.0", "0.0.0.0"), vec!["0.0.0.0/32"]);
+        assert_eq!(cidrs("255.255.255.254", "255.255.255.255"), vec!["255.255.255.254/31"]);
+        assert_eq!(cidrs("128.0.0.0", "255.255.255.255"), vec!["128.0.0.0/1"]);
+        assert_eq!(cidrs("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), vec!["::/0"]);
+        assert_eq!(cidrs("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+            vec!["ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128"]);
+        assert_eq!(cidrs("::1", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").len(), 128);
+    }
+
+    #[test]
+    fn merged_ranges() {
+        let range = |a: &str, b: &str| {
+            let ((start, v6), (end, _)) = (to_number(a.parse().unwrap()), to_number(b.parse().unwrap()));
+            (start, end, v6)
+        };
+        let networks = to_networks(vec![
+            range("10.0.1.0", "10.0.1.255"),
+            range("2001:db8::", "2001:db8::ff"),
+            range("10.0.0.0", "10.0.0.255"),
+            range("10.0.0.128", "10.0.1.10"),
+        ]);
+        let list: Vec<String> = networks.iter().map(Network::to_string).collect();
+        assert_eq!(list, vec!["10.0.0.0/23", "2001:db8::/120"]);
+    }
+}
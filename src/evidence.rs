@@ -1,9 +1,11 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sha2::{Sha256, Digest};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+use crate::idempotency;
 use crate::models::{EvidenceEventRow, EvidenceEventResponse};
+use crate::webhooks;
 
 const SEAL_SALT: &str = "VERIDION_PROFESSIONAL_COMPLIANCE_SEAL_2024";
 
@@ -24,29 +26,6 @@ pub fn compute_nexus_seal(payload_hash: &str, previous_hash: &str) -> String {
     sha256_hex(&input)
 }
 
-pub async fn get_latest_hash(pool: &PgPool, source_system: &str) -> Option<String> {
-    sqlx::query_scalar::<_, String>(
-        "SELECT payload_hash FROM evidence_events WHERE source_system = $1 ORDER BY sequence_number DESC LIMIT 1"
-    )
-    .bind(source_system)
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten()
-}
-
-pub async fn get_next_sequence(pool: &PgPool, source_system: &str) -> i64 {
-    let max: Option<i64> = sqlx::query_scalar(
-        "SELECT MAX(sequence_number) FROM evidence_events WHERE source_system = $1"
-    )
-    .bind(source_system)
-    .fetch_one(pool)
-    .await
-    .ok()
-    .flatten();
-    max.unwrap_or(0) + 1
-}
-
 pub struct CreateEventParams {
     pub event_type: String,
     pub severity: String,
@@ -58,16 +37,114 @@ pub struct CreateEventParams {
     pub causation_id: Option<String>,
     pub source_ip: Option<String>,
     pub source_user_agent: Option<String>,
+    /// When the event happened at the source; defaults to the time it is recorded.
+    pub occurred_at: Option<DateTime<Utc>>,
+    /// Client-supplied key (Idempotency-Key header or per-entry external_id) used to detect retries.
+    pub idempotency_key: Option<String>,
 }
 
 pub async fn create_event(pool: &PgPool, params: CreateEventParams) -> Result<EvidenceEventRow, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let row = create_event_in_tx(&mut tx, params).await?;
+    tx.commit().await.map_err(|e| format!("Failed to commit evidence event: {}", e))?;
+    Ok(row)
+}
+
+/// `create_event` inside the caller's transaction, so the event (and its webhook deliveries) commits
+/// or rolls back with the change it records. The chain stays locked until that transaction ends.
+pub async fn create_event_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    params: CreateEventParams,
+) -> Result<EvidenceEventRow, String> {
+    let source_system = params.source_system.clone();
+    let key = params.idempotency_key.clone();
+    append_events(tx, &source_system, vec![params])
+        .await?
+        .remove(0)
+        .ok_or_else(|| format!("Idempotency key {} was already recorded", key.unwrap_or_default()))
+}
+
+/// Append several events to one source system's chain in a single transaction.
+/// The chain is locked for the duration so concurrent writers cannot interleave
+/// sequence numbers or previous hashes; on error nothing from the batch is stored.
+/// Returns one entry per event, in order: `None` when its idempotency key was already
+/// recorded within the replay window (look the original up with `idempotency::find_recent`).
+pub async fn create_events_batch(
+    pool: &PgPool,
+    source_system: &str,
+    events: Vec<CreateEventParams>,
+) -> Result<Vec<Option<EvidenceEventRow>>, String> {
+    if events.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let rows = append_events(&mut tx, source_system, events).await?;
+    tx.commit().await.map_err(|e| format!("Failed to commit evidence batch: {}", e))?;
+    Ok(rows)
+}
+
+/// Lock a chain with `pg_advisory_xact_lock` (every writer takes it before reading the chain head),
+/// claim the events' idempotency keys, append the events that won their claim after its current head
+/// and queue their webhook deliveries.
+async fn append_events(
+    tx: &mut Transaction<'_, Postgres>,
+    source_system: &str,
+    events: Vec<CreateEventParams>,
+) -> Result<Vec<Option<EvidenceEventRow>>, String> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(source_system)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to lock evidence chain: {}", e))?;
+
+    let (max_sequence, mut previous_hash): (Option<i64>, Option<String>) = sqlx::query_as(
+        r#"SELECT MAX(sequence_number),
+                  (SELECT payload_hash FROM evidence_events WHERE source_system = $1
+                   ORDER BY sequence_number DESC LIMIT 1)
+           FROM evidence_events WHERE source_system = $1"#
+    )
+    .bind(source_system)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to read evidence chain head: {}", e))?;
+    let mut sequence_number = max_sequence.unwrap_or(0);
+
+    let keys: Vec<String> = events.iter().filter_map(|p| p.idempotency_key.clone()).collect();
+    let mut claimed = idempotency::claim(tx, source_system, &keys).await?;
+
+    let mut rows = Vec::with_capacity(events.len());
+    for mut params in events {
+        // A key is written once: a repeat in the same batch loses to the first event
+        if params.idempotency_key.as_ref().is_some_and(|k| !claimed.remove(k)) {
+            rows.push(None);
+            continue;
+        }
+        params.source_system = source_system.to_string();
+        sequence_number += 1;
+        let row = insert_event(&mut **tx, params, sequence_number, previous_hash.take().unwrap_or_default()).await?;
+        previous_hash = Some(row.payload_hash.clone());
+        rows.push(Some(row));
+    }
+    let written: Vec<EvidenceEventRow> = rows.iter().flatten().cloned().collect();
+    webhooks::enqueue(tx, &written).await?;
+    Ok(rows)
+}
+
+async fn insert_event<'e, E>(
+    executor: E,
+    params: CreateEventParams,
+    sequence_number: i64,
+    previous_hash: String,
+) -> Result<EvidenceEventRow, String>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
     let now = Utc::now();
+    let occurred_at = params.occurred_at.unwrap_or(now);
     let event_id = Uuid::new_v4().to_string();
     let correlation_id = params.correlation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
 
-    let sequence_number = get_next_sequence(pool, &params.source_system).await;
     let payload_hash = compute_payload_hash(&params.payload);
-    let previous_hash = get_latest_hash(pool, &params.source_system).await.unwrap_or_default();
     let nexus_seal = compute_nexus_seal(&payload_hash, &previous_hash);
 
     let tags_json = serde_json::to_value(&params.regulatory_tags).unwrap_or_default();
@@ -80,14 +157,14 @@ pub async fn create_event(pool: &PgPool, params: CreateEventParams) -> Result<Ev
              source_system, source_ip, source_user_agent,
              regulatory_tags, articles, payload,
              payload_hash, previous_hash, nexus_seal,
-             verification_status)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, 'VERIFIED')"#
+             verification_status, idempotency_key)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, 'VERIFIED', $18)"#
     )
     .bind(&event_id)
     .bind(&correlation_id)
     .bind(&params.causation_id)
     .bind(sequence_number)
-    .bind(now)
+    .bind(occurred_at)
     .bind(now)
     .bind(&params.event_type)
     .bind(&params.severity)
@@ -100,7 +177,8 @@ pub async fn create_event(pool: &PgPool, params: CreateEventParams) -> Result<Ev
     .bind(&payload_hash)
     .bind(&previous_hash)
     .bind(&nexus_seal)
-    .execute(pool)
+    .bind(&params.idempotency_key)
+    .execute(executor)
     .await
     .map_err(|e| format!("Failed to insert evidence event: {}", e))?;
 
@@ -109,7 +187,7 @@ pub async fn create_event(pool: &PgPool, params: CreateEventParams) -> Result<Ev
         correlation_id,
         causation_id: params.causation_id,
         sequence_number,
-        occurred_at: now,
+        occurred_at,
         recorded_at: now,
         event_type: params.event_type,
         severity: params.severity,
@@ -146,15 +224,19 @@ pub async fn count_sealed_chain_roots(pool: &PgPool) -> Result<i64, String> {
     Ok(count.unwrap_or(0))
 }
 
-pub async fn list_events(
-    pool: &PgPool,
-    severity: Option<&str>,
-    event_type: Option<&str>,
-    search: Option<&str>,
-    destination_country: Option<&str>,
-    limit: i64,
-    offset: i64,
-) -> Result<(Vec<EvidenceEventResponse>, i64), String> {
+pub struct EventFilters<'a> {
+    pub severity: Option<&'a str>,
+    pub event_type: Option<&'a str>,
+    pub search: Option<&'a str>,
+    pub destination_country: Option<&'a str>,
+    /// Bounds on occurred_at (inclusive from, exclusive to).
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Page and count statements for `list_events`. Filters bind in declaration order, then offset and limit.
+fn event_queries(filters: &EventFilters<'_>) -> (String, String) {
+    let EventFilters { severity, event_type, search, destination_country, from, to } = *filters;
     let mut conditions = vec!["1=1".to_string()];
     let mut bind_idx = 0u32;
 
@@ -181,18 +263,38 @@ pub async fn list_events(
         ));
     }
 
+    if from.is_some() {
+        bind_idx += 1;
+        conditions.push(format!("occurred_at >= ${}", bind_idx));
+    }
+    if to.is_some() {
+        bind_idx += 1;
+        conditions.push(format!("occurred_at < ${}", bind_idx));
+    }
+
     let where_clause = conditions.join(" AND ");
-    bind_idx += 1;
-    let limit_idx = bind_idx;
+    let offset_idx = bind_idx + 1;
+    let limit_idx = bind_idx + 2;
 
     let query_str = format!(
-        "SELECT * FROM evidence_events WHERE {} ORDER BY created_at DESC LIMIT ${}",
-        where_clause, limit_idx
+        "SELECT * FROM evidence_events WHERE {} ORDER BY occurred_at DESC OFFSET ${} LIMIT ${}",
+        where_clause, offset_idx, limit_idx
     );
     let count_str = format!(
         "SELECT COUNT(*) FROM evidence_events WHERE {}",
         where_clause
     );
+    (query_str, count_str)
+}
+
+pub async fn list_events(
+    pool: &PgPool,
+    filters: &EventFilters<'_>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<EvidenceEventResponse>, i64), String> {
+    let EventFilters { severity, event_type, search, destination_country, from, to } = *filters;
+    let (query_str, count_str) = event_queries(filters);
 
     let mut query = sqlx::query_as::<_, EvidenceEventRow>(&query_str);
     let mut count_query = sqlx::query_scalar::<_, i64>(&count_str);
@@ -214,6 +316,15 @@ pub async fn list_events(
         count_query = count_query.bind(dc.to_string());
     }
 
+    if let Some(f) = from {
+        query = query.bind(f);
+        count_query = count_query.bind(f);
+    }
+    if let Some(t) = to {
+        query = query.bind(t);
+        count_query = count_query.bind(t);
+    }
+
     query = query.bind(offset).bind(limit);
 
     let rows = query.fetch_all(pool).await.map_err(|e| e.to_string())?;
@@ -259,3 +370,34 @@ pub async fn verify_chain_integrity(pool: &PgPool, source_system: &str) -> Resul
 
     Ok((true, format!("Chain verified: {} events", rows.len())))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_filters() -> EventFilters<'static> {
+        EventFilters { severity: None, event_type: None, search: None, destination_country: None, from: None, to: None }
+    }
+
+    #[test]
+    fn unfiltered_page_binds_offset_then_limit() {
+        let (query, count) = event_queries(&no_filters());
+        assert!(query.ends_with("WHERE 1=1 ORDER BY occurred_at DESC OFFSET $1 LIMIT $2"), "{}", query);
+        assert_eq!(count, "SELECT COUNT(*) FROM evidence_events WHERE 1=1");
+    }
+
+    #[test]
+    fn occurred_at_filters_precede_paging() {
+        let from = Utc::now() - chrono::Duration::days(1);
+        let to = Utc::now();
+        let (query, count) = event_queries(&EventFilters { from: Some(from), to: Some(to), ..no_filters() });
+        assert!(query.contains("WHERE 1=1 AND occurred_at >= $1 AND occurred_at < $2 ORDER BY"), "{}", query);
+        assert!(query.ends_with("OFFSET $3 LIMIT $4"), "{}", query);
+        assert!(count.ends_with("occurred_at >= $1 AND occurred_at < $2"), "{}", count);
+
+        let (query, _) = event_queries(&EventFilters { severity: Some("HIGH"), search: Some("x"), to: Some(to), ..no_filters() });
+        assert!(query.contains("severity = $1 AND (event_id ILIKE '%' || $2 || '%'"), "{}", query);
+        assert!(query.contains("AND occurred_at < $3 ORDER BY"), "{}", query);
+        assert!(query.ends_with("OFFSET $4 LIMIT $5"), "{}", query);
+    }
+}
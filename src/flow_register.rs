@@ -0,0 +1,564 @@
+//! Data-flow register: the transfers teams expect, declared up front (source system, partner,
+//! destination, categories, purpose). Once any flow is declared, each evaluated or ingested transfer is
+//! matched against the register; a transfer no declaration covers is marked `UNDECLARED_FLOW` whatever
+//! the policy decision, and grouped by source, partner, destination and categories for DPO triage.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::evidence::{self, CreateEventParams};
+use crate::shield::TransferContext;
+
+/// `flow_status` of a transfer some declaration covers.
+pub const DECLARED: &str = "DECLARED";
+/// `flow_status` of a transfer no declaration covers.
+pub const UNDECLARED_FLOW: &str = "UNDECLARED_FLOW";
+
+pub const UNDECLARED_STATUSES: &[&str] = &["open", "declared", "dismissed"];
+
+const SOURCE_SYSTEM: &str = "flow-register";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclaredFlowRow {
+    pub id: Uuid,
+    pub name: String,
+    /// Unset fields match any value.
+    pub source_system: Option<String>,
+    pub partner_name: Option<String>,
+    pub destination_country_code: Option<String>,
+    /// Categories the flow may carry; empty allows any.
+    #[schema(value_type = Vec<String>)]
+    pub data_categories: serde_json::Value,
+    pub purpose: String,
+    pub owner: Option<String>,
+    pub active: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UndeclaredFlowRow {
+    pub id: Uuid,
+    pub source_system: Option<String>,
+    pub partner_name: Option<String>,
+    pub destination_country_code: Option<String>,
+    #[schema(value_type = Vec<String>)]
+    pub data_categories: serde_json::Value,
+    pub transfer_count: i64,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub last_decision: Option<String>,
+    /// Evidence of the latest transfer, or the aggregated flow that will carry it.
+    pub last_evidence_id: Option<String>,
+    pub last_flow_id: Option<Uuid>,
+    pub status: String,
+    pub declared_flow_id: Option<Uuid>,
+    pub resolved_by: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub evidence_event_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn text_matches(declared: Option<&str>, actual: Option<&str>) -> bool {
+    match declared {
+        None => true,
+        Some(d) => actual.map(|a| a.trim().eq_ignore_ascii_case(d.trim())).unwrap_or(false),
+    }
+}
+
+fn categories_of(value: &serde_json::Value) -> Vec<String> {
+    value.as_array()
+        .map(|a| a.iter().filter_map(|c| c.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+impl DeclaredFlowRow {
+    /// Every declared field matches and the transfer carries no category the flow does not declare.
+    /// A transfer that states its purpose must state the declared one.
+    fn covers(&self, source_system: Option<&str>, partner_name: Option<&str>, country: Option<&str>, categories: &[String], purpose: Option<&str>) -> bool {
+        let declared = categories_of(&self.data_categories);
+        text_matches(self.source_system.as_deref(), source_system)
+            && text_matches(self.partner_name.as_deref(), partner_name)
+            && text_matches(self.destination_country_code.as_deref(), country)
+            && (declared.is_empty() || categories.iter().all(|c| declared.contains(c)))
+            && purpose.map(|p| text_matches(Some(&self.purpose), Some(p))).unwrap_or(true)
+    }
+
+    /// Declarations naming more fields win over broader ones.
+    fn specificity(&self) -> usize {
+        [self.source_system.is_some(), self.partner_name.is_some(), self.destination_country_code.is_some()]
+            .iter()
+            .filter(|set| **set)
+            .count()
+            + usize::from(!categories_of(&self.data_categories).is_empty())
+    }
+}
+
+/// Active declarations, loaded once per evaluation or ingest batch.
+pub struct Register {
+    flows: Vec<DeclaredFlowRow>,
+}
+
+impl Register {
+    /// `None` while nothing is declared: no transfer is matched until the register is in use. A register
+    /// that cannot be read is logged and transfers are evaluated without matching.
+    pub async fn load(pool: &PgPool) -> Option<Self> {
+        match sqlx::query_as::<_, DeclaredFlowRow>("SELECT * FROM declared_flows WHERE active").fetch_all(pool).await {
+            Ok(flows) => Self::from_flows(flows),
+            Err(e) => {
+                log::error!("Failed to load flow register: {}; transfers are not matched against declared flows", e);
+                None
+            }
+        }
+    }
+
+    fn from_flows(flows: Vec<DeclaredFlowRow>) -> Option<Self> {
+        (!flows.is_empty()).then_some(Register { flows })
+    }
+
+    /// The most specific declaration covering a transfer (categories already normalised).
+    pub fn matching(&self, ctx: &TransferContext) -> Option<&DeclaredFlowRow> {
+        let categories = ctx.data_categories.clone().unwrap_or_default();
+        self.flows.iter()
+            .filter(|f| f.covers(
+                ctx.source_system.as_deref(),
+                ctx.partner_name.as_deref(),
+                ctx.destination_country_code.as_deref(),
+                &categories,
+                ctx.purpose.as_deref(),
+            ))
+            .max_by_key(|f| f.specificity())
+    }
+
+    /// `DECLARED` or `UNDECLARED_FLOW` for a transfer, with the covering declaration.
+    pub fn status(&self, ctx: &TransferContext) -> (&'static str, Option<Uuid>) {
+        match self.matching(ctx) {
+            Some(flow) => (DECLARED, Some(flow.id)),
+            None => (UNDECLARED_FLOW, None),
+        }
+    }
+}
+
+pub struct DeclareFlowParams {
+    pub name: String,
+    pub source_system: Option<String>,
+    pub partner_name: Option<String>,
+    pub destination_country_code: Option<String>,
+    /// Canonical codes (normalised by the caller).
+    pub data_categories: Vec<String>,
+    pub purpose: String,
+    pub owner: Option<String>,
+    pub created_by: Option<String>,
+    /// Recorded on the open groups the declaration closes.
+    pub resolution_note: Option<String>,
+}
+
+/// Add a declaration and close the open undeclared groups it now covers, in one transaction so a
+/// group opened meanwhile is either closed here or seen by the next declaration.
+pub async fn declare(pool: &PgPool, params: DeclareFlowParams) -> Result<(DeclaredFlowRow, Vec<Uuid>), String> {
+    let mut categories = params.data_categories;
+    categories.sort();
+    categories.dedup();
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let flow: DeclaredFlowRow = sqlx::query_as(
+        r#"INSERT INTO declared_flows
+               (name, source_system, partner_name, destination_country_code, data_categories, purpose, owner, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           RETURNING *"#
+    )
+    .bind(&params.name)
+    .bind(&params.source_system)
+    .bind(&params.partner_name)
+    .bind(params.destination_country_code.as_deref().map(str::to_uppercase))
+    .bind(serde_json::json!(categories))
+    .bind(&params.purpose)
+    .bind(&params.owner)
+    .bind(&params.created_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to declare flow: {}", e))?;
+
+    let covered = close_covered(&mut tx, &flow, params.created_by.as_deref(), params.resolution_note.as_deref()).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    register_event(pool, "FLOW_DECLARED", "L1", serde_json::json!({
+        "declared_flow_id": flow.id.to_string(),
+        "name": flow.name,
+        "source_system": flow.source_system,
+        "partner_name": flow.partner_name,
+        "destination_country_code": flow.destination_country_code,
+        "data_categories": flow.data_categories,
+        "purpose": flow.purpose,
+        "owner": flow.owner,
+        "created_by": flow.created_by,
+        "closed_undeclared_groups": covered.iter().map(Uuid::to_string).collect::<Vec<_>>(),
+    }), flow.id, None).await;
+    Ok((flow, covered))
+}
+
+/// Close the open groups `flow` covers (the SQL form of `DeclaredFlowRow::covers`), returning their IDs.
+async fn close_covered(
+    tx: &mut Transaction<'_, Postgres>,
+    flow: &DeclaredFlowRow,
+    resolved_by: Option<&str>,
+    note: Option<&str>,
+) -> Result<Vec<Uuid>, String> {
+    sqlx::query_scalar(
+        r#"UPDATE undeclared_flows
+           SET status = 'declared', declared_flow_id = $1, resolved_by = $2, resolution_note = $3,
+               resolved_at = NOW(), updated_at = NOW()
+           WHERE status = 'open'
+             AND ($4::text IS NULL OR LOWER(BTRIM(source_system)) = LOWER(BTRIM($4)))
+             AND ($5::text IS NULL OR LOWER(BTRIM(partner_name)) = LOWER(BTRIM($5)))
+             AND ($6::text IS NULL OR LOWER(BTRIM(destination_country_code)) = LOWER(BTRIM($6)))
+             AND ($7::jsonb = '[]'::jsonb OR data_categories <@ $7::jsonb)
+           RETURNING id"#
+    )
+    .bind(flow.id)
+    .bind(resolved_by)
+    .bind(note)
+    .bind(&flow.source_system)
+    .bind(&flow.partner_name)
+    .bind(&flow.destination_country_code)
+    .bind(&flow.data_categories)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to close covered groups: {}", e))
+}
+
+pub async fn list_declared(pool: &PgPool, active: Option<bool>) -> Result<Vec<DeclaredFlowRow>, String> {
+    sqlx::query_as::<_, DeclaredFlowRow>(
+        "SELECT * FROM declared_flows WHERE ($1::boolean IS NULL OR active = $1) ORDER BY created_at DESC"
+    )
+    .bind(active)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list declared flows: {}", e))
+}
+
+/// Retire a declaration; transfers it covered are undeclared from the next batch on.
+pub async fn retire(pool: &PgPool, id: Uuid, retired_by: Option<&str>) -> Result<Option<DeclaredFlowRow>, String> {
+    let flow: Option<DeclaredFlowRow> = sqlx::query_as(
+        "UPDATE declared_flows SET active = FALSE, updated_at = NOW() WHERE id = $1 AND active RETURNING *"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to retire flow: {}", e))?;
+    if let Some(flow) = &flow {
+        register_event(pool, "FLOW_RETIRED", "L1", serde_json::json!({
+            "declared_flow_id": flow.id.to_string(),
+            "name": flow.name,
+            "retired_by": retired_by,
+        }), flow.id, None).await;
+    }
+    Ok(flow)
+}
+
+/// One transfer marked `UNDECLARED_FLOW`, with where its evidence went.
+pub struct Sighting<'a> {
+    pub ctx: &'a TransferContext,
+    pub occurred_at: DateTime<Utc>,
+    pub decision: String,
+    pub evidence_id: Option<String>,
+    pub flow_id: Option<Uuid>,
+}
+
+fn group_key(source_system: Option<&str>, partner_name: Option<&str>, country: Option<&str>, categories: &[String]) -> String {
+    let parts = serde_json::json!([
+        source_system.map(|s| s.trim().to_lowercase()),
+        partner_name.map(|p| p.trim().to_lowercase()),
+        country.map(str::to_uppercase),
+        categories,
+    ]);
+    let mut hasher = Sha256::new();
+    hasher.update(parts.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Count sightings into their triage groups. A new group writes `UNDECLARED_FLOW_DETECTED` evidence;
+/// a dismissed group keeps counting without reopening. Returns the groups opened.
+pub async fn record_undeclared(pool: &PgPool, sightings: &[Sighting<'_>]) -> Result<Vec<UndeclaredFlowRow>, String> {
+    struct Group<'a> {
+        first: &'a Sighting<'a>,
+        categories: Vec<String>,
+        latest: &'a Sighting<'a>,
+        count: i64,
+        first_seen: DateTime<Utc>,
+    }
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    for sighting in sightings {
+        let mut categories = sighting.ctx.data_categories.clone().unwrap_or_default();
+        categories.sort();
+        categories.dedup();
+        let key = group_key(
+            sighting.ctx.source_system.as_deref(),
+            sighting.ctx.partner_name.as_deref(),
+            sighting.ctx.destination_country_code.as_deref(),
+            &categories,
+        );
+        let group = groups.entry(key.clone()).or_insert_with(|| {
+            order.push(key);
+            Group { first: sighting, categories, latest: sighting, count: 0, first_seen: sighting.occurred_at }
+        });
+        group.count += 1;
+        group.first_seen = group.first_seen.min(sighting.occurred_at);
+        if sighting.occurred_at >= group.latest.occurred_at {
+            group.latest = sighting;
+        }
+    }
+
+    let mut opened = Vec::new();
+    for key in order {
+        let group = &groups[&key];
+        let ctx = group.first.ctx;
+        let (row, inserted): (UndeclaredFlowRow, bool) = {
+            #[derive(sqlx::FromRow)]
+            struct Upserted {
+                #[sqlx(flatten)]
+                group: UndeclaredFlowRow,
+                inserted: bool,
+            }
+            let upserted: Upserted = sqlx::query_as(
+                r#"INSERT INTO undeclared_flows
+                       (group_key, source_system, partner_name, destination_country_code, data_categories,
+                        transfer_count, first_seen_at, last_seen_at, last_decision, last_evidence_id, last_flow_id)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                   ON CONFLICT (group_key) WHERE status <> 'declared' DO UPDATE SET
+                       transfer_count = undeclared_flows.transfer_count + EXCLUDED.transfer_count,
+                       first_seen_at = LEAST(undeclared_flows.first_seen_at, EXCLUDED.first_seen_at),
+                       last_seen_at = GREATEST(undeclared_flows.last_seen_at, EXCLUDED.last_seen_at),
+                       last_decision = EXCLUDED.last_decision,
+                       last_evidence_id = COALESCE(EXCLUDED.last_evidence_id, undeclared_flows.last_evidence_id),
+                       last_flow_id = COALESCE(EXCLUDED.last_flow_id, undeclared_flows.last_flow_id),
+                       updated_at = NOW()
+                   RETURNING *, (xmax = 0) AS inserted"#
+            )
+            .bind(&key)
+            .bind(&ctx.source_system)
+            .bind(&ctx.partner_name)
+            .bind(&ctx.destination_country_code)
+            .bind(serde_json::json!(group.categories))
+            .bind(group.count)
+            .bind(group.first_seen)
+            .bind(group.latest.occurred_at)
+            .bind(&group.latest.decision)
+            .bind(&group.latest.evidence_id)
+            .bind(group.latest.flow_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to record undeclared flow: {}", e))?;
+            (upserted.group, upserted.inserted)
+        };
+        if !inserted {
+            continue;
+        }
+
+        let mut row = row;
+        let event_id = register_event(pool, "UNDECLARED_FLOW_DETECTED", "L2", serde_json::json!({
+            "undeclared_flow_id": row.id.to_string(),
+            "source_system": row.source_system,
+            "partner_name": row.partner_name,
+            "destination_country_code": row.destination_country_code,
+            "data_categories": row.data_categories,
+            "decision": row.last_decision,
+            "transfer_evidence_id": row.last_evidence_id,
+            "transfer_flow_id": row.last_flow_id.map(|id| id.to_string()),
+        }), row.id, group.first.evidence_id.clone()).await;
+        if let Some(event_id) = event_id {
+            let _ = sqlx::query("UPDATE undeclared_flows SET evidence_event_id = $1 WHERE id = $2")
+                .bind(&event_id)
+                .bind(row.id)
+                .execute(pool)
+                .await;
+            row.evidence_event_id = Some(event_id);
+        }
+        opened.push(row);
+    }
+    Ok(opened)
+}
+
+pub async fn list_undeclared(pool: &PgPool, status: Option<&str>, limit: i64) -> Result<Vec<UndeclaredFlowRow>, String> {
+    sqlx::query_as::<_, UndeclaredFlowRow>(
+        r#"SELECT * FROM undeclared_flows
+           WHERE ($1::text IS NULL OR status = $1)
+           ORDER BY last_seen_at DESC
+           LIMIT $2"#
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list undeclared flows: {}", e))
+}
+
+pub async fn get_undeclared(pool: &PgPool, id: Uuid) -> Result<Option<UndeclaredFlowRow>, String> {
+    sqlx::query_as::<_, UndeclaredFlowRow>("SELECT * FROM undeclared_flows WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to read undeclared flow: {}", e))
+}
+
+pub async fn get_declared(pool: &PgPool, id: Uuid) -> Result<Option<DeclaredFlowRow>, String> {
+    sqlx::query_as::<_, DeclaredFlowRow>("SELECT * FROM declared_flows WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to read declared flow: {}", e))
+}
+
+/// Whether `flow` covers the transfers grouped in `group`.
+pub fn covers_group(flow: &DeclaredFlowRow, group: &UndeclaredFlowRow) -> bool {
+    flow.covers(
+        group.source_system.as_deref(),
+        group.partner_name.as_deref(),
+        group.destination_country_code.as_deref(),
+        &categories_of(&group.data_categories),
+        None,
+    )
+}
+
+/// Close an open group as `declared` (linked to the declaration now covering it) or `dismissed`.
+/// `None` when the group does not exist or is already resolved.
+pub async fn resolve(
+    pool: &PgPool,
+    id: Uuid,
+    declared_flow_id: Option<Uuid>,
+    resolved_by: Option<&str>,
+    note: Option<&str>,
+) -> Result<Option<UndeclaredFlowRow>, String> {
+    let status = if declared_flow_id.is_some() { "declared" } else { "dismissed" };
+    let row: Option<UndeclaredFlowRow> = sqlx::query_as(
+        r#"UPDATE undeclared_flows
+           SET status = $2, declared_flow_id = $3, resolved_by = $4, resolution_note = $5,
+               resolved_at = NOW(), updated_at = NOW()
+           WHERE id = $1 AND status = 'open'
+           RETURNING *"#
+    )
+    .bind(id)
+    .bind(status)
+    .bind(declared_flow_id)
+    .bind(resolved_by)
+    .bind(note)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to resolve undeclared flow: {}", e))?;
+    if let Some(row) = &row {
+        register_event(pool, "UNDECLARED_FLOW_RESOLVED", "L1", serde_json::json!({
+            "undeclared_flow_id": row.id.to_string(),
+            "status": row.status,
+            "declared_flow_id": row.declared_flow_id.map(|id| id.to_string()),
+            "transfer_count": row.transfer_count,
+            "resolved_by": row.resolved_by,
+            "resolution_note": row.resolution_note,
+        }), row.id, row.evidence_event_id.clone()).await;
+    }
+    Ok(row)
+}
+
+/// Write a register event; failures are logged, since the register change itself has been stored.
+async fn register_event(
+    pool: &PgPool,
+    event_type: &str,
+    severity: &str,
+    payload: serde_json::Value,
+    correlation_id: Uuid,
+    causation_id: Option<String>,
+) -> Option<String> {
+    match evidence::create_event(pool, CreateEventParams {
+        event_type: event_type.into(),
+        severity: severity.into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: vec!["GDPR Art. 30".into(), "GDPR Art. 44".into()],
+        payload,
+        correlation_id: Some(correlation_id.to_string()),
+        causation_id,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await {
+        Ok(event) => Some(event.event_id),
+        Err(e) => {
+            log::error!("Failed to create {} evidence for {}: {}", event_type, correlation_id, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(source: Option<&str>, partner: Option<&str>, country: Option<&str>, categories: &[&str], purpose: &str) -> DeclaredFlowRow {
+        DeclaredFlowRow {
+            id: Uuid::new_v4(),
+            name: purpose.into(),
+            source_system: source.map(String::from),
+            partner_name: partner.map(String::from),
+            destination_country_code: country.map(String::from),
+            data_categories: serde_json::json!(categories),
+            purpose: purpose.into(),
+            owner: None,
+            active: true,
+            created_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn transfer(source: &str, partner: &str, country: &str, categories: &[&str], purpose: Option<&str>) -> TransferContext {
+        TransferContext {
+            source_system: Some(source.into()),
+            partner_name: Some(partner.into()),
+            destination_country_code: Some(country.into()),
+            data_categories: Some(categories.iter().map(|c| c.to_string()).collect()),
+            purpose: purpose.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn wildcard_partner_and_country() {
+        let any = flow(Some("crm"), None, None, &["email"], "Newsletter delivery");
+        let register = Register::from_flows(vec![any.clone()]).unwrap();
+        for (partner, country) in [("Mailer Inc", "US"), ("Other GmbH", "DE")] {
+            assert_eq!(register.status(&transfer(" CRM ", partner, country, &["email"], None)), (DECLARED, Some(any.id)));
+        }
+        assert_eq!(register.status(&transfer("billing", "Mailer Inc", "US", &["email"], None)), (UNDECLARED_FLOW, None));
+        assert_eq!(register.status(&transfer("crm", "Mailer Inc", "US", &["email", "health"], None)), (UNDECLARED_FLOW, None));
+
+        // The most specific covering declaration wins
+        let exact = flow(Some("crm"), Some("mailer inc"), Some("US"), &["email"], "Newsletter delivery");
+        let register = Register::from_flows(vec![any, exact.clone()]).unwrap();
+        assert_eq!(register.status(&transfer("crm", "Mailer Inc", "us", &["email"], None)).1, Some(exact.id));
+    }
+
+    #[test]
+    fn purpose_mismatch() {
+        let declared = flow(Some("crm"), Some("Mailer Inc"), Some("US"), &[], "Newsletter delivery");
+        let register = Register::from_flows(vec![declared.clone()]).unwrap();
+        assert_eq!(register.status(&transfer("crm", "Mailer Inc", "US", &["email"], Some("newsletter delivery"))).1, Some(declared.id));
+        assert_eq!(register.status(&transfer("crm", "Mailer Inc", "US", &["email"], Some("Ad targeting"))), (UNDECLARED_FLOW, None));
+        // A transfer that states no purpose is matched on the other fields
+        assert_eq!(register.status(&transfer("crm", "Mailer Inc", "US", &["email"], None)).1, Some(declared.id));
+    }
+
+    #[test]
+    fn no_register_configured() {
+        assert!(Register::from_flows(Vec::new()).is_none());
+    }
+}
@@ -0,0 +1,32 @@
+//! Host maps: comma-separated `host=CC` or `host=CC:Partner Name` entries placing destination hosts
+//! in a country, where `*.example.com` matches any subdomain. `EXT_AUTHZ_HOST_MAP` is read by every
+//! source that sees host names (ext_authz, traces, CSP reports, IaC scans); `MAIL_PROVIDER_MAP`
+//! uses the same syntax for mail relays.
+
+/// (country code, partner) for `host` from `EXT_AUTHZ_HOST_MAP`.
+pub fn lookup_env(host: &str) -> Option<(String, Option<String>)> {
+    lookup(&std::env::var("EXT_AUTHZ_HOST_MAP").ok()?, host)
+}
+
+/// Match `host` against a map in the `EXT_AUTHZ_HOST_MAP` syntax; the first matching entry wins.
+pub fn lookup(map: &str, host: &str) -> Option<(String, Option<String>)> {
+    for entry in map.split(',') {
+        let Some((pattern, target)) = entry.split_once('=') else {
+            continue;
+        };
+        let pattern = pattern.trim().to_lowercase();
+        let matches = match pattern.strip_prefix("*.") {
+            Some(suffix) => host.ends_with(&format!(".{}", suffix)),
+            None => host == pattern,
+        };
+        if !matches {
+            continue;
+        }
+        let (code, partner) = match target.split_once(':') {
+            Some((c, p)) => (c, Some(p.trim().to_string()).filter(|p| !p.is_empty())),
+            None => (target, None),
+        };
+        return Some((code.trim().to_uppercase(), partner));
+    }
+    None
+}
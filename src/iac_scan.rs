@@ -0,0 +1,628 @@
+//! Infrastructure-as-code scanning: cloud regions, external endpoints and replication targets found in
+//! a Terraform plan (`terraform show -json`) or Kubernetes manifests are evaluated as transfers against
+//! an exported policy snapshot, without a database, so CI can stop a deployment before data moves.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
+
+use crate::log_formats::{host_of, is_internal};
+use crate::host_map;
+use crate::shield::{Decision, ExportedPolicy, PolicySnapshot, TransferContext};
+
+/// Cloud region (or location) to country and provider. Zones (`eu-west-1a`, `europe-west3-b`) resolve
+/// through their region; anything else can be added with `--map region=CC`.
+const REGIONS: &[(&str, &str, &str)] = &[
+    // AWS
+    ("us-east-1", "US", "aws"), ("us-east-2", "US", "aws"), ("us-west-1", "US", "aws"), ("us-west-2", "US", "aws"),
+    ("us-gov-east-1", "US", "aws"), ("us-gov-west-1", "US", "aws"), ("ca-central-1", "CA", "aws"), ("ca-west-1", "CA", "aws"),
+    ("mx-central-1", "MX", "aws"), ("sa-east-1", "BR", "aws"),
+    ("eu-central-1", "DE", "aws"), ("eu-central-2", "CH", "aws"), ("eu-west-1", "IE", "aws"), ("eu-west-2", "GB", "aws"),
+    ("eu-west-3", "FR", "aws"), ("eu-north-1", "SE", "aws"), ("eu-south-1", "IT", "aws"), ("eu-south-2", "ES", "aws"),
+    ("il-central-1", "IL", "aws"), ("me-south-1", "BH", "aws"), ("me-central-1", "AE", "aws"), ("af-south-1", "ZA", "aws"),
+    ("ap-east-1", "HK", "aws"), ("ap-south-1", "IN", "aws"), ("ap-south-2", "IN", "aws"), ("ap-southeast-1", "SG", "aws"),
+    ("ap-southeast-2", "AU", "aws"), ("ap-southeast-3", "ID", "aws"), ("ap-southeast-4", "AU", "aws"),
+    ("ap-southeast-5", "MY", "aws"), ("ap-southeast-7", "TH", "aws"), ("ap-northeast-1", "JP", "aws"),
+    ("ap-northeast-2", "KR", "aws"), ("ap-northeast-3", "JP", "aws"), ("cn-north-1", "CN", "aws"), ("cn-northwest-1", "CN", "aws"),
+    // Google Cloud
+    ("us-central1", "US", "gcp"), ("us-east1", "US", "gcp"), ("us-east4", "US", "gcp"), ("us-east5", "US", "gcp"),
+    ("us-south1", "US", "gcp"), ("us-west1", "US", "gcp"), ("us-west2", "US", "gcp"), ("us-west3", "US", "gcp"),
+    ("us-west4", "US", "gcp"), ("us", "US", "gcp"), ("northamerica-northeast1", "CA", "gcp"),
+    ("northamerica-northeast2", "CA", "gcp"), ("northamerica-south1", "MX", "gcp"), ("southamerica-east1", "BR", "gcp"),
+    ("southamerica-west1", "CL", "gcp"), ("europe-west1", "BE", "gcp"), ("europe-west2", "GB", "gcp"),
+    ("europe-west3", "DE", "gcp"), ("europe-west4", "NL", "gcp"), ("europe-west6", "CH", "gcp"), ("europe-west8", "IT", "gcp"),
+    ("europe-west9", "FR", "gcp"), ("europe-west10", "DE", "gcp"), ("europe-west12", "IT", "gcp"), ("europe-north1", "FI", "gcp"),
+    ("europe-north2", "SE", "gcp"), ("europe-central2", "PL", "gcp"), ("europe-southwest1", "ES", "gcp"), ("me-west1", "IL", "gcp"),
+    ("me-central1", "QA", "gcp"), ("me-central2", "SA", "gcp"), ("africa-south1", "ZA", "gcp"), ("asia-east1", "TW", "gcp"),
+    ("asia-east2", "HK", "gcp"), ("asia-northeast1", "JP", "gcp"), ("asia-northeast2", "JP", "gcp"), ("asia-northeast3", "KR", "gcp"),
+    ("asia-south1", "IN", "gcp"), ("asia-south2", "IN", "gcp"), ("asia-southeast1", "SG", "gcp"), ("asia-southeast2", "ID", "gcp"),
+    ("australia-southeast1", "AU", "gcp"), ("australia-southeast2", "AU", "gcp"),
+    // Azure (names as in `location`, spaces removed)
+    ("eastus", "US", "azure"), ("eastus2", "US", "azure"), ("centralus", "US", "azure"), ("northcentralus", "US", "azure"),
+    ("southcentralus", "US", "azure"), ("westcentralus", "US", "azure"), ("westus", "US", "azure"), ("westus2", "US", "azure"),
+    ("westus3", "US", "azure"), ("canadacentral", "CA", "azure"), ("canadaeast", "CA", "azure"), ("mexicocentral", "MX", "azure"),
+    ("brazilsouth", "BR", "azure"), ("northeurope", "IE", "azure"), ("westeurope", "NL", "azure"), ("uksouth", "GB", "azure"),
+    ("ukwest", "GB", "azure"), ("francecentral", "FR", "azure"), ("francesouth", "FR", "azure"),
+    ("germanywestcentral", "DE", "azure"), ("germanynorth", "DE", "azure"), ("switzerlandnorth", "CH", "azure"),
+    ("switzerlandwest", "CH", "azure"), ("norwayeast", "NO", "azure"), ("norwaywest", "NO", "azure"),
+    ("swedencentral", "SE", "azure"), ("polandcentral", "PL", "azure"), ("italynorth", "IT", "azure"),
+    ("spaincentral", "ES", "azure"), ("israelcentral", "IL", "azure"), ("qatarcentral", "QA", "azure"),
+    ("uaenorth", "AE", "azure"), ("uaecentral", "AE", "azure"), ("southafricanorth", "ZA", "azure"),
+    ("southafricawest", "ZA", "azure"), ("eastasia", "HK", "azure"), ("southeastasia", "SG", "azure"),
+    ("japaneast", "JP", "azure"), ("japanwest", "JP", "azure"), ("koreacentral", "KR", "azure"), ("koreasouth", "KR", "azure"),
+    ("australiaeast", "AU", "azure"), ("australiasoutheast", "AU", "azure"), ("australiacentral", "AU", "azure"),
+    ("centralindia", "IN", "azure"), ("southindia", "IN", "azure"), ("westindia", "IN", "azure"),
+    ("chinanorth", "CN", "azure"), ("chinaeast", "CN", "azure"), ("chinanorth3", "CN", "azure"), ("chinaeast2", "CN", "azure"),
+];
+
+const PROVIDERS: &[(&str, &str)] = &[("aws", "Amazon Web Services"), ("gcp", "Google Cloud"), ("azure", "Microsoft Azure")];
+
+/// Keys whose value names a region; `location` only counts when the value is a known region, since
+/// manifests use it for other things (`location: MESH_EXTERNAL`).
+const REGION_KEYS: &[&str] = &["region", "region_name", "regionName", "replica_region", "destination_region", "replica_location"];
+const REGION_LABELS: &[&str] = &["topology.kubernetes.io/region", "failure-domain.beta.kubernetes.io/region"];
+/// A region found under one of these keys is a copy of the data, not where the resource itself runs.
+const REPLICATION_KEYWORDS: &[&str] = &["replica", "replication", "geo_location", "destination", "backup", "secondary"];
+const CATEGORY_KEYS: &[&str] = &["data-categories", "data_categories", "veridion:data-categories", "veridion.io/data-categories"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    Region,
+    Endpoint,
+    Replication,
+}
+
+impl std::fmt::Display for TargetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TargetKind::Region => write!(f, "region"),
+            TargetKind::Endpoint => write!(f, "endpoint"),
+            TargetKind::Replication => write!(f, "replication"),
+        }
+    }
+}
+
+/// Where data would go, and the resource or manifest object that sends it there.
+struct Target {
+    source: String,
+    kind: TargetKind,
+    value: String,
+    /// Provider of the resource (`aws`, `gcp`, `azure`), when the source names one.
+    provider: Option<&'static str>,
+    categories: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub kind: TargetKind,
+    pub target: String,
+    pub destination_country_code: Option<String>,
+    pub partner_name: Option<String>,
+    pub data_categories: Option<Vec<String>>,
+    pub decision: Decision,
+    pub reason: String,
+    pub country_status: String,
+    /// Resources or manifest objects that produce this transfer.
+    pub sources: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ScanOptions {
+    /// Categories for targets whose resource declares none.
+    pub categories: Option<Vec<String>>,
+    /// `region=CC` / `host=CC:Partner` entries in the `EXT_AUTHZ_HOST_MAP` syntax, checked first.
+    pub map: String,
+    /// Partner names per provider key, replacing the defaults (to match SCC registry entries).
+    pub partners: HashMap<String, String>,
+}
+
+fn region_entry(region: &str) -> Option<&'static (&'static str, &'static str, &'static str)> {
+    let key: String = region.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let find = |k: &str| REGIONS.iter().find(|(r, _, _)| *r == k);
+    find(&key)
+        // AWS zone `eu-west-1a`
+        .or_else(|| key.strip_suffix(|c: char| c.is_ascii_lowercase()).filter(|k| k.ends_with(|c: char| c.is_ascii_digit())).and_then(find))
+        // Google zone `europe-west3-b`
+        .or_else(|| key.rsplit_once('-').filter(|(_, zone)| zone.len() == 1).and_then(|(r, _)| find(r)))
+}
+
+fn is_cluster_local(host: &str) -> bool {
+    host == "localhost"
+        || !host.contains('.')
+        || [".local", ".internal", ".svc", ".cluster.local", ".svc.cluster.local"].iter().any(|s| host.ends_with(s))
+        || host.parse::<IpAddr>().map(|ip| is_internal(&ip)).unwrap_or(false)
+}
+
+fn provider_of_resource(resource_type: &str) -> Option<&'static str> {
+    match resource_type.split('_').next()? {
+        "aws" => Some("aws"),
+        "google" => Some("gcp"),
+        "azurerm" | "azuread" => Some("azure"),
+        _ => None,
+    }
+}
+
+fn provider_of_host(host: &str) -> Option<&'static str> {
+    if host.ends_with(".amazonaws.com") || host.ends_with(".amazonaws.com.cn") {
+        Some("aws")
+    } else if host.ends_with(".googleapis.com") || host.ends_with(".run.app") || host.ends_with(".cloudfunctions.net") {
+        Some("gcp")
+    } else if host.ends_with(".windows.net") || host.ends_with(".azure.com") || host.ends_with(".azurewebsites.net") {
+        Some("azure")
+    } else {
+        None
+    }
+}
+
+fn categories_in(tags: Option<&Value>) -> Option<Vec<String>> {
+    let tags = tags?.as_object()?;
+    let raw = CATEGORY_KEYS.iter().find_map(|k| tags.get(*k)?.as_str())?;
+    let list: Vec<String> = raw.split([',', ' ']).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+    (!list.is_empty()).then_some(list)
+}
+
+fn is_replication(path: &[String]) -> bool {
+    path.iter().any(|p| REPLICATION_KEYWORDS.iter().any(|k| p.to_lowercase().contains(k)))
+}
+
+/// Region keys, topology selectors and URLs anywhere under `value`.
+fn walk(value: &Value, path: &mut Vec<String>, found: &mut Vec<(TargetKind, String)>) {
+    match value {
+        Value::Object(map) => {
+            // nodeAffinity match expression: {key: topology.kubernetes.io/region, operator: In, values: [...]}
+            if map.get("key").and_then(Value::as_str).map(|k| REGION_LABELS.contains(&k)).unwrap_or(false) {
+                for region in map.get("values").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                    found.push((TargetKind::Region, region.to_string()));
+                }
+            }
+            for (key, child) in map {
+                if let Some(text) = child.as_str() {
+                    let is_region = REGION_LABELS.contains(&key.as_str())
+                        || REGION_KEYS.contains(&key.as_str())
+                        || (key.ends_with("location") && region_entry(text).is_some());
+                    if is_region && !text.is_empty() && text != "global" {
+                        path.push(key.clone());
+                        let kind = if is_replication(path) { TargetKind::Replication } else { TargetKind::Region };
+                        path.pop();
+                        found.push((kind, text.to_string()));
+                        continue;
+                    }
+                }
+                path.push(key.clone());
+                walk(child, path, found);
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                walk(item, path, found);
+            }
+        }
+        Value::String(text) => {
+            let Some((scheme, _)) = text.split_once("://") else {
+                return;
+            };
+            if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+') || text.contains(char::is_whitespace) {
+                return;
+            }
+            if let Some(host) = host_of(text).filter(|h| !is_cluster_local(h)) {
+                let kind = if is_replication(path) { TargetKind::Replication } else { TargetKind::Endpoint };
+                found.push((kind, host));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_found(targets: &mut Vec<Target>, source: &str, provider: Option<&'static str>, categories: &Option<Vec<String>>, found: Vec<(TargetKind, String)>) {
+    for (kind, value) in found {
+        targets.push(Target { source: source.to_string(), kind, value, provider, categories: categories.clone() });
+    }
+}
+
+fn module_resources<'a>(module: &'a Value, out: &mut Vec<&'a Value>) {
+    out.extend(module.get("resources").and_then(Value::as_array).into_iter().flatten());
+    for child in module.get("child_modules").and_then(Value::as_array).into_iter().flatten() {
+        module_resources(child, out);
+    }
+}
+
+/// Resources in `planned_values`; a resource without its own region runs in its provider's.
+fn terraform_targets(plan: &Value) -> Vec<Target> {
+    let mut provider_regions: HashMap<String, String> = HashMap::new();
+    for (key, config) in plan.pointer("/configuration/provider_config").and_then(Value::as_object).into_iter().flatten() {
+        let region = ["region", "location"].iter()
+            .find_map(|k| config.pointer(&format!("/expressions/{}/constant_value", k))?.as_str());
+        if let Some(region) = region {
+            provider_regions.insert(key.clone(), region.to_string());
+        }
+    }
+    let provider_keys: HashMap<&str, &str> = plan.pointer("/configuration/root_module/resources")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|r| Some((r.get("address")?.as_str()?, r.get("provider_config_key")?.as_str()?)))
+        .collect();
+
+    let mut resources = Vec::new();
+    if let Some(root) = plan.pointer("/planned_values/root_module") {
+        module_resources(root, &mut resources);
+    }
+    let mut targets = Vec::new();
+    for resource in resources {
+        let address = resource.get("address").and_then(Value::as_str).unwrap_or("resource");
+        let resource_type = resource.get("type").and_then(Value::as_str).unwrap_or("");
+        if resource.get("mode").and_then(Value::as_str) == Some("data") {
+            continue;
+        }
+        let provider = provider_of_resource(resource_type);
+        let values = resource.get("values").unwrap_or(&Value::Null);
+        let categories = categories_in(values.get("tags")).or_else(|| categories_in(values.get("labels")));
+
+        let mut found = Vec::new();
+        walk(values, &mut Vec::new(), &mut found);
+        if provider.is_some() && !found.iter().any(|(kind, _)| *kind == TargetKind::Region) {
+            let provider_name = resource_type.split('_').next().unwrap_or("");
+            let key = provider_keys.get(address).copied().unwrap_or(provider_name);
+            if let Some(region) = provider_regions.get(key) {
+                found.push((TargetKind::Region, region.clone()));
+            }
+        }
+        push_found(&mut targets, address, provider, &categories, found);
+    }
+    targets
+}
+
+fn manifest_targets(doc: &Value, targets: &mut Vec<Target>) {
+    if doc.get("kind").and_then(Value::as_str) == Some("List") {
+        for item in doc.get("items").and_then(Value::as_array).into_iter().flatten() {
+            manifest_targets(item, targets);
+        }
+        return;
+    }
+    let Some(kind) = doc.get("kind").and_then(Value::as_str) else {
+        return;
+    };
+    let name = doc.pointer("/metadata/name").and_then(Value::as_str).unwrap_or("unnamed");
+    let source = match doc.pointer("/metadata/namespace").and_then(Value::as_str) {
+        Some(ns) => format!("{}/{}/{}", kind, ns, name),
+        None => format!("{}/{}", kind, name),
+    };
+    let categories = categories_in(doc.pointer("/metadata/annotations")).or_else(|| categories_in(doc.pointer("/metadata/labels")));
+
+    let mut found = Vec::new();
+    match kind {
+        "Service" if doc.pointer("/spec/type").and_then(Value::as_str) == Some("ExternalName") => {
+            if let Some(host) = doc.pointer("/spec/externalName").and_then(Value::as_str).and_then(host_of) {
+                if !is_cluster_local(&host) {
+                    found.push((TargetKind::Endpoint, host));
+                }
+            }
+        }
+        // Istio: hosts outside the mesh
+        "ServiceEntry" if doc.pointer("/spec/location").and_then(Value::as_str) != Some("MESH_INTERNAL") => {
+            for host in doc.pointer("/spec/hosts").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                let host = host.trim_start_matches("*.").to_lowercase();
+                if !is_cluster_local(&host) {
+                    found.push((TargetKind::Endpoint, host));
+                }
+            }
+        }
+        _ => {}
+    }
+    walk(doc, &mut Vec::new(), &mut found);
+    push_found(targets, &source, None, &categories, found);
+}
+
+/// Targets in one file: a Terraform plan (JSON with `planned_values`) or Kubernetes manifests
+/// (YAML, possibly several documents, or JSON).
+fn file_targets(path: &str, content: &str) -> Result<Vec<Target>, String> {
+    if let Ok(json) = serde_json::from_str::<Value>(content) {
+        if json.get("planned_values").is_some() || json.get("resource_changes").is_some() {
+            return Ok(terraform_targets(&json));
+        }
+        let mut targets = Vec::new();
+        manifest_targets(&json, &mut targets);
+        return Ok(targets);
+    }
+    let mut targets = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let yaml = serde_yaml::Value::deserialize(document).map_err(|e| format!("{}: {}", path, e))?;
+        let json = serde_json::to_value(yaml).map_err(|e| format!("{}: {}", path, e))?;
+        manifest_targets(&json, &mut targets);
+    }
+    Ok(targets)
+}
+
+/// Kind, target, partner and categories: targets with the same key are one transfer.
+type TargetKey = (TargetKind, String, Option<String>, Option<Vec<String>>);
+
+/// Evaluate each distinct target once, listing every source that produces it.
+fn evaluate(snapshot: &PolicySnapshot, targets: Vec<Target>, options: &ScanOptions) -> Vec<Finding> {
+    let host_map = std::env::var("EXT_AUTHZ_HOST_MAP").unwrap_or_default();
+    let partner_for = |provider: &str| {
+        options.partners.get(provider).cloned()
+            .or_else(|| PROVIDERS.iter().find(|(k, _)| *k == provider).map(|(_, name)| name.to_string()))
+    };
+
+    let mut grouped: BTreeMap<TargetKey, (Option<String>, BTreeSet<String>)> = BTreeMap::new();
+    for target in targets {
+        let mapped = host_map::lookup(&options.map, &target.value)
+            .or_else(|| (target.kind != TargetKind::Region).then(|| host_map::lookup(&host_map, &target.value)).flatten());
+        let (country, partner) = match mapped {
+            Some((code, partner)) => {
+                let partner = partner.or_else(|| target.provider.and_then(partner_for));
+                (Some(code), partner)
+            }
+            None => {
+                // Regions directly; hosts through a region label (`s3.eu-west-1.amazonaws.com`)
+                let entry = region_entry(&target.value)
+                    .or_else(|| target.value.split('.').find_map(region_entry).filter(|_| target.value.contains('.')));
+                let provider = target.provider
+                    .or_else(|| provider_of_host(&target.value))
+                    .or_else(|| entry.map(|(_, _, p)| *p));
+                let partner = provider.and_then(partner_for)
+                    .or_else(|| (target.kind != TargetKind::Region).then(|| target.value.clone()));
+                (entry.map(|(_, code, _)| code.to_string()), partner)
+            }
+        };
+        let categories = target.categories.or_else(|| options.categories.clone());
+        let key = (target.kind, target.value, partner, categories);
+        grouped.entry(key).or_insert_with(|| (country, BTreeSet::new())).1.insert(target.source);
+    }
+
+    grouped.into_iter().map(|((kind, target, partner, categories), (country, sources))| {
+        let ctx = TransferContext {
+            destination_country_code: country.clone(),
+            data_categories: categories.clone(),
+            partner_name: partner.clone(),
+            source_system: sources.iter().next().cloned(),
+            ..Default::default()
+        };
+        let mut decision = snapshot.evaluate(&ctx);
+        if country.is_none() {
+            decision.reason = format!("{} — add `--map {}=CC` to place it", decision.reason, target);
+        }
+        Finding {
+            kind,
+            target,
+            destination_country_code: country,
+            partner_name: partner,
+            data_categories: categories,
+            decision: decision.decision,
+            reason: decision.reason,
+            country_status: decision.country_status,
+            sources: sources.into_iter().collect(),
+        }
+    }).collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanReport<'a> {
+    policy_exported_at: &'a str,
+    allow: usize,
+    review: usize,
+    block: usize,
+    findings: &'a [Finding],
+}
+
+const USAGE: &str = "Usage: veridion-api scan-iac --policy <snapshot.json> [--categories a,b] [--map key=CC[:Partner]]... \
+[--partner aws|gcp|azure=Name]... [--json] <plan.json|manifest.yaml>...";
+
+/// `scan-iac`: exit code 0 when every target is allowed, 1 on any BLOCK or REVIEW, 2 on usage or input errors.
+pub fn run_cli(args: &[String]) -> std::io::Result<i32> {
+    let mut policy_path: Option<&str> = None;
+    let mut files: Vec<&str> = Vec::new();
+    let mut json = false;
+    let mut options = ScanOptions::default();
+    let mut maps: Vec<String> = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--policy" => policy_path = iter.next().map(String::as_str),
+            "--categories" => {
+                options.categories = iter.next().map(|c| c.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect());
+            }
+            "--map" => maps.extend(iter.next().cloned()),
+            "--partner" => {
+                if let Some((provider, name)) = iter.next().and_then(|p| p.split_once('=')) {
+                    options.partners.insert(provider.trim().to_lowercase(), name.trim().to_string());
+                }
+            }
+            "--json" => json = true,
+            other if other.starts_with("--") => {
+                eprintln!("Unknown option {}\n{}", other, USAGE);
+                return Ok(2);
+            }
+            file => files.push(file),
+        }
+    }
+    let (Some(policy_path), false) = (policy_path, files.is_empty()) else {
+        eprintln!("{}", USAGE);
+        return Ok(2);
+    };
+    options.map = maps.join(",");
+
+    let exported: ExportedPolicy = match std::fs::read_to_string(policy_path).map_err(|e| e.to_string())
+        .and_then(|s| serde_json::from_str(&s).map_err(|e| e.to_string()))
+    {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Cannot read policy snapshot {}: {}", policy_path, e);
+            return Ok(2);
+        }
+    };
+    let exported_at = exported.exported_at.clone();
+    let snapshot = match PolicySnapshot::from_export(exported) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}: {}", policy_path, e);
+            return Ok(2);
+        }
+    };
+
+    let mut targets = Vec::new();
+    for file in &files {
+        let content = std::fs::read_to_string(file)?;
+        match file_targets(file, &content) {
+            Ok(found) => targets.extend(found),
+            Err(e) => {
+                eprintln!("Cannot parse {}", e);
+                return Ok(2);
+            }
+        }
+    }
+    let findings = evaluate(&snapshot, targets, &options);
+    let count = |d: Decision| findings.iter().filter(|f| f.decision == d).count();
+    let (allow, review, block) = (count(Decision::ALLOW), count(Decision::REVIEW), count(Decision::BLOCK));
+
+    if json {
+        let report = ScanReport { policy_exported_at: &exported_at, allow, review, block, findings: &findings };
+        println!("{}", serde_json::to_string_pretty(&report).map_err(std::io::Error::other)?);
+    } else {
+        println!("Policy snapshot exported {}", exported_at);
+        for f in &findings {
+            let more = if f.sources.len() > 1 { format!(" (+{} more)", f.sources.len() - 1) } else { String::new() };
+            println!(
+                "{:<7} {:<12} {:<40} {:<3} {}",
+                f.decision.to_string(),
+                f.kind.to_string(),
+                f.target,
+                f.destination_country_code.as_deref().unwrap_or("?"),
+                f.partner_name.as_deref().unwrap_or("-"),
+            );
+            println!("        {}{}: {}", f.sources.first().map(String::as_str).unwrap_or(""), more, f.reason);
+        }
+        println!("{} target(s): {} ALLOW, {} REVIEW, {} BLOCK", findings.len(), allow, review, block);
+    }
+    Ok(if review + block > 0 { 1 } else { 0 })
+}
+
+/// `export-policy-snapshot [file]`: write the current policy for `scan-iac`, to stdout without a file.
+pub async fn export_cli(pool: &PgPool, args: &[String]) -> std::io::Result<()> {
+    let exported = PolicySnapshot::load(pool).await.and_then(|s| s.export()).map_err(std::io::Error::other)?;
+    let body = serde_json::to_string_pretty(&exported).map_err(std::io::Error::other)?;
+    match args.first() {
+        Some(path) => {
+            std::fs::write(path, body)?;
+            println!("Policy snapshot written to {} ({} overrides, {} SCCs)", path, exported.country_overrides.len(), exported.sccs.len());
+        }
+        None => println!("{}", body),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/iac");
+
+    fn fixture(name: &str) -> String {
+        format!("{}/{}", FIXTURES, name)
+    }
+
+    fn snapshot(name: &str) -> PolicySnapshot {
+        let exported: ExportedPolicy = serde_json::from_str(&std::fs::read_to_string(fixture(name)).unwrap()).unwrap();
+        PolicySnapshot::from_export(exported).unwrap()
+    }
+
+    fn scan(file: &str, policy: &str, options: &ScanOptions) -> Vec<Finding> {
+        let path = fixture(file);
+        let targets = file_targets(&path, &std::fs::read_to_string(&path).unwrap()).unwrap();
+        evaluate(&snapshot(policy), targets, options)
+    }
+
+    /// (kind, target, country, decision) per finding, in evaluation order.
+    fn summary(findings: &[Finding]) -> Vec<(TargetKind, &str, Option<&str>, Decision)> {
+        findings.iter()
+            .map(|f| (f.kind, f.target.as_str(), f.destination_country_code.as_deref(), f.decision))
+            .collect()
+    }
+
+    fn cli(args: &[&str]) -> i32 {
+        run_cli(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn region_names_and_zones() {
+        assert_eq!(region_entry("eu-central-1").map(|e| e.1), Some("DE"));
+        assert_eq!(region_entry("eu-west-1a").map(|e| e.1), Some("IE"));
+        assert_eq!(region_entry("EUROPE-WEST3").map(|e| e.1), Some("DE"));
+        assert_eq!(region_entry("europe-west3-b").map(|e| e.1), Some("DE"));
+        assert_eq!(region_entry("us-east-1").map(|e| e.2), Some("aws"));
+        assert!(region_entry("MESH_EXTERNAL").is_none());
+    }
+
+    #[test]
+    fn terraform_plan_fixture() {
+        let findings = scan("plan.json", "policy.json", &ScanOptions::default());
+        assert_eq!(summary(&findings), vec![
+            (TargetKind::Region, "EUROPE-WEST3", Some("DE"), Decision::ALLOW),
+            (TargetKind::Region, "eu-central-1", Some("DE"), Decision::ALLOW),
+            (TargetKind::Region, "eu-central-1", Some("DE"), Decision::ALLOW),
+            (TargetKind::Replication, "us-east-1", Some("US"), Decision::REVIEW),
+        ]);
+        // The provider's region places resources without their own; data sources are not resources
+        let sources: Vec<&str> = findings.iter().flat_map(|f| f.sources.iter().map(String::as_str)).collect();
+        assert_eq!(sources, vec![
+            "module.archive.google_storage_bucket.archive",
+            "aws_dynamodb_table.users",
+            "aws_s3_bucket.exports",
+            "aws_dynamodb_table.users",
+        ]);
+        assert_eq!(findings[0].partner_name.as_deref(), Some("Google Cloud"));
+        assert_eq!(findings[3].partner_name.as_deref(), Some("Amazon Web Services"));
+        assert_eq!(findings[3].data_categories, Some(vec!["email".to_string()]));
+
+        let findings = scan("plan.json", "policy-scc.json", &ScanOptions::default());
+        assert!(findings.iter().all(|f| f.decision == Decision::ALLOW), "{:?}", findings);
+    }
+
+    #[test]
+    fn kubernetes_manifest_fixture() {
+        let options = ScanOptions { categories: Some(vec!["email".into()]), ..Default::default() };
+        let findings = scan("manifests.yaml", "policy.json", &options);
+        assert_eq!(summary(&findings), vec![
+            (TargetKind::Region, "eu-west-1", Some("IE"), Decision::ALLOW),
+            (TargetKind::Endpoint, "api.payments.example", None, Decision::REVIEW),
+            (TargetKind::Endpoint, "crm.example", None, Decision::REVIEW),
+            (TargetKind::Endpoint, "s3.cn-north-1.amazonaws.com.cn", Some("CN"), Decision::BLOCK),
+        ]);
+        assert_eq!(findings[0].sources, vec!["Deployment/shop/checkout"]);
+        assert!(findings[1].reason.ends_with("add `--map api.payments.example=CC` to place it"), "{}", findings[1].reason);
+        assert_eq!(findings[3].partner_name.as_deref(), Some("Amazon Web Services"));
+
+        let options = ScanOptions {
+            categories: Some(vec!["email".into()]),
+            map: "api.payments.example=IE,crm.example=BR:CRM Ltd".into(),
+            ..Default::default()
+        };
+        let findings = scan("manifests.yaml", "policy.json", &options);
+        assert_eq!(findings[1].destination_country_code.as_deref(), Some("IE"));
+        assert_eq!(findings[1].decision, Decision::ALLOW);
+        assert_eq!((findings[2].destination_country_code.as_deref(), findings[2].partner_name.as_deref()), (Some("BR"), Some("CRM Ltd")));
+        assert_eq!(findings[2].decision, Decision::ALLOW);
+    }
+
+    #[test]
+    fn exit_codes() {
+        let (policy, scc_policy) = (fixture("policy.json"), fixture("policy-scc.json"));
+        let (plan, manifests) = (fixture("plan.json"), fixture("manifests.yaml"));
+        assert_eq!(cli(&["--policy", &scc_policy, &plan]), 0);
+        assert_eq!(cli(&["--policy", &policy, "--json", &plan]), 1);
+        assert_eq!(cli(&["--policy", &policy, "--map", "us-east-1=IE", &plan]), 0);
+        assert_eq!(cli(&["--policy", &scc_policy, "--categories", "email", &plan, &manifests]), 1);
+
+        assert_eq!(cli(&[&plan]), 2);
+        assert_eq!(cli(&["--policy", &policy]), 2);
+        assert_eq!(cli(&["--policy", &policy, "--verbose", &plan]), 2);
+        assert_eq!(cli(&["--policy", &fixture("missing.json"), &plan]), 2);
+        assert_eq!(cli(&["--policy", &plan, &plan]), 2);
+    }
+}
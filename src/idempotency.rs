@@ -0,0 +1,144 @@
+use actix_web::HttpRequest;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{HashMap, HashSet};
+
+/// Default replay window for idempotency keys (24 hours).
+const DEFAULT_WINDOW_SECS: i64 = 86_400;
+
+/// Keys are stored in a VARCHAR(255) column.
+pub const MAX_KEY_LEN: usize = 255;
+
+/// The evidence event (and review) originally written for an idempotency key.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PriorOutcome {
+    pub idempotency_key: String,
+    pub event_id: String,
+    pub severity: String,
+    pub articles: serde_json::Value,
+    pub payload: serde_json::Value,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub review_id: Option<String>,
+}
+
+impl PriorOutcome {
+    fn payload_str(&self, key: &str) -> Option<String> {
+        self.payload.get(key).and_then(|v| v.as_str()).map(String::from)
+    }
+
+    pub fn decision(&self) -> Option<String> {
+        self.payload_str("decision")
+    }
+
+    pub fn reason(&self) -> Option<String> {
+        self.payload_str("reason")
+    }
+
+    pub fn country_status(&self) -> Option<String> {
+        self.payload_str("country_status")
+    }
+
+    pub fn flow_status(&self) -> Option<String> {
+        self.payload_str("flow_status")
+    }
+
+    pub fn declared_flow_id(&self) -> Option<String> {
+        self.payload_str("declared_flow_id")
+    }
+}
+
+/// Replay window: `IDEMPOTENCY_WINDOW_SECS` (default 86400).
+pub fn window_secs() -> i64 {
+    std::env::var("IDEMPOTENCY_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_WINDOW_SECS)
+        .max(0)
+}
+
+/// Read and validate the `Idempotency-Key` header.
+pub fn key_from_request(req: &HttpRequest) -> Result<Option<String>, String> {
+    let Some(value) = req.headers().get("Idempotency-Key") else {
+        return Ok(None);
+    };
+    let key = value.to_str().map_err(|_| "Idempotency-Key must be visible ASCII".to_string())?.trim();
+    validate_key(key)?;
+    Ok(Some(key.to_string()))
+}
+
+pub fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() {
+        return Err("Idempotency key must not be empty".into());
+    }
+    if key.len() > MAX_KEY_LEN {
+        return Err(format!("Idempotency key exceeds {} characters", MAX_KEY_LEN));
+    }
+    Ok(())
+}
+
+/// Latest event per key recorded within the replay window, with the review it opened if any.
+pub async fn find_recent(
+    pool: &PgPool,
+    source_system: &str,
+    keys: &[String],
+) -> Result<HashMap<String, PriorOutcome>, String> {
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<PriorOutcome> = sqlx::query_as(
+        r#"SELECT DISTINCT ON (e.idempotency_key)
+                  e.idempotency_key, e.event_id, e.severity, e.articles, e.payload, e.occurred_at,
+                  (SELECT cr.seal_id FROM compliance_records cr
+                   WHERE cr.evidence_event_id = e.event_id LIMIT 1) AS review_id
+           FROM evidence_events e
+           WHERE e.source_system = $1
+             AND e.idempotency_key = ANY($2)
+             AND e.recorded_at >= NOW() - make_interval(secs => $3)
+           ORDER BY e.idempotency_key, e.recorded_at DESC"#
+    )
+    .bind(source_system)
+    .bind(keys)
+    .bind(window_secs() as f64)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to look up idempotency keys: {}", e))?;
+
+    Ok(rows.into_iter().map(|r| (r.idempotency_key.clone(), r)).collect())
+}
+
+/// Claim keys for new events in the evidence transaction. Returns the keys this transaction now owns;
+/// a key claimed by another writer within the replay window is left out (a concurrent claim blocks
+/// until that writer commits or rolls back).
+pub async fn claim(
+    tx: &mut Transaction<'_, Postgres>,
+    source_system: &str,
+    keys: &[String],
+) -> Result<HashSet<String>, String> {
+    if keys.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let distinct: Vec<String> = keys.iter().cloned().collect::<HashSet<_>>().into_iter().collect();
+    let claimed: Vec<String> = sqlx::query_scalar(
+        r#"INSERT INTO idempotency_keys (source_system, idempotency_key)
+           SELECT $1, unnest($2::text[])
+           ON CONFLICT (source_system, idempotency_key) DO UPDATE SET recorded_at = NOW()
+           WHERE idempotency_keys.recorded_at < NOW() - make_interval(secs => $3)
+           RETURNING idempotency_key"#
+    )
+    .bind(source_system)
+    .bind(&distinct)
+    .bind(window_secs() as f64)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to claim idempotency keys: {}", e))?;
+    Ok(claimed.into_iter().collect())
+}
+
+/// Forget claims older than the replay window.
+pub async fn prune(pool: &PgPool) -> Result<u64, String> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE recorded_at < NOW() - make_interval(secs => $1)")
+        .bind(window_secs() as f64)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected())
+        .map_err(|e| format!("Failed to prune idempotency keys: {}", e))
+}
@@ -0,0 +1,441 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::routes_shield::{self, entry_idempotency_key, IngestEntryResult, IngestItem, IngestLogEntry};
+
+/// Jobs claimed per worker pass; each pass is evaluated against one policy snapshot.
+pub const CLAIM_BATCH: i64 = 100;
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestBatchRow {
+    pub id: Uuid,
+    pub idempotency_key: Option<String>,
+    pub total_entries: i32,
+    /// `pending`, `processing`, `completed` or `completed_with_errors`.
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestJobRow {
+    #[sqlx(rename = "entry_index")]
+    pub index: i32,
+    /// `pending`, `processing`, `done` or `failed`.
+    pub status: String,
+    pub retry_count: i32,
+    /// Last failure; kept after a retry succeeds.
+    pub error_message: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    #[schema(value_type = Option<IngestEntryResult>)]
+    pub result: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestBatchStatus {
+    #[serde(flatten)]
+    pub batch: IngestBatchRow,
+    pub pending: i64,
+    pub processing: i64,
+    pub done: i64,
+    pub failed: i64,
+    /// Pending jobs waiting out a retry backoff.
+    pub retrying: i64,
+    /// Finished (done or failed) entries as a fraction of the batch, 0.0 to 1.0.
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<Vec<IngestJobRow>>,
+}
+
+/// Retry policy for transient failures: `max_attempts` tries in total, backing off
+/// exponentially from `base_backoff_secs` (capped at an hour).
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: i32,
+    pub base_backoff_secs: i64,
+}
+
+const BATCH_COLUMNS: &str = "id, idempotency_key, total_entries, status, created_at, updated_at, completed_at";
+
+async fn find_batch_by_key(pool: &PgPool, key: &str) -> Result<Option<IngestBatchRow>, String> {
+    sqlx::query_as(&format!("SELECT {} FROM ingest_batches WHERE idempotency_key = $1", BATCH_COLUMNS))
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to look up ingest batch: {}", e))
+}
+
+/// Store a submitted batch as one job per entry. Returns the batch and whether it was created;
+/// a batch already submitted under `batch_key` is returned as is.
+/// Entries without an idempotency key get one derived from their job ID, so a retried job
+/// never writes a second evidence event. Entries whose `external_id` is invalid fail immediately.
+pub async fn enqueue(
+    pool: &PgPool,
+    entries: Vec<IngestLogEntry>,
+    batch_key: Option<&str>,
+) -> Result<(IngestBatchRow, bool), String> {
+    if let Some(key) = batch_key {
+        if let Some(existing) = find_batch_by_key(pool, key).await? {
+            return Ok((existing, false));
+        }
+    }
+
+    let total = entries.len();
+    let mut ids = Vec::with_capacity(total);
+    let mut indices = Vec::with_capacity(total);
+    let mut payloads = Vec::with_capacity(total);
+    let mut keys: Vec<Option<String>> = Vec::with_capacity(total);
+    let mut statuses = Vec::with_capacity(total);
+    let mut errors: Vec<Option<String>> = Vec::with_capacity(total);
+    let mut results: Vec<Option<serde_json::Value>> = Vec::with_capacity(total);
+    for (index, entry) in entries.into_iter().enumerate() {
+        let id = Uuid::new_v4();
+        let key = entry_idempotency_key(&entry, batch_key, index);
+        let payload = serde_json::to_value(&entry).map_err(|e| format!("Failed to serialize ingest entry: {}", e))?;
+        ids.push(id);
+        indices.push(index as i32);
+        payloads.push(payload);
+        match key {
+            Ok(key) => {
+                keys.push(Some(key.unwrap_or_else(|| format!("ingest-job:{}", id))));
+                statuses.push("pending".to_string());
+                errors.push(None);
+                results.push(None);
+            }
+            Err(e) => {
+                let result = IngestEntryResult { index, error: Some(e.clone()), ..Default::default() };
+                keys.push(None);
+                statuses.push("failed".to_string());
+                errors.push(Some(e));
+                results.push(serde_json::to_value(result).ok());
+            }
+        }
+    }
+    let queued = statuses.iter().filter(|s| *s == "pending").count();
+    let (status, completed) = if queued == 0 { ("completed_with_errors", true) } else { ("pending", false) };
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let batch: Option<IngestBatchRow> = sqlx::query_as(&format!(
+        r#"INSERT INTO ingest_batches (idempotency_key, total_entries, status, completed_at)
+           VALUES ($1, $2, $3, CASE WHEN $4 THEN NOW() END)
+           ON CONFLICT (idempotency_key) WHERE idempotency_key IS NOT NULL DO NOTHING
+           RETURNING {}"#,
+        BATCH_COLUMNS
+    ))
+    .bind(batch_key)
+    .bind(total as i32)
+    .bind(status)
+    .bind(completed)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create ingest batch: {}", e))?;
+
+    let Some(batch) = batch else {
+        // Lost a race with a concurrent submission of the same key
+        tx.rollback().await.map_err(|e| e.to_string())?;
+        let key = batch_key.unwrap_or_default();
+        return find_batch_by_key(pool, key)
+            .await?
+            .map(|b| (b, false))
+            .ok_or_else(|| format!("Ingest batch for key {} disappeared", key));
+    };
+
+    sqlx::query(
+        r#"INSERT INTO ingest_jobs (id, batch_id, entry_index, payload, idempotency_key, status, error_message, result)
+           SELECT * FROM UNNEST($1::uuid[], $2::uuid[], $3::int[], $4::jsonb[], $5::varchar[], $6::varchar[], $7::text[], $8::jsonb[])"#
+    )
+    .bind(&ids)
+    .bind(vec![batch.id; total])
+    .bind(&indices)
+    .bind(&payloads)
+    .bind(&keys)
+    .bind(&statuses)
+    .bind(&errors)
+    .bind(&results)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to queue ingest jobs: {}", e))?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok((batch, true))
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedJob {
+    id: Uuid,
+    batch_id: Uuid,
+    entry_index: i32,
+    payload: serde_json::Value,
+    idempotency_key: Option<String>,
+    retry_count: i32,
+    error_message: Option<String>,
+    received_at: DateTime<Utc>,
+}
+
+/// Claim due jobs (leased for five minutes so a crashed worker's jobs are picked up again),
+/// evaluate them together and record each outcome. Returns the number of jobs claimed.
+pub async fn process_due(pool: &PgPool, policy: RetryPolicy) -> Result<usize, String> {
+    let claimed: Vec<ClaimedJob> = sqlx::query_as(
+        r#"WITH due AS (
+               SELECT id FROM ingest_jobs
+               WHERE status IN ('pending', 'processing') AND next_attempt_at <= NOW()
+               ORDER BY next_attempt_at
+               LIMIT $1
+               FOR UPDATE SKIP LOCKED
+           )
+           UPDATE ingest_jobs j
+           SET status = 'processing', next_attempt_at = NOW() + INTERVAL '5 minutes', updated_at = NOW()
+           FROM due
+           WHERE j.id = due.id
+           RETURNING j.id, j.batch_id, j.entry_index, j.payload, j.idempotency_key,
+                     j.retry_count, j.error_message, j.received_at"#
+    )
+    .bind(CLAIM_BATCH)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to claim ingest jobs: {}", e))?;
+
+    let count = claimed.len();
+    if count == 0 {
+        return Ok(0);
+    }
+    let batch_ids: Vec<Uuid> = claimed.iter().map(|j| j.batch_id).collect::<HashSet<_>>().into_iter().collect();
+    sqlx::query("UPDATE ingest_batches SET status = 'processing', updated_at = NOW() WHERE id = ANY($1) AND status = 'pending'")
+        .bind(&batch_ids)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to update ingest batches: {}", e))?;
+
+    let mut jobs = Vec::with_capacity(count);
+    let mut items = Vec::with_capacity(count);
+    for job in claimed {
+        match serde_json::from_value::<IngestLogEntry>(job.payload.clone()) {
+            Ok(entry) => {
+                items.push(IngestItem {
+                    entry,
+                    idempotency_key: Ok(job.idempotency_key.clone()),
+                    received_at: job.received_at,
+                });
+                jobs.push(job);
+            }
+            Err(e) => {
+                let error = format!("Unreadable job payload: {}", e);
+                let result = IngestEntryResult { index: job.entry_index as usize, error: Some(error.clone()), ..Default::default() };
+                fail(pool, &job, &error, &result).await?;
+            }
+        }
+    }
+
+    match routes_shield::ingest_entries(pool, items).await {
+        Ok(results) => {
+            for (job, mut result) in jobs.iter().zip(results) {
+                result.index = job.entry_index as usize;
+                let outcome = if result.evidence_id.is_some() || result.flow_id.is_some() {
+                    complete(pool, job, &result).await
+                } else {
+                    let error = result.error.clone().unwrap_or_else(|| "No evidence recorded".into());
+                    retry_or_fail(pool, job, &error, &result, policy).await
+                };
+                if let Err(e) = outcome {
+                    log::error!("Failed to record ingest job {}: {}", job.id, e);
+                }
+            }
+        }
+        Err(e) => {
+            let error = e.to_string();
+            for job in &jobs {
+                let result = IngestEntryResult { index: job.entry_index as usize, error: Some(error.clone()), ..Default::default() };
+                if let Err(e) = retry_or_fail(pool, job, &error, &result, policy).await {
+                    log::error!("Failed to record ingest job {}: {}", job.id, e);
+                }
+            }
+        }
+    }
+
+    refresh_batches(pool, &batch_ids).await?;
+    Ok(count)
+}
+
+async fn complete(pool: &PgPool, job: &ClaimedJob, result: &IngestEntryResult) -> Result<(), String> {
+    sqlx::query("UPDATE ingest_jobs SET status = 'done', result = $2, updated_at = NOW() WHERE id = $1")
+        .bind(job.id)
+        .bind(serde_json::to_value(result).unwrap_or_default())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Carry the retry history onto the evidence this job wrote (not onto an event it merely replayed)
+    if job.retry_count > 0 && !result.replayed {
+        if let Some(event_id) = &result.evidence_id {
+            sqlx::query("UPDATE evidence_events SET retry_count = $1, error_message = $2 WHERE event_id = $3")
+                .bind(job.retry_count)
+                .bind(&job.error_message)
+                .bind(event_id)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+async fn retry_or_fail(
+    pool: &PgPool,
+    job: &ClaimedJob,
+    error: &str,
+    result: &IngestEntryResult,
+    policy: RetryPolicy,
+) -> Result<(), String> {
+    let attempts = job.retry_count + 1;
+    if attempts >= policy.max_attempts {
+        log::warn!("Ingest job {} failed after {} attempt(s): {}", job.id, attempts, error);
+        return fail(pool, job, error, result).await;
+    }
+    let backoff = (policy.base_backoff_secs << job.retry_count.min(20)).min(MAX_BACKOFF_SECS);
+    sqlx::query(
+        r#"UPDATE ingest_jobs
+           SET status = 'pending', retry_count = $2, error_message = $3,
+               next_attempt_at = NOW() + make_interval(secs => $4), updated_at = NOW()
+           WHERE id = $1"#
+    )
+    .bind(job.id)
+    .bind(attempts)
+    .bind(error)
+    .bind(backoff as f64)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn fail(pool: &PgPool, job: &ClaimedJob, error: &str, result: &IngestEntryResult) -> Result<(), String> {
+    sqlx::query(
+        r#"UPDATE ingest_jobs
+           SET status = 'failed', retry_count = retry_count + 1, error_message = $2, result = $3, updated_at = NOW()
+           WHERE id = $1"#
+    )
+    .bind(job.id)
+    .bind(error)
+    .bind(serde_json::to_value(result).unwrap_or_default())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Mark batches whose jobs have all finished as completed.
+async fn refresh_batches(pool: &PgPool, batch_ids: &[Uuid]) -> Result<(), String> {
+    sqlx::query(
+        r#"UPDATE ingest_batches b
+           SET status = CASE WHEN s.failed > 0 THEN 'completed_with_errors' ELSE 'completed' END,
+               completed_at = NOW(), updated_at = NOW()
+           FROM (SELECT batch_id,
+                        COUNT(*) FILTER (WHERE status IN ('pending', 'processing')) AS open,
+                        COUNT(*) FILTER (WHERE status = 'failed' OR result->>'error' IS NOT NULL) AS failed
+                 FROM ingest_jobs WHERE batch_id = ANY($1)
+                 GROUP BY batch_id) s
+           WHERE b.id = s.batch_id AND s.open = 0 AND b.completed_at IS NULL"#
+    )
+    .bind(batch_ids)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update ingest batches: {}", e))?;
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct StatusCounts {
+    pending: i64,
+    processing: i64,
+    done: i64,
+    failed: i64,
+    retrying: i64,
+}
+
+pub async fn batch_status(pool: &PgPool, id: Uuid, include_entries: bool) -> Result<Option<IngestBatchStatus>, String> {
+    let batch: Option<IngestBatchRow> = sqlx::query_as(&format!("SELECT {} FROM ingest_batches WHERE id = $1", BATCH_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch ingest batch: {}", e))?;
+    let Some(batch) = batch else { return Ok(None) };
+
+    let counts: StatusCounts = sqlx::query_as(
+        r#"SELECT COUNT(*) FILTER (WHERE status = 'pending') AS pending,
+                  COUNT(*) FILTER (WHERE status = 'processing') AS processing,
+                  COUNT(*) FILTER (WHERE status = 'done') AS done,
+                  COUNT(*) FILTER (WHERE status = 'failed') AS failed,
+                  COUNT(*) FILTER (WHERE status = 'pending' AND retry_count > 0) AS retrying
+           FROM ingest_jobs WHERE batch_id = $1"#
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to count ingest jobs: {}", e))?;
+
+    let entries = if include_entries {
+        let rows: Vec<IngestJobRow> = sqlx::query_as(
+            r#"SELECT entry_index, status, retry_count, error_message, next_attempt_at, result
+               FROM ingest_jobs WHERE batch_id = $1 ORDER BY entry_index"#
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to fetch ingest jobs: {}", e))?;
+        Some(rows)
+    } else {
+        None
+    };
+
+    let progress = if batch.total_entries > 0 {
+        (counts.done + counts.failed) as f64 / batch.total_entries as f64
+    } else {
+        1.0
+    };
+    Ok(Some(IngestBatchStatus {
+        batch,
+        pending: counts.pending,
+        processing: counts.processing,
+        done: counts.done,
+        failed: counts.failed,
+        retrying: counts.retrying,
+        progress,
+        entries,
+    }))
+}
+
+/// Batches deleted per statement while pruning, so one pass never holds a huge cascade.
+const PRUNE_CHUNK: i64 = 1000;
+
+/// Delete batches that finished more than `retention_days` ago, with their jobs and stored payloads.
+/// Open batches are kept however old they are. Returns the number of batches removed.
+pub async fn prune(pool: &PgPool, retention_days: i32) -> Result<u64, String> {
+    let mut removed = 0;
+    loop {
+        let deleted = sqlx::query(
+            r#"DELETE FROM ingest_batches WHERE id IN (
+                   SELECT id FROM ingest_batches
+                   WHERE completed_at < NOW() - make_interval(days => $1)
+                   LIMIT $2)"#
+        )
+        .bind(retention_days)
+        .bind(PRUNE_CHUNK)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to prune ingest batches: {}", e))?
+        .rows_affected();
+        removed += deleted;
+        if (deleted as i64) < PRUNE_CHUNK {
+            return Ok(removed);
+        }
+    }
+}
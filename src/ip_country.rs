@@ -0,0 +1,332 @@
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::log_formats::is_internal;
+
+const DNS_TIMEOUT: Duration = Duration::from_secs(2);
+const DNS_CONCURRENCY: usize = 16;
+/// How long a resolved host is reused, and a host that failed to resolve is not retried.
+const DNS_TTL: Duration = Duration::from_secs(300);
+const DNS_NEGATIVE_TTL: Duration = Duration::from_secs(60);
+/// Beyond this many cached hosts, expired entries are dropped (and everything, if none has expired).
+const DNS_CACHE_CAPACITY: usize = 10_000;
+
+/// Host → (first public address, expiry), shared by every caller of `resolve_hosts`.
+type DnsCache = HashMap<String, (Option<IpAddr>, Instant)>;
+static DNS_CACHE: Mutex<Option<DnsCache>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IpCountryDatasetRow {
+    pub id: Uuid,
+    pub source_name: String,
+    pub content_hash: String,
+    pub range_count: i32,
+    pub active: bool,
+    pub loaded_by: Option<String>,
+    pub loaded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpRange {
+    pub start: IpAddr,
+    pub end: IpAddr,
+    pub country_code: String,
+}
+
+/// `a.b.c.d`, an IPv6 address, or an integer as used by IP2Location CSVs (IPv4 when it fits in 32 bits).
+fn parse_ip(raw: &str) -> Option<IpAddr> {
+    if let Ok(ip) = raw.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    let n: u128 = raw.parse().ok()?;
+    Some(match u32::try_from(n) {
+        Ok(v4) => IpAddr::V4(Ipv4Addr::from(v4)),
+        Err(_) => IpAddr::V6(Ipv6Addr::from(n)),
+    })
+}
+
+fn cidr_bounds(cidr: &str) -> Option<(IpAddr, IpAddr)> {
+    let (addr, len) = cidr.split_once('/')?;
+    let len: u32 = len.trim().parse().ok()?;
+    match addr.trim().parse::<IpAddr>().ok()? {
+        IpAddr::V4(a) if len <= 32 => {
+            let mask = if len == 0 { 0 } else { u32::MAX << (32 - len) };
+            let start = u32::from(a) & mask;
+            Some((IpAddr::V4(start.into()), IpAddr::V4((start | !mask).into())))
+        }
+        IpAddr::V6(a) if len <= 128 => {
+            let mask = if len == 0 { 0 } else { u128::MAX << (128 - len) };
+            let start = u128::from(a) & mask;
+            Some((IpAddr::V6(start.into()), IpAddr::V6((start | !mask).into())))
+        }
+        _ => None,
+    }
+}
+
+/// Parse CSV rows of `network/len,CC` or `start,end,CC` (dotted, IPv6 or integer addresses); extra
+/// columns are ignored, as are a header row and rows for unknown countries (`-`, `ZZ`).
+pub fn parse_ranges(content: &str) -> Result<Vec<IpRange>, String> {
+    let mut ranges = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let cols: Vec<&str> = line.split(',').map(|c| c.trim().trim_matches('"')).collect();
+        let parsed = if cols[0].contains('/') {
+            cidr_bounds(cols[0]).zip(cols.get(1).copied())
+        } else {
+            match (cols.first().and_then(|c| parse_ip(c)), cols.get(1).and_then(|c| parse_ip(c)), cols.get(2)) {
+                (Some(start), Some(end), Some(cc)) if start.is_ipv4() == end.is_ipv4() && start <= end => Some(((start, end), *cc)),
+                _ => None,
+            }
+        };
+        let Some(((start, end), cc)) = parsed else {
+            if i == 0 {
+                continue;
+            }
+            return Err(format!("Line {}: expected `network/len,CC` or `start,end,CC`", i + 1));
+        };
+        let cc = cc.to_uppercase();
+        if cc == "-" || cc == "ZZ" {
+            continue;
+        }
+        if cc.len() != 2 || !cc.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Line {}: invalid country code {}", i + 1, cc));
+        }
+        ranges.push(IpRange { start, end, country_code: cc });
+    }
+    if ranges.is_empty() {
+        return Err("No IP ranges found".into());
+    }
+    Ok(ranges)
+}
+
+/// Store a dataset and make it the active one.
+pub async fn load_dataset(pool: &PgPool, source_name: &str, content: &str, loaded_by: Option<&str>) -> Result<IpCountryDatasetRow, String> {
+    let ranges = parse_ranges(content)?;
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    let content_hash = format!("{:x}", hasher.finalize());
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let dataset: IpCountryDatasetRow = sqlx::query_as(
+        r#"INSERT INTO ip_country_datasets (source_name, content_hash, range_count, active, loaded_by)
+           VALUES ($1, $2, $3, FALSE, $4)
+           RETURNING *"#
+    )
+    .bind(source_name)
+    .bind(&content_hash)
+    .bind(ranges.len() as i32)
+    .bind(loaded_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create IP country dataset: {}", e))?;
+
+    for chunk in ranges.chunks(5000) {
+        let starts: Vec<String> = chunk.iter().map(|r| r.start.to_string()).collect();
+        let ends: Vec<String> = chunk.iter().map(|r| r.end.to_string()).collect();
+        let countries: Vec<&str> = chunk.iter().map(|r| r.country_code.as_str()).collect();
+        sqlx::query(
+            r#"INSERT INTO ip_country_ranges (dataset_id, start_ip, end_ip, country_code)
+               SELECT $1, s::inet, e::inet, c FROM UNNEST($2::text[], $3::text[], $4::text[]) AS t(s, e, c)"#
+        )
+        .bind(dataset.id)
+        .bind(&starts)
+        .bind(&ends)
+        .bind(&countries)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to store IP ranges: {}", e))?;
+    }
+
+    sqlx::query("UPDATE ip_country_datasets SET active = (id = $1)")
+        .bind(dataset.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Superseded datasets are kept as rows for the record, but their ranges are not needed
+    sqlx::query("DELETE FROM ip_country_ranges WHERE dataset_id <> $1")
+        .bind(dataset.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(IpCountryDatasetRow { active: true, ..dataset })
+}
+
+/// Country code for each address found in the active dataset; unparseable and unknown addresses are absent.
+pub async fn resolve(pool: &PgPool, ips: &[String]) -> Result<HashMap<String, String>, String> {
+    let ips: Vec<String> = ips.iter().filter(|ip| ip.parse::<IpAddr>().is_ok()).cloned().collect();
+    if ips.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"WITH active AS (SELECT id FROM ip_country_datasets WHERE active ORDER BY loaded_at DESC LIMIT 1)
+           SELECT q.ip, r.country_code::text
+           FROM unnest($1::text[]) AS q(ip)
+           JOIN LATERAL (
+               SELECT country_code, end_ip FROM ip_country_ranges
+               WHERE dataset_id = (SELECT id FROM active)
+                 AND start_ip <= q.ip::inet AND family(start_ip) = family(q.ip::inet)
+               ORDER BY start_ip DESC
+               LIMIT 1
+           ) r ON r.end_ip >= q.ip::inet"#
+    )
+    .bind(&ips)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to resolve IP countries: {}", e))?;
+    Ok(rows.into_iter().collect())
+}
+
+/// First public address each host name resolves to, for hosts that arrive without one (trace spans,
+/// browser reports). Hosts that fail to resolve or time out are left out. Answers are cached for
+/// `DNS_TTL` (failures for `DNS_NEGATIVE_TTL`), so batches naming the same hosts resolve them once.
+pub async fn resolve_hosts(hosts: HashSet<String>) -> HashMap<String, String> {
+    let mut resolved = HashMap::new();
+    let mut pending = Vec::new();
+    {
+        let now = Instant::now();
+        let cache = DNS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        for host in hosts {
+            match cache.as_ref().and_then(|c| c.get(&host)) {
+                Some((ip, expires)) if *expires > now => {
+                    if let Some(ip) = ip {
+                        resolved.insert(host, ip.to_string());
+                    }
+                }
+                _ => pending.push(host),
+            }
+        }
+    }
+    if pending.is_empty() {
+        return resolved;
+    }
+
+    let looked_up: Vec<(String, Option<IpAddr>)> = stream::iter(pending)
+        .map(|host| async move {
+            let lookup = tokio::time::timeout(DNS_TIMEOUT, tokio::net::lookup_host((host.clone(), 443))).await;
+            let ip = match lookup {
+                Ok(Ok(addrs)) => addrs.map(|a| a.ip()).find(|ip| !is_internal(ip)),
+                _ => None,
+            };
+            (host, ip)
+        })
+        .buffer_unordered(DNS_CONCURRENCY)
+        .collect()
+        .await;
+
+    let now = Instant::now();
+    let mut guard = DNS_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = guard.get_or_insert_with(HashMap::new);
+    if cache.len() + looked_up.len() > DNS_CACHE_CAPACITY {
+        cache.retain(|_, (_, expires)| *expires > now);
+        if cache.len() + looked_up.len() > DNS_CACHE_CAPACITY {
+            cache.clear();
+        }
+    }
+    for (host, ip) in looked_up {
+        let ttl = if ip.is_some() { DNS_TTL } else { DNS_NEGATIVE_TTL };
+        cache.insert(host.clone(), (ip, now + ttl));
+        if let Some(ip) = ip {
+            resolved.insert(host, ip.to_string());
+        }
+    }
+    resolved
+}
+
+pub async fn run_cli(pool: &PgPool, args: &[String]) -> std::io::Result<()> {
+    let mut path: Option<&str> = None;
+    let mut source: Option<String> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--source" => source = iter.next().cloned(),
+            other => path = Some(other),
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("Usage: veridion-api load-ip-country-db <file.csv> [--source NAME]");
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing file"));
+    };
+    let content = std::fs::read_to_string(path)?;
+    let source = source.unwrap_or_else(|| path.to_string());
+    match load_dataset(pool, &source, &content, Some("cli")).await {
+        Ok(dataset) => {
+            println!("Loaded IP country dataset {} ({} ranges, sha256 {})", dataset.id, dataset.range_count, dataset.content_hash);
+            Ok(())
+        }
+        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_dotted_ipv6_and_integer_addresses() {
+        assert_eq!(parse_ip("203.0.113.9"), Some(ip("203.0.113.9")));
+        assert_eq!(parse_ip("2001:db8::1"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_ip("16777216"), Some(ip("1.0.0.0")));
+        assert_eq!(parse_ip("4294967295"), Some(ip("255.255.255.255")));
+        assert_eq!(parse_ip("4294967296"), Some(ip("::1:0:0")));
+        assert_eq!(parse_ip("not-an-ip"), None);
+    }
+
+    #[test]
+    fn cidr_bounds_cover_the_network() {
+        let cases = [
+            ("203.0.113.77/24", Some(("203.0.113.0", "203.0.113.255"))),
+            ("203.0.113.77/32", Some(("203.0.113.77", "203.0.113.77"))),
+            ("10.1.0.0/15", Some(("10.0.0.0", "10.1.255.255"))),
+            ("0.0.0.0/0", Some(("0.0.0.0", "255.255.255.255"))),
+            ("2001:db8:1::/48", Some(("2001:db8:1::", "2001:db8:1:ffff:ffff:ffff:ffff:ffff"))),
+            ("::/0", Some(("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"))),
+            ("203.0.113.0/33", None),
+            ("2001:db8::/129", None),
+            ("203.0.113.0", None),
+            ("bogus/24", None),
+        ];
+        for (cidr, expected) in cases {
+            assert_eq!(cidr_bounds(cidr), expected.map(|(s, e)| (ip(s), ip(e))), "{}", cidr);
+        }
+    }
+
+    #[test]
+    fn parses_cidr_and_range_rows() {
+        let content = "network,country_iso_code\n# comment\n203.0.113.0/24,de\n\"16777216\",\"16777471\",\"AU\",\"Australia\"\n2001:db8::/32,FR\n198.51.100.0/24,ZZ\n192.0.2.0,192.0.2.255,-\n";
+        let ranges = parse_ranges(content).unwrap();
+        assert_eq!(ranges, vec![
+            IpRange { start: ip("203.0.113.0"), end: ip("203.0.113.255"), country_code: "DE".into() },
+            IpRange { start: ip("1.0.0.0"), end: ip("1.0.0.255"), country_code: "AU".into() },
+            IpRange { start: ip("2001:db8::"), end: ip("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff"), country_code: "FR".into() },
+        ]);
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        assert_eq!(parse_ranges("203.0.113.0/24,DE\n192.0.2.255,192.0.2.0,FR").unwrap_err(), "Line 2: expected `network/len,CC` or `start,end,CC`");
+        assert_eq!(parse_ranges("203.0.113.0/24,DE\n192.0.2.0,::1,FR").unwrap_err(), "Line 2: expected `network/len,CC` or `start,end,CC`");
+        assert_eq!(parse_ranges("203.0.113.0/24,Germany").unwrap_err(), "Line 1: invalid country code GERMANY");
+        assert_eq!(parse_ranges("network,country\n").unwrap_err(), "No IP ranges found");
+    }
+}
@@ -0,0 +1,290 @@
+use actix_web::web::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Postgres channel the `live_events` trigger notifies on. The payload is empty so one transaction
+/// produces one notification; the listener reads everything past its cursor.
+const CHANNEL: &str = "veridion_live";
+const BROADCAST_CAPACITY: usize = 1024;
+/// Most events replayed for one resume (or one lagged subscriber catching up).
+const REPLAY_LIMIT: i64 = 1000;
+const KEEPALIVE_SECS: u64 = 15;
+/// IDs are drawn at insert but rows become visible at commit, so a longer transaction can commit
+/// below an ID already delivered. Resume also replays rows created this long before the resumed one.
+const RESUME_OVERLAP_SECS: f64 = 30.0;
+/// How long the listener keeps polling for a skipped ID before treating it as rolled back.
+const GAP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Cap on tracked gaps, so a large rolled-back batch cannot grow the poll without bound.
+const MAX_GAPS: usize = 10_000;
+/// IDs a subscriber remembers having sent, to drop repeats from overlapping replays.
+const SENT_MEMORY: usize = 4 * REPLAY_LIMIT as usize;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveEvent {
+    pub id: i64,
+    pub kind: String,
+    pub source_system: Option<String>,
+    pub event_type: Option<String>,
+    pub severity: Option<String>,
+    pub data: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LiveEvent {
+    fn to_frame(&self) -> Bytes {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        Bytes::from(format!("id: {}\nevent: {}\ndata: {}\n\n", self.id, self.kind, json))
+    }
+}
+
+/// Comma-separated filters from the query string; an empty list matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct LiveFilter {
+    pub kinds: Vec<String>,
+    pub source_systems: Vec<String>,
+    pub event_types: Vec<String>,
+}
+
+impl LiveFilter {
+    pub fn parse_list(raw: Option<&str>) -> Vec<String> {
+        raw.map(|s| s.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    fn matches(&self, ev: &LiveEvent) -> bool {
+        fn allowed(list: &[String], value: Option<&str>) -> bool {
+            list.is_empty() || value.map(|v| list.iter().any(|l| l == v)).unwrap_or(false)
+        }
+        allowed(&self.kinds, Some(&ev.kind))
+            && allowed(&self.source_systems, ev.source_system.as_deref())
+            && allowed(&self.event_types, ev.event_type.as_deref())
+    }
+}
+
+/// Fans out `live_events` rows announced over LISTEN/NOTIFY to this instance's SSE subscribers.
+/// Every API instance runs its own listener, so clients see events written by any instance.
+pub struct LiveHub {
+    tx: broadcast::Sender<Arc<LiveEvent>>,
+}
+
+impl LiveHub {
+    pub fn start(pool: PgPool) -> Self {
+        let (tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+        tokio::spawn(run_listener(pool, tx.clone()));
+        LiveHub { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.tx.subscribe()
+    }
+}
+
+/// Events after `after_id`, oldest first, optionally narrowed by the subscriber's filter. Rows at or
+/// below `after_id` created within `RESUME_OVERLAP_SECS` of it are included too, in case they
+/// committed after it was delivered; clients see those again and dedupe by ID.
+pub async fn fetch_since(pool: &PgPool, after_id: i64, filter: &LiveFilter) -> Result<Vec<LiveEvent>, sqlx::Error> {
+    sqlx::query_as::<_, LiveEvent>(
+        r#"SELECT * FROM live_events
+           WHERE (id > $1 OR (id < $1 AND created_at >= (SELECT created_at FROM live_events WHERE id = $1)
+                                                       - make_interval(secs => $6)))
+             AND (cardinality($2::text[]) = 0 OR kind = ANY($2))
+             AND (cardinality($3::text[]) = 0 OR source_system = ANY($3))
+             AND (cardinality($4::text[]) = 0 OR event_type = ANY($4))
+           ORDER BY id
+           LIMIT $5"#
+    )
+    .bind(after_id)
+    .bind(&filter.kinds)
+    .bind(&filter.source_systems)
+    .bind(&filter.event_types)
+    .bind(REPLAY_LIMIT)
+    .bind(RESUME_OVERLAP_SECS)
+    .fetch_all(pool)
+    .await
+}
+
+/// The listener's position: the highest ID published, plus IDs below it that were not visible yet
+/// (still in flight in another transaction, or rolled back) with when they were first missed.
+struct Cursor {
+    last_id: i64,
+    gaps: BTreeMap<i64, Instant>,
+}
+
+impl Cursor {
+    /// Publish every newly visible row, oldest first, and re-check the open gaps.
+    async fn poll(&mut self, pool: &PgPool, tx: &broadcast::Sender<Arc<LiveEvent>>) -> Result<(), sqlx::Error> {
+        let now = Instant::now();
+        self.gaps.retain(|_, since| now.duration_since(*since) < GAP_TIMEOUT);
+        loop {
+            let gaps: Vec<i64> = self.gaps.keys().copied().collect();
+            let rows = sqlx::query_as::<_, LiveEvent>(
+                "SELECT * FROM live_events WHERE id > $1 OR id = ANY($2) ORDER BY id LIMIT $3"
+            )
+            .bind(self.last_id)
+            .bind(&gaps)
+            .bind(REPLAY_LIMIT)
+            .fetch_all(pool)
+            .await?;
+            let full = rows.len() as i64 == REPLAY_LIMIT;
+            for ev in rows {
+                if self.gaps.remove(&ev.id).is_none() && ev.id <= self.last_id {
+                    continue;
+                }
+                if ev.id > self.last_id {
+                    for missing in (self.last_id + 1)..ev.id {
+                        if self.gaps.len() >= MAX_GAPS {
+                            break;
+                        }
+                        self.gaps.insert(missing, now);
+                    }
+                    self.last_id = ev.id;
+                }
+                let _ = tx.send(Arc::new(ev));
+            }
+            if !full {
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn run_listener(pool: PgPool, tx: broadcast::Sender<Arc<LiveEvent>>) {
+    let last_id: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM live_events")
+        .fetch_one(&pool)
+        .await
+        .unwrap_or(0);
+    let mut cursor = Cursor { last_id, gaps: BTreeMap::new() };
+
+    loop {
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(l) => l,
+            Err(e) => {
+                log::error!("Live stream: failed to connect listener: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        if let Err(e) = listener.listen(CHANNEL).await {
+            log::error!("Live stream: LISTEN {} failed: {}", CHANNEL, e);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(_)) => {}
+                // Connection dropped; sqlx reconnects on the next call. Publish anything missed meanwhile.
+                Ok(None) => log::warn!("Live stream: listener connection lost, catching up from {}", cursor.last_id),
+                Err(e) => {
+                    log::error!("Live stream: listener error: {}", e);
+                    break;
+                }
+            }
+            if let Err(e) = cursor.poll(&pool, &tx).await {
+                log::warn!("Live stream: failed to load events after {}: {}", cursor.last_id, e);
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+struct StreamState {
+    pool: PgPool,
+    rx: broadcast::Receiver<Arc<LiveEvent>>,
+    filter: LiveFilter,
+    pending: VecDeque<Arc<LiveEvent>>,
+    /// IDs queued or sent recently (oldest first in `sent_order`), so rows arriving both from a
+    /// replay and live, or from two overlapping replays, go out once.
+    sent: HashSet<i64>,
+    sent_order: VecDeque<i64>,
+    last_sent: i64,
+    keepalive: tokio::time::Interval,
+    started: bool,
+}
+
+impl StreamState {
+    /// Queue events not already queued or sent on this connection.
+    fn queue(&mut self, events: impl Iterator<Item = Arc<LiveEvent>>) {
+        for ev in events {
+            if !self.sent.insert(ev.id) {
+                continue;
+            }
+            self.sent_order.push_back(ev.id);
+            if self.sent_order.len() > SENT_MEMORY {
+                if let Some(oldest) = self.sent_order.pop_front() {
+                    self.sent.remove(&oldest);
+                }
+            }
+            self.pending.push_back(ev);
+        }
+    }
+}
+
+/// SSE body for one subscriber: the replay after `last_event_id` (when resuming), then live events,
+/// with comment keepalives so proxies do not close an idle connection.
+pub async fn subscribe_stream(
+    pool: PgPool,
+    hub: &LiveHub,
+    filter: LiveFilter,
+    last_event_id: Option<i64>,
+) -> Result<impl Stream<Item = Result<Bytes, actix_web::Error>>, String> {
+    // Subscribe before replaying so nothing committed in between is lost.
+    let rx = hub.subscribe();
+    let mut keepalive = tokio::time::interval(Duration::from_secs(KEEPALIVE_SECS));
+    keepalive.reset();
+    let mut state = StreamState {
+        pool,
+        rx,
+        filter,
+        pending: VecDeque::new(),
+        sent: HashSet::new(),
+        sent_order: VecDeque::new(),
+        last_sent: 0,
+        keepalive,
+        started: false,
+    };
+    if let Some(after) = last_event_id {
+        let rows = fetch_since(&state.pool, after, &state.filter).await.map_err(|e| e.to_string())?;
+        state.queue(rows.into_iter().map(Arc::new));
+        state.last_sent = after;
+    }
+
+    Ok(stream::unfold(state, |mut s| async move {
+        if !s.started {
+            s.started = true;
+            return Some((Ok(Bytes::from_static(b"retry: 5000\n\n")), s));
+        }
+        loop {
+            if let Some(ev) = s.pending.pop_front() {
+                s.last_sent = s.last_sent.max(ev.id);
+                return Some((Ok(ev.to_frame()), s));
+            }
+            tokio::select! {
+                received = s.rx.recv() => match received {
+                    Ok(ev) => {
+                        if s.filter.matches(&ev) {
+                            s.queue(std::iter::once(ev));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        log::warn!("Live stream subscriber lagged by {} event(s), replaying from {}", skipped, s.last_sent);
+                        if let Ok(rows) = fetch_since(&s.pool, s.last_sent, &s.filter).await {
+                            s.queue(rows.into_iter().map(Arc::new));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = s.keepalive.tick() => return Some((Ok(Bytes::from_static(b": keepalive\n\n")), s)),
+            }
+        }
+    }))
+}
@@ -0,0 +1,919 @@
+//! Parsers for network and proxy log formats that ingest maps onto `IngestLogEntry`:
+//! AWS VPC Flow Logs, CEF (bare or inside syslog), RFC 5424 syslog with key=value data,
+//! Envoy and NGINX access logs, Squid native access logs, Zeek conn.log and Postfix / Exim
+//! delivery logs.
+//! Timestamps are passed through as written; `source_time` resolves them at ingest.
+
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use utoipa::ToSchema;
+
+use crate::host_map;
+use crate::routes_shield::IngestLogEntry;
+
+/// Supported formats, by the name used in `POST /api/v1/shield/ingest-logs/{format}`.
+pub const FORMATS: &[&str] = &["vpc-flow", "cef", "syslog", "envoy", "nginx", "squid", "zeek-conn", "postfix", "exim"];
+
+/// Categories for entries of `format` when neither the line nor the request names any: a delivered
+/// mail always carries the sender's and recipients' contact data.
+pub fn default_categories(format: &str) -> Option<Vec<String>> {
+    matches!(format, "postfix" | "exim").then(|| vec!["email".to_string()])
+}
+
+const VPC_DEFAULT_FIELDS: &[&str] = &[
+    "version", "account-id", "interface-id", "srcaddr", "dstaddr", "srcport", "dstport",
+    "protocol", "packets", "bytes", "start", "end", "action", "log-status",
+];
+
+const ZEEK_DEFAULT_FIELDS: &[&str] = &[
+    "ts", "uid", "id.orig_h", "id.orig_p", "id.resp_h", "id.resp_p", "proto", "service", "duration",
+    "orig_bytes", "resp_bytes", "conn_state", "local_orig", "local_resp", "missed_bytes", "history",
+    "orig_pkts", "orig_ip_bytes", "resp_pkts", "resp_ip_bytes", "tunnel_parents",
+];
+
+/// Why a line produced no entry. `line` is 1-based.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct LineIssue {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct ParsedLog {
+    /// Entries with the line they came from.
+    pub entries: Vec<(usize, IngestLogEntry)>,
+    /// Lines that are valid but describe no outbound transfer (internal destination, rejected flow, ...).
+    pub skipped: Vec<LineIssue>,
+    pub errors: Vec<LineIssue>,
+}
+
+enum Outcome {
+    Entry(Box<IngestLogEntry>),
+    Skip(String),
+}
+
+fn skip(reason: impl Into<String>) -> Result<Outcome, String> {
+    Ok(Outcome::Skip(reason.into()))
+}
+
+/// Private, loopback, link-local and unique-local addresses never leave the network.
+pub fn is_internal(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast(),
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback() || v6.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn present(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty() && *v != "-" && *v != "(empty)")
+}
+
+fn parse_size(value: Option<&str>, field: &str) -> Result<Option<u64>, String> {
+    match present(value) {
+        Some(v) => v.parse::<u64>().map(Some).map_err(|_| format!("{} is not a byte count: {}", field, v)),
+        None => Ok(None),
+    }
+}
+
+/// `tcp://10.0.0.1:443`, `10.0.0.1:443` and `[2001:db8::1]:443` all give the bare IP.
+pub fn ip_of(addr: &str) -> Option<IpAddr> {
+    let addr = addr.rsplit("://").next().unwrap_or(addr).trim();
+    if let Ok(ip) = addr.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Some(rest) = addr.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    addr.rsplit_once(':').and_then(|(host, _)| host.parse().ok())
+}
+
+/// Host of a URL or `host:port` authority, lowercased and without userinfo or port.
+pub fn host_of(target: &str) -> Option<String> {
+    let rest = target.split_once("://").map(|(_, r)| r).unwrap_or(target);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let host = if let Some(v6) = authority.strip_prefix('[') {
+        v6.split(']').next()?
+    } else {
+        authority.split(':').next()?
+    };
+    let host = host.trim().trim_end_matches('.').to_lowercase();
+    (!host.is_empty() && host != "-").then_some(host)
+}
+
+pub fn path_of(target: &str) -> Option<String> {
+    let rest = target.split_once("://").map(|(_, r)| r)?;
+    rest.find('/').map(|i| rest[i..].to_string())
+}
+
+/// Fill destination fields and decide whether the line is an outbound transfer at all.
+fn outbound(mut entry: IngestLogEntry, host: Option<String>) -> Result<Outcome, String> {
+    let dest = entry.dest_ip.as_deref().and_then(ip_of);
+    if let Some(ip) = dest {
+        entry.dest_ip = Some(ip.to_string());
+    }
+    let host = host.filter(|h| h.parse::<IpAddr>().is_err());
+    match (&host, dest) {
+        (None, None) => return Err("no destination address or host".into()),
+        (None, Some(ip)) if is_internal(&ip) => return skip(format!("internal destination {}", ip)),
+        _ => {}
+    }
+    if entry.partner_name.is_none() {
+        entry.partner_name = host;
+    }
+    Ok(Outcome::Entry(Box::new(entry)))
+}
+
+fn protocol_name(number: &str) -> String {
+    match number {
+        "1" => "icmp".into(),
+        "6" => "tcp".into(),
+        "17" => "udp".into(),
+        "58" => "icmpv6".into(),
+        "132" => "sctp".into(),
+        other => format!("ip-proto-{}", other),
+    }
+}
+
+/// Split on whitespace, keeping `"quoted strings"` and `[bracketed]` spans whole (without delimiters).
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let close = match c {
+            '"' => Some('"'),
+            '[' => Some(']'),
+            _ => None,
+        };
+        let mut field = String::new();
+        if let Some(close) = close {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' if close == '"' => {
+                        if let Some(next) = chars.next() {
+                            field.push(next);
+                        }
+                    }
+                    c if c == close => break,
+                    c => field.push(c),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+/// `key=value` and `key="quoted value"` pairs; other tokens are ignored.
+fn key_values(text: &str) -> HashMap<String, String> {
+    split_fields(text)
+        .into_iter()
+        .filter_map(|token| {
+            let (k, v) = token.split_once('=')?;
+            Some((k.to_string(), v.trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+/// Map loosely named fields (CEF keys, syslog structured data, key=value messages) onto an entry.
+fn entry_from_fields(fields: &HashMap<String, String>) -> Result<Outcome, String> {
+    let get = |keys: &[&str]| keys.iter().find_map(|k| present(fields.get(*k).map(String::as_str))).map(str::to_string);
+
+    let request = get(&["request", "url", "uri", "request_path", "requestPath", "path"]);
+    let host = get(&["dhost", "destinationHostName", "dest_host", "server.address", "host", "sni"])
+        .and_then(|h| host_of(&h))
+        .or_else(|| request.as_deref().filter(|r| r.contains("://")).and_then(host_of));
+    let entry = IngestLogEntry {
+        source_ip: get(&["src", "srcaddr", "src_ip", "source_ip", "sourceIp", "client_ip"]),
+        dest_ip: get(&["dst", "dstaddr", "dst_ip", "dest_ip", "destIp", "destination_ip"]),
+        protocol: get(&["app", "protocol", "proto"]).map(|p| p.to_lowercase()),
+        data_size: parse_size(get(&["out", "bytesOut", "bytes_out", "sent_bytes", "data_size", "dataSize", "bytes"]).as_deref(), "bytes")?,
+        timestamp: get(&["rt", "end", "start", "timestamp", "time", "ts"]),
+        user_agent: get(&["requestClientApplication", "user_agent", "userAgent", "ua"]),
+        request_path: request.map(|r| path_of(&r).unwrap_or(r)),
+        destination_country_code: get(&["destinationCountryCode", "destination_country_code", "dst_country", "country"]),
+        data_categories: get(&["dataCategories", "data_categories", "categories"])
+            .map(|c| c.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()),
+        partner_name: get(&["partnerName", "partner_name", "partner"]),
+        ..Default::default()
+    };
+    outbound(entry, host)
+}
+
+/// `CEF:Version|Vendor|Product|Version|SignatureID|Name|Severity|Extension`, optionally after a syslog header.
+fn parse_cef(line: &str, extension_key: &Regex) -> Result<Outcome, String> {
+    let start = line.find("CEF:").ok_or("no CEF header")?;
+    let cef = &line[start..];
+
+    let mut pipes = 0;
+    let mut escaped = false;
+    let mut ext_start = None;
+    for (i, c) in cef.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '|' if !escaped => {
+                pipes += 1;
+                if pipes == 7 {
+                    ext_start = Some(i + 1);
+                    break;
+                }
+            }
+            _ => escaped = false,
+        }
+    }
+    let ext = &cef[ext_start.ok_or("CEF header needs 7 `|`-separated fields")?..];
+
+    let unescape = |v: &str| v.trim().replace("\\=", "=").replace("\\n", "\n").replace("\\r", "\r").replace("\\\\", "\\");
+    let keys: Vec<(usize, usize, &str)> = extension_key
+        .captures_iter(ext)
+        .filter_map(|c| c.get(1).map(|k| (c.get(0).map(|m| m.start()).unwrap_or(0), k.end() + 1, k.as_str())))
+        .collect();
+    let mut fields = HashMap::new();
+    for (i, (_, value_start, key)) in keys.iter().enumerate() {
+        let value_end = keys.get(i + 1).map(|(next, _, _)| *next).unwrap_or(ext.len());
+        fields.insert(key.to_string(), unescape(&ext[*value_start..value_end]));
+    }
+    // Custom strings carry their meaning in a label: `cs1Label=dataCategories cs1=email,name`
+    for n in 1..=6 {
+        if let (Some(label), Some(value)) = (fields.get(&format!("cs{}Label", n)), fields.get(&format!("cs{}", n))) {
+            fields.insert(label.clone(), value.clone());
+        }
+    }
+    entry_from_fields(&fields)
+}
+
+/// `<PRI>1 TIMESTAMP HOST APP PROCID MSGID [SD-ID k="v" ...] MSG`; structured data and key=value pairs
+/// in the message are both read, and a CEF message is parsed as CEF.
+fn parse_syslog(line: &str, extension_key: &Regex) -> Result<Outcome, String> {
+    let rest = line.strip_prefix('<').ok_or("not an RFC 5424 message (no <PRI>)")?;
+    let (_, rest) = rest.split_once('>').ok_or("not an RFC 5424 message (no <PRI>)")?;
+    let mut header = rest.splitn(7, ' ');
+    let version = header.next().unwrap_or("");
+    if version != "1" {
+        return Err(format!("unsupported syslog version {:?} (RFC 5424 requires 1)", version));
+    }
+    let timestamp = header.next().ok_or("truncated syslog header")?;
+    for _ in 0..4 {
+        header.next().ok_or("truncated syslog header")?;
+    }
+    let mut rest = header.next().unwrap_or("");
+
+    let mut fields = HashMap::new();
+    if let Some(after) = rest.strip_prefix('-') {
+        rest = after;
+    } else {
+        while rest.starts_with('[') {
+            let mut end = None;
+            let mut in_quotes = false;
+            let mut escaped = false;
+            for (i, c) in rest.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_quotes = !in_quotes,
+                    ']' if !in_quotes => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            let end = end.ok_or("unterminated structured data element")?;
+            let element = &rest[1..end];
+            let params = element.split_once(' ').map(|(_, p)| p).unwrap_or("");
+            fields.extend(key_values(params));
+            rest = &rest[end + 1..];
+        }
+    }
+    let msg = rest.trim_start().trim_start_matches('\u{feff}');
+
+    if msg.contains("CEF:") {
+        return parse_cef(msg, extension_key).map(|outcome| match outcome {
+            Outcome::Entry(mut e) => {
+                e.timestamp = e.timestamp.or_else(|| present(Some(timestamp)).map(str::to_string));
+                Outcome::Entry(e)
+            }
+            skipped => skipped,
+        });
+    }
+    fields.extend(key_values(msg));
+    fields.entry("timestamp".into()).or_insert_with(|| timestamp.to_string());
+    entry_from_fields(&fields)
+}
+
+fn parse_vpc_flow(line: &str, fields: &[String]) -> Result<Outcome, String> {
+    let values: Vec<&str> = line.split_whitespace().collect();
+    if values.len() != fields.len() {
+        return Err(format!("expected {} fields, found {}", fields.len(), values.len()));
+    }
+    let get = |name: &str| present(fields.iter().position(|f| f == name).map(|i| values[i]));
+
+    if let Some(status @ ("NODATA" | "SKIPDATA")) = get("log-status") {
+        return skip(format!("log-status {}", status));
+    }
+    if get("action") == Some("REJECT") {
+        return skip("rejected flow");
+    }
+    if get("flow-direction") == Some("ingress") {
+        return skip("ingress flow");
+    }
+    let entry = IngestLogEntry {
+        source_ip: get("pkt-srcaddr").or(get("srcaddr")).map(str::to_string),
+        // pkt-dstaddr is the real destination behind NAT gateways and load balancers
+        dest_ip: Some(get("pkt-dstaddr").or(get("dstaddr")).ok_or("no dstaddr")?.to_string()),
+        protocol: get("protocol").map(protocol_name),
+        data_size: parse_size(get("bytes"), "bytes")?,
+        timestamp: get("start").map(str::to_string),
+        partner_name: get("pkt-dst-aws-service").map(|s| format!("AWS {}", s)),
+        ..Default::default()
+    };
+    outbound(entry, None)
+}
+
+/// Envoy's default text format, or a JSON access log using the default format's operator names.
+fn parse_envoy(line: &str, default_format: &Regex) -> Result<Outcome, String> {
+    if line.starts_with('{') {
+        let obj: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+        let fields: HashMap<String, String> = obj
+            .into_iter()
+            .filter_map(|(k, v)| {
+                let key = k.trim_start_matches(':').to_lowercase().replace('-', "_");
+                let value = match v {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Number(n) => n.to_string(),
+                    _ => return None,
+                };
+                Some((key, value))
+            })
+            .collect();
+        let get = |keys: &[&str]| keys.iter().find_map(|k| present(fields.get(*k).map(String::as_str)));
+        let entry = IngestLogEntry {
+            source_ip: get(&["x_forwarded_for", "downstream_remote_address"])
+                .and_then(|v| ip_of(v.split(',').next().unwrap_or(v)))
+                .map(|ip| ip.to_string()),
+            dest_ip: get(&["upstream_host"]).map(str::to_string),
+            protocol: get(&["protocol"]).map(str::to_string),
+            data_size: parse_size(get(&["bytes_received"]), "bytes_received")?,
+            timestamp: get(&["start_time", "timestamp"]).map(str::to_string),
+            user_agent: get(&["user_agent"]).map(str::to_string),
+            request_path: get(&["x_envoy_original_path", "path"]).map(str::to_string),
+            ..Default::default()
+        };
+        return outbound(entry, get(&["authority", "host"]).and_then(host_of));
+    }
+
+    let caps = default_format.captures(line).ok_or("does not match Envoy's default access log format")?;
+    let group = |name: &str| present(caps.name(name).map(|m| m.as_str()));
+    if group("upstream").is_none() {
+        return skip("no upstream connection");
+    }
+    let entry = IngestLogEntry {
+        source_ip: group("xff").and_then(|v| v.split(',').next()).map(|v| v.trim().to_string()),
+        dest_ip: group("upstream").map(str::to_string),
+        protocol: group("protocol").map(str::to_string),
+        data_size: parse_size(group("received"), "BYTES_RECEIVED")?,
+        timestamp: group("time").map(str::to_string),
+        user_agent: group("ua").map(str::to_string),
+        request_path: group("path").map(str::to_string),
+        ..Default::default()
+    };
+    outbound(entry, group("authority").and_then(host_of))
+}
+
+/// NGINX `combined`, optionally followed by key=value fields naming the destination:
+/// `host=$host upstream_addr=$upstream_addr request_length=$request_length`.
+fn parse_nginx(line: &str, combined: &Regex) -> Result<Outcome, String> {
+    let caps = combined.captures(line).ok_or("does not match the NGINX combined format")?;
+    let group = |name: &str| present(caps.name(name).map(|m| m.as_str()));
+    let extra = key_values(group("rest").unwrap_or(""));
+    let extra_get = |keys: &[&str]| keys.iter().find_map(|k| present(extra.get(*k).map(String::as_str)));
+
+    let mut request = group("request").unwrap_or("").split_whitespace();
+    let (_method, target, protocol) = (request.next(), request.next(), request.next());
+    // Forward proxies log absolute URLs; reverse proxies need host= in the format
+    let host = extra_get(&["host", "server_name", "http_host"])
+        .and_then(host_of)
+        .or_else(|| target.filter(|t| t.contains("://")).and_then(host_of));
+    let size = match extra_get(&["request_length", "bytes_sent_upstream"]) {
+        Some(v) => parse_size(Some(v), "request_length")?,
+        None => parse_size(group("bytes"), "body_bytes_sent")?,
+    };
+    let entry = IngestLogEntry {
+        source_ip: group("remote").map(str::to_string),
+        dest_ip: extra_get(&["upstream_addr", "upstream"])
+            .and_then(|v| v.split([',', ' ']).next())
+            .map(str::to_string),
+        protocol: protocol.map(str::to_string),
+        data_size: size,
+        timestamp: group("time").map(str::to_string),
+        user_agent: group("ua").map(str::to_string),
+        request_path: target.map(|t| path_of(t).unwrap_or_else(|| t.to_string())),
+        ..Default::default()
+    };
+    outbound(entry, host)
+}
+
+/// Squid native: `time elapsed client code/status bytes method URL user hierarchy/peer type`.
+fn parse_squid(line: &str) -> Result<Outcome, String> {
+    let f: Vec<&str> = line.split_whitespace().collect();
+    if f.len() < 9 {
+        return Err(format!("expected at least 9 fields, found {}", f.len()));
+    }
+    let (code, status) = f[3].split_once('/').ok_or("result code is not CODE/STATUS")?;
+    if code.contains("DENIED") {
+        return skip(format!("{} (request not forwarded)", code));
+    }
+    if status == "000" {
+        return skip("aborted before a response");
+    }
+    let (method, url) = (f[5], f[6]);
+    let (host, protocol, path) = if method == "CONNECT" {
+        (host_of(url), Some("https".to_string()), None)
+    } else {
+        let scheme = url.split_once("://").map(|(s, _)| s.to_lowercase());
+        (host_of(url), scheme, path_of(url))
+    };
+    let peer = f[8].split_once('/').map(|(_, p)| p).filter(|p| p.parse::<IpAddr>().is_ok());
+    let entry = IngestLogEntry {
+        source_ip: Some(f[2].to_string()),
+        dest_ip: peer.map(str::to_string),
+        protocol,
+        data_size: parse_size(Some(f[4]), "bytes")?,
+        timestamp: Some(f[0].to_string()),
+        request_path: path,
+        ..Default::default()
+    };
+    outbound(entry, host)
+}
+
+/// Delivery details shared by the Postfix and Exim parsers.
+struct MailDelivery<'a> {
+    timestamp: String,
+    recipient: &'a str,
+    relay_host: Option<String>,
+    relay_ip: Option<IpAddr>,
+    size: Option<u64>,
+}
+
+/// `MAIL_PROVIDER_MAP` (same syntax as `EXT_AUTHZ_HOST_MAP`) is matched against the relay (MX) host,
+/// then the recipient domain, so `*.google.com=US:Google Workspace` covers every Gmail-hosted domain.
+fn mail_entry(delivery: MailDelivery, provider_map: &str) -> Result<Outcome, String> {
+    let domain = delivery.recipient.rsplit_once('@').map(|(_, d)| d.trim_end_matches('>').to_lowercase())
+        .filter(|d| !d.is_empty())
+        .ok_or_else(|| format!("recipient {} has no domain", delivery.recipient))?;
+    if let Some(ip) = delivery.relay_ip.filter(is_internal) {
+        return skip(format!("relayed to internal host {}", ip));
+    }
+    let relay_host = delivery.relay_host.filter(|h| h.parse::<IpAddr>().is_err());
+    let mapped = relay_host.as_deref()
+        .and_then(|h| host_map::lookup(provider_map, h))
+        .or_else(|| host_map::lookup(provider_map, &domain));
+    let entry = IngestLogEntry {
+        dest_ip: delivery.relay_ip.map(|ip| ip.to_string()),
+        protocol: Some("smtp".into()),
+        data_size: delivery.size,
+        timestamp: Some(delivery.timestamp),
+        destination_country_code: mapped.as_ref().map(|(code, _)| code.clone()),
+        partner_name: mapped.and_then(|(_, provider)| provider).or(Some(domain)),
+        ..Default::default()
+    };
+    outbound(entry, relay_host)
+}
+
+/// `relay=mx.example.com[203.0.113.5]:25`
+fn postfix_relay(relay: &str) -> (Option<String>, Option<IpAddr>) {
+    let (host, rest) = relay.split_once('[').unwrap_or((relay, ""));
+    let ip = rest.split_once(']').and_then(|(ip, _)| ip.strip_prefix("IPv6:").unwrap_or(ip).parse().ok());
+    (host_of(host), ip)
+}
+
+/// Postfix: `smtp` delivery lines (`QUEUEID: to=<rcpt>, relay=host[ip]:port, ..., status=sent`);
+/// `qmgr` lines supply each queue ID's message size. Syslog (BSD or RFC 3339) prefix.
+fn parse_postfix(line: &str, pattern: &Regex, sizes: &mut HashMap<String, u64>, provider_map: &str) -> Result<Outcome, String> {
+    let caps = pattern.captures(line).ok_or("not a syslog line")?;
+    let (timestamp, program, message) = (caps["time"].to_string(), &caps["program"], &caps["message"]);
+    if !program.contains("postfix") {
+        return skip(format!("not a Postfix line ({})", program));
+    }
+    let service = program.rsplit('/').next().unwrap_or(program);
+    let Some((queue_id, details)) = message.split_once(": ").filter(|(id, _)| id.chars().all(|c| c.is_ascii_alphanumeric())) else {
+        return skip(format!("{} message without a queue ID", service));
+    };
+    let fields: HashMap<&str, &str> = details
+        .split(", ")
+        .filter_map(|kv| kv.split_once('='))
+        .collect();
+    if service == "qmgr" {
+        if let Some(size) = fields.get("size").and_then(|s| s.parse().ok()) {
+            sizes.insert(queue_id.to_string(), size);
+        }
+        return skip("queue manager record");
+    }
+    let Some(recipient) = fields.get("to") else {
+        return skip(format!("{} record without a recipient", service));
+    };
+    if !matches!(service, "smtp" | "relay") {
+        return skip(format!("local delivery ({})", service));
+    }
+    let status = fields.get("status").map(|s| s.split(' ').next().unwrap_or(s)).unwrap_or("");
+    if status != "sent" {
+        return skip(format!("status {}", if status.is_empty() { "missing" } else { status }));
+    }
+    let (relay_host, relay_ip) = match fields.get("relay") {
+        Some(&"none") | None => return skip("no relay"),
+        Some(relay) => postfix_relay(relay),
+    };
+    mail_entry(MailDelivery {
+        timestamp,
+        recipient: recipient.trim_start_matches('<').trim_end_matches('>'),
+        relay_host,
+        relay_ip,
+        size: sizes.get(queue_id).copied(),
+    }, provider_map)
+}
+
+/// Exim main log: `=>` / `->` deliveries with `H=host [ip]`; `<=` arrivals supply the size (`S=`).
+fn parse_exim(line: &str, pattern: &Regex, host: &Regex, sizes: &mut HashMap<String, u64>, provider_map: &str) -> Result<Outcome, String> {
+    let Some(caps) = pattern.captures(line) else {
+        return if line.starts_with(|c: char| c.is_ascii_digit() || c.is_whitespace()) {
+            skip("not a message log line")
+        } else {
+            Err("no Exim timestamp".into())
+        };
+    };
+    let id = &caps["id"];
+    let rest = &caps["rest"];
+    match &caps["flag"] {
+        "<=" => {
+            if let Some(size) = rest.split_whitespace().find_map(|f| f.strip_prefix("S=")).and_then(|s| s.parse().ok()) {
+                sizes.insert(id.to_string(), size);
+            }
+            return skip("message arrival");
+        }
+        "*>" => return skip("delivery suppressed (-N)"),
+        "==" => return skip("delivery deferred"),
+        "**" => return skip("delivery failed"),
+        _ => {}
+    }
+    let Some(relay) = host.captures(rest) else {
+        return skip("local delivery");
+    };
+    let timestamp = match caps.name("tz") {
+        Some(tz) => format!("{}{}", &caps["date"], tz.as_str()),
+        None => caps["date"].to_string(),
+    };
+    mail_entry(MailDelivery {
+        timestamp,
+        recipient: &caps["addr"],
+        relay_host: host_of(relay["host"].trim_start_matches('[').trim_end_matches(']')),
+        relay_ip: relay.name("ip").and_then(|ip| ip.as_str().parse().ok()),
+        size: sizes.get(id).copied(),
+    }, provider_map)
+}
+
+fn zeek_entry(get: &dyn Fn(&str) -> Option<String>) -> Result<Outcome, String> {
+    if get("local_resp").as_deref() == Some("T") || get("local_resp").as_deref() == Some("true") {
+        return skip("responder is local");
+    }
+    if let Some(state @ ("S0" | "REJ" | "OTH")) = get("conn_state").as_deref() {
+        return skip(format!("conn_state {} (no data exchanged)", state));
+    }
+    let entry = IngestLogEntry {
+        source_ip: get("id.orig_h"),
+        dest_ip: Some(get("id.resp_h").ok_or("no id.resp_h")?),
+        protocol: get("service").or_else(|| get("proto")),
+        data_size: parse_size(get("orig_bytes").as_deref(), "orig_bytes")?,
+        timestamp: get("ts"),
+        ..Default::default()
+    };
+    outbound(entry, get("server_name").or_else(|| get("host")))
+}
+
+/// Line-by-line parser; VPC Flow Logs and Zeek carry their field order in header lines.
+struct Parser {
+    format: &'static str,
+    vpc_fields: Vec<String>,
+    zeek_fields: Vec<String>,
+    zeek_separator: String,
+    cef_key: Regex,
+    envoy: Regex,
+    nginx: Regex,
+    postfix: Regex,
+    exim: Regex,
+    exim_host: Regex,
+    /// Queue / message ID to size, from arrival records earlier in the file.
+    mail_sizes: HashMap<String, u64>,
+    mail_provider_map: String,
+}
+
+impl Parser {
+    fn new(format: &'static str) -> Self {
+        Parser {
+            format,
+            vpc_fields: VPC_DEFAULT_FIELDS.iter().map(|s| s.to_string()).collect(),
+            zeek_fields: ZEEK_DEFAULT_FIELDS.iter().map(|s| s.to_string()).collect(),
+            zeek_separator: "\t".into(),
+            cef_key: Regex::new(r"(?:^|\s)([A-Za-z0-9_.\[\]-]+)=").expect("valid regex"),
+            envoy: Regex::new(
+                r#"^\[(?P<time>[^\]]+)\] "(?P<method>\S+) (?P<path>\S+) (?P<protocol>[^"]+)" (?P<code>\d+) (?P<flags>\S+) (?P<received>\d+|-) (?P<sent>\d+|-) (?P<duration>\S+) (?P<ust>\S+) "(?P<xff>[^"]*)" "(?P<ua>[^"]*)" "(?P<rid>[^"]*)" "(?P<authority>[^"]*)" "(?P<upstream>[^"]*)""#,
+            ).expect("valid regex"),
+            nginx: Regex::new(
+                r#"^(?P<remote>\S+) \S+ (?P<user>\S+) \[(?P<time>[^\]]+)\] "(?P<request>[^"]*)" (?P<status>\d{3}) (?P<bytes>\d+|-) "(?P<referer>[^"]*)" "(?P<ua>[^"]*)"(?P<rest>.*)$"#,
+            ).expect("valid regex"),
+            postfix: Regex::new(
+                r"^(?P<time>\d{4}-\d\d-\d\dT\S+|[A-Z][a-z]{2} +\d{1,2} \d\d:\d\d:\d\d(?:\.\d+)?) \S+ (?P<program>[^\s\[:]+)(?:\[\d+\])?: (?P<message>.*)$",
+            ).expect("valid regex"),
+            exim: Regex::new(
+                r"^(?P<date>\d{4}-\d\d-\d\d \d\d:\d\d:\d\d(?:\.\d+)?)(?: (?P<tz>[+-]\d{4}))?(?: \[\d+\])? (?P<id>[0-9A-Za-z]{6}-[0-9A-Za-z]{6,11}-[0-9A-Za-z]{2,4}) (?P<flag><=|=>|->|\*>|==|\*\*) (?P<addr>\S+)(?P<rest>.*)$",
+            ).expect("valid regex"),
+            exim_host: Regex::new(r"\sH=(?P<host>\S+)(?: \[(?P<ip>[0-9A-Fa-f.:]+)\](?::\d+)?)?").expect("valid regex"),
+            mail_sizes: HashMap::new(),
+            mail_provider_map: std::env::var("MAIL_PROVIDER_MAP").unwrap_or_default(),
+        }
+    }
+
+    /// `None` for header and comment lines.
+    fn line(&mut self, line: &str) -> Option<Result<Outcome, String>> {
+        match self.format {
+            "vpc-flow" => {
+                if line.starts_with("version ") || line.split_whitespace().any(|f| f == "dstaddr" || f == "srcaddr") {
+                    self.vpc_fields = line.split_whitespace().map(str::to_string).collect();
+                    return None;
+                }
+                Some(parse_vpc_flow(line, &self.vpc_fields))
+            }
+            "zeek-conn" => {
+                if let Some(header) = line.strip_prefix('#') {
+                    if let Some(sep) = header.strip_prefix("separator ") {
+                        self.zeek_separator = sep.trim().replace("\\x09", "\t").replace("\\x20", " ");
+                    } else if let Some(fields) = header.strip_prefix("fields") {
+                        self.zeek_fields = fields.split(self.zeek_separator.as_str())
+                            .map(str::trim)
+                            .filter(|f| !f.is_empty())
+                            .map(str::to_string)
+                            .collect();
+                    }
+                    return None;
+                }
+                if line.starts_with('{') {
+                    let obj: serde_json::Map<String, serde_json::Value> = match serde_json::from_str(line) {
+                        Ok(o) => o,
+                        Err(e) => return Some(Err(format!("invalid JSON: {}", e))),
+                    };
+                    let get = |k: &str| -> Option<String> {
+                        match obj.get(k)? {
+                            serde_json::Value::String(s) => present(Some(s)).map(str::to_string),
+                            serde_json::Value::Bool(b) => Some(if *b { "T".into() } else { "F".into() }),
+                            other => Some(other.to_string()),
+                        }
+                    };
+                    return Some(zeek_entry(&get));
+                }
+                let values: Vec<&str> = line.split(self.zeek_separator.as_str()).collect();
+                if values.len() != self.zeek_fields.len() {
+                    return Some(Err(format!("expected {} fields, found {}", self.zeek_fields.len(), values.len())));
+                }
+                let get = |k: &str| {
+                    let i = self.zeek_fields.iter().position(|f| f == k)?;
+                    present(Some(values[i])).map(str::to_string)
+                };
+                Some(zeek_entry(&get))
+            }
+            "cef" => Some(parse_cef(line, &self.cef_key)),
+            "syslog" => Some(parse_syslog(line, &self.cef_key)),
+            "envoy" => Some(parse_envoy(line, &self.envoy)),
+            "nginx" => Some(parse_nginx(line, &self.nginx)),
+            "squid" => Some(parse_squid(line)),
+            "postfix" => Some(parse_postfix(line, &self.postfix, &mut self.mail_sizes, &self.mail_provider_map)),
+            "exim" => Some(parse_exim(line, &self.exim, &self.exim_host, &mut self.mail_sizes, &self.mail_provider_map)),
+            _ => Some(Err(format!("unsupported format {}", self.format))),
+        }
+    }
+}
+
+/// Parse a log file's content. Blank lines are ignored; every other line yields an entry,
+/// a skip reason or an error, so callers can report each line's fate.
+pub fn parse(format: &str, content: &str) -> Result<ParsedLog, String> {
+    let format = FORMATS.iter().find(|f| **f == format)
+        .ok_or_else(|| format!("unknown format {} (expected one of {})", format, FORMATS.join(", ")))?;
+    let mut parser = Parser::new(format);
+    let mut parsed = ParsedLog::default();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parser.line(line) {
+            None => {}
+            Some(Ok(Outcome::Entry(entry))) => parsed.entries.push((i + 1, *entry)),
+            Some(Ok(Outcome::Skip(message))) => parsed.skipped.push(LineIssue { line: i + 1, message }),
+            Some(Err(message)) => parsed.errors.push(LineIssue { line: i + 1, message }),
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(format: &str, content: &str) -> IngestLogEntry {
+        let parsed = parse(format, content).unwrap();
+        assert!(parsed.errors.is_empty(), "{}: {:?}", format, parsed.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+        assert_eq!(parsed.entries.len(), 1, "{}", format);
+        parsed.entries.into_iter().next().unwrap().1
+    }
+
+    fn skipped(format: &str, content: &str) -> Vec<String> {
+        let parsed = parse(format, content).unwrap();
+        assert!(parsed.entries.is_empty() && parsed.errors.is_empty(), "{}", format);
+        parsed.skipped.into_iter().map(|s| s.message).collect()
+    }
+
+    #[test]
+    fn addresses_and_hosts() {
+        assert_eq!(ip_of("tcp://203.0.113.9:443"), "203.0.113.9".parse().ok());
+        assert_eq!(ip_of("[2001:db8::1]:443"), "2001:db8::1".parse().ok());
+        assert_eq!(ip_of("2001:db8::1"), "2001:db8::1".parse().ok());
+        assert_eq!(ip_of("api.example.com:443"), None);
+        assert_eq!(host_of("https://user@API.Example.com.:8443/path?q=1").as_deref(), Some("api.example.com"));
+        assert_eq!(host_of("[2001:db8::1]:443").as_deref(), Some("2001:db8::1"));
+        assert_eq!(host_of("-"), None);
+        assert_eq!(path_of("http://files.example.br/a.csv?x=1").as_deref(), Some("/a.csv?x=1"));
+        assert!(is_internal(&"10.1.2.3".parse().unwrap()));
+        assert!(is_internal(&"fd00::1".parse().unwrap()));
+        assert!(!is_internal(&"203.0.113.9".parse().unwrap()));
+    }
+
+    #[test]
+    fn vpc_flow_default_and_custom_fields() {
+        let e = single("vpc-flow", "2 123456789010 eni-1235b8ca 10.0.1.5 203.0.113.12 49152 443 6 20 4249 1418530010 1418530070 ACCEPT OK");
+        assert_eq!(e.source_ip.as_deref(), Some("10.0.1.5"));
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.12"));
+        assert_eq!(e.protocol.as_deref(), Some("tcp"));
+        assert_eq!(e.data_size, Some(4249));
+        assert_eq!(e.timestamp.as_deref(), Some("1418530010"));
+
+        let e = single("vpc-flow", "version srcaddr dstaddr pkt-dstaddr action log-status bytes start\n5 10.0.1.5 10.0.0.9 198.51.100.4 ACCEPT OK 10 1418530010");
+        assert_eq!(e.dest_ip.as_deref(), Some("198.51.100.4"));
+
+        let reasons = skipped("vpc-flow", concat!(
+            "2 123456789010 eni-1 10.0.1.5 10.0.2.7 49152 443 6 20 4249 1418530010 1418530070 ACCEPT OK\n",
+            "2 123456789010 eni-1 10.0.1.5 203.0.113.12 49152 443 6 20 4249 1418530010 1418530070 REJECT OK\n",
+            "2 123456789010 eni-1 - - - - - - - 1418530010 1418530070 - NODATA",
+        ));
+        assert_eq!(reasons, vec!["internal destination 10.0.2.7", "rejected flow", "log-status NODATA"]);
+    }
+
+    #[test]
+    fn cef_with_custom_string_labels() {
+        let e = single("cef", "Mar 15 10:30:00 fw01 CEF:0|Vendor|Proxy|1.0|100|Allowed|3|src=10.0.0.5 dst=198.51.100.7 dhost=api.example.de out=512 rt=1700000000000 cs1Label=dataCategories cs1=email,name");
+        assert_eq!(e.dest_ip.as_deref(), Some("198.51.100.7"));
+        assert_eq!(e.partner_name.as_deref(), Some("api.example.de"));
+        assert_eq!(e.data_size, Some(512));
+        assert_eq!(e.timestamp.as_deref(), Some("1700000000000"));
+        assert_eq!(e.data_categories, Some(vec!["email".to_string(), "name".to_string()]));
+
+        let parsed = parse("cef", "CEF:0|Vendor|Proxy|1.0").unwrap();
+        assert_eq!(parsed.errors[0].message, "CEF header needs 7 `|`-separated fields");
+    }
+
+    #[test]
+    fn syslog_structured_data_and_message_fields() {
+        let e = single("syslog", r#"<134>1 2026-03-15T10:30:00Z fw01 proxy - - [meta@1 dst="198.51.100.7" host="api.example.fr"] bytes=100"#);
+        assert_eq!(e.dest_ip.as_deref(), Some("198.51.100.7"));
+        assert_eq!(e.partner_name.as_deref(), Some("api.example.fr"));
+        assert_eq!(e.data_size, Some(100));
+        assert_eq!(e.timestamp.as_deref(), Some("2026-03-15T10:30:00Z"));
+
+        let parsed = parse("syslog", "<134>Mar 15 10:30:00 fw01 proxy: dst=198.51.100.7").unwrap();
+        assert_eq!(parsed.errors.len(), 1);
+    }
+
+    #[test]
+    fn envoy_text_and_json() {
+        let e = single("envoy", r#"[2026-03-15T10:30:00.000Z] "POST /v1/upload HTTP/1.1" 200 - 1024 52 12 10 "10.0.0.5" "curl/8.0" "req-1" "api.example.jp" "203.0.113.9:443""#);
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.9"));
+        assert_eq!(e.partner_name.as_deref(), Some("api.example.jp"));
+        assert_eq!(e.data_size, Some(1024));
+        assert_eq!(e.request_path.as_deref(), Some("/v1/upload"));
+        assert_eq!(e.source_ip.as_deref(), Some("10.0.0.5"));
+
+        let e = single("envoy", r#"{"authority":"api.example.jp","upstream_host":"203.0.113.9:443","bytes_received":10,"start_time":"2026-03-15T10:30:00Z"}"#);
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.9"));
+        assert_eq!(e.data_size, Some(10));
+
+        let reasons = skipped("envoy", r#"[2026-03-15T10:30:00.000Z] "GET / HTTP/1.1" 503 UF 0 0 1 - "-" "curl/8.0" "req-2" "api.example.jp" "-""#);
+        assert_eq!(reasons, vec!["no upstream connection"]);
+    }
+
+    #[test]
+    fn nginx_forward_proxy_line() {
+        let e = single("nginx", r#"10.0.0.5 - - [15/Mar/2026:10:30:00 +0000] "GET http://files.example.br/a.csv HTTP/1.1" 200 2048 "-" "Wget" host=files.example.br upstream_addr=198.51.100.20:80 request_length=300"#);
+        assert_eq!(e.dest_ip.as_deref(), Some("198.51.100.20"));
+        assert_eq!(e.partner_name.as_deref(), Some("files.example.br"));
+        assert_eq!(e.data_size, Some(300));
+        assert_eq!(e.request_path.as_deref(), Some("/a.csv"));
+        assert_eq!(e.timestamp.as_deref(), Some("15/Mar/2026:10:30:00 +0000"));
+    }
+
+    #[test]
+    fn squid_tunnel_and_denied() {
+        let e = single("squid", "1700000000.123 52 10.0.0.5 TCP_TUNNEL/200 4096 CONNECT api.example.in:443 - HIER_DIRECT/203.0.113.50 -");
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.50"));
+        assert_eq!(e.partner_name.as_deref(), Some("api.example.in"));
+        assert_eq!(e.protocol.as_deref(), Some("https"));
+        assert_eq!(e.data_size, Some(4096));
+
+        let reasons = skipped("squid", "1700000000.123 0 10.0.0.5 TCP_DENIED/403 0 CONNECT blocked.example:443 - HIER_NONE/- text/html");
+        assert_eq!(reasons, vec!["TCP_DENIED (request not forwarded)"]);
+    }
+
+    #[test]
+    fn zeek_json_and_tsv() {
+        let e = single("zeek-conn", r#"{"ts":1700000000.5,"id.orig_h":"10.0.0.5","id.resp_h":"203.0.113.7","proto":"tcp","service":"ssl","orig_bytes":1200,"conn_state":"SF","local_resp":false,"server_name":"api.example.ca"}"#);
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(e.protocol.as_deref(), Some("ssl"));
+        assert_eq!(e.data_size, Some(1200));
+        assert_eq!(e.partner_name.as_deref(), Some("api.example.ca"));
+
+        let tsv = "#separator \\x09\n#fields\tts\tid.orig_h\tid.resp_h\tproto\torig_bytes\tconn_state\n1700000000.5\t10.0.0.5\t203.0.113.7\ttcp\t99\tSF\n1700000001.5\t10.0.0.5\t203.0.113.8\ttcp\t-\tS0";
+        let parsed = parse("zeek-conn", tsv).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].1.data_size, Some(99));
+        assert_eq!(parsed.skipped[0].message, "conn_state S0 (no data exchanged)");
+    }
+
+    #[test]
+    fn postfix_delivery_takes_size_from_qmgr() {
+        let log = concat!(
+            "Mar 15 10:30:00 mail postfix/qmgr[123]: 4ABC123: from=<a@corp.example>, size=2048, nrcpt=1 (queue active)\n",
+            "Mar 15 10:30:01 mail postfix/smtp[124]: 4ABC123: to=<bob@partner.example.de>, relay=mx.partner.example.de[203.0.113.25]:25, delay=1, status=sent (250 ok)\n",
+            "Mar 15 10:30:02 mail postfix/smtp[124]: 4ABC124: to=<eve@partner.example.de>, relay=mx.partner.example.de[203.0.113.25]:25, delay=1, status=deferred (try later)",
+        );
+        let parsed = parse("postfix", log).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        let e = &parsed.entries[0].1;
+        assert_eq!(e.dest_ip.as_deref(), Some("203.0.113.25"));
+        assert_eq!(e.data_size, Some(2048));
+        assert_eq!(e.protocol.as_deref(), Some("smtp"));
+        assert_eq!(e.partner_name.as_deref(), Some("partner.example.de"));
+        assert_eq!(parsed.skipped.iter().map(|s| s.message.as_str()).collect::<Vec<_>>(), vec!["queue manager record", "status deferred"]);
+    }
+
+    #[test]
+    fn mail_provider_map_matches_relay_then_domain() {
+        let mut parser = Parser::new("postfix");
+        parser.mail_provider_map = "*.google.com=US:Google Workspace,corp.example.nl=NL".into();
+        let line = "Mar 15 10:30:01 mail postfix/smtp[124]: 4ABC123: to=<bob@customer.example>, relay=aspmx.l.google.com[142.250.1.27]:25, status=sent (250 ok)";
+        let Some(Ok(Outcome::Entry(e))) = parser.line(line) else { panic!("expected an entry") };
+        assert_eq!(e.destination_country_code.as_deref(), Some("US"));
+        assert_eq!(e.partner_name.as_deref(), Some("Google Workspace"));
+
+        let line = "Mar 15 10:30:01 mail postfix/smtp[124]: 4ABC123: to=<bob@corp.example.nl>, relay=mx.corp.example.nl[198.51.100.8]:25, status=sent (250 ok)";
+        let Some(Ok(Outcome::Entry(e))) = parser.line(line) else { panic!("expected an entry") };
+        assert_eq!(e.destination_country_code.as_deref(), Some("NL"));
+        assert_eq!(e.partner_name.as_deref(), Some("corp.example.nl"));
+    }
+
+    #[test]
+    fn exim_delivery_takes_size_from_arrival() {
+        let log = concat!(
+            "2026-03-15 10:30:00 1pQxYz-0001Ab-Cd <= alice@corp.example H=localhost [127.0.0.1] P=esmtp S=4096\n",
+            "2026-03-15 10:30:02 +0100 1pQxYz-0001Ab-Cd => bob@partner.example.fr R=dnslookup T=remote_smtp H=mx.partner.example.fr [198.51.100.30] X=TLS1.3 C=\"250 OK\"\n",
+            "2026-03-15 10:30:03 1pQxYz-0001Ab-Ce => carol@corp.example R=local T=local_delivery",
+        );
+        let parsed = parse("exim", log).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        let e = &parsed.entries[0].1;
+        assert_eq!(e.dest_ip.as_deref(), Some("198.51.100.30"));
+        assert_eq!(e.data_size, Some(4096));
+        assert_eq!(e.timestamp.as_deref(), Some("2026-03-15 10:30:02+0100"));
+        assert_eq!(e.partner_name.as_deref(), Some("partner.example.fr"));
+        assert_eq!(parsed.skipped.iter().map(|s| s.message.as_str()).collect::<Vec<_>>(), vec!["message arrival", "local delivery"]);
+    }
+
+    #[test]
+    fn unknown_format_is_rejected() {
+        assert!(parse("iis", "x").is_err());
+    }
+}
@@ -1,12 +1,49 @@
 mod models;
+mod api_error;
+mod validation;
 mod evidence;
 mod shield;
+mod source_time;
+mod idempotency;
+mod ip_country;
+mod host_map;
+mod log_formats;
+mod otlp;
+mod csp_reports;
+mod iac_scan;
+mod enforcement;
+mod flow_register;
+mod ingest_queue;
+mod transfer_flows;
+mod transfer_permits;
+mod data_categories;
+mod country_policy;
+mod country_import;
+mod sanctions;
+mod drift;
+mod background_worker;
 mod crypto_shredder;
+mod webhooks;
+mod live_stream;
 mod review_queue;
 mod routes_evidence;
 mod routes_shield;
 mod routes_review_queue;
 mod routes_erasure;
+mod routes_data_categories;
+mod routes_country_policy;
+mod routes_sanctions;
+mod routes_drift;
+mod routes_ext_authz;
+mod routes_webhooks;
+mod routes_stream;
+mod routes_log_formats;
+mod routes_otlp;
+mod routes_csp_reports;
+mod routes_permits;
+mod routes_enforcement;
+mod routes_flow_register;
+mod openapi;
 
 use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, get};
 use actix_cors::Cors;
@@ -143,22 +180,18 @@ async fn modules() -> HttpResponse {
 }
 
 #[get("/api/v1/auth/me")]
-async fn auth_me(req: HttpRequest) -> HttpResponse {
+async fn auth_me(req: HttpRequest) -> Result<HttpResponse, api_error::ApiError> {
     let auth = req.headers().get("Authorization").and_then(|v| v.to_str().ok());
     let token = match auth {
         Some(s) if s.starts_with("Bearer ") => s.trim_start_matches("Bearer "),
-        _ => {
-            return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "Missing or invalid Authorization" }));
-        }
+        _ => return Err(api_error::ApiError::Unauthorized("Missing or invalid Authorization")),
     };
     let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "veridion-api-dev-secret-change-in-production".to_string());
     let key = jsonwebtoken::DecodingKey::from_secret(secret.as_ref());
     let validation = jsonwebtoken::Validation::default();
     let claims: serde_json::Value = match jsonwebtoken::decode::<serde_json::Value>(token, &key, &validation) {
         Ok(t) => t.claims,
-        Err(_) => {
-            return HttpResponse::Unauthorized().json(serde_json::json!({ "error": "Invalid token" }));
-        }
+        Err(_) => return Err(api_error::ApiError::Unauthorized("Invalid token")),
     };
     let sub = claims.get("sub").and_then(|v| v.as_str()).unwrap_or("");
     let username = claims.get("username").and_then(|v| v.as_str()).unwrap_or("user");
@@ -168,7 +201,7 @@ async fn auth_me(req: HttpRequest) -> HttpResponse {
         .unwrap_or_else(|| vec!["admin".to_string(), "editor".to_string()]);
     let onboarded = claims.get("onboarded").and_then(|v| v.as_bool()).unwrap_or(true);
     let company_id = claims.get("company_id").and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(String::from);
-    HttpResponse::Ok().json(serde_json::json!({
+    Ok(HttpResponse::Ok().json(serde_json::json!({
         "id": sub,
         "username": username,
         "email": null,
@@ -177,7 +210,7 @@ async fn auth_me(req: HttpRequest) -> HttpResponse {
         "onboarded": onboarded,
         "enforcement_override": false,
         "company_id": company_id
-    }))
+    })))
 }
 
 #[get("/api/v1/audit/alerts")]
@@ -190,6 +223,13 @@ async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
     env_logger::init();
 
+    let args: Vec<String> = env::args().collect();
+    // Evaluates against an exported policy snapshot, so it runs before (and without) the database
+    if args.get(1).map(String::as_str) == Some("scan-iac") {
+        let code = iac_scan::run_cli(&args[2..])?;
+        std::process::exit(code);
+    }
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let database_url = database_url.replace("localhost", "127.0.0.1");
     let server_host = env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
@@ -254,6 +294,39 @@ async fn main() -> std::io::Result<()> {
         }
     }
     println!("Migrations applied.");
+    match webhooks::wrap_plaintext_secrets(&pool).await {
+        Ok(n) if n > 0 => println!("Wrapped {} plaintext webhook secret(s).", n),
+        Ok(_) => {}
+        Err(e) => panic!("Failed to wrap webhook secrets: {}", e),
+    }
+
+    if args.get(1).map(String::as_str) == Some("import-country-list") {
+        return country_import::run_cli(&pool, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("load-sanctions-list") {
+        return sanctions::run_cli(&pool, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("load-ip-country-db") {
+        return ip_country::run_cli(&pool, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("rotate-permit-key") {
+        return transfer_permits::run_cli(&pool).await;
+    }
+    if args.get(1).map(String::as_str) == Some("export-enforcement") {
+        return enforcement::run_cli(&pool, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("export-policy-snapshot") {
+        return iac_scan::export_cli(&pool, &args[2..]).await;
+    }
+
+    background_worker::spawn_country_policy_scheduler(pool.clone());
+    background_worker::spawn_webhook_dispatcher(pool.clone());
+    background_worker::spawn_live_events_pruner(pool.clone());
+    background_worker::spawn_idempotency_pruner(pool.clone());
+    background_worker::spawn_ingest_workers(pool.clone());
+    background_worker::spawn_ingest_pruner(pool.clone());
+    background_worker::spawn_flow_sealer(pool.clone());
+    let live_hub = web::Data::new(live_stream::LiveHub::start(pool.clone()));
 
     let origins: Vec<String> = allowed_origins.split(',').map(|s| s.trim().to_string()).collect();
 
@@ -261,24 +334,51 @@ async fn main() -> std::io::Result<()> {
     println!("  Health:          GET  /health");
     println!("  Dev login:       GET  /api/v1/auth/dev-bypass");
     println!("  Evidence events: GET  /api/v1/evidence/events");
-    println!("  Shield evaluate: POST /api/v1/shield/evaluate (synchronous runtime enforcement)");
-    println!("  Shield ingest:   POST /api/v1/shield/ingest-logs (batch processing)");
+    println!("  Shield evaluate: POST /api/v1/shield/evaluate (synchronous runtime enforcement; ?permit=true for a signed permit)");
+    println!("  Permit keys:     GET  /api/v1/shield/permits/jwks.json");
+    println!("  Revoked permits: GET  /api/v1/shield/permits/revoked");
+    println!("  Shield ingest:   POST /api/v1/shield/ingest-logs (queued; ?sync=true for inline)");
+    println!("  Shield ingest:   POST /api/v1/shield/ingest-logs/{{format}} (vpc-flow, cef, syslog, envoy, nginx, squid, zeek-conn, postfix, exim)");
+    println!("  OTLP traces:     POST /api/v1/shield/otlp/v1/traces (protobuf or JSON)");
+    println!("  CSP reports:     POST /api/v1/shield/csp-reports (report-uri / Reporting API)");
+    println!("  Ingest batches:  GET  /api/v1/shield/ingest-batches/{{id}}");
+    println!("  Transfer flows:  GET  /api/v1/shield/flows");
+    println!("  Policy snapshot: GET  /api/v1/shield/policy-snapshot (for scan-iac)");
+    println!("  Enforcement:     POST /api/v1/shield/enforcement/{{format}} (nftables, ipset, rpz)");
+    println!("  Flow register:   POST /api/v1/flow-register/flows, GET /api/v1/flow-register/undeclared");
     println!("  Shield stats:    GET  /api/v1/lenses/sovereign-shield/stats");
     println!("  SCC register:    POST /api/v1/scc-registries");
     println!("  SCC list:        GET  /api/v1/scc-registries");
     println!("  SCC revoke:      DELETE /api/v1/scc-registries/{{id}}");
     println!("  Review queue:    GET  /api/v1/review-queue");
     println!("  Erasure:         POST /api/v1/lenses/gdpr-rights/erasure/execute");
+    println!("  Data categories: GET  /api/v1/data-categories");
+    println!("  Policy changes:  GET  /api/v1/country-policy/upcoming?days=90");
+    println!("  Sanctions lists: POST /api/v1/sanctions/lists");
+    println!("  Drift report:    POST /api/v1/shield/drift-reports");
+    println!("  Envoy ext_authz: ANY  /api/v1/shield/ext-authz/{{path}}");
+    println!("  Webhooks:        POST /api/v1/webhooks/subscriptions");
+    println!("  Live stream:     GET  /api/v1/stream/events (Server-Sent Events)");
+    println!("  OpenAPI:         GET  /api/v1/openapi.json");
 
     HttpServer::new(move || {
         let mut cors = Cors::default()
             .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"])
-            .allowed_headers(vec![actix_web::http::header::AUTHORIZATION, actix_web::http::header::CONTENT_TYPE]);
+            .allowed_headers(vec![
+                actix_web::http::header::AUTHORIZATION,
+                actix_web::http::header::CONTENT_TYPE,
+                actix_web::http::header::HeaderName::from_static("last-event-id"),
+            ]);
         for origin in &origins {
             cors = cors.allowed_origin(origin.as_str());
         }
+        cors = cors.allowed_origin_fn(routes_csp_reports::cors_allows);
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(live_hub.clone())
+            .app_data(web::JsonConfig::default().error_handler(api_error::json_error))
+            .app_data(web::QueryConfig::default().error_handler(api_error::query_error))
+            .app_data(web::PathConfig::default().error_handler(api_error::path_error))
             .wrap(cors)
             .service(index)
             .service(health)
@@ -290,8 +390,22 @@ async fn main() -> std::io::Result<()> {
             .service(audit_alerts)
             .configure(routes_evidence::configure)
             .configure(routes_shield::configure)
+            .configure(routes_log_formats::configure)
+            .configure(routes_otlp::configure)
+            .configure(routes_csp_reports::configure)
+            .configure(routes_permits::configure)
+            .configure(routes_enforcement::configure)
+            .configure(routes_flow_register::configure)
             .configure(routes_review_queue::configure)
             .configure(routes_erasure::configure)
+            .configure(routes_data_categories::configure)
+            .configure(routes_country_policy::configure)
+            .configure(routes_sanctions::configure)
+            .configure(routes_drift::configure)
+            .configure(routes_ext_authz::configure)
+            .configure(routes_webhooks::configure)
+            .configure(routes_stream::configure)
+            .configure(openapi::configure)
     })
     .bind((server_host.as_str(), server_port))?
     .run()
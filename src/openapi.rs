@@ -0,0 +1,140 @@
+use actix_web::{web, HttpResponse, get};
+use utoipa::openapi::{ContentBuilder, OpenApi as OpenApiDoc, Ref, RefOr, ResponseBuilder};
+use utoipa::{Modify, OpenApi};
+
+use crate::api_error::{ErrorBody, FieldError};
+use crate::transfer_permits;
+use crate::{
+    routes_country_policy, routes_csp_reports, routes_data_categories, routes_drift, routes_enforcement, routes_erasure, routes_evidence,
+    routes_ext_authz, routes_flow_register, routes_log_formats, routes_otlp, routes_permits, routes_review_queue, routes_sanctions, routes_shield, routes_stream, routes_webhooks,
+};
+
+/// Every operation shares the `ErrorBody` error model; rather than repeating it on each handler,
+/// operations that declare no error responses get 400 and 500 (and 404 when addressed by ID).
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let error = |description: &str| {
+            RefOr::T(
+                ResponseBuilder::new()
+                    .description(description)
+                    .content(
+                        "application/json",
+                        ContentBuilder::new().schema(Some(Ref::from_schema_name("ErrorBody"))).build(),
+                    )
+                    .build(),
+            )
+        };
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let operations = [
+                &mut item.get, &mut item.post, &mut item.put, &mut item.patch,
+                &mut item.delete, &mut item.head, &mut item.options, &mut item.trace,
+            ];
+            for op in operations.into_iter().flatten() {
+                let responses = &mut op.responses.responses;
+                if responses.keys().any(|code| code.starts_with('4') || code.starts_with('5')) {
+                    continue;
+                }
+                responses.insert("400".into(), error("Invalid request (`VALIDATION_FAILED`, `INVALID_JSON`, `INVALID_QUERY`, `INVALID_ID`, ...)"));
+                if path.contains('{') {
+                    responses.insert("404".into(), error("`NOT_FOUND`"));
+                }
+                responses.insert("500".into(), error("Internal error; the body carries a `reference` for the server log"));
+            }
+        }
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Veridion API",
+        description = "Sovereign Shield transfer enforcement, evidence vault and compliance workflows. \
+                       Errors use `ErrorBody`: match on the stable `error` code, not on `message`.",
+    ),
+    paths(
+        routes_evidence::list_events,
+        routes_evidence::create_event,
+        routes_evidence::verify_integrity,
+        routes_shield::evaluate,
+        routes_shield::ingest_logs,
+        routes_shield::ingest_batch_status,
+        routes_shield::list_flows,
+        routes_shield::policy_snapshot,
+        routes_log_formats::ingest_format,
+        routes_otlp::export_traces,
+        routes_csp_reports::receive_reports,
+        routes_permits::jwks,
+        routes_permits::revocation_list,
+        routes_permits::revoke_permit,
+        routes_enforcement::generate_artifact,
+        routes_enforcement::latest_artifact,
+        routes_enforcement::list_artifacts,
+        routes_enforcement::get_artifact,
+        routes_enforcement::record_deployment,
+        routes_flow_register::declare_flow,
+        routes_flow_register::list_flows,
+        routes_flow_register::retire_flow,
+        routes_flow_register::list_undeclared,
+        routes_flow_register::resolve_undeclared,
+        routes_shield::shield_stats,
+        routes_shield::shield_countries,
+        routes_shield::shield_requires_attention,
+        routes_shield::transfers_by_destination,
+        routes_shield::register_scc,
+        routes_shield::list_scc_registries,
+        routes_shield::patch_scc_registry,
+        routes_shield::revoke_scc,
+        routes_ext_authz::check,
+        routes_review_queue::list_reviews,
+        routes_review_queue::get_pending,
+        routes_review_queue::get_decided_evidence_ids,
+        routes_review_queue::create_review,
+        routes_review_queue::approve_action,
+        routes_review_queue::reject_action,
+        routes_erasure::execute_erasure,
+        routes_data_categories::list_categories,
+        routes_data_categories::upsert_category,
+        routes_data_categories::delete_category,
+        routes_data_categories::normalise_categories,
+        routes_country_policy::list_changes,
+        routes_country_policy::schedule_change,
+        routes_country_policy::cancel_change,
+        routes_country_policy::upcoming_changes,
+        routes_country_policy::apply_due,
+        routes_country_policy::create_import,
+        routes_country_policy::list_imports,
+        routes_country_policy::get_import,
+        routes_sanctions::load_list,
+        routes_sanctions::list_lists,
+        routes_sanctions::screen,
+        routes_drift::create_report,
+        routes_drift::list_reports,
+        routes_drift::get_report,
+        routes_webhooks::create_subscription,
+        routes_webhooks::list_subscriptions,
+        routes_webhooks::update_subscription,
+        routes_webhooks::delete_subscription,
+        routes_webhooks::list_deliveries,
+        routes_webhooks::redeliver,
+        routes_webhooks::list_dead_letters,
+        routes_webhooks::redeliver_dead_letter,
+        routes_stream::stream_events,
+    ),
+    components(schemas(ErrorBody, FieldError, transfer_permits::PermitClaims)),
+    modifiers(&ErrorResponses),
+)]
+pub struct ApiDoc;
+
+/// The OpenAPI 3 document for every route above, generated from the handler annotations.
+#[get("/api/v1/openapi.json")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(openapi_json);
+}
+
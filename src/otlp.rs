@@ -0,0 +1,391 @@
+//! OTLP trace export payloads (`opentelemetry.proto.collector.trace.v1`) in both OTLP/HTTP encodings,
+//! protobuf and JSON, and the mapping of outbound client spans onto `IngestLogEntry`.
+//! Only the fields Shield reads are declared; unknown fields are ignored in either encoding.
+//! Attribute names follow the OpenTelemetry semantic conventions, current and pre-1.21.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::DateTime;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::net::IpAddr;
+
+use crate::log_formats::{host_of, ip_of, is_internal, path_of};
+use crate::host_map;
+use crate::routes_shield::IngestLogEntry;
+
+const SPAN_KIND_CLIENT: i32 = 3;
+const SPAN_KIND_PRODUCER: i32 = 4;
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportTraceServiceRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceSpans {
+    #[prost(message, optional, tag = "1")]
+    pub resource: Option<Resource>,
+    #[prost(message, repeated, tag = "2")]
+    pub scope_spans: Vec<ScopeSpans>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Resource {
+    #[prost(message, repeated, tag = "1")]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeSpans {
+    #[prost(message, repeated, tag = "2")]
+    pub spans: Vec<Span>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Span {
+    /// Hex-encoded in JSON, raw bytes in protobuf.
+    #[prost(bytes = "vec", tag = "1")]
+    #[serde(deserialize_with = "hex_bytes")]
+    pub trace_id: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    #[serde(deserialize_with = "hex_bytes")]
+    pub span_id: Vec<u8>,
+    #[prost(string, tag = "5")]
+    pub name: String,
+    #[prost(int32, tag = "6")]
+    #[serde(deserialize_with = "span_kind")]
+    pub kind: i32,
+    #[prost(fixed64, tag = "7")]
+    #[serde(deserialize_with = "json_u64")]
+    pub start_time_unix_nano: u64,
+    #[prost(message, repeated, tag = "9")]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(default)]
+pub struct KeyValue {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(message, optional, tag = "2")]
+    pub value: Option<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(from = "JsonAnyValue")]
+pub struct AnyValue {
+    #[prost(oneof = "Value", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub value: Option<Value>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum Value {
+    #[prost(string, tag = "1")]
+    String(String),
+    #[prost(bool, tag = "2")]
+    Bool(bool),
+    #[prost(int64, tag = "3")]
+    Int(i64),
+    #[prost(double, tag = "4")]
+    Double(f64),
+    #[prost(message, tag = "5")]
+    Array(ArrayValue),
+    #[prost(message, tag = "6")]
+    KvList(KeyValueList),
+    #[prost(bytes = "vec", tag = "7")]
+    Bytes(Vec<u8>),
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(default)]
+pub struct ArrayValue {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Deserialize)]
+#[serde(default)]
+pub struct KeyValueList {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<KeyValue>,
+}
+
+/// JSON form of `AnyValue`: an object with exactly one `*Value` member; bytes are base64.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct JsonAnyValue {
+    string_value: Option<String>,
+    bool_value: Option<bool>,
+    #[serde(deserialize_with = "json_i64_opt")]
+    int_value: Option<i64>,
+    double_value: Option<f64>,
+    array_value: Option<ArrayValue>,
+    kvlist_value: Option<KeyValueList>,
+    bytes_value: Option<String>,
+}
+
+impl From<JsonAnyValue> for AnyValue {
+    fn from(v: JsonAnyValue) -> Self {
+        let value = if let Some(s) = v.string_value {
+            Some(Value::String(s))
+        } else if let Some(b) = v.bool_value {
+            Some(Value::Bool(b))
+        } else if let Some(i) = v.int_value {
+            Some(Value::Int(i))
+        } else if let Some(d) = v.double_value {
+            Some(Value::Double(d))
+        } else if let Some(a) = v.array_value {
+            Some(Value::Array(a))
+        } else if let Some(kv) = v.kvlist_value {
+            Some(Value::KvList(kv))
+        } else {
+            v.bytes_value.and_then(|b| BASE64.decode(b).ok()).map(Value::Bytes)
+        };
+        AnyValue { value }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTraceServiceResponse {
+    #[prost(message, optional, tag = "1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_success: Option<ExportTracePartialSuccess>,
+}
+
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTracePartialSuccess {
+    #[prost(int64, tag = "1")]
+    pub rejected_spans: i64,
+    #[prost(string, tag = "2")]
+    pub error_message: String,
+}
+
+fn hex_bytes<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+    let s = Option::<String>::deserialize(d)?.unwrap_or_default();
+    if s.len() % 2 != 0 {
+        return Err(serde::de::Error::custom("odd-length hex ID"));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2).unwrap_or("-"), 16))
+        .collect::<Result<_, _>>()
+        .map_err(|_| serde::de::Error::custom(format!("invalid hex ID: {}", s)))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 64-bit integers are strings in proto3 JSON, but many exporters write plain numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonInt {
+    Number(serde_json::Number),
+    String(String),
+}
+
+impl JsonInt {
+    fn parse<T: std::str::FromStr>(&self) -> Option<T> {
+        match self {
+            JsonInt::Number(n) => n.to_string().parse().ok(),
+            JsonInt::String(s) => s.parse().ok(),
+        }
+    }
+}
+
+fn json_u64<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+    match Option::<JsonInt>::deserialize(d)? {
+        Some(v) => v.parse().ok_or_else(|| serde::de::Error::custom("expected an unsigned integer")),
+        None => Ok(0),
+    }
+}
+
+fn json_i64_opt<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i64>, D::Error> {
+    match Option::<JsonInt>::deserialize(d)? {
+        Some(v) => v.parse().map(Some).ok_or_else(|| serde::de::Error::custom("expected an integer")),
+        None => Ok(None),
+    }
+}
+
+/// Span kind as the enum number, or its name (`SPAN_KIND_CLIENT`) as some JSON encoders write it.
+fn span_kind<'de, D: Deserializer<'de>>(d: D) -> Result<i32, D::Error> {
+    match Option::<JsonInt>::deserialize(d)? {
+        Some(JsonInt::String(name)) => Ok(match name.as_str() {
+            "SPAN_KIND_INTERNAL" => 1,
+            "SPAN_KIND_SERVER" => 2,
+            "SPAN_KIND_CLIENT" => SPAN_KIND_CLIENT,
+            "SPAN_KIND_PRODUCER" => SPAN_KIND_PRODUCER,
+            "SPAN_KIND_CONSUMER" => 5,
+            other => other.parse().unwrap_or(0),
+        }),
+        Some(n) => Ok(n.parse().unwrap_or(0)),
+        None => Ok(0),
+    }
+}
+
+impl AnyValue {
+    fn as_text(&self) -> Option<String> {
+        match self.value.as_ref()? {
+            Value::String(s) => Some(s.clone()),
+            Value::Int(i) => Some(i.to_string()),
+            Value::Double(d) => Some(d.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self.value.as_ref()? {
+            Value::Int(i) => u64::try_from(*i).ok(),
+            Value::String(s) => s.trim().parse().ok(),
+            Value::Double(d) if *d >= 0.0 => Some(*d as u64),
+            _ => None,
+        }
+    }
+
+    /// A string array, or one comma-separated string.
+    fn as_list(&self) -> Vec<String> {
+        match self.value.as_ref() {
+            Some(Value::Array(a)) => a.values.iter().filter_map(AnyValue::as_text).collect(),
+            Some(Value::String(s)) => s.split(',').map(str::to_string).collect(),
+            _ => Vec::new(),
+        }
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+    }
+}
+
+struct Attributes<'a>(&'a [KeyValue]);
+
+impl Attributes<'_> {
+    fn value(&self, keys: &[&str]) -> Option<&AnyValue> {
+        keys.iter().find_map(|k| self.0.iter().find(|kv| kv.key == *k)?.value.as_ref())
+    }
+
+    fn text(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|k| {
+            let text = self.0.iter().find(|kv| kv.key == *k)?.value.as_ref()?.as_text()?;
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        })
+    }
+
+    fn list(&self, keys: &[&str]) -> Option<Vec<String>> {
+        Some(self.value(keys)?.as_list()).filter(|l| !l.is_empty())
+    }
+}
+
+/// Cluster-local names (`payments`, `db.svc.cluster.local`, `*.internal`) never leave the network.
+fn is_internal_host(host: &str) -> bool {
+    host == "localhost"
+        || !host.contains('.')
+        || [".local", ".internal", ".localdomain", ".svc", ".cluster.local", ".svc.cluster.local"]
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+}
+
+/// An outbound call found in a trace. `host` is the destination name, for DNS resolution when
+/// neither the span nor `EXT_AUTHZ_HOST_MAP` places it in a country.
+pub struct ClientSpan {
+    pub host: Option<String>,
+    pub entry: IngestLogEntry,
+}
+
+#[derive(Default)]
+pub struct ExtractedSpans {
+    pub spans: usize,
+    /// Server, internal and consumer spans, and client spans to internal destinations.
+    pub not_outbound: usize,
+    pub client_spans: Vec<ClientSpan>,
+}
+
+/// Pull the outbound client and producer spans out of an export request.
+pub fn extract(request: &ExportTraceServiceRequest) -> ExtractedSpans {
+    let mut out = ExtractedSpans::default();
+    for resource_spans in &request.resource_spans {
+        let resource_attrs = resource_spans.resource.as_ref().map(|r| r.attributes.as_slice()).unwrap_or_default();
+        let resource = Attributes(resource_attrs);
+        for span in resource_spans.scope_spans.iter().flat_map(|s| &s.spans) {
+            out.spans += 1;
+            match client_span(&resource, span) {
+                Some(client) => out.client_spans.push(client),
+                None => out.not_outbound += 1,
+            }
+        }
+    }
+    out
+}
+
+fn client_span(resource: &Attributes, span: &Span) -> Option<ClientSpan> {
+    if span.kind != SPAN_KIND_CLIENT && span.kind != SPAN_KIND_PRODUCER {
+        return None;
+    }
+    let attrs = Attributes(&span.attributes);
+    let url = attrs.text(&["url.full", "http.url"]);
+    let host = attrs.text(&["server.address", "net.peer.name", "http.host"])
+        .and_then(|h| host_of(&h))
+        .or_else(|| url.as_deref().and_then(host_of));
+    let peer_ip = attrs.text(&["network.peer.address", "net.sock.peer.addr", "net.peer.ip", "server.socket.address"])
+        .and_then(|a| ip_of(&a))
+        .or_else(|| host.as_deref().and_then(|h| h.parse::<IpAddr>().ok()));
+    let host = host.filter(|h| h.parse::<IpAddr>().is_err());
+
+    match (&host, peer_ip) {
+        (Some(h), _) if is_internal_host(h) => return None,
+        (None, Some(ip)) if is_internal(&ip) => return None,
+        (None, None) => return None,
+        _ => {}
+    }
+
+    let mapped = host.as_deref().and_then(host_map::lookup_env);
+    let destination_country_code = attrs.text(&["veridion.destination_country_code"])
+        .or_else(|| mapped.as_ref().map(|(code, _)| code.clone()));
+    let partner_name = attrs.text(&["veridion.partner_name", "peer.service"])
+        .or_else(|| mapped.and_then(|(_, partner)| partner))
+        .or_else(|| host.clone());
+    let data_categories = attrs.list(&["veridion.data_categories"])
+        .or_else(|| resource.list(&["veridion.data_categories"]));
+    let protocol = attrs.text(&["url.scheme", "http.scheme", "rpc.system", "messaging.system", "db.system", "network.protocol.name"])
+        .or_else(|| url.as_deref().and_then(|u| u.split_once("://")).map(|(scheme, _)| scheme.to_string()))
+        .map(|p| p.to_lowercase());
+    let request_path = attrs.text(&["url.path", "http.target"])
+        .or_else(|| url.as_deref().and_then(path_of));
+    let data_size = attrs.value(&["http.request.body.size", "http.request_content_length", "messaging.message.body.size"])
+        .and_then(AnyValue::as_u64);
+    let timestamp = (span.start_time_unix_nano > 0)
+        .then(|| {
+            let nanos = span.start_time_unix_nano;
+            DateTime::from_timestamp((nanos / 1_000_000_000) as i64, (nanos % 1_000_000_000) as u32)
+        })
+        .flatten()
+        .map(|t| t.to_rfc3339());
+    let external_id = (!span.trace_id.is_empty() && !span.span_id.is_empty())
+        .then(|| format!("otlp:{}:{}", hex(&span.trace_id), hex(&span.span_id)));
+
+    let entry = IngestLogEntry {
+        source_ip: resource.list(&["host.ip"]).and_then(|ips| ips.into_iter().next()),
+        dest_ip: peer_ip.map(|ip| ip.to_string()),
+        protocol,
+        data_size,
+        timestamp,
+        user_agent: attrs.text(&["user_agent.original", "http.user_agent"]),
+        request_path,
+        destination_country_code,
+        data_categories,
+        partner_name,
+        source_system: resource.text(&["service.name"]),
+        external_id,
+        ..Default::default()
+    };
+    Some(ClientSpan { host, entry })
+}
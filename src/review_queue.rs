@@ -1,8 +1,10 @@
 use chrono::Utc;
 use sha2::{Sha256, Digest};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+use crate::api_error::ApiError;
+use crate::country_import;
 use crate::evidence::{self, CreateEventParams};
 use crate::models::{ComplianceRecordRow, HumanOversightRow, ReviewItemResponse};
 
@@ -19,6 +21,21 @@ pub async fn create_review(
     module: &str,
     context: &serde_json::Value,
     evidence_event_id: &str,
+) -> Result<String, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let seal_id = create_review_in_tx(&mut tx, agent_id, action, module, context, evidence_event_id).await?;
+    tx.commit().await.map_err(|e| format!("Failed to commit review: {}", e))?;
+    Ok(seal_id)
+}
+
+/// `create_review` inside the caller's transaction, so the review commits with the change it gates.
+pub async fn create_review_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    agent_id: &str,
+    action: &str,
+    module: &str,
+    context: &serde_json::Value,
+    evidence_event_id: &str,
 ) -> Result<String, String> {
     // Do not create a duplicate review for the same evidence event (e.g. already rejected or approved).
     // Return existing seal_id so frontend stops re-adding; REQUIRES ATTENTION only shows PENDING items.
@@ -26,7 +43,7 @@ pub async fn create_review(
         "SELECT seal_id FROM compliance_records WHERE evidence_event_id = $1 LIMIT 1",
     )
     .bind(evidence_event_id)
-    .fetch_optional(pool)
+    .fetch_optional(&mut **tx)
     .await
     .map_err(|e| format!("Failed to check existing review: {}", e))?;
 
@@ -49,7 +66,7 @@ pub async fn create_review(
     .bind(&tx_id)
     .bind(&payload_hash)
     .bind(evidence_event_id)
-    .execute(pool)
+    .execute(&mut **tx)
     .await
     .map_err(|e| format!("Failed to create compliance record: {}", e))?;
 
@@ -57,7 +74,7 @@ pub async fn create_review(
         "INSERT INTO human_oversight (seal_id, status) VALUES ($1, 'PENDING')"
     )
     .bind(&seal_id)
-    .execute(pool)
+    .execute(&mut **tx)
     .await
     .map_err(|e| format!("Failed to create human oversight entry: {}", e))?;
 
@@ -172,11 +189,11 @@ pub async fn decide_review(
     decision: &str,
     reviewer_id: &str,
     comments: &str,
-) -> Result<(), String> {
+) -> Result<(), ApiError> {
     let ho_status = match decision {
         "ALLOW" | "APPROVE" => "APPROVED",
         "BLOCK" | "REJECT" => "REJECTED",
-        _ => return Err(format!("Invalid decision: {}", decision)),
+        _ => return Err(ApiError::invalid_field("decision", format!("invalid decision: {}", decision))),
     };
 
     let result = sqlx::query(
@@ -189,10 +206,18 @@ pub async fn decide_review(
     .bind(seal_id)
     .execute(pool)
     .await
-    .map_err(|e| format!("Failed to update human oversight: {}", e))?;
+    .map_err(|e| ApiError::internal("REVIEW_UPDATE_FAILED", format!("Failed to update human oversight: {}", e)))?;
 
     if result.rows_affected() == 0 {
-        return Err("Review not found or already decided".into());
+        let current: Option<String> = sqlx::query_scalar("SELECT status FROM human_oversight WHERE seal_id = $1")
+            .bind(seal_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| ApiError::internal("REVIEW_UPDATE_FAILED", e))?;
+        return Err(match current {
+            Some(status) => ApiError::conflict("REVIEW_ALREADY_DECIDED", format!("Review is already {}", status)),
+            None => ApiError::NotFound("Review"),
+        });
     }
 
     sqlx::query(
@@ -202,7 +227,7 @@ pub async fn decide_review(
     .bind(seal_id)
     .execute(pool)
     .await
-    .map_err(|e| format!("Failed to update compliance record: {}", e))?;
+    .map_err(|e| ApiError::internal("REVIEW_UPDATE_FAILED", format!("Failed to update compliance record: {}", e)))?;
 
     let params = CreateEventParams {
         event_type: format!("HUMAN_OVERSIGHT_{}", ho_status),
@@ -220,12 +245,19 @@ pub async fn decide_review(
         causation_id: None,
         source_ip: None,
         source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
     };
 
     if let Err(e) = evidence::create_event(pool, params).await {
         log::error!("Failed to create evidence event for review decision: {}", e);
     }
 
+    // A failure leaves the import pending; the country policy scheduler retries it
+    if let Err(e) = country_import::on_review_decided(pool, seal_id, ho_status).await {
+        log::error!("Failed to process country list import for review {}, will retry: {}", seal_id, e);
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,296 @@
+use actix_web::{web, HttpResponse, get, post, delete};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::country_import::{self, CountryListImportRow, CreateImportParams, LIST_TYPES};
+use crate::country_policy::{self, CountryPolicyChangeRow, ScheduleChangeParams, UpcomingChange, COUNTRY_STATUSES};
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListChangesQuery {
+    /// `scheduled`, `applied` or `cancelled`.
+    pub status: Option<String>,
+}
+
+impl Validate for ListChangesQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("status", self.status.as_deref(), &["scheduled", "applied", "cancelled"]);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChangeListResponse {
+    pub changes: Vec<CountryPolicyChangeRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Country policy",
+    params(ListChangesQuery),
+    responses((status = 200, body = ChangeListResponse)),
+)]
+#[get("/api/v1/country-policy/changes")]
+pub async fn list_changes(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListChangesQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let changes = country_policy::list_changes(pool.get_ref(), query.status.as_deref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = changes.len();
+    Ok(HttpResponse::Ok().json(ChangeListResponse { changes, total }))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleChangeRequest {
+    #[schema(pattern = "^[A-Za-z]{2}$")]
+    pub country_code: String,
+    #[schema(example = "adequate_protection")]
+    pub new_status: String,
+    #[schema(format = DateTime)]
+    pub effective_at: String,
+    pub reason: Option<String>,
+    pub legal_reference: Option<String>,
+    pub created_by: Option<String>,
+}
+
+impl Validate for ScheduleChangeRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.country_code("countryCode", Some(self.country_code.trim()));
+        v.one_of("newStatus", Some(&self.new_status), COUNTRY_STATUSES);
+        v.rfc3339("effectiveAt", Some(&self.effective_at));
+    }
+}
+
+/// Schedule a country reclassification; it is applied and sealed in evidence once `effectiveAt` passes.
+#[utoipa::path(
+    tag = "Country policy",
+    request_body = ScheduleChangeRequest,
+    responses((status = 201, body = CountryPolicyChangeRow)),
+)]
+#[post("/api/v1/country-policy/changes")]
+pub async fn schedule_change(
+    pool: web::Data<PgPool>,
+    body: ValidJson<ScheduleChangeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let effective_at = chrono::DateTime::parse_from_rfc3339(&body.effective_at)
+        .map_err(|_| ApiError::invalid_field("effectiveAt", "must be an RFC 3339 timestamp"))?
+        .with_timezone(&chrono::Utc);
+
+    let params = ScheduleChangeParams {
+        country_code: body.country_code,
+        new_status: body.new_status,
+        effective_at,
+        reason: body.reason,
+        legal_reference: body.legal_reference,
+        created_by: Some(body.created_by.unwrap_or_else(|| "admin".into())),
+    };
+
+    let row = country_policy::schedule_change(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("SCHEDULE_FAILED", e))?;
+    Ok(HttpResponse::Created().json(row))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CancelChangeResponse {
+    pub success: bool,
+    pub id: String,
+    pub status: String,
+}
+
+#[utoipa::path(
+    tag = "Country policy",
+    params(("id" = Uuid, Path, description = "Scheduled change ID")),
+    responses((status = 200, body = CancelChangeResponse)),
+)]
+#[delete("/api/v1/country-policy/changes/{id}")]
+pub async fn cancel_change(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let cancelled = country_policy::cancel_change(pool.get_ref(), id)
+        .await
+        .map_err(|e| ApiError::internal("CANCEL_FAILED", e))?;
+    if !cancelled {
+        return Err(ApiError::NotFound("Scheduled change"));
+    }
+    Ok(HttpResponse::Ok().json(CancelChangeResponse {
+        success: true,
+        id: id.to_string(),
+        status: "cancelled".into(),
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UpcomingQuery {
+    /// Look-ahead window in days (default 90).
+    #[param(minimum = 0, maximum = 3650)]
+    pub days: Option<i64>,
+}
+
+impl Validate for UpcomingQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.range("days", self.days, 0, 3650);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UpcomingResponse {
+    pub days: i64,
+    pub changes: Vec<UpcomingChange>,
+    pub total: usize,
+}
+
+/// What will change in the next N days (default 90).
+#[utoipa::path(
+    tag = "Country policy",
+    params(UpcomingQuery),
+    responses((status = 200, body = UpcomingResponse)),
+)]
+#[get("/api/v1/country-policy/upcoming")]
+pub async fn upcoming_changes(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<UpcomingQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let days = query.days.unwrap_or(90);
+    let changes = country_policy::upcoming_changes(pool.get_ref(), days)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = changes.len();
+    Ok(HttpResponse::Ok().json(UpcomingResponse { days, changes, total }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApplyDueResponse {
+    pub applied: usize,
+    pub timestamp: String,
+}
+
+/// Apply due changes immediately instead of waiting for the scheduler tick.
+#[utoipa::path(
+    tag = "Country policy",
+    responses((status = 200, body = ApplyDueResponse)),
+)]
+#[post("/api/v1/country-policy/apply-due")]
+pub async fn apply_due(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let applied = country_policy::apply_due_changes(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("APPLY_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(ApplyDueResponse {
+        applied,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRequest {
+    #[schema(min_length = 1)]
+    pub source_name: String,
+    /// `adequate`, `blocked`, `scc_required` or `classifications`.
+    pub list_type: String,
+    /// `json` or `csv`.
+    pub format: String,
+    pub content: String,
+    pub created_by: Option<String>,
+}
+
+impl Validate for ImportRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("sourceName", &self.source_name);
+        v.one_of("listType", Some(&self.list_type), LIST_TYPES);
+        let format = self.format.to_lowercase();
+        v.one_of("format", Some(&format), &["json", "csv"]);
+        if LIST_TYPES.contains(&self.list_type.as_str()) && matches!(format.as_str(), "json" | "csv") {
+            match country_import::parse_list(&self.content, &format, &self.list_type) {
+                Ok((entries, errors)) if entries.is_empty() => {
+                    v.error("content", format!("no valid entries found ({} error(s))", errors.len()));
+                }
+                Ok(_) => {}
+                Err(e) => v.error("content", e),
+            }
+        }
+    }
+}
+
+/// Upload a JSON/CSV country list; the diff is applied only after its review item is approved.
+#[utoipa::path(
+    tag = "Country policy",
+    request_body = ImportRequest,
+    responses((status = 201, body = country_import::ImportSummary)),
+)]
+#[post("/api/v1/country-policy/imports")]
+pub async fn create_import(
+    pool: web::Data<PgPool>,
+    body: ValidJson<ImportRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let params = CreateImportParams {
+        source_name: body.source_name,
+        list_type: body.list_type,
+        format: body.format,
+        content: body.content,
+        created_by: Some(body.created_by.unwrap_or_else(|| "admin".into())),
+    };
+
+    let summary = country_import::create_import(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("IMPORT_FAILED", e))?;
+    Ok(HttpResponse::Created().json(summary))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportListResponse {
+    pub imports: Vec<CountryListImportRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Country policy",
+    responses((status = 200, body = ImportListResponse)),
+)]
+#[get("/api/v1/country-policy/imports")]
+pub async fn list_imports(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let imports = country_import::list_imports(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = imports.len();
+    Ok(HttpResponse::Ok().json(ImportListResponse { imports, total }))
+}
+
+#[utoipa::path(
+    tag = "Country policy",
+    params(("id" = Uuid, Path, description = "Import ID")),
+    responses((status = 200, body = CountryListImportRow)),
+)]
+#[get("/api/v1/country-policy/imports/{id}")]
+pub async fn get_import(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let row = country_import::get_import(pool.get_ref(), path.into_inner())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Import"))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_changes)
+       .service(schedule_change)
+       .service(cancel_change)
+       .service(upcoming_changes)
+       .service(apply_due)
+       .service(create_import)
+       .service(list_imports)
+       .service(get_import);
+}
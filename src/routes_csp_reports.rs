@@ -0,0 +1,169 @@
+use actix_web::dev::RequestHead;
+use actix_web::http::header::HeaderValue;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api_error::ApiError;
+use crate::csp_reports;
+use crate::ingest_queue;
+use crate::ip_country;
+use crate::log_formats::host_of;
+use crate::routes_shield::{IngestBatchAccepted, IngestLogEntry};
+use crate::validation::{self, ValidQuery, Validate, Validator};
+
+const REPORT_PATH: &str = "/api/v1/shield/csp-reports";
+/// Report batches are small; anything larger is not a browser.
+const MAX_REPORT_BYTES: usize = 256 * 1024;
+/// Skip reasons listed in a response; the count always covers every report.
+const MAX_REPORTED_SKIPS: usize = 100;
+
+/// Browsers post reports from the monitored websites' origins, which are not API clients: CORS lets
+/// the websites listed in `CSP_REPORT_WEBSITES` reach this one path.
+pub fn cors_allows(origin: &HeaderValue, head: &RequestHead) -> bool {
+    head.uri.path() == REPORT_PATH
+        && origin.to_str().ok().and_then(host_of).map(|host| csp_reports::website_allowed(&host)).unwrap_or(false)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct CspReportQuery {
+    /// Website tag recorded as the source system; defaults to the reporting document's host.
+    pub website: Option<String>,
+    /// Comma-separated categories the website's pages expose to third parties
+    /// (default `online_identifier,usage_data`).
+    #[serde(alias = "data_categories")]
+    pub data_categories: Option<String>,
+}
+
+impl Validate for CspReportQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.max_len("website", self.website.as_deref(), 255);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CspReportResponse {
+    /// CSP violations in the body.
+    pub reports: usize,
+    /// Third-party origins queued for evaluation.
+    pub transfers: usize,
+    /// Reporting API entries of other types.
+    pub ignored: usize,
+    /// Violations that are not third-party transfers (`inline`, first-party, unmonitored website, ...).
+    pub skipped: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<IngestBatchAccepted>,
+}
+
+/// Receive CSP violation reports, either a `report-uri` body (`application/csp-report`) or a Reporting
+/// API batch (`application/reports+json`, via `report-to`). Every third-party origin a page loaded or
+/// tried to load is queued as a transfer like `POST /api/v1/shield/ingest-logs`, tagged with the
+/// website as `sourceSystem`. The country comes from `EXT_AUTHZ_HOST_MAP`, else from the host's
+/// resolved address. Repeats of the same origin, directive and disposition within an hour are
+/// recorded once. Use a report-only policy to monitor without blocking. The receiver answers 404 until
+/// `CSP_REPORT_WEBSITES` lists a website, and each client address is limited to
+/// `CSP_REPORT_RATE_LIMIT` requests a minute.
+#[utoipa::path(
+    post,
+    path = "/api/v1/shield/csp-reports",
+    tag = "Sovereign Shield",
+    params(CspReportQuery),
+    request_body(content = String, content_type = "application/reports+json", description = "Reporting API array, or `{\"csp-report\": {...}}` as `application/csp-report`"),
+    responses(
+        (status = 202, body = CspReportResponse, description = "Third-party origins queued"),
+        (status = 200, body = CspReportResponse, description = "No report named a third-party origin"),
+        (status = 404, description = "No website is configured in `CSP_REPORT_WEBSITES`"),
+        (status = 429, description = "Rate limit for the client address exceeded; see `Retry-After`"),
+    ),
+)]
+pub async fn receive_reports(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: ValidQuery<CspReportQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    if !csp_reports::enabled() {
+        return Err(ApiError::NotFound("CSP report receiver"));
+    }
+    if let Some(client) = req.peer_addr() {
+        csp_reports::check_rate(client.ip()).map_err(ApiError::RateLimited)?;
+    }
+    let value: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| ApiError::bad_request("INVALID_JSON", format!("Invalid report body: {}", e)))?;
+    let (violations, ignored) = csp_reports::parse(&value).map_err(|e| ApiError::invalid_field("body", e))?;
+
+    let categories: Vec<String> = match query.data_categories.as_deref() {
+        Some(c) => c.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+        None => csp_reports::DEFAULT_CATEGORIES.iter().map(|c| c.to_string()).collect(),
+    };
+    let request_agent = req.headers().get("user-agent").and_then(|v| v.to_str().ok()).map(String::from);
+
+    let mut origins = Vec::with_capacity(violations.len());
+    let mut skipped = Vec::new();
+    for violation in &violations {
+        match csp_reports::origin(violation, query.website.as_deref(), &categories) {
+            Ok(mut origin) => {
+                if origin.entry.user_agent.is_none() {
+                    origin.entry.user_agent = request_agent.clone();
+                }
+                origins.push(origin);
+            }
+            Err(reason) => skipped.push(reason),
+        }
+    }
+
+    let unplaced: HashSet<String> = origins.iter()
+        .filter(|o| o.entry.destination_country_code.is_none() && o.entry.dest_ip.is_none())
+        .map(|o| o.host.clone())
+        .collect();
+    if !unplaced.is_empty() {
+        let resolved = ip_country::resolve_hosts(unplaced).await;
+        for origin in origins.iter_mut().filter(|o| o.entry.dest_ip.is_none()) {
+            origin.entry.dest_ip = resolved.get(&origin.host).cloned();
+        }
+    }
+
+    let mut entries: Vec<IngestLogEntry> = Vec::with_capacity(origins.len());
+    for origin in origins {
+        match validation::validate(&origin.entry) {
+            Ok(()) => entries.push(origin.entry),
+            Err(e) => skipped.push(e.body().fields.iter().map(|f| format!("{}: {}", f.field, f.message)).collect::<Vec<_>>().join("; ")),
+        }
+    }
+
+    let mut response = CspReportResponse {
+        reports: violations.len(),
+        transfers: entries.len(),
+        ignored,
+        skipped: skipped.into_iter().take(MAX_REPORTED_SKIPS).collect(),
+        batch: None,
+    };
+    if entries.is_empty() {
+        return Ok(HttpResponse::Ok().json(response));
+    }
+
+    let (batch, created) = ingest_queue::enqueue(pool.get_ref(), entries, None)
+        .await
+        .map_err(|e| ApiError::internal("INGEST_ENQUEUE_FAILED", e))?;
+    response.batch = Some(IngestBatchAccepted {
+        status_url: format!("/api/v1/shield/ingest-batches/{}", batch.id),
+        batch_id: batch.id,
+        total_entries: batch.total_entries,
+        status: batch.status,
+        replayed: !created,
+    });
+    Ok(HttpResponse::Accepted().json(response))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource(REPORT_PATH)
+            .app_data(web::PayloadConfig::new(MAX_REPORT_BYTES))
+            .route(web::post().to(receive_reports)),
+    );
+}
@@ -0,0 +1,155 @@
+use actix_web::{web, HttpResponse, get, post, delete};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::api_error::ApiError;
+use crate::data_categories::{self, DataCategoryRow, NormalisedCategories, UpsertCategoryParams};
+use crate::validation::{ValidJson, Validate, Validator};
+
+#[derive(Serialize, ToSchema)]
+pub struct CategoryListResponse {
+    pub categories: Vec<DataCategoryRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Data categories",
+    responses((status = 200, body = CategoryListResponse)),
+)]
+#[get("/api/v1/data-categories")]
+pub async fn list_categories(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let categories = data_categories::list_categories(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = categories.len();
+    Ok(HttpResponse::Ok().json(CategoryListResponse { categories, total }))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryRequest {
+    #[schema(min_length = 1, max_length = 100)]
+    pub code: String,
+    #[schema(min_length = 1, max_length = 255)]
+    pub label: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    /// `none`, `standard`, `high` or `special`; forced to `special` for Art. 9/10 data.
+    pub sensitivity_tier: Option<String>,
+    #[serde(default)]
+    pub special_category: bool,
+    #[serde(default)]
+    pub criminal_data: bool,
+    #[serde(default)]
+    pub anonymised: bool,
+}
+
+impl Validate for CategoryRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("code", &self.code);
+        v.max_len("code", Some(&self.code), 100);
+        v.required("label", &self.label);
+        v.max_len("label", Some(&self.label), 255);
+        v.one_of("sensitivityTier", self.sensitivity_tier.as_deref(), &["none", "standard", "high", "special"]);
+    }
+}
+
+/// Create or replace a canonical category (keyed by code).
+#[utoipa::path(
+    tag = "Data categories",
+    request_body = CategoryRequest,
+    responses((status = 200, body = DataCategoryRow)),
+)]
+#[post("/api/v1/data-categories")]
+pub async fn upsert_category(
+    pool: web::Data<PgPool>,
+    body: ValidJson<CategoryRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    // Art. 9 / Art. 10 categories are always the top tier regardless of what was submitted.
+    let sensitivity_tier = if body.special_category || body.criminal_data {
+        "special".to_string()
+    } else if body.anonymised {
+        "none".to_string()
+    } else {
+        body.sensitivity_tier.unwrap_or_else(|| "standard".into())
+    };
+
+    let params = UpsertCategoryParams {
+        code: body.code,
+        label: body.label,
+        description: body.description,
+        synonyms: body.synonyms,
+        sensitivity_tier,
+        special_category: body.special_category,
+        criminal_data: body.criminal_data,
+        anonymised: body.anonymised,
+    };
+
+    let row = data_categories::upsert_category(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("CATEGORY_SAVE_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeleteCategoryResponse {
+    pub success: bool,
+    pub code: String,
+}
+
+#[utoipa::path(
+    tag = "Data categories",
+    params(("code" = String, Path, description = "Category code")),
+    responses((status = 200, body = DeleteCategoryResponse)),
+)]
+#[delete("/api/v1/data-categories/{code}")]
+pub async fn delete_category(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let code = path.into_inner();
+    let deleted = data_categories::delete_category(pool.get_ref(), &code)
+        .await
+        .map_err(|e| ApiError::internal("DELETE_FAILED", e))?;
+    if !deleted {
+        return Err(ApiError::NotFound("Data category"));
+    }
+    Ok(HttpResponse::Ok().json(DeleteCategoryResponse { success: true, code }))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NormaliseRequest {
+    pub data_categories: Vec<String>,
+}
+
+impl Validate for NormaliseRequest {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+/// Preview how free-form category strings map onto the taxonomy.
+#[utoipa::path(
+    tag = "Data categories",
+    request_body = NormaliseRequest,
+    responses((status = 200, body = NormalisedCategories)),
+)]
+#[post("/api/v1/data-categories/normalise")]
+pub async fn normalise_categories(
+    pool: web::Data<PgPool>,
+    body: ValidJson<NormaliseRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let taxonomy = data_categories::load_taxonomy(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(taxonomy.normalise(&body.data_categories)))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_categories)
+       .service(normalise_categories)
+       .service(upsert_category)
+       .service(delete_category);
+}
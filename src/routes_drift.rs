@@ -0,0 +1,113 @@
+use actix_web::{web, HttpResponse, get, post};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::drift::{self, DriftReportRow};
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReportRequest {
+    #[schema(format = DateTime)]
+    pub since: Option<String>,
+    #[schema(format = DateTime)]
+    pub until: Option<String>,
+    pub requested_by: Option<String>,
+}
+
+impl Validate for DriftReportRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.rfc3339("since", self.since.as_deref());
+        v.rfc3339("until", self.until.as_deref());
+    }
+}
+
+fn parse_bound(value: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
+    value
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+/// Start a drift report; the replay runs in the background and the report is polled by ID.
+#[utoipa::path(
+    tag = "Drift",
+    request_body = DriftReportRequest,
+    responses((status = 202, body = DriftReportRow)),
+)]
+#[post("/api/v1/shield/drift-reports")]
+pub async fn create_report(
+    pool: web::Data<PgPool>,
+    body: ValidJson<DriftReportRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let since = parse_bound(body.since.as_deref());
+    let until = parse_bound(body.until.as_deref());
+    let requested_by = body.requested_by.as_deref().unwrap_or("admin");
+
+    let report = drift::start_report(pool.get_ref(), since, until, Some(requested_by))
+        .await
+        .map_err(|e| ApiError::internal("DRIFT_REPORT_FAILED", e))?;
+    drift::spawn_report(pool.get_ref().clone(), report.id);
+    Ok(HttpResponse::Accepted().json(report))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListReportsQuery {
+    #[param(minimum = 1, maximum = 200)]
+    pub limit: Option<i64>,
+}
+
+impl Validate for ListReportsQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.range("limit", self.limit, 1, 200);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DriftReportListResponse {
+    pub reports: Vec<DriftReportRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Drift",
+    params(ListReportsQuery),
+    responses((status = 200, body = DriftReportListResponse)),
+)]
+#[get("/api/v1/shield/drift-reports")]
+pub async fn list_reports(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListReportsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let reports = drift::list_reports(pool.get_ref(), query.limit.unwrap_or(20))
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = reports.len();
+    Ok(HttpResponse::Ok().json(DriftReportListResponse { reports, total }))
+}
+
+#[utoipa::path(
+    tag = "Drift",
+    params(("id" = Uuid, Path, description = "Drift report ID")),
+    responses((status = 200, body = DriftReportRow)),
+)]
+#[get("/api/v1/shield/drift-reports/{id}")]
+pub async fn get_report(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let report = drift::get_report(pool.get_ref(), path.into_inner())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Drift report"))?;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_report)
+       .service(list_reports)
+       .service(get_report);
+}
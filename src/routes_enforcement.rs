@@ -0,0 +1,206 @@
+use actix_web::{web, HttpRequest, HttpResponse, get, post};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::enforcement::{self, EnforcementArtifactRow, EnforcementDeploymentRow};
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+
+fn check_format(format: &str) -> Result<(), ApiError> {
+    if enforcement::FORMATS.contains(&format) {
+        Ok(())
+    } else {
+        Err(ApiError::invalid_field("format", format!("must be one of: {}", enforcement::FORMATS.join(", "))))
+    }
+}
+
+/// Artifact content as text, with its version and hash in headers; `If-None-Match` on the hash gives 304.
+fn content_response(req: &HttpRequest, artifact: &EnforcementArtifactRow, content: String) -> HttpResponse {
+    let etag = format!("\"{}\"", artifact.content_hash);
+    let unchanged = req.headers()
+        .get("if-none-match")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|tag| tag.trim() == etag))
+        .unwrap_or(false);
+    let mut response = if unchanged { HttpResponse::NotModified() } else { HttpResponse::Ok() };
+    response
+        .insert_header(("ETag", etag))
+        .insert_header(("X-Artifact-Id", artifact.id.to_string()))
+        .insert_header(("X-Artifact-Version", artifact.version.to_string()))
+        .insert_header(("X-Artifact-Sha256", artifact.content_hash.clone()));
+    if unchanged {
+        return response.finish();
+    }
+    let content_type = if artifact.format == "rpz" { "text/dns; charset=utf-8" } else { "text/plain; charset=utf-8" };
+    response.content_type(content_type).body(content)
+}
+
+/// Generate the enforcement artifact for a format (`nftables`, `ipset` or `rpz`) from the countries
+/// currently classified `blocked`: address blocks from the active IP-to-country dataset, or provider
+/// hosts that `EXT_AUTHZ_HOST_MAP` / `MAIL_PROVIDER_MAP` place in those countries. A new version is
+/// stored and its SHA-256 sealed into evidence; when nothing it is built from has changed, the latest
+/// version is returned with 200 instead.
+#[utoipa::path(
+    tag = "Network enforcement",
+    params(("format" = String, Path, description = "`nftables`, `ipset` or `rpz`")),
+    responses(
+        (status = 201, body = EnforcementArtifactRow, description = "New version generated"),
+        (status = 200, body = EnforcementArtifactRow, description = "Policy unchanged; latest version"),
+    ),
+)]
+#[post("/api/v1/shield/enforcement/{format}")]
+pub async fn generate_artifact(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let format = path.into_inner().to_lowercase();
+    check_format(&format)?;
+    let (artifact, created) = enforcement::generate(pool.get_ref(), &format, Some("api"))
+        .await
+        .map_err(|e| ApiError::internal("ENFORCEMENT_GENERATION_FAILED", e))?;
+    Ok(if created { HttpResponse::Created().json(artifact) } else { HttpResponse::Ok().json(artifact) })
+}
+
+/// Content of the latest version of a format, for firewalls and resolvers to pull. Send the previous
+/// `ETag` as `If-None-Match` to get 304 while it is unchanged.
+#[utoipa::path(
+    tag = "Network enforcement",
+    params(("format" = String, Path, description = "`nftables`, `ipset` or `rpz`")),
+    responses((status = 200, body = String, content_type = "text/plain", description = "Artifact content; `X-Artifact-Version` and `X-Artifact-Sha256` headers")),
+)]
+#[get("/api/v1/shield/enforcement/{format}/latest")]
+pub async fn latest_artifact(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let format = path.into_inner().to_lowercase();
+    check_format(&format)?;
+    let (artifact, content) = enforcement::get(pool.get_ref(), None, Some(&format))
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Enforcement artifact"))?;
+    Ok(content_response(&req, &artifact, content))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListArtifactsQuery {
+    /// `nftables`, `ipset` or `rpz`
+    pub format: Option<String>,
+    /// Default 50, max 500.
+    pub limit: Option<i64>,
+}
+
+impl Validate for ListArtifactsQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("format", self.format.as_deref(), enforcement::FORMATS);
+        v.range("limit", self.limit, 1, 500);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<EnforcementArtifactRow>,
+    pub total: usize,
+}
+
+/// Generated versions, newest first.
+#[utoipa::path(
+    tag = "Network enforcement",
+    params(ListArtifactsQuery),
+    responses((status = 200, body = ArtifactsResponse)),
+)]
+#[get("/api/v1/shield/enforcement/artifacts")]
+pub async fn list_artifacts(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListArtifactsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let artifacts = enforcement::list(pool.get_ref(), query.format.as_deref(), query.limit.unwrap_or(50))
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = artifacts.len();
+    Ok(HttpResponse::Ok().json(ArtifactsResponse { artifacts, total }))
+}
+
+/// Content of one version.
+#[utoipa::path(
+    tag = "Network enforcement",
+    params(("id" = Uuid, Path, description = "Artifact ID")),
+    responses((status = 200, body = String, content_type = "text/plain", description = "Artifact content; `X-Artifact-Version` and `X-Artifact-Sha256` headers")),
+)]
+#[get("/api/v1/shield/enforcement/artifacts/{id}")]
+pub async fn get_artifact(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let (artifact, content) = enforcement::get(pool.get_ref(), Some(path.into_inner()), None)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Enforcement artifact"))?;
+    Ok(content_response(&req, &artifact, content))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordDeploymentRequest {
+    /// Firewall, gateway or resolver that loaded the artifact.
+    #[schema(min_length = 1)]
+    pub target: String,
+    /// SHA-256 of the content as loaded; must match the artifact.
+    #[schema(min_length = 64, max_length = 64)]
+    pub sha256: String,
+}
+
+impl Validate for RecordDeploymentRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("target", &self.target);
+        v.max_len("target", Some(&self.target), 255);
+        if self.sha256.len() != 64 || !self.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            v.error("sha256", "must be a hex SHA-256 digest");
+        }
+    }
+}
+
+/// Record that a firewall or resolver loaded a version. The reported hash must match the artifact's
+/// (409 `HASH_MISMATCH` otherwise), so the evidence shows the exact rules in force; the event is linked
+/// to the generation event.
+#[utoipa::path(
+    tag = "Network enforcement",
+    params(("id" = Uuid, Path, description = "Artifact ID")),
+    request_body = RecordDeploymentRequest,
+    responses((status = 201, body = EnforcementDeploymentRow)),
+)]
+#[post("/api/v1/shield/enforcement/artifacts/{id}/deployments")]
+pub async fn record_deployment(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    body: ValidJson<RecordDeploymentRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let (artifact, _) = enforcement::get(pool.get_ref(), Some(path.into_inner()), None)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Enforcement artifact"))?;
+    if !body.sha256.eq_ignore_ascii_case(&artifact.content_hash) {
+        return Err(ApiError::conflict(
+            "HASH_MISMATCH",
+            format!("Loaded content does not match {} version {} (sha256 {})", artifact.format, artifact.version, artifact.content_hash),
+        ));
+    }
+    let deployment = enforcement::record_deployment(pool.get_ref(), &artifact, body.target.trim())
+        .await
+        .map_err(|e| ApiError::internal("DEPLOYMENT_RECORD_FAILED", e))?;
+    Ok(HttpResponse::Created().json(deployment))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // `artifacts/...` before `{format}/latest`, which would otherwise capture it
+    cfg.service(list_artifacts)
+       .service(get_artifact)
+       .service(record_deployment)
+       .service(generate_artifact)
+       .service(latest_artifact);
+}
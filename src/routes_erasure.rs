@@ -1,48 +1,95 @@
 use actix_web::{web, HttpResponse, post};
 use chrono::Utc;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
+use crate::api_error::ApiError;
 use crate::crypto_shredder;
 use crate::evidence::{self, CreateEventParams};
+use crate::validation::{ValidJson, Validate, Validator};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ErasureRequest {
+    #[schema(min_length = 1)]
     pub request_id: String,
+    #[schema(min_length = 1)]
     pub user_id: String,
     pub grounds: String,
+    /// Must be exactly `ERASE <userId>`.
     pub confirmation: String,
 }
 
+impl Validate for ErasureRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("requestId", &self.request_id);
+        v.required("userId", &self.user_id);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasureSummary {
+    pub total_records: i64,
+    pub total_size_mb: f64,
+    pub crypto_log_id: String,
+    pub evidence_sealed: bool,
+    pub integrity_level: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasureCertificate {
+    pub id: String,
+    pub issued_at: String,
+    pub issued_by: String,
+    pub compliance: String,
+    pub verification: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErasureResponse {
+    pub success: bool,
+    pub request_id: String,
+    pub user_id: String,
+    pub executed_at: String,
+    pub executed_by: String,
+    pub grounds: String,
+    pub shredded_items: Vec<serde_json::Value>,
+    pub summary: ErasureSummary,
+    pub certificate: ErasureCertificate,
+}
+
+/// Crypto-shred a data subject's records (GDPR Art. 17) and seal the erasure in evidence.
+#[utoipa::path(
+    tag = "GDPR rights",
+    request_body = ErasureRequest,
+    responses((status = 200, body = ErasureResponse)),
+)]
 #[post("/api/v1/lenses/gdpr-rights/erasure/execute")]
 pub async fn execute_erasure(
     pool: web::Data<PgPool>,
-    body: web::Json<ErasureRequest>,
-) -> HttpResponse {
+    body: ValidJson<ErasureRequest>,
+) -> Result<HttpResponse, ApiError> {
     let expected_confirmation = format!("ERASE {}", body.user_id);
     if body.confirmation != expected_confirmation {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "INVALID_CONFIRMATION",
-            "message": format!("Confirmation must be 'ERASE {}'", body.user_id),
-        }));
+        return Err(ApiError::bad_request(
+            "INVALID_CONFIRMATION",
+            format!("Confirmation must be 'ERASE {}'", body.user_id),
+        ));
     }
 
     let (crypto_log_id, shredded_items, total_records, total_size) =
-        match crypto_shredder::execute_erasure(
+        crypto_shredder::execute_erasure(
             pool.get_ref(),
             &body.user_id,
             &body.request_id,
             &body.grounds,
-        ).await {
-            Ok(result) => result,
-            Err(e) => {
-                return HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "ERASURE_FAILED",
-                    "message": e,
-                }));
-            }
-        };
+        )
+        .await
+        .map_err(|e| ApiError::internal("ERASURE_FAILED", e))?;
 
     let now = Utc::now();
     let params = CreateEventParams {
@@ -65,39 +112,39 @@ pub async fn execute_erasure(
         causation_id: None,
         source_ip: None,
         source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
     };
 
-    if let Err(e) = evidence::create_event(pool.get_ref(), params).await {
-        return HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "EVIDENCE_LOGGING_FAILED",
-            "message": format!("Failed to log erasure to evidence graph: {}", e),
-        }));
-    }
+    evidence::create_event(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("EVIDENCE_LOGGING_FAILED", e))?;
 
     let cert_id = format!("CERT-{}-{}", body.request_id, now.format("%Y%m%d%H%M%S"));
+    let body = body.into_inner();
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "success": true,
-        "requestId": body.request_id,
-        "userId": body.user_id,
-        "executedAt": now.to_rfc3339(),
-        "executedBy": "admin",
-        "grounds": body.grounds,
-        "shreddedItems": shredded_items,
-        "summary": {
-            "totalRecords": total_records,
-            "totalSizeMb": total_size,
-            "cryptoLogId": crypto_log_id,
-            "evidenceSealed": true,
-            "integrityLevel": "L4"
+    Ok(HttpResponse::Ok().json(ErasureResponse {
+        success: true,
+        request_id: body.request_id,
+        user_id: body.user_id,
+        executed_at: now.to_rfc3339(),
+        executed_by: "admin".into(),
+        grounds: body.grounds,
+        shredded_items,
+        summary: ErasureSummary {
+            total_records,
+            total_size_mb: total_size,
+            crypto_log_id: crypto_log_id.clone(),
+            evidence_sealed: true,
+            integrity_level: "L4".into(),
+        },
+        certificate: ErasureCertificate {
+            id: cert_id,
+            issued_at: now.to_rfc3339(),
+            issued_by: "Veridion API Crypto-Shredder".into(),
+            compliance: "GDPR Article 17 - Right to Erasure".into(),
+            verification: format!("Evidence sealed with L4 integrity: {}", crypto_log_id),
         },
-        "certificate": {
-            "id": cert_id,
-            "issuedAt": now.to_rfc3339(),
-            "issuedBy": "Veridion API Crypto-Shredder",
-            "compliance": "GDPR Article 17 - Right to Erasure",
-            "verification": format!("Evidence sealed with L4 integrity: {}", crypto_log_id),
-        }
     }))
 }
 
@@ -1,55 +1,100 @@
 use actix_web::{web, HttpResponse, get, post};
 use sqlx::PgPool;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
+use crate::api_error::ApiError;
 use crate::evidence;
+use crate::models::EvidenceEventResponse;
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
 
-#[derive(Deserialize)]
+const SEVERITIES: &[&str] = &["L1", "L2", "L3", "L4"];
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListEventsQuery {
+    #[param(pattern = "^L[1-4]$")]
     pub severity: Option<String>,
     pub event_type: Option<String>,
     pub search: Option<String>,
     pub destination_country: Option<String>,
+    /// RFC 3339 bounds on when events occurred at the source.
+    #[param(format = DateTime)]
+    pub from: Option<String>,
+    #[param(format = DateTime)]
+    pub to: Option<String>,
+    #[param(minimum = 1, maximum = 10000)]
     pub limit: Option<i64>,
+    #[param(minimum = 0)]
     pub offset: Option<i64>,
 }
 
+impl Validate for ListEventsQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("severity", self.severity.as_deref(), SEVERITIES);
+        v.rfc3339("from", self.from.as_deref());
+        v.rfc3339("to", self.to.as_deref());
+        v.range("limit", self.limit, 1, 10000);
+        v.range("offset", self.offset, 0, i64::MAX);
+    }
+}
+
+fn parse_bound(value: Option<&str>) -> Option<chrono::DateTime<chrono::Utc>> {
+    value
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventListResponse {
+    pub events: Vec<EvidenceEventResponse>,
+    pub total_count: i64,
+    pub merkle_roots: i64,
+}
+
+/// Evidence events, newest first by when they occurred at the source.
+#[utoipa::path(
+    tag = "Evidence",
+    params(ListEventsQuery),
+    responses((status = 200, body = EventListResponse)),
+)]
 #[get("/api/v1/evidence/events")]
 pub async fn list_events(
     pool: web::Data<PgPool>,
-    query: web::Query<ListEventsQuery>,
-) -> HttpResponse {
-    let limit = query.limit.unwrap_or(50).min(10000);
-    let offset = query.offset.unwrap_or(0).max(0);
-
-    match evidence::list_events(
-        pool.get_ref(),
-        query.severity.as_deref(),
-        query.event_type.as_deref(),
-        query.search.as_deref(),
-        query.destination_country.as_deref(),
-        limit,
-        offset,
-    ).await {
-        Ok((events, total)) => {
-            let merkle_roots = evidence::count_sealed_chain_roots(pool.get_ref()).await.unwrap_or(0);
-            HttpResponse::Ok().json(serde_json::json!({
-                "events": events,
-                "totalCount": total,
-                "merkleRoots": merkle_roots,
-            }))
-        }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    query: ValidQuery<ListEventsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    let filters = evidence::EventFilters {
+        severity: query.severity.as_deref(),
+        event_type: query.event_type.as_deref(),
+        search: query.search.as_deref(),
+        destination_country: query.destination_country.as_deref(),
+        from: parse_bound(query.from.as_deref()),
+        to: parse_bound(query.to.as_deref()),
+    };
+
+    let (events, total) = evidence::list_events(pool.get_ref(), &filters, limit, offset)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let merkle_roots = evidence::count_sealed_chain_roots(pool.get_ref()).await.unwrap_or(0);
+    Ok(HttpResponse::Ok().json(EventListResponse {
+        events,
+        total_count: total,
+        merkle_roots,
+    }))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateEventBody {
+    #[schema(min_length = 1, max_length = 100)]
     pub event_type: String,
+    #[schema(pattern = "^L[1-4]$")]
     pub severity: String,
+    #[schema(min_length = 1, max_length = 255)]
     pub source_system: String,
     pub regulatory_tags: Option<Vec<String>>,
     pub articles: Option<Vec<String>>,
@@ -60,63 +105,110 @@ pub struct CreateEventBody {
     pub source_user_agent: Option<String>,
 }
 
+impl Validate for CreateEventBody {
+    fn validate(&self, v: &mut Validator) {
+        v.required("eventType", &self.event_type);
+        v.max_len("eventType", Some(&self.event_type), 100);
+        v.one_of("severity", Some(&self.severity), SEVERITIES);
+        v.required("sourceSystem", &self.source_system);
+        v.max_len("sourceSystem", Some(&self.source_system), 255);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedEventResponse {
+    pub event_id: String,
+    pub sequence_number: i64,
+    pub payload_hash: String,
+    pub previous_hash: String,
+    pub created_at: String,
+}
+
+/// Append an event to its source system's hash chain.
+#[utoipa::path(
+    tag = "Evidence",
+    request_body = CreateEventBody,
+    responses((status = 201, body = CreatedEventResponse)),
+)]
 #[post("/api/v1/evidence/events")]
 pub async fn create_event(
     pool: web::Data<PgPool>,
-    body: web::Json<CreateEventBody>,
-) -> HttpResponse {
+    body: ValidJson<CreateEventBody>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
     let params = evidence::CreateEventParams {
-        event_type: body.event_type.clone(),
-        severity: body.severity.clone(),
-        source_system: body.source_system.clone(),
-        regulatory_tags: body.regulatory_tags.clone().unwrap_or_default(),
-        articles: body.articles.clone().unwrap_or_default(),
-        payload: body.payload.clone(),
-        correlation_id: body.correlation_id.clone(),
-        causation_id: body.causation_id.clone(),
-        source_ip: body.source_ip.clone(),
-        source_user_agent: body.source_user_agent.clone(),
+        event_type: body.event_type,
+        severity: body.severity,
+        source_system: body.source_system,
+        regulatory_tags: body.regulatory_tags.unwrap_or_default(),
+        articles: body.articles.unwrap_or_default(),
+        payload: body.payload,
+        correlation_id: body.correlation_id,
+        causation_id: body.causation_id,
+        source_ip: body.source_ip,
+        source_user_agent: body.source_user_agent,
+        occurred_at: None,
+        idempotency_key: None,
     };
 
-    match evidence::create_event(pool.get_ref(), params).await {
-        Ok(row) => HttpResponse::Created().json(serde_json::json!({
-            "eventId": row.event_id,
-            "sequenceNumber": row.sequence_number,
-            "payloadHash": row.payload_hash,
-            "previousHash": row.previous_hash,
-            "createdAt": row.created_at.to_rfc3339(),
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    let row = evidence::create_event(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("EVIDENCE_CREATION_FAILED", e))?;
+    Ok(HttpResponse::Created().json(CreatedEventResponse {
+        event_id: row.event_id,
+        sequence_number: row.sequence_number,
+        payload_hash: row.payload_hash,
+        previous_hash: row.previous_hash,
+        created_at: row.created_at.to_rfc3339(),
+    }))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct VerifyBody {
+    /// Defaults to `sovereign-shield`.
     #[serde(alias = "source_system")]
     pub source_system: Option<String>,
 }
 
+impl Validate for VerifyBody {
+    fn validate(&self, v: &mut Validator) {
+        v.max_len("sourceSystem", self.source_system.as_deref(), 255);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyIntegrityResponse {
+    pub verified: bool,
+    pub source_system: String,
+    pub timestamp: String,
+    pub message: String,
+}
+
+/// Recompute one source system's hash chain and report the first break, if any.
+#[utoipa::path(
+    tag = "Evidence",
+    request_body = VerifyBody,
+    responses((status = 200, body = VerifyIntegrityResponse)),
+)]
 #[post("/api/v1/evidence/verify-integrity")]
 pub async fn verify_integrity(
     pool: web::Data<PgPool>,
-    body: web::Json<VerifyBody>,
-) -> HttpResponse {
+    body: ValidJson<VerifyBody>,
+) -> Result<HttpResponse, ApiError> {
     let source = body.source_system.as_deref().unwrap_or("sovereign-shield");
 
-    match evidence::verify_chain_integrity(pool.get_ref(), source).await {
-        Ok((verified, message)) => HttpResponse::Ok().json(serde_json::json!({
-            "verified": verified,
-            "sourceSystem": source,
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "message": message,
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    let (verified, message) = evidence::verify_chain_integrity(pool.get_ref(), source)
+        .await
+        .map_err(|e| ApiError::internal("VERIFICATION_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(VerifyIntegrityResponse {
+        verified,
+        source_system: source.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        message,
+    }))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -0,0 +1,150 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+use crate::api_error::ErrorBody;
+use crate::host_map;
+use crate::ip_country;
+use crate::routes_shield::evaluate_and_record;
+use crate::shield::TransferContext;
+
+/// Envoy `ext_authz` HTTP service contract: Envoy forwards the original request (method, headers,
+/// path appended to `path_prefix`) here; a 200 lets it through and any other status is returned
+/// to the caller as the denial. Configure Envoy with `path_prefix: /api/v1/shield/ext-authz`
+/// and add the `x-shield-*` headers to `allowed_upstream_headers` / `allowed_client_headers`.
+const PATH_PREFIX: &str = "/api/v1/shield/ext-authz";
+
+fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).ok().filter(|v| !v.trim().is_empty()).unwrap_or_else(|| default.to_string())
+}
+
+fn header(req: &HttpRequest, name: &str) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Destination host of the original request, without port.
+fn request_host(req: &HttpRequest) -> Option<String> {
+    let host = header(req, "x-forwarded-host").or_else(|| header(req, "host"))?;
+    let host = host.split(',').next().unwrap_or("").trim();
+    let host = match host.rsplit_once(':') {
+        Some((h, port)) if port.chars().all(|c| c.is_ascii_digit()) => h,
+        _ => host,
+    };
+    Some(host.trim_end_matches('.').to_lowercase())
+}
+
+/// Map the forwarded request onto a transfer. Explicit headers win over the host map;
+/// the partner defaults to the destination host.
+fn context_from_request(req: &HttpRequest, path: &str) -> TransferContext {
+    let host = request_host(req);
+    let mapped = host.as_deref().and_then(host_map::lookup_env);
+
+    let country = header(req, &env_or("EXT_AUTHZ_COUNTRY_HEADER", "x-destination-country"))
+        .map(|c| c.to_uppercase())
+        .or_else(|| mapped.as_ref().map(|(c, _)| c.clone()));
+    let partner = header(req, &env_or("EXT_AUTHZ_PARTNER_HEADER", "x-partner-name"))
+        .or_else(|| mapped.as_ref().and_then(|(_, p)| p.clone()))
+        .or_else(|| host.clone());
+    let categories = header(req, &env_or("EXT_AUTHZ_CATEGORIES_HEADER", "x-data-categories")).map(|v| {
+        v.split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let source_ip = header(req, "x-forwarded-for")
+        .and_then(|v| v.split(',').next().map(|ip| ip.trim().to_string()))
+        .or_else(|| header(req, "x-envoy-external-address"));
+    let request_path = match req.query_string() {
+        "" => path.to_string(),
+        q => format!("{}?{}", path, q),
+    };
+
+    TransferContext {
+        destination_country_code: country,
+        destination_country: None,
+        data_categories: categories,
+        partner_name: partner,
+        source_ip,
+        dest_ip: host.filter(|h| h.parse::<std::net::IpAddr>().is_ok()),
+        data_size: header(req, "content-length").and_then(|v| v.parse().ok()),
+        protocol: Some(header(req, "x-forwarded-proto").unwrap_or_else(|| "http".into())),
+        user_agent: header(req, "user-agent"),
+        request_path: Some(request_path),
+        source_system: None,
+        purpose: None,
+    }
+}
+
+/// Header values must be visible ASCII; reasons contain dashes and country names that may not be.
+fn header_safe(value: &str) -> String {
+    value.chars().map(|c| if c.is_ascii_graphic() || c == ' ' { c } else { '-' }).collect()
+}
+
+/// Inline enforcement for Envoy ext_authz (or any HTTP client). Any method, any path under the prefix.
+/// REVIEW decisions are denied unless `EXT_AUTHZ_REVIEW_ACTION=allow`.
+#[utoipa::path(
+    method(get, post, put, patch, delete, head),
+    path = "/api/v1/shield/ext-authz/{tail}",
+    tag = "Sovereign Shield",
+    params(
+        ("tail" = String, Path, description = "Original request path"),
+        ("x-destination-country" = Option<String>, Header, description = "Destination country (ISO 3166-1 alpha-2)"),
+        ("x-partner-name" = Option<String>, Header, description = "Receiving partner; defaults to the host"),
+        ("x-data-categories" = Option<String>, Header, description = "Comma-separated data categories"),
+        ("x-request-id" = Option<String>, Header, description = "Retries with the same ID replay the first decision"),
+    ),
+    responses(
+        (status = 200, description = "Allowed; decision in the `x-shield-*` headers"),
+        (status = 403, body = veridion_client::models::EvaluateResponse, description = "Denied"),
+        (status = 503, body = ErrorBody, description = "Shield could not record the decision"),
+    ),
+)]
+pub async fn check(req: HttpRequest, pool: web::Data<PgPool>) -> HttpResponse {
+    let path = req.path().strip_prefix(PATH_PREFIX).unwrap_or("");
+    let path = if path.is_empty() { "/" } else { path };
+    let mut ctx = context_from_request(&req, path);
+    // Without a header or host map entry the country comes from the address the host resolves to
+    if ctx.destination_country_code.is_none() && ctx.dest_ip.is_none() {
+        if let Some(host) = request_host(&req) {
+            ctx.dest_ip = ip_country::resolve_hosts(HashSet::from([host.clone()])).await.remove(&host);
+        }
+    }
+    // Envoy retries the check with the same request ID; replay rather than record twice
+    let idempotency_key = header(&req, "x-request-id").map(|id| format!("ext-authz:{}", id));
+
+    let outcome = match evaluate_and_record(pool.get_ref(), ctx, idempotency_key).await {
+        Ok(o) => o,
+        Err(e) => {
+            return HttpResponse::ServiceUnavailable()
+                .insert_header(("x-shield-decision", "ERROR"))
+                .json(e.body());
+        }
+    };
+
+    let allow_review = env_or("EXT_AUTHZ_REVIEW_ACTION", "deny").eq_ignore_ascii_case("allow");
+    let allowed = outcome.decision == "ALLOW" || (outcome.decision == "REVIEW" && allow_review);
+
+    let mut response = if allowed { HttpResponse::Ok() } else { HttpResponse::Forbidden() };
+    response
+        .insert_header(("x-shield-decision", outcome.decision.as_str()))
+        .insert_header(("x-shield-evidence-id", outcome.evidence_id.as_str()))
+        .insert_header(("x-shield-reason", header_safe(&outcome.reason)));
+    if let Some(review_id) = &outcome.review_id {
+        response.insert_header(("x-shield-review-id", review_id.as_str()));
+    }
+
+    if allowed {
+        response.finish()
+    } else {
+        response.json(outcome.to_response())
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource(PATH_PREFIX).route(web::route().to(check)))
+       .service(web::resource(format!("{}/{{tail:.*}}", PATH_PREFIX)).route(web::route().to(check)));
+}
@@ -0,0 +1,329 @@
+use actix_web::{web, HttpResponse, delete, get, post};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::data_categories;
+use crate::flow_register::{self, DeclareFlowParams, DeclaredFlowRow, UndeclaredFlowRow};
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+
+const RESOLVE_ACTIONS: &[&str] = &["declare", "dismiss"];
+
+fn trimmed(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(String::from)
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclareFlowRequest {
+    #[schema(min_length = 1)]
+    pub name: String,
+    /// At least one of `sourceSystem`, `partnerName` and `destinationCountryCode`; unset fields match any value.
+    pub source_system: Option<String>,
+    pub partner_name: Option<String>,
+    /// ISO 3166-1 alpha-2.
+    pub destination_country_code: Option<String>,
+    /// Categories the flow may carry, normalised against the taxonomy; empty allows any.
+    #[serde(default)]
+    pub data_categories: Vec<String>,
+    #[schema(min_length = 1)]
+    pub purpose: String,
+    pub owner: Option<String>,
+    pub created_by: Option<String>,
+}
+
+impl Validate for DeclareFlowRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.max_len("name", Some(&self.name), 255);
+        v.required("purpose", &self.purpose);
+        v.max_len("sourceSystem", self.source_system.as_deref(), 255);
+        v.max_len("partnerName", self.partner_name.as_deref(), 500);
+        v.country_code("destinationCountryCode", self.destination_country_code.as_deref());
+        v.max_len("owner", self.owner.as_deref(), 255);
+        v.max_len("createdBy", self.created_by.as_deref(), 255);
+        if trimmed(&self.source_system).is_none()
+            && trimmed(&self.partner_name).is_none()
+            && trimmed(&self.destination_country_code).is_none()
+        {
+            v.error("sourceSystem", "at least one of sourceSystem, partnerName or destinationCountryCode is required");
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclareFlowResponse {
+    pub flow: DeclaredFlowRow,
+    /// Open undeclared groups the declaration now covers, closed as `declared`.
+    pub closed_undeclared_flows: Vec<Uuid>,
+}
+
+/// Declare an expected transfer. Once any flow is declared, evaluated and ingested transfers no active
+/// declaration covers are marked `UNDECLARED_FLOW` (whatever the policy decision) and grouped for
+/// triage; open groups this declaration covers are closed.
+#[utoipa::path(
+    tag = "Flow register",
+    request_body = DeclareFlowRequest,
+    responses((status = 201, body = DeclareFlowResponse)),
+)]
+#[post("/api/v1/flow-register/flows")]
+pub async fn declare_flow(
+    pool: web::Data<PgPool>,
+    body: ValidJson<DeclareFlowRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    let taxonomy = data_categories::load_taxonomy(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let (flow, closed_undeclared_flows) = flow_register::declare(pool.get_ref(), DeclareFlowParams {
+        name: body.name.trim().to_string(),
+        source_system: trimmed(&body.source_system),
+        partner_name: trimmed(&body.partner_name),
+        destination_country_code: trimmed(&body.destination_country_code),
+        data_categories: taxonomy.normalise(&body.data_categories).categories,
+        purpose: body.purpose.trim().to_string(),
+        owner: trimmed(&body.owner),
+        created_by: trimmed(&body.created_by),
+        resolution_note: None,
+    })
+    .await
+    .map_err(|e| ApiError::internal("FLOW_DECLARE_FAILED", e))?;
+    Ok(HttpResponse::Created().json(DeclareFlowResponse { flow, closed_undeclared_flows }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListFlowsQuery {
+    /// Only active (`true`) or retired (`false`) declarations.
+    pub active: Option<bool>,
+}
+
+impl Validate for ListFlowsQuery {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeclaredFlowsResponse {
+    pub flows: Vec<DeclaredFlowRow>,
+    pub total: usize,
+}
+
+/// Declared flows, newest first.
+#[utoipa::path(
+    tag = "Flow register",
+    params(ListFlowsQuery),
+    responses((status = 200, body = DeclaredFlowsResponse)),
+)]
+#[get("/api/v1/flow-register/flows")]
+pub async fn list_flows(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListFlowsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let flows = flow_register::list_declared(pool.get_ref(), query.active)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = flows.len();
+    Ok(HttpResponse::Ok().json(DeclaredFlowsResponse { flows, total }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct RetireFlowQuery {
+    pub retired_by: Option<String>,
+}
+
+impl Validate for RetireFlowQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.max_len("retiredBy", self.retired_by.as_deref(), 255);
+    }
+}
+
+/// Retire a declaration. Transfers it covered are undeclared again from the next ingest on.
+#[utoipa::path(
+    tag = "Flow register",
+    params(("id" = Uuid, Path, description = "Declared flow ID"), RetireFlowQuery),
+    responses((status = 200, body = DeclaredFlowRow)),
+)]
+#[delete("/api/v1/flow-register/flows/{id}")]
+pub async fn retire_flow(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    query: ValidQuery<RetireFlowQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let flow = flow_register::retire(pool.get_ref(), path.into_inner(), query.retired_by.as_deref())
+        .await
+        .map_err(|e| ApiError::internal("FLOW_RETIRE_FAILED", e))?
+        .ok_or(ApiError::NotFound("Active declared flow"))?;
+    Ok(HttpResponse::Ok().json(flow))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListUndeclaredQuery {
+    /// `open` (default), `declared` or `dismissed`.
+    pub status: Option<String>,
+    /// Default 100, max 1000.
+    pub limit: Option<i64>,
+}
+
+impl Validate for ListUndeclaredQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("status", self.status.as_deref(), flow_register::UNDECLARED_STATUSES);
+        v.range("limit", self.limit, 1, 1000);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UndeclaredFlowsResponse {
+    pub groups: Vec<UndeclaredFlowRow>,
+    pub total: usize,
+}
+
+/// Undeclared transfers grouped by source system, partner, destination and categories, most recently
+/// seen first: the DPO's triage queue.
+#[utoipa::path(
+    tag = "Flow register",
+    params(ListUndeclaredQuery),
+    responses((status = 200, body = UndeclaredFlowsResponse)),
+)]
+#[get("/api/v1/flow-register/undeclared")]
+pub async fn list_undeclared(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListUndeclaredQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let groups = flow_register::list_undeclared(
+        pool.get_ref(),
+        Some(query.status.as_deref().unwrap_or("open")),
+        query.limit.unwrap_or(100),
+    )
+    .await
+    .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = groups.len();
+    Ok(HttpResponse::Ok().json(UndeclaredFlowsResponse { groups, total }))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveUndeclaredRequest {
+    /// `declare` or `dismiss`.
+    pub action: String,
+    /// Existing declaration covering the group. With `declare` and no ID, one is created from the
+    /// group's fields and `purpose`.
+    pub declared_flow_id: Option<Uuid>,
+    /// Name of the created declaration (defaults to source and destination).
+    pub name: Option<String>,
+    pub purpose: Option<String>,
+    pub owner: Option<String>,
+    pub note: Option<String>,
+    pub resolved_by: Option<String>,
+}
+
+impl Validate for ResolveUndeclaredRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("action", &self.action);
+        v.one_of("action", Some(&self.action), RESOLVE_ACTIONS);
+        v.max_len("name", self.name.as_deref(), 255);
+        v.max_len("owner", self.owner.as_deref(), 255);
+        v.max_len("resolvedBy", self.resolved_by.as_deref(), 255);
+        if self.action == "declare" && self.declared_flow_id.is_none() && trimmed(&self.purpose).is_none() {
+            v.error("purpose", "is required to declare a new flow");
+        }
+        if self.action == "dismiss" && self.declared_flow_id.is_some() {
+            v.error("declaredFlowId", "only applies to action declare");
+        }
+    }
+}
+
+/// Triage an open group: `declare` links it to a declaration covering it (409 `FLOW_DOES_NOT_COVER`
+/// otherwise) or creates one from the group; `dismiss` closes it while later transfers keep counting.
+/// A group already resolved gives 409 `ALREADY_RESOLVED`.
+#[utoipa::path(
+    tag = "Flow register",
+    params(("id" = Uuid, Path, description = "Undeclared flow group ID")),
+    request_body = ResolveUndeclaredRequest,
+    responses((status = 200, body = UndeclaredFlowRow)),
+)]
+#[post("/api/v1/flow-register/undeclared/{id}/resolve")]
+pub async fn resolve_undeclared(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    body: ValidJson<ResolveUndeclaredRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let body = body.into_inner();
+    let group = flow_register::get_undeclared(pool.get_ref(), id)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Undeclared flow"))?;
+    let already_resolved = || ApiError::conflict("ALREADY_RESOLVED", format!("Undeclared flow is already {}", group.status));
+    if group.status != "open" {
+        return Err(already_resolved());
+    }
+    let resolved_by = trimmed(&body.resolved_by);
+    let note = trimmed(&body.note);
+
+    let declared_flow_id = match (body.action.as_str(), body.declared_flow_id) {
+        ("dismiss", _) => None,
+        (_, Some(flow_id)) => {
+            let flow = flow_register::get_declared(pool.get_ref(), flow_id)
+                .await
+                .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+                .filter(|f| f.active)
+                .ok_or(ApiError::NotFound("Active declared flow"))?;
+            if !flow_register::covers_group(&flow, &group) {
+                return Err(ApiError::conflict(
+                    "FLOW_DOES_NOT_COVER",
+                    format!("Declared flow '{}' does not cover this group's source, partner, destination or categories", flow.name),
+                ));
+            }
+            Some(flow.id)
+        }
+        (_, None) => {
+            // The new declaration closes this group (and any other it covers) itself
+            let name = trimmed(&body.name).unwrap_or_else(|| {
+                let from = group.source_system.as_deref().unwrap_or("any source");
+                let to = group.partner_name.as_deref().or(group.destination_country_code.as_deref()).unwrap_or("any destination");
+                format!("{} → {}", from, to)
+            });
+            let (flow, _) = flow_register::declare(pool.get_ref(), DeclareFlowParams {
+                name,
+                source_system: group.source_system.clone(),
+                partner_name: group.partner_name.clone(),
+                destination_country_code: group.destination_country_code.clone(),
+                data_categories: serde_json::from_value(group.data_categories.clone()).unwrap_or_default(),
+                purpose: trimmed(&body.purpose).unwrap_or_default(),
+                owner: trimmed(&body.owner),
+                created_by: resolved_by.clone(),
+                resolution_note: note.clone(),
+            })
+            .await
+            .map_err(|e| ApiError::internal("FLOW_DECLARE_FAILED", e))?;
+            let row = flow_register::get_undeclared(pool.get_ref(), id)
+                .await
+                .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+                .ok_or(ApiError::NotFound("Undeclared flow"))?;
+            if row.declared_flow_id != Some(flow.id) {
+                return Err(already_resolved());
+            }
+            return Ok(HttpResponse::Ok().json(row));
+        }
+    };
+
+    let row = flow_register::resolve(pool.get_ref(), id, declared_flow_id, resolved_by.as_deref(), note.as_deref())
+        .await
+        .map_err(|e| ApiError::internal("RESOLVE_FAILED", e))?
+        .ok_or_else(already_resolved)?;
+    Ok(HttpResponse::Ok().json(row))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(declare_flow)
+       .service(list_flows)
+       .service(retire_flow)
+       .service(list_undeclared)
+       .service(resolve_undeclared);
+}
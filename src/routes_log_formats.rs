@@ -0,0 +1,187 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api_error::ApiError;
+use crate::ingest_queue;
+use crate::log_formats::{self, LineIssue, FORMATS};
+use crate::routes_shield::{
+    self, entry_idempotency_key, IngestBatchAccepted, IngestEntryResult, IngestItem, IngestLogEntry,
+};
+use crate::validation::{self, ValidQuery, Validate, Validator};
+
+/// Raw log uploads may be much larger than JSON batches.
+const MAX_LOG_BYTES: usize = 32 * 1024 * 1024;
+/// Line issues listed in a response; the counts always cover every line.
+const MAX_REPORTED_ISSUES: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct FormatIngestQuery {
+    /// Evaluate inline and return every result instead of queueing.
+    #[serde(default)]
+    pub sync: bool,
+    /// Comma-separated categories for entries whose log line names none (network logs never do;
+    /// mail logs default to `email`).
+    #[serde(alias = "data_categories")]
+    pub data_categories: Option<String>,
+    /// Partner for entries without a destination host, e.g. when one file covers one provider.
+    #[serde(alias = "partner_name")]
+    pub partner_name: Option<String>,
+}
+
+impl Validate for FormatIngestQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.max_len("partnerName", self.partner_name.as_deref(), 255);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatIngestResponse {
+    pub format: String,
+    /// Non-blank lines read, headers included.
+    pub lines: usize,
+    pub parsed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Lines that describe no outbound transfer (internal destination, rejected flow, ...).
+    pub skipped_lines: Vec<LineIssue>,
+    /// Lines that could not be parsed.
+    pub errors: Vec<LineIssue>,
+    /// The queued batch; absent with `?sync=true` or when no line produced an entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<IngestBatchAccepted>,
+    /// `?sync=true` only; each result's `index` is its source line number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<IngestEntryResult>>,
+    pub timestamp: String,
+}
+
+fn capped(mut issues: Vec<LineIssue>) -> Vec<LineIssue> {
+    issues.truncate(MAX_REPORTED_ISSUES);
+    issues
+}
+
+/// Ingest a raw log file in a native format: `vpc-flow` (AWS VPC Flow Logs, default or custom field
+/// order via the header line), `cef` (optionally syslog-wrapped), `syslog` (RFC 5424 with structured
+/// data or key=value pairs), `envoy` (default text format or JSON), `nginx` (combined, plus optional
+/// `host=` / `upstream_addr=` / `request_length=` fields), `squid` (native), `zeek-conn` (TSV or JSON),
+/// `postfix` (syslog maillog) or `exim` (main log). Mail deliveries are recorded per recipient with the
+/// relay as destination and `MAIL_PROVIDER_MAP` naming the provider; categories default to `email`.
+/// Lines become ingest entries and are queued like `POST /api/v1/shield/ingest-logs`; every line that
+/// produced no entry is reported with its line number. A destination with only an IP address is
+/// resolved to a country from the loaded IP-to-country data.
+#[utoipa::path(
+    post,
+    path = "/api/v1/shield/ingest-logs/{format}",
+    tag = "Sovereign Shield",
+    params(
+        ("format" = String, Path, description = "`vpc-flow`, `cef`, `syslog`, `envoy`, `nginx`, `squid`, `zeek-conn`, `postfix` or `exim`"),
+        FormatIngestQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Batch replay key; the Nth parsed entry uses `<key>#N`"),
+    ),
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 202, body = FormatIngestResponse, description = "Entries queued"),
+        (status = 200, body = FormatIngestResponse, description = "`?sync=true`, or no line produced an entry"),
+    ),
+)]
+pub async fn ingest_format(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+    query: ValidQuery<FormatIngestQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let format = path.into_inner();
+    if !FORMATS.contains(&format.as_str()) {
+        return Err(ApiError::invalid_field("format", format!("must be one of {}", FORMATS.join(", "))));
+    }
+    let batch_key = routes_shield::idempotency_key(&req)?;
+    let content = std::str::from_utf8(&body).map_err(|_| ApiError::invalid_field("body", "must be UTF-8 text"))?;
+    let parsed = log_formats::parse(&format, content).map_err(|e| ApiError::invalid_field("format", e))?;
+
+    let default_categories: Option<Vec<String>> = query.data_categories.as_deref().map(|c| {
+        c.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+    }).or_else(|| log_formats::default_categories(&format));
+    let lines = content.lines().filter(|l| !l.trim().is_empty()).count();
+    let mut errors = parsed.errors;
+    let mut line_numbers = Vec::with_capacity(parsed.entries.len());
+    let mut entries: Vec<IngestLogEntry> = Vec::with_capacity(parsed.entries.len());
+    for (line, mut entry) in parsed.entries {
+        if entry.data_categories.is_none() {
+            entry.data_categories = default_categories.clone();
+        }
+        if entry.partner_name.is_none() {
+            entry.partner_name = query.partner_name.clone();
+        }
+        // A field taken verbatim from the line (e.g. a syslog `country=`) can still be invalid
+        if let Err(e) = validation::validate(&entry) {
+            let message = e.body().fields.iter().map(|f| format!("{}: {}", f.field, f.message)).collect::<Vec<_>>().join("; ");
+            errors.push(LineIssue { line, message });
+            continue;
+        }
+        line_numbers.push(line);
+        entries.push(entry);
+    }
+    errors.sort_by_key(|e| e.line);
+
+    let mut response = FormatIngestResponse {
+        format,
+        lines,
+        parsed: entries.len(),
+        skipped: parsed.skipped.len(),
+        failed: errors.len(),
+        skipped_lines: capped(parsed.skipped),
+        errors: capped(errors),
+        batch: None,
+        results: None,
+        timestamp: Utc::now().to_rfc3339(),
+    };
+    if entries.is_empty() {
+        return Ok(HttpResponse::Ok().json(response));
+    }
+
+    if query.sync {
+        let received_at = Utc::now();
+        let items = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| IngestItem {
+                idempotency_key: entry_idempotency_key(&entry, batch_key.as_deref(), index),
+                entry,
+                received_at,
+            })
+            .collect();
+        let mut results = routes_shield::ingest_entries(pool.get_ref(), items).await?;
+        for (result, line) in results.iter_mut().zip(&line_numbers) {
+            result.index = *line;
+        }
+        response.results = Some(results);
+        return Ok(HttpResponse::Ok().json(response));
+    }
+
+    let (batch, created) = ingest_queue::enqueue(pool.get_ref(), entries, batch_key.as_deref())
+        .await
+        .map_err(|e| ApiError::internal("INGEST_ENQUEUE_FAILED", e))?;
+    response.batch = Some(IngestBatchAccepted {
+        status_url: format!("/api/v1/shield/ingest-batches/{}", batch.id),
+        batch_id: batch.id,
+        total_entries: batch.total_entries,
+        status: batch.status,
+        replayed: !created,
+    });
+    Ok(HttpResponse::Accepted().json(response))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/v1/shield/ingest-logs/{format}")
+            .app_data(web::PayloadConfig::new(MAX_LOG_BYTES))
+            .route(web::post().to(ingest_format)),
+    );
+}
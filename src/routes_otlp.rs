@@ -0,0 +1,131 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use prost::Message;
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+use crate::api_error::ApiError;
+use crate::ingest_queue;
+use crate::ip_country;
+use crate::otlp::{self, ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse};
+use crate::routes_shield::IngestLogEntry;
+use crate::validation;
+
+/// Collectors batch spans; allow the same ceiling as raw log uploads.
+const MAX_OTLP_BYTES: usize = 32 * 1024 * 1024;
+
+/// `OTLP_RESOLVE_HOSTS`: resolve span hosts that carry no peer address and no mapped country, so the
+/// IP-to-country data can place them (default `true`).
+fn resolve_hosts_enabled() -> bool {
+    std::env::var("OTLP_RESOLVE_HOSTS")
+        .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+        .unwrap_or(true)
+}
+
+fn is_protobuf(req: &HttpRequest) -> Result<bool, ApiError> {
+    let content_type = req.headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase();
+    match content_type.as_str() {
+        "application/x-protobuf" | "application/protobuf" => Ok(true),
+        "application/json" => Ok(false),
+        _ => Err(ApiError::bad_request(
+            "UNSUPPORTED_CONTENT_TYPE",
+            "OTLP/HTTP bodies must be application/x-protobuf or application/json",
+        )),
+    }
+}
+
+/// OTLP/HTTP trace receiver (`ExportTraceServiceRequest`, protobuf or JSON; gzip accepted). Client and
+/// producer spans to external destinations become ingest entries and are queued like
+/// `POST /api/v1/shield/ingest-logs`: the host comes from `server.address` / `net.peer.name` / the URL,
+/// the country from `EXT_AUTHZ_HOST_MAP` or the peer address (resolving the host when needed), the
+/// partner from `peer.service` and the source system from the resource's `service.name`. Categories
+/// are read from a `veridion.data_categories` span or resource attribute. Every span is keyed by its
+/// trace and span ID, so re-exported batches are not evaluated twice. Point an exporter's traces
+/// endpoint at this path; the response is an `ExportTraceServiceResponse` in the request's encoding.
+#[utoipa::path(
+    post,
+    path = "/api/v1/shield/otlp/v1/traces",
+    tag = "Sovereign Shield",
+    request_body(content = Vec<u8>, content_type = "application/x-protobuf", description = "`ExportTraceServiceRequest`; `application/json` is also accepted"),
+    responses(
+        (status = 200, description = "`ExportTraceServiceResponse`; `partialSuccess` counts spans rejected as invalid"),
+    ),
+)]
+pub async fn export_traces(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let protobuf = is_protobuf(&req)?;
+    let request = if protobuf {
+        ExportTraceServiceRequest::decode(body.as_ref())
+            .map_err(|e| ApiError::bad_request("INVALID_OTLP", format!("Invalid protobuf body: {}", e)))?
+    } else {
+        serde_json::from_slice::<ExportTraceServiceRequest>(&body)
+            .map_err(|e| ApiError::bad_request("INVALID_OTLP", format!("Invalid JSON body: {}", e)))?
+    };
+
+    let extracted = otlp::extract(&request);
+    let mut client_spans = extracted.client_spans;
+    if resolve_hosts_enabled() {
+        let unplaced: HashSet<String> = client_spans.iter()
+            .filter(|s| s.entry.destination_country_code.is_none() && s.entry.dest_ip.is_none())
+            .filter_map(|s| s.host.clone())
+            .collect();
+        if !unplaced.is_empty() {
+            let resolved = ip_country::resolve_hosts(unplaced).await;
+            for span in client_spans.iter_mut().filter(|s| s.entry.dest_ip.is_none()) {
+                span.entry.dest_ip = span.host.as_ref().and_then(|h| resolved.get(h)).cloned();
+            }
+        }
+    }
+
+    let mut entries: Vec<IngestLogEntry> = Vec::with_capacity(client_spans.len());
+    let mut rejected: Vec<String> = Vec::new();
+    for span in client_spans {
+        match validation::validate(&span.entry) {
+            Ok(()) => entries.push(span.entry),
+            Err(e) => {
+                let fields = e.body().fields.iter().map(|f| format!("{}: {}", f.field, f.message)).collect::<Vec<_>>().join("; ");
+                rejected.push(format!("{}: {}", span.entry.external_id.as_deref().unwrap_or("span"), fields));
+            }
+        }
+    }
+
+    if !entries.is_empty() {
+        let (batch, _) = ingest_queue::enqueue(pool.get_ref(), entries, None)
+            .await
+            .map_err(|e| ApiError::internal("INGEST_ENQUEUE_FAILED", e))?;
+        log::debug!(
+            "OTLP export: {} spans, {} outbound queued as batch {}, {} not outbound, {} rejected",
+            extracted.spans, batch.total_entries, batch.id, extracted.not_outbound, rejected.len()
+        );
+    }
+
+    let response = ExportTraceServiceResponse {
+        partial_success: (!rejected.is_empty()).then(|| ExportTracePartialSuccess {
+            rejected_spans: rejected.len() as i64,
+            error_message: rejected.into_iter().take(10).collect::<Vec<_>>().join("; "),
+        }),
+    };
+    Ok(if protobuf {
+        HttpResponse::Ok().content_type("application/x-protobuf").body(response.encode_to_vec())
+    } else {
+        HttpResponse::Ok().json(response)
+    })
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/v1/shield/otlp/v1/traces")
+            .app_data(web::PayloadConfig::new(MAX_OTLP_BYTES))
+            .route(web::post().to(export_traces)),
+    );
+}
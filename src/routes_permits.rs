@@ -0,0 +1,100 @@
+use actix_web::{web, HttpResponse, get, post};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::transfer_permits::{self, JwkSet, RevokedPermit};
+use crate::validation::{ValidJson, Validate, Validator};
+
+/// Verification keys for transfer permits (JWKS, Ed25519). Retired keys stay listed until the last
+/// permit they signed has expired, so verifiers can cache this and refresh on an unknown `kid`.
+#[utoipa::path(
+    tag = "Transfer permits",
+    responses((status = 200, body = JwkSet)),
+)]
+#[get("/api/v1/shield/permits/jwks.json")]
+pub async fn jwks(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let keys = transfer_permits::jwks(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("JWKS_FAILED", e))?;
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(keys))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationList {
+    pub issuer: String,
+    pub generated_at: String,
+    /// Unexpired permits that must no longer be honoured.
+    pub revoked: Vec<RevokedPermit>,
+}
+
+/// Revoked, unexpired permits. Permits are revoked when their basis is withdrawn (an SCC revoked,
+/// a country's status changed) or on request; verifiers refresh this at least once per permit TTL.
+#[utoipa::path(
+    tag = "Transfer permits",
+    responses((status = 200, body = RevocationList)),
+)]
+#[get("/api/v1/shield/permits/revoked")]
+pub async fn revocation_list(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let revoked = transfer_permits::revoked(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(RevocationList {
+        issuer: transfer_permits::issuer(),
+        generated_at: Utc::now().to_rfc3339(),
+        revoked,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RevokePermitRequest {
+    #[schema(min_length = 1)]
+    pub reason: String,
+}
+
+impl Validate for RevokePermitRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("reason", &self.reason);
+        v.max_len("reason", Some(&self.reason), 1000);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RevokePermitResponse {
+    pub success: bool,
+    pub jti: Uuid,
+}
+
+#[utoipa::path(
+    tag = "Transfer permits",
+    params(("jti" = Uuid, Path, description = "Permit ID (`jti` claim)")),
+    request_body = RevokePermitRequest,
+    responses((status = 200, body = RevokePermitResponse)),
+)]
+#[post("/api/v1/shield/permits/{jti}/revoke")]
+pub async fn revoke_permit(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    body: ValidJson<RevokePermitRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let jti = path.into_inner();
+    let revoked = transfer_permits::revoke(pool.get_ref(), jti, body.reason.trim())
+        .await
+        .map_err(|e| ApiError::internal("REVOKE_FAILED", e))?;
+    if !revoked {
+        return Err(ApiError::NotFound("Active permit"));
+    }
+    Ok(HttpResponse::Ok().json(RevokePermitResponse { success: true, jti }))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(jwks)
+       .service(revocation_list)
+       .service(revoke_permit);
+}
@@ -1,149 +1,218 @@
 use actix_web::{web, HttpResponse, get, post};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 
+use crate::api_error::ApiError;
+use crate::models::ReviewItemResponse;
 use crate::review_queue;
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListQuery {
+    /// Human oversight status: `PENDING`, `APPROVED`, `REJECTED` or `EXPIRED`.
     pub status: Option<String>,
 }
 
+impl Validate for ListQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("status", self.status.as_deref(), &["PENDING", "APPROVED", "REJECTED", "EXPIRED"]);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReviewListResponse {
+    pub reviews: Vec<ReviewItemResponse>,
+    pub total: usize,
+    pub pending: usize,
+    pub decided: usize,
+    pub expired: usize,
+}
+
+#[utoipa::path(
+    tag = "Review queue",
+    params(ListQuery),
+    responses((status = 200, body = ReviewListResponse)),
+)]
 #[get("/api/v1/review-queue")]
 pub async fn list_reviews(
     pool: web::Data<PgPool>,
-    query: web::Query<ListQuery>,
-) -> HttpResponse {
-    match review_queue::list_reviews(pool.get_ref(), query.status.as_deref()).await {
-        Ok(reviews) => {
-            let pending = reviews.iter().filter(|r| r.status == "PENDING").count();
-            let decided = reviews.iter().filter(|r| r.status == "DECIDED").count();
-            let expired = reviews.iter().filter(|r| r.status == "EXPIRED").count();
-            let total = reviews.len();
-
-            HttpResponse::Ok().json(serde_json::json!({
-                "reviews": reviews,
-                "total": total,
-                "pending": pending,
-                "decided": decided,
-                "expired": expired,
-            }))
-        }
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    query: ValidQuery<ListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let reviews = review_queue::list_reviews(pool.get_ref(), query.status.as_deref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let pending = reviews.iter().filter(|r| r.status == "PENDING").count();
+    let decided = reviews.iter().filter(|r| r.status == "DECIDED").count();
+    let expired = reviews.iter().filter(|r| r.status == "EXPIRED").count();
+    let total = reviews.len();
+
+    Ok(HttpResponse::Ok().json(ReviewListResponse { reviews, total, pending, decided, expired }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PendingReviewsResponse {
+    pub reviews: Vec<ReviewItemResponse>,
+    pub total: usize,
 }
 
+#[utoipa::path(
+    tag = "Review queue",
+    responses((status = 200, body = PendingReviewsResponse)),
+)]
 #[get("/api/v1/human_oversight/pending")]
-pub async fn get_pending(pool: web::Data<PgPool>) -> HttpResponse {
-    match review_queue::list_reviews(pool.get_ref(), Some("PENDING")).await {
-        Ok(reviews) => HttpResponse::Ok().json(serde_json::json!({
-            "reviews": reviews,
-            "total": reviews.len(),
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+pub async fn get_pending(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let reviews = review_queue::list_reviews(pool.get_ref(), Some("PENDING"))
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = reviews.len();
+    Ok(HttpResponse::Ok().json(PendingReviewsResponse { reviews, total }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecidedEvidenceIdsResponse {
+    pub evidence_event_ids: Vec<String>,
 }
 
+#[utoipa::path(
+    tag = "Review queue",
+    responses((status = 200, body = DecidedEvidenceIdsResponse)),
+)]
 #[get("/api/v1/human_oversight/decided-evidence-ids")]
-pub async fn get_decided_evidence_ids(pool: web::Data<PgPool>) -> HttpResponse {
-    match review_queue::get_decided_evidence_event_ids(pool.get_ref()).await {
-        Ok(ids) => HttpResponse::Ok().json(serde_json::json!({
-            "evidenceEventIds": ids,
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+pub async fn get_decided_evidence_ids(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let ids = review_queue::get_decided_evidence_event_ids(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(DecidedEvidenceIdsResponse { evidence_event_ids: ids }))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DecisionBody {
     pub decision: String,
+    #[schema(min_length = 1, max_length = 2000)]
     pub reason: String,
     pub reviewer_id: Option<String>,
 }
 
-#[derive(Deserialize)]
+impl Validate for DecisionBody {
+    fn validate(&self, v: &mut Validator) {
+        v.required("reason", &self.reason);
+        v.max_len("reason", Some(&self.reason), 2000);
+        v.max_len("reviewerId", self.reviewer_id.as_deref(), 255);
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateReviewBody {
     pub agent_id: Option<String>,
+    #[schema(min_length = 1)]
     pub action: String,
     pub module: Option<String>,
     pub context: serde_json::Value,
+    #[schema(min_length = 1, max_length = 255)]
     pub evidence_event_id: String,
 }
 
+impl Validate for CreateReviewBody {
+    fn validate(&self, v: &mut Validator) {
+        v.max_len("agentId", self.agent_id.as_deref(), 255);
+        v.required("action", &self.action);
+        v.required("evidenceEventId", &self.evidence_event_id);
+        v.max_len("evidenceEventId", Some(&self.evidence_event_id), 255);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReviewResponse {
+    pub success: bool,
+    pub seal_id: String,
+}
+
+/// Open a review for an evidence event; an existing review for the same event is returned instead.
+#[utoipa::path(
+    tag = "Review queue",
+    request_body = CreateReviewBody,
+    responses((status = 200, body = CreateReviewResponse)),
+)]
 #[post("/api/v1/review-queue")]
 pub async fn create_review(
     pool: web::Data<PgPool>,
-    body: web::Json<CreateReviewBody>,
-) -> HttpResponse {
+    body: ValidJson<CreateReviewBody>,
+) -> Result<HttpResponse, ApiError> {
     let agent_id = body.agent_id.as_deref().unwrap_or("sovereign-shield");
     let module = body.module.as_deref().unwrap_or("sovereign-shield");
-    
-    match review_queue::create_review(
+
+    let seal_id = review_queue::create_review(
         pool.get_ref(),
         agent_id,
         &body.action,
         module,
         &body.context,
         &body.evidence_event_id,
-    ).await {
-        Ok(seal_id) => HttpResponse::Ok().json(serde_json::json!({
-            "success": true,
-            "sealId": seal_id,
-        })),
-        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    )
+    .await
+    .map_err(|e| ApiError::internal("REVIEW_CREATION_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(CreateReviewResponse { success: true, seal_id }))
 }
 
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewDecisionResponse {
+    pub success: bool,
+    pub seal_id: String,
+    /// `APPROVED` or `REJECTED`.
+    pub decision: String,
+}
+
+#[utoipa::path(
+    tag = "Review queue",
+    params(("seal_id" = String, Path, description = "Review seal ID")),
+    request_body = DecisionBody,
+    responses((status = 200, body = ReviewDecisionResponse)),
+)]
 #[post("/api/v1/action/{seal_id}/approve")]
 pub async fn approve_action(
     pool: web::Data<PgPool>,
     path: web::Path<String>,
-    body: web::Json<DecisionBody>,
-) -> HttpResponse {
+    body: ValidJson<DecisionBody>,
+) -> Result<HttpResponse, ApiError> {
     let seal_id = path.into_inner();
     let reviewer = body.reviewer_id.as_deref().unwrap_or("admin");
 
-    match review_queue::decide_review(pool.get_ref(), &seal_id, "APPROVE", reviewer, &body.reason).await {
-        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
-            "success": true,
-            "sealId": seal_id,
-            "decision": "APPROVED",
-        })),
-        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    review_queue::decide_review(pool.get_ref(), &seal_id, "APPROVE", reviewer, &body.reason).await?;
+    Ok(HttpResponse::Ok().json(ReviewDecisionResponse {
+        success: true,
+        seal_id,
+        decision: "APPROVED".into(),
+    }))
 }
 
+#[utoipa::path(
+    tag = "Review queue",
+    params(("seal_id" = String, Path, description = "Review seal ID")),
+    request_body = DecisionBody,
+    responses((status = 200, body = ReviewDecisionResponse)),
+)]
 #[post("/api/v1/action/{seal_id}/reject")]
 pub async fn reject_action(
     pool: web::Data<PgPool>,
     path: web::Path<String>,
-    body: web::Json<DecisionBody>,
-) -> HttpResponse {
+    body: ValidJson<DecisionBody>,
+) -> Result<HttpResponse, ApiError> {
     let seal_id = path.into_inner();
     let reviewer = body.reviewer_id.as_deref().unwrap_or("admin");
 
-    match review_queue::decide_review(pool.get_ref(), &seal_id, "REJECT", reviewer, &body.reason).await {
-        Ok(()) => HttpResponse::Ok().json(serde_json::json!({
-            "success": true,
-            "sealId": seal_id,
-            "decision": "REJECTED",
-        })),
-        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({
-            "error": e,
-        })),
-    }
+    review_queue::decide_review(pool.get_ref(), &seal_id, "REJECT", reviewer, &body.reason).await?;
+    Ok(HttpResponse::Ok().json(ReviewDecisionResponse {
+        success: true,
+        seal_id,
+        decision: "REJECTED".into(),
+    }))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
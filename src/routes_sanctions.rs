@@ -0,0 +1,152 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse, post};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api_error::ApiError;
+use crate::sanctions::{self, LoadListParams, SanctionsListRow, SanctionsMatch};
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+
+/// Consolidated lists run to tens of megabytes.
+const MAX_LIST_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct LoadListQuery {
+    /// `xml` or `csv`; taken from the `Content-Type` when omitted.
+    pub format: Option<String>,
+    /// Name the list is recorded under, e.g. `EU consolidated 2026-10-01`.
+    pub source_name: String,
+    pub loaded_by: Option<String>,
+}
+
+impl Validate for LoadListQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.required("sourceName", &self.source_name);
+        v.max_len("sourceName", Some(&self.source_name), 255);
+        let format = self.format.as_deref().map(str::to_lowercase);
+        v.one_of("format", format.as_deref(), &["xml", "csv"]);
+        v.max_len("loadedBy", self.loaded_by.as_deref(), 255);
+    }
+}
+
+/// Upload a sanctions list (EU consolidated XML or CSV) as the request body and make it the active list.
+#[utoipa::path(
+    post,
+    path = "/api/v1/sanctions/lists",
+    tag = "Sanctions",
+    params(LoadListQuery),
+    request_body(content = String, content_type = "application/xml"),
+    responses((status = 201, body = SanctionsListRow)),
+)]
+pub async fn load_list(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: ValidQuery<LoadListQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let content = String::from_utf8(body.to_vec()).map_err(|_| ApiError::invalid_field("body", "must be UTF-8 text"))?;
+    let format = match query.format.as_deref().map(str::to_lowercase) {
+        Some(format) => format,
+        None => {
+            let content_type = req.headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_lowercase();
+            if content_type.contains("csv") {
+                "csv".to_string()
+            } else if content_type.contains("xml") {
+                "xml".to_string()
+            } else {
+                return Err(ApiError::invalid_field("format", "required unless Content-Type names xml or csv"));
+            }
+        }
+    };
+    sanctions::parse_entries(&content, &format).map_err(|e| ApiError::invalid_field("body", e))?;
+
+    let params = LoadListParams {
+        source_name: query.source_name.trim().to_string(),
+        format,
+        content,
+        loaded_by: Some(query.loaded_by.clone().unwrap_or_else(|| "admin".into())),
+    };
+
+    let list = sanctions::load_list(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("LOAD_FAILED", e))?;
+    Ok(HttpResponse::Created().json(list))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SanctionsListsResponse {
+    pub lists: Vec<SanctionsListRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sanctions/lists",
+    tag = "Sanctions",
+    responses((status = 200, body = SanctionsListsResponse)),
+)]
+pub async fn list_lists(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let lists = sanctions::list_lists(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    let total = lists.len();
+    Ok(HttpResponse::Ok().json(SanctionsListsResponse { lists, total }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ScreenRequest {
+    #[schema(min_length = 1, max_length = 500)]
+    pub name: String,
+}
+
+impl Validate for ScreenRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.max_len("name", Some(&self.name), 500);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ScreenResponse {
+    pub name: String,
+    pub matched: bool,
+    #[serde(rename = "match")]
+    pub hit: Option<SanctionsMatch>,
+}
+
+/// Screen a single name without evaluating a transfer.
+#[utoipa::path(
+    tag = "Sanctions",
+    request_body = ScreenRequest,
+    responses((status = 200, body = ScreenResponse)),
+)]
+#[post("/api/v1/sanctions/screen")]
+pub async fn screen(
+    pool: web::Data<PgPool>,
+    body: ValidJson<ScreenRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let hit = sanctions::screen_name(pool.get_ref(), &body.name)
+        .await
+        .map_err(|e| ApiError::internal("SCREENING_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(ScreenResponse {
+        name: body.into_inner().name,
+        matched: hit.is_some(),
+        hit,
+    }))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/v1/sanctions/lists")
+            .app_data(web::PayloadConfig::new(MAX_LIST_BYTES))
+            .route(web::post().to(load_list))
+            .route(web::get().to(list_lists)),
+    )
+    .service(screen);
+}
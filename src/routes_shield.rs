@@ -1,14 +1,32 @@
-use actix_web::{web, HttpResponse, get, post, patch, delete};
-use chrono::Utc;
+use actix_web::{web, HttpRequest, HttpResponse, get, post, patch, delete};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use futures_util::stream::{self, StreamExt};
 use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::api_error::ApiError;
 use crate::evidence::{self, CreateEventParams};
-use crate::shield::{Decision, TransferContext, evaluate_transfer_with_db, all_country_classifications, country_name};
+use crate::flow_register::{self, Register, Sighting};
+use crate::data_categories::NormalisedCategories;
+use crate::sanctions::SanctionsMatch;
+use crate::host_map;
+use crate::idempotency;
+use crate::ip_country;
+use crate::ingest_queue::{self, IngestBatchStatus};
+use crate::source_time;
+use crate::transfer_flows::{self, FlowSample, TransferFlowRow};
+use crate::transfer_permits;
+use crate::shield::{Decision, ExportedPolicy, PolicySnapshot, TransferContext, TransferDecision, screen_partner, country_name};
+use crate::country_policy;
 use crate::review_queue;
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+use veridion_client::models::EvaluateResponse;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Default, ToSchema)]
 pub struct IngestLogEntry {
     #[serde(alias = "sourceIp", alias = "source_ip")]
     pub source_ip: Option<String>,
@@ -30,66 +48,52 @@ pub struct IngestLogEntry {
     pub data_categories: Option<Vec<String>>,
     #[serde(alias = "partnerName", alias = "partner_name")]
     pub partner_name: Option<String>,
+    #[serde(alias = "sourceSystem", alias = "source_system")]
+    pub source_system: Option<String>,
+    /// Why the data is sent, checked against the declared flow it matches.
+    pub purpose: Option<String>,
+    /// Shipper-assigned ID; a retried entry with the same ID returns the original result.
+    #[serde(alias = "externalId", alias = "external_id")]
+    pub external_id: Option<String>,
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct EvaluateRequest {
-    pub destination_country_code: Option<String>,
-    pub destination_country: Option<String>,
-    pub data_categories: Option<Vec<String>>,
-    pub partner_name: Option<String>,
-    pub source_ip: Option<String>,
-    pub dest_ip: Option<String>,
-    pub data_size: Option<u64>,
-    pub protocol: Option<String>,
-    pub user_agent: Option<String>,
-    pub request_path: Option<String>,
+impl Validate for IngestLogEntry {
+    fn validate(&self, v: &mut Validator) {
+        v.country_code("destination_country_code", self.destination_country_code.as_deref());
+        v.max_len("external_id", self.external_id.as_deref(), 255);
+        v.max_len("source_system", self.source_system.as_deref(), 255);
+        v.max_len("purpose", self.purpose.as_deref(), 500);
+    }
 }
 
-#[post("/api/v1/shield/evaluate")]
-pub async fn evaluate(
-    pool: web::Data<PgPool>,
-    body: web::Json<EvaluateRequest>,
-) -> HttpResponse {
-    let ctx = TransferContext {
-        destination_country_code: body.destination_country_code.clone(),
-        destination_country: body.destination_country.clone(),
-        data_categories: body.data_categories.clone(),
-        partner_name: body.partner_name.clone(),
-        source_ip: body.source_ip.clone(),
-        dest_ip: body.dest_ip.clone(),
-        data_size: body.data_size,
-        protocol: body.protocol.clone(),
-        user_agent: body.user_agent.clone(),
-        request_path: body.request_path.clone(),
-    };
+impl Validate for TransferContext {
+    fn validate(&self, v: &mut Validator) {
+        v.country_code("destinationCountryCode", self.destination_country_code.as_deref());
+        v.max_len("partnerName", self.partner_name.as_deref(), 500);
+        v.max_len("sourceSystem", self.source_system.as_deref(), 255);
+        v.max_len("purpose", self.purpose.as_deref(), 500);
+    }
+}
 
-    let decision = match evaluate_transfer_with_db(pool.get_ref(), &ctx).await {
-        Ok(d) => d,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "EVALUATION_FAILED",
-                "message": e,
-            }));
-        }
-    };
+/// Evidence payload recorded for every evaluated transfer.
+fn transfer_payload(
+    ctx: &TransferContext,
+    categories: &NormalisedCategories,
+    decision: &TransferDecision,
+    sanctions_match: &Option<SanctionsMatch>,
+) -> serde_json::Value {
     let dest_code = ctx.destination_country_code.clone().unwrap_or_default();
-    let dest_name = if dest_code.is_empty() {
-        ctx.destination_country.clone().unwrap_or("Unknown".into())
-    } else {
-        country_name(&dest_code)
-    };
-
-    let correlation_id = Uuid::new_v4().to_string();
-
-    let payload = serde_json::json!({
-        "destination_country": dest_name,
+    serde_json::json!({
+        "destination_country": destination_name(ctx),
         "destination_country_code": dest_code,
         "country_status": decision.country_status,
         "decision": decision.decision.to_string(),
         "reason": decision.reason,
         "data_categories": ctx.data_categories,
+        "unrecognised_data_categories": categories.unrecognised,
+        "sensitivity_tier": categories.highest_tier,
+        "special_category": categories.special_category,
+        "criminal_data": categories.criminal_data,
         "data_size": ctx.data_size,
         "source_ip": ctx.source_ip,
         "dest_ip": ctx.dest_ip,
@@ -97,202 +101,861 @@ pub async fn evaluate(
         "user_agent": ctx.user_agent,
         "request_path": ctx.request_path,
         "partner_name": ctx.partner_name,
-    });
+        "source_system": ctx.source_system,
+        "purpose": ctx.purpose,
+        "sanctions_match": sanctions_match,
+    })
+}
+
+fn destination_name(ctx: &TransferContext) -> String {
+    match ctx.destination_country_code.as_deref() {
+        Some(code) if !code.is_empty() => country_name(code),
+        _ => ctx.destination_country.clone().unwrap_or("Unknown".into()),
+    }
+}
 
-    let params = CreateEventParams {
+fn transfer_event_params(
+    ctx: &TransferContext,
+    decision: &TransferDecision,
+    payload: serde_json::Value,
+    occurred_at: Option<DateTime<Utc>>,
+) -> CreateEventParams {
+    CreateEventParams {
         event_type: decision.event_type.clone(),
         severity: decision.severity.clone(),
         source_system: "sovereign-shield".into(),
         regulatory_tags: vec!["GDPR".into()],
         articles: decision.articles.clone(),
-        payload: payload.clone(),
-        correlation_id: Some(correlation_id.clone()),
+        payload,
+        correlation_id: Some(Uuid::new_v4().to_string()),
         causation_id: None,
         source_ip: ctx.source_ip.clone(),
         source_user_agent: ctx.user_agent.clone(),
-    };
+        occurred_at,
+        idempotency_key: None,
+    }
+}
 
-    let (event_id, review_id) = match evidence::create_event(pool.get_ref(), params).await {
-        Ok(event_row) => {
-            let review_id = if decision.decision == Decision::REVIEW {
-                let action = format!("transfer_data_to_{}", dest_code.to_lowercase());
-                match review_queue::create_review(
-                    pool.get_ref(),
-                    "sovereign-shield",
-                    &action,
-                    "sovereign-shield",
-                    &serde_json::json!({
-                        "destination": dest_name,
-                        "destination_country_code": dest_code,
-                        "data_categories": ctx.data_categories,
-                        "reason": decision.reason,
-                    }),
-                    &event_row.event_id,
-                ).await {
-                    Ok(seal_id) => Some(seal_id),
-                    Err(e) => {
-                        log::error!("Failed to create review for event {}: {}", event_row.event_id, e);
-                        None
-                    }
-                }
-            } else {
+/// Open a human-oversight review for a REVIEW decision; returns the seal ID.
+async fn create_transfer_review(
+    pool: &PgPool,
+    ctx: &TransferContext,
+    decision: &TransferDecision,
+    event_id: &str,
+) -> Result<String, String> {
+    let dest_code = ctx.destination_country_code.clone().unwrap_or_default();
+    let action = format!("transfer_data_to_{}", dest_code.to_lowercase());
+    review_queue::create_review(
+        pool,
+        "sovereign-shield",
+        &action,
+        "sovereign-shield",
+        &serde_json::json!({
+            "destination": destination_name(ctx),
+            "destination_country_code": dest_code,
+            "data_categories": ctx.data_categories,
+            "reason": decision.reason,
+        }),
+        event_id,
+    ).await
+}
+
+/// Outcome of a single recorded evaluation, either freshly written or replayed from an idempotency key.
+pub struct EvaluationOutcome {
+    pub decision: String,
+    pub reason: String,
+    pub severity: String,
+    pub articles: serde_json::Value,
+    pub country_status: String,
+    pub evidence_id: String,
+    pub review_id: Option<String>,
+    pub flow_status: Option<String>,
+    pub declared_flow_id: Option<String>,
+    pub replayed: bool,
+}
+
+impl EvaluationOutcome {
+    /// Replayed outcomes carry the decision as stored; anything unrecognised is reported as REVIEW.
+    pub fn to_response(&self) -> EvaluateResponse {
+        EvaluateResponse {
+            decision: serde_json::from_value(serde_json::json!(self.decision)).unwrap_or(Decision::REVIEW),
+            reason: self.reason.clone(),
+            severity: self.severity.clone(),
+            articles: serde_json::from_value(self.articles.clone()).unwrap_or_default(),
+            country_status: self.country_status.clone(),
+            evidence_id: self.evidence_id.clone(),
+            review_id: self.review_id.clone(),
+            replayed: self.replayed,
+            permit: None,
+            permit_expires_at: None,
+            flow_status: self.flow_status.clone(),
+            declared_flow_id: self.declared_flow_id.clone(),
+            timestamp: Some(Utc::now().to_rfc3339()),
+        }
+    }
+}
+
+/// Evaluate one transfer, write its evidence event and open a review for REVIEW decisions.
+/// Shared by `evaluate` and inline enforcement.
+pub async fn evaluate_and_record(
+    pool: &PgPool,
+    mut ctx: TransferContext,
+    idempotency_key: Option<String>,
+) -> Result<EvaluationOutcome, ApiError> {
+    if let Some(key) = &idempotency_key {
+        if let Some(outcome) = replay(pool, key).await? {
+            return Ok(outcome);
+        }
+    }
+
+    let snapshot = PolicySnapshot::load(pool)
+        .await
+        .map_err(|e| ApiError::internal("POLICY_LOAD_FAILED", e))?;
+    let categories = snapshot.normalise_context(&mut ctx);
+    let country_from_ip = country_from_dest_ip(pool, &mut ctx).await;
+    let screening = screen_partner(pool, ctx.partner_name.as_deref()).await;
+    let (decision, sanctions_match) = snapshot.evaluate_screened(&ctx, categories.as_ref(), screening);
+    let matched = Register::load(pool).await.map(|r| r.status(&ctx));
+
+    let mut payload = transfer_payload(&ctx, &categories.unwrap_or_default(), &decision, &sanctions_match);
+    if let Some(obj) = payload.as_object_mut() {
+        if country_from_ip {
+            obj.insert("destination_country_source".into(), serde_json::json!("dest_ip"));
+        }
+        if let Some((status, declared)) = matched {
+            obj.insert("flow_status".into(), serde_json::json!(status));
+            obj.insert("declared_flow_id".into(), serde_json::json!(declared.map(|id| id.to_string())));
+        }
+    }
+    let mut params = transfer_event_params(&ctx, &decision, payload, None);
+    params.idempotency_key = idempotency_key.clone();
+
+    let written = evidence::create_events_batch(pool, "sovereign-shield", vec![params])
+        .await
+        .map_err(|e| ApiError::internal("EVIDENCE_CREATION_FAILED", e))?
+        .remove(0);
+    let Some(event_row) = written else {
+        // A concurrent request with the same key committed first: report its outcome
+        let key = idempotency_key.unwrap_or_default();
+        return replay(pool, &key).await?.ok_or_else(|| {
+            ApiError::conflict("IDEMPOTENCY_KEY_IN_USE", format!("Idempotency key {} is already in use", key))
+        });
+    };
+    if let Some((flow_register::UNDECLARED_FLOW, _)) = matched {
+        let sighting = Sighting {
+            ctx: &ctx,
+            occurred_at: event_row.occurred_at,
+            decision: decision.decision.to_string(),
+            evidence_id: Some(event_row.event_id.clone()),
+            flow_id: None,
+        };
+        if let Err(e) = flow_register::record_undeclared(pool, &[sighting]).await {
+            log::error!("Failed to group undeclared transfer {}: {}", event_row.event_id, e);
+        }
+    }
+    let review_id = if decision.decision == Decision::REVIEW {
+        match create_transfer_review(pool, &ctx, &decision, &event_row.event_id).await {
+            Ok(seal_id) => Some(seal_id),
+            Err(e) => {
+                log::error!("Failed to create review for event {}: {}", event_row.event_id, e);
                 None
-            };
-            (Some(event_row.event_id), review_id)
+            }
         }
-        Err(e) => {
-            log::error!("Failed to create evidence event: {}", e);
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "EVIDENCE_CREATION_FAILED",
-                "message": format!("Failed to create evidence: {}", e),
-            }));
+    } else {
+        None
+    };
+
+    Ok(EvaluationOutcome {
+        decision: decision.decision.to_string(),
+        reason: decision.reason,
+        severity: decision.severity,
+        articles: serde_json::json!(decision.articles),
+        country_status: decision.country_status,
+        evidence_id: event_row.event_id,
+        review_id,
+        flow_status: matched.map(|(status, _)| status.to_string()),
+        declared_flow_id: matched.and_then(|(_, id)| id).map(|id| id.to_string()),
+        replayed: false,
+    })
+}
+
+/// Fill a missing destination country from `dest_ip` with the active IP country dataset. Clients
+/// that only know the URL send the host name there: it is placed by `EXT_AUTHZ_HOST_MAP` when listed,
+/// otherwise resolved and replaced by its address. Returns true when the country came from the
+/// address; a failed lookup is logged and the transfer is evaluated without it.
+pub async fn country_from_dest_ip(pool: &PgPool, ctx: &mut TransferContext) -> bool {
+    if ctx.destination_country_code.as_deref().is_some_and(|c| !c.is_empty()) {
+        return false;
+    }
+    let Some(dest) = ctx.dest_ip.clone() else {
+        return false;
+    };
+    let ip = if dest.parse::<IpAddr>().is_ok() {
+        dest
+    } else {
+        let host = dest.trim_end_matches('.').to_lowercase();
+        if let Some((code, partner)) = host_map::lookup_env(&host) {
+            ctx.destination_country_code = Some(code);
+            // The mapped partner replaces a partner that is only the host name
+            if partner.is_some() && ctx.partner_name.as_deref().map(|p| p.eq_ignore_ascii_case(&host)).unwrap_or(true) {
+                ctx.partner_name = partner;
+            }
+            return false;
+        }
+        match ip_country::resolve_hosts(HashSet::from([host.clone()])).await.remove(&host) {
+            Some(ip) => {
+                ctx.dest_ip = Some(ip.clone());
+                ip
+            }
+            None => return false,
         }
     };
+    match ip_country::resolve(pool, std::slice::from_ref(&ip)).await {
+        Ok(mut countries) => match countries.remove(&ip) {
+            Some(code) => {
+                ctx.destination_country_code = Some(code);
+                true
+            }
+            None => false,
+        },
+        Err(e) => {
+            log::warn!("IP country lookup failed, evaluating without it: {}", e);
+            false
+        }
+    }
+}
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "decision": decision.decision.to_string(),
-        "reason": decision.reason,
-        "severity": decision.severity,
-        "articles": decision.articles,
-        "country_status": decision.country_status,
-        "evidence_id": event_id,
-        "review_id": review_id,
-        "timestamp": Utc::now().to_rfc3339(),
+/// The outcome already recorded for an idempotency key within the replay window, if any.
+async fn replay(pool: &PgPool, key: &str) -> Result<Option<EvaluationOutcome>, ApiError> {
+    let mut prior = idempotency::find_recent(pool, "sovereign-shield", &[key.to_string()])
+        .await
+        .map_err(|e| ApiError::internal("IDEMPOTENCY_LOOKUP_FAILED", e))?;
+    Ok(prior.remove(key).map(|p| EvaluationOutcome {
+        decision: p.decision().unwrap_or_default(),
+        reason: p.reason().unwrap_or_default(),
+        country_status: p.country_status().unwrap_or_default(),
+        flow_status: p.flow_status(),
+        declared_flow_id: p.declared_flow_id(),
+        severity: p.severity,
+        articles: p.articles,
+        evidence_id: p.event_id,
+        review_id: p.review_id,
+        replayed: true,
     }))
 }
 
-#[post("/api/v1/shield/ingest-logs")]
-pub async fn ingest_logs(
+pub fn idempotency_key(req: &HttpRequest) -> Result<Option<String>, ApiError> {
+    idempotency::key_from_request(req).map_err(|e| ApiError::bad_request("INVALID_IDEMPOTENCY_KEY", e))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EvaluateQuery {
+    /// Return a signed transfer permit with an ALLOW decision.
+    #[serde(default)]
+    pub permit: bool,
+}
+
+impl Validate for EvaluateQuery {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+/// With an `Idempotency-Key` header, a retry inside the replay window returns the original decision
+/// and evidence ID without writing anything. With `?permit=true`, an ALLOW also carries a short-lived
+/// permit (`PERMIT_TTL_SECS`) binding the decision to the submitted transfer attributes and the evidence
+/// ID, verifiable offline against `GET /api/v1/shield/permits/jwks.json`. A replay returns the permit
+/// issued originally while it is still valid, and none once it has expired or been revoked.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    params(
+        EvaluateQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Replay key for retries"),
+    ),
+    request_body = TransferContext,
+    responses((status = 200, body = EvaluateResponse)),
+)]
+#[post("/api/v1/shield/evaluate")]
+pub async fn evaluate(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
-    body: web::Json<Vec<IngestLogEntry>>,
-) -> HttpResponse {
-    let mut processed = 0u64;
-
-    for entry in body.into_inner() {
-        let ctx = TransferContext {
-            destination_country_code: entry.destination_country_code.clone(),
-            destination_country: entry.destination_country.clone(),
-            data_categories: entry.data_categories.clone(),
-            partner_name: entry.partner_name.clone(),
-            source_ip: entry.source_ip.clone(),
-            dest_ip: entry.dest_ip.clone(),
-            data_size: entry.data_size,
-            protocol: entry.protocol.clone(),
-            user_agent: entry.user_agent.clone(),
-            request_path: entry.request_path.clone(),
+    query: ValidQuery<EvaluateQuery>,
+    body: ValidJson<TransferContext>,
+) -> Result<HttpResponse, ApiError> {
+    let idempotency_key = idempotency_key(&req)?;
+    let ctx = body.into_inner();
+    let permit_ctx = query.permit.then(|| ctx.clone());
+    let outcome = evaluate_and_record(pool.get_ref(), ctx, idempotency_key).await?;
+    let mut response = outcome.to_response();
+    if let (Some(ctx), Decision::ALLOW) = (permit_ctx, &response.decision) {
+        let permit = if outcome.replayed {
+            transfer_permits::find_for_evidence(pool.get_ref(), &outcome.evidence_id)
+                .await
+                .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        } else {
+            let issued = transfer_permits::issue(pool.get_ref(), &ctx, &outcome.evidence_id, &outcome.country_status)
+                .await
+                .map_err(|e| ApiError::internal("PERMIT_ISSUE_FAILED", e))?;
+            Some(issued)
         };
+        if let Some(permit) = permit {
+            response.permit = Some(permit.token);
+            response.permit_expires_at = Some(permit.expires_at.to_rfc3339());
+        }
+    }
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Evidence rows written per transaction during ingest.
+const INGEST_BATCH_SIZE: usize = 100;
+/// Concurrent sanctions screenings / review creations during ingest.
+const INGEST_CONCURRENCY: usize = 8;
+
+#[derive(Serialize, Deserialize, Default, Clone, ToSchema)]
+pub struct IngestEntryResult {
+    pub index: usize,
+    pub decision: Option<String>,
+    pub reason: Option<String>,
+    pub evidence_id: Option<String>,
+    pub review_id: Option<String>,
+    /// Aggregated flow that counted this entry; its evidence (and any review) is written when the flow's window closes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow_id: Option<String>,
+    /// `DECLARED` or `UNDECLARED_FLOW`, once the flow register has any declaration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow_status: Option<String>,
+    /// Declared flow covering this transfer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_flow_id: Option<String>,
+    pub occurred_at: Option<String>,
+    /// True when an earlier event with the same idempotency key was returned instead of writing a new one.
+    pub replayed: bool,
+    pub warning: Option<String>,
+    pub error: Option<String>,
+}
+
+impl IngestEntryResult {
+    fn from_prior(index: usize, prior: &idempotency::PriorOutcome) -> Self {
+        IngestEntryResult {
+            index,
+            decision: prior.decision(),
+            reason: prior.reason(),
+            evidence_id: Some(prior.event_id.clone()),
+            review_id: prior.review_id.clone(),
+            flow_status: prior.flow_status(),
+            declared_flow_id: prior.declared_flow_id(),
+            occurred_at: Some(prior.occurred_at.to_rfc3339()),
+            replayed: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IngestResponse {
+    pub processed: usize,
+    pub replayed: usize,
+    pub failed: usize,
+    pub results: Vec<IngestEntryResult>,
+    pub timestamp: String,
+}
+
+struct PreparedEntry {
+    ctx: TransferContext,
+    categories: NormalisedCategories,
+    timestamp: Option<String>,
+    received_at: DateTime<Utc>,
+    idempotency_key: Result<Option<String>, String>,
+    /// Destination country looked up from `dest_ip` rather than supplied by the source.
+    country_from_ip: bool,
+}
+
+/// One log entry to ingest, with the idempotency key it resolved to (or why its key was rejected)
+/// and when the API received it, which bounds clock skew in its source timestamp.
+pub struct IngestItem {
+    pub entry: IngestLogEntry,
+    pub idempotency_key: Result<Option<String>, String>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Idempotency key for entry `index` of a submitted batch: its `external_id`, else the batch key plus index.
+pub fn entry_idempotency_key(entry: &IngestLogEntry, batch_key: Option<&str>, index: usize) -> Result<Option<String>, String> {
+    match (&entry.external_id, batch_key) {
+        (Some(id), _) => idempotency::validate_key(id).map(|_| Some(id.clone())),
+        (None, Some(key)) => Ok(Some(format!("{}#{}", key, index))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Evaluate a batch of transfer logs against one policy snapshot. Partners are screened once each,
+/// evidence is written in grouped transactions and every entry gets its own result, at the entry's
+/// position in `items`. Shared by synchronous ingest and the ingest queue worker.
+pub async fn ingest_entries(pool: &PgPool, items: Vec<IngestItem>) -> Result<Vec<IngestEntryResult>, ApiError> {
+    let snapshot = PolicySnapshot::load(pool)
+        .await
+        .map_err(|e| ApiError::internal("POLICY_LOAD_FAILED", e))?;
+    let register = Register::load(pool).await;
 
-        let decision = match evaluate_transfer_with_db(pool.get_ref(), &ctx).await {
-            Ok(d) => d,
+    let max_skew = source_time::max_clock_skew();
+    let mut entries: Vec<PreparedEntry> = items
+        .into_iter()
+        .map(|IngestItem { entry, idempotency_key, received_at }| {
+            let mut ctx = TransferContext {
+                destination_country_code: entry.destination_country_code,
+                destination_country: entry.destination_country,
+                data_categories: entry.data_categories,
+                partner_name: entry.partner_name,
+                source_ip: entry.source_ip,
+                dest_ip: entry.dest_ip,
+                data_size: entry.data_size,
+                protocol: entry.protocol,
+                user_agent: entry.user_agent,
+                request_path: entry.request_path,
+                source_system: entry.source_system,
+                purpose: entry.purpose,
+            };
+            let categories = snapshot.normalise_context(&mut ctx).unwrap_or_default();
+            PreparedEntry { ctx, categories, timestamp: entry.timestamp, received_at, idempotency_key, country_from_ip: false }
+        })
+        .collect();
+
+    // Network logs often carry only addresses: resolve the destination country from local IP data
+    let unresolved: Vec<String> = entries.iter()
+        .filter(|e| e.ctx.destination_country_code.as_deref().map(str::is_empty).unwrap_or(true))
+        .filter_map(|e| e.ctx.dest_ip.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if !unresolved.is_empty() {
+        match ip_country::resolve(pool, &unresolved).await {
+            Ok(countries) => {
+                for entry in entries.iter_mut().filter(|e| e.ctx.destination_country_code.as_deref().map(str::is_empty).unwrap_or(true)) {
+                    if let Some(code) = entry.ctx.dest_ip.as_ref().and_then(|ip| countries.get(ip)) {
+                        entry.ctx.destination_country_code = Some(code.clone());
+                        entry.country_from_ip = true;
+                    }
+                }
+            }
+            Err(e) => log::warn!("IP country lookup failed, evaluating without it: {}", e),
+        }
+    }
+
+    let keys: Vec<String> = entries.iter()
+        .filter_map(|e| e.idempotency_key.clone().ok().flatten())
+        .collect();
+    let prior = idempotency::find_recent(pool, "sovereign-shield", &keys)
+        .await
+        .map_err(|e| ApiError::internal("IDEMPOTENCY_LOOKUP_FAILED", e))?;
+
+    // Screen each distinct partner once, a few at a time
+    let partners: HashSet<String> = entries.iter()
+        .filter_map(|e| e.ctx.partner_name.clone())
+        .filter(|p| !p.trim().is_empty())
+        .collect();
+    let screenings: HashMap<String, Result<Option<SanctionsMatch>, String>> = stream::iter(partners)
+        .map(|partner| async move {
+            let result = screen_partner(pool, Some(&partner)).await;
+            (partner, result)
+        })
+        .buffer_unordered(INGEST_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut results: Vec<IngestEntryResult> = Vec::with_capacity(entries.len());
+    let mut decisions: Vec<Option<TransferDecision>> = Vec::with_capacity(entries.len());
+    let mut events: Vec<(usize, CreateEventParams)> = Vec::with_capacity(entries.len());
+    let flow_window = transfer_flows::window_secs();
+    let mut flows: Vec<(usize, FlowSample)> = Vec::new();
+    let mut first_with_key: HashMap<String, usize> = HashMap::new();
+    let mut repeats: Vec<(usize, usize)> = Vec::new();
+    let mut undeclared: Vec<(usize, DateTime<Utc>)> = Vec::new();
+    for (index, entry) in entries.iter_mut().enumerate() {
+        let key = match &entry.idempotency_key {
+            Ok(k) => k.clone(),
             Err(e) => {
-                log::error!("Ingest evaluation failed for entry: {}", e);
+                results.push(IngestEntryResult { index, error: Some(e.clone()), ..Default::default() });
+                decisions.push(None);
                 continue;
             }
         };
-        let dest_code = ctx.destination_country_code.clone().unwrap_or_default();
-        let dest_name = if dest_code.is_empty() {
-            ctx.destination_country.clone().unwrap_or("Unknown".into())
-        } else {
-            country_name(&dest_code)
-        };
-
-        let correlation_id = Uuid::new_v4().to_string();
-
-        let payload = serde_json::json!({
-            "destination_country": dest_name,
-            "destination_country_code": dest_code,
-            "country_status": decision.country_status,
-            "decision": decision.decision.to_string(),
-            "reason": decision.reason,
-            "data_categories": ctx.data_categories,
-            "data_size": ctx.data_size,
-            "source_ip": ctx.source_ip,
-            "dest_ip": ctx.dest_ip,
-            "protocol": ctx.protocol,
-            "user_agent": ctx.user_agent,
-            "request_path": ctx.request_path,
-            "partner_name": ctx.partner_name,
-        });
+        if let Some(k) = &key {
+            if let Some(p) = prior.get(k) {
+                results.push(IngestEntryResult::from_prior(index, p));
+                decisions.push(None);
+                continue;
+            }
+            if let Some(&first) = first_with_key.get(k) {
+                // Same key twice in one batch: report the first entry's outcome
+                repeats.push((index, first));
+                results.push(IngestEntryResult { index, ..Default::default() });
+                decisions.push(None);
+                continue;
+            }
+            first_with_key.insert(k.clone(), index);
+        }
 
-        let params = CreateEventParams {
-            event_type: decision.event_type.clone(),
-            severity: decision.severity.clone(),
-            source_system: "sovereign-shield".into(),
-            regulatory_tags: vec!["GDPR".into()],
-            articles: decision.articles.clone(),
-            payload: payload.clone(),
-            correlation_id: Some(correlation_id.clone()),
-            causation_id: None,
-            source_ip: ctx.source_ip.clone(),
-            source_user_agent: ctx.user_agent.clone(),
+        let source = source_time::resolve(entry.timestamp.as_deref(), entry.received_at, max_skew);
+        let screening = match entry.ctx.partner_name.as_ref().and_then(|p| screenings.get(p)) {
+            Some(result) => result.clone(),
+            None => Ok(None),
         };
+        let (decision, sanctions_match) = snapshot.evaluate_screened(&entry.ctx, entry.ctx.data_categories.as_ref().map(|_| &entry.categories), screening);
+        let mut payload = transfer_payload(&entry.ctx, &entry.categories, &decision, &sanctions_match);
+        // Matched whatever the decision: an allowed transfer nobody declared still needs triage
+        let matched = register.as_ref().map(|r| r.status(&entry.ctx));
+        let (flow_status, declared) = (matched.map(|(status, _)| status), matched.and_then(|(_, id)| id));
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert("source_timestamp".into(), serde_json::json!(entry.timestamp));
+            obj.insert("clock_skew_seconds".into(), serde_json::json!(source.clock_skew_seconds));
+            if entry.country_from_ip {
+                obj.insert("destination_country_source".into(), serde_json::json!("dest_ip"));
+            }
+            if let Some(status) = flow_status {
+                obj.insert("flow_status".into(), serde_json::json!(status));
+                obj.insert("declared_flow_id".into(), serde_json::json!(declared.map(|id| id.to_string())));
+            }
+        }
+        if flow_status == Some(flow_register::UNDECLARED_FLOW) {
+            undeclared.push((index, source.occurred_at));
+        }
+        if flow_window > 0 && decision.decision != Decision::BLOCK {
+            flows.push((index, FlowSample {
+                idempotency_key: key,
+                occurred_at: source.occurred_at,
+                ctx: entry.ctx.clone(),
+                decision: decision.clone(),
+                payload,
+            }));
+        } else {
+            let mut params = transfer_event_params(&entry.ctx, &decision, payload, Some(source.occurred_at));
+            params.idempotency_key = key;
+            events.push((index, params));
+        }
+        results.push(IngestEntryResult {
+            index,
+            decision: Some(decision.decision.to_string()),
+            reason: Some(decision.reason.clone()),
+            occurred_at: Some(source.occurred_at.to_rfc3339()),
+            warning: source.warning,
+            flow_status: flow_status.map(String::from),
+            declared_flow_id: declared.map(|id| id.to_string()),
+            ..Default::default()
+        });
+        decisions.push(Some(decision));
+    }
 
-        match evidence::create_event(pool.get_ref(), params).await {
-            Ok(event_row) => {
-                if decision.decision == Decision::REVIEW {
-                    let action = format!("transfer_data_to_{}", dest_code.to_lowercase());
-                    if let Err(e) = review_queue::create_review(
-                        pool.get_ref(),
-                        "sovereign-shield",
-                        &action,
-                        "sovereign-shield",
-                        &serde_json::json!({
-                            "destination": dest_name,
-                            "destination_country_code": dest_code,
-                            "data_categories": ctx.data_categories,
-                            "reason": decision.reason,
-                        }),
-                        &event_row.event_id,
-                    ).await {
-                        log::error!("Failed to create review for event {}: {}", event_row.event_id, e);
+    let mut events = events.into_iter().peekable();
+    // Entries whose key a concurrent request recorded first
+    let mut lost: Vec<usize> = Vec::new();
+    while events.peek().is_some() {
+        let (indices, chunk): (Vec<usize>, Vec<CreateEventParams>) = events.by_ref().take(INGEST_BATCH_SIZE).unzip();
+        match evidence::create_events_batch(pool, "sovereign-shield", chunk).await {
+            Ok(rows) => {
+                for (index, row) in indices.into_iter().zip(rows) {
+                    match row {
+                        Some(row) => results[index].evidence_id = Some(row.event_id),
+                        None => lost.push(index),
                     }
                 }
-                processed += 1;
             }
             Err(e) => {
-                log::error!("Failed to create evidence event: {}", e);
+                log::error!("Failed to create evidence for {} ingest entries: {}", indices.len(), e);
+                for index in indices {
+                    results[index].error = Some("Failed to create evidence".into());
+                }
+            }
+        }
+    }
+
+    if !lost.is_empty() {
+        let keys: Vec<String> = lost.iter()
+            .filter_map(|&i| entries[i].idempotency_key.clone().ok().flatten())
+            .collect();
+        match idempotency::find_recent(pool, "sovereign-shield", &keys).await {
+            Ok(prior) => {
+                for index in lost {
+                    let key = entries[index].idempotency_key.clone().ok().flatten().unwrap_or_default();
+                    decisions[index] = None;
+                    results[index] = match prior.get(&key) {
+                        Some(p) => IngestEntryResult::from_prior(index, p),
+                        None => IngestEntryResult { index, error: Some("Idempotency key is already in use".into()), ..Default::default() },
+                    };
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to look up {} concurrently recorded ingest entries: {}", lost.len(), e);
+                for index in lost {
+                    results[index].error = Some("Failed to look up idempotency key".into());
+                }
             }
         }
     }
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "processed": processed,
-        "timestamp": Utc::now().to_rfc3339(),
+    // BLOCK decisions always get their own evidence above; the rest are counted per flow and window
+    if !flows.is_empty() {
+        let (indices, samples): (Vec<usize>, Vec<FlowSample>) = flows.into_iter().unzip();
+        match transfer_flows::record(pool, flow_window, &samples).await {
+            Ok(outcomes) => {
+                for (index, outcome) in indices.into_iter().zip(outcomes) {
+                    results[index].flow_id = outcome.flow_id.map(|id| id.to_string());
+                    results[index].replayed = !outcome.counted;
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to record {} ingest entries in transfer flows: {}", indices.len(), e);
+                for index in indices {
+                    results[index].error = Some("Failed to record transfer flow".into());
+                }
+            }
+        }
+    }
+
+    // Undeclared transfers are grouped for triage once their evidence (or flow) is known
+    let sightings: Vec<Sighting> = undeclared.iter()
+        .filter(|(index, _)| results[*index].error.is_none() && !results[*index].replayed)
+        .map(|&(index, occurred_at)| Sighting {
+            ctx: &entries[index].ctx,
+            occurred_at,
+            decision: results[index].decision.clone().unwrap_or_default(),
+            evidence_id: results[index].evidence_id.clone(),
+            flow_id: results[index].flow_id.as_deref().and_then(|id| id.parse().ok()),
+        })
+        .collect();
+    if !sightings.is_empty() {
+        if let Err(e) = flow_register::record_undeclared(pool, &sightings).await {
+            log::error!("Failed to group {} undeclared transfers: {}", sightings.len(), e);
+        }
+    }
+
+    // Reviews reference committed evidence, so they are opened after the batches land
+    let to_review: Vec<(usize, String)> = results.iter().filter_map(|r| {
+            let event_id = r.evidence_id.clone()?;
+            let decision = decisions[r.index].as_ref()?;
+            (decision.decision == Decision::REVIEW).then_some((r.index, event_id))
+        })
+        .collect();
+    let (entries, decisions) = (&entries, &decisions);
+    let reviews: Vec<(usize, Result<String, String>)> = stream::iter(to_review)
+        .map(|(index, event_id)| async move {
+            let decision = decisions[index].as_ref().expect("review candidates have a decision");
+            (index, create_transfer_review(pool, &entries[index].ctx, decision, &event_id).await)
+        })
+        .buffer_unordered(INGEST_CONCURRENCY)
+        .collect()
+        .await;
+    for (index, review) in reviews {
+        match review {
+            Ok(seal_id) => results[index].review_id = Some(seal_id),
+            Err(e) => {
+                log::error!("Failed to create review for ingest entry {}: {}", index, e);
+                results[index].error = Some("Failed to create review".into());
+            }
+        }
+    }
+
+    for (index, first) in repeats {
+        let mut repeated = results[first].clone();
+        repeated.index = index;
+        repeated.replayed = true;
+        results[index] = repeated;
+    }
+
+    Ok(results)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct IngestQuery {
+    /// Evaluate inline and return every result instead of queueing (small batches only).
+    #[serde(default)]
+    pub sync: bool,
+}
+
+impl Validate for IngestQuery {
+    fn validate(&self, _v: &mut Validator) {}
+}
+
+/// Queue a batch of transfer logs for the ingest workers and return 202 with the batch ID; poll
+/// `GET /api/v1/shield/ingest-batches/{id}` for progress. With `?sync=true` the batch is evaluated
+/// inline and the results returned directly.
+/// Entries are deduplicated by `external_id`, or by the `Idempotency-Key` header plus the entry index;
+/// resubmitting a batch with the same `Idempotency-Key` returns the original batch.
+/// With `FLOW_AGGREGATION_WINDOW_SECS` set, ALLOW and REVIEW entries are counted into flows (result
+/// `flow_id`) whose evidence is written once per window; BLOCK entries are always recorded individually.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    params(
+        IngestQuery,
+        ("Idempotency-Key" = Option<String>, Header, description = "Batch replay key; entry N uses `<key>#N`"),
+    ),
+    request_body = Vec<IngestLogEntry>,
+    responses(
+        (status = 202, body = IngestBatchAccepted),
+        (status = 200, body = IngestResponse, description = "`?sync=true`"),
+    ),
+)]
+#[post("/api/v1/shield/ingest-logs")]
+pub async fn ingest_logs(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: ValidQuery<IngestQuery>,
+    body: ValidJson<Vec<IngestLogEntry>>,
+) -> Result<HttpResponse, ApiError> {
+    let pool = pool.get_ref();
+    let batch_key = idempotency_key(&req)?;
+    let entries = body.into_inner();
+    if entries.is_empty() {
+        return Err(ApiError::invalid_field("body", "must contain at least one entry"));
+    }
+
+    if !query.sync {
+        let (batch, created) = ingest_queue::enqueue(pool, entries, batch_key.as_deref())
+            .await
+            .map_err(|e| ApiError::internal("INGEST_ENQUEUE_FAILED", e))?;
+        return Ok(HttpResponse::Accepted().json(IngestBatchAccepted {
+            status_url: format!("/api/v1/shield/ingest-batches/{}", batch.id),
+            batch_id: batch.id,
+            total_entries: batch.total_entries,
+            status: batch.status,
+            replayed: !created,
+        }));
+    }
+
+    let received_at = Utc::now();
+    let items = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| IngestItem {
+            idempotency_key: entry_idempotency_key(&entry, batch_key.as_deref(), index),
+            entry,
+            received_at,
+        })
+        .collect();
+    let results = ingest_entries(pool, items).await?;
+
+    let processed = results.iter().filter(|r| r.evidence_id.is_some() || r.flow_id.is_some()).count();
+    let replayed = results.iter().filter(|r| r.replayed).count();
+    Ok(HttpResponse::Ok().json(IngestResponse {
+        processed,
+        replayed,
+        failed: results.len() - processed,
+        results,
+        timestamp: Utc::now().to_rfc3339(),
     }))
 }
 
-#[get("/api/v1/lenses/sovereign-shield/stats")]
-pub async fn shield_stats(pool: web::Data<PgPool>) -> HttpResponse {
-    let total_transfers: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM evidence_events WHERE source_system = 'sovereign-shield'"
-    )
-    .fetch_one(pool.get_ref())
-    .await
-    .unwrap_or(0);
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestBatchAccepted {
+    pub batch_id: Uuid,
+    pub total_entries: i32,
+    pub status: String,
+    pub status_url: String,
+    /// True when an earlier batch with the same `Idempotency-Key` was returned instead of queueing a new one.
+    pub replayed: bool,
+}
 
-    let blocked_today: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM evidence_events WHERE source_system = 'sovereign-shield' AND event_type = 'DATA_TRANSFER_BLOCKED' AND created_at >= CURRENT_DATE"
-    )
-    .fetch_one(pool.get_ref())
-    .await
-    .unwrap_or(0);
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchStatusQuery {
+    /// Include per-entry results for finished entries.
+    #[serde(default)]
+    pub results: bool,
+}
 
-    let pending_reviews: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM human_oversight WHERE status = 'PENDING'"
-    )
-    .fetch_one(pool.get_ref())
-    .await
-    .unwrap_or(0);
+impl Validate for BatchStatusQuery {
+    fn validate(&self, _v: &mut Validator) {}
+}
 
-    let active_agents: i64 = sqlx::query_scalar(
-        "SELECT COUNT(DISTINCT payload->>'source_ip') FROM evidence_events WHERE source_system = 'sovereign-shield' AND created_at >= NOW() - INTERVAL '24 hours'"
-    )
-    .fetch_one(pool.get_ref())
-    .await
-    .unwrap_or(0);
+/// Progress of a queued ingest batch: entry counts by state and, with `?results=true`, each finished entry's result.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    params(("id" = Uuid, Path, description = "Ingest batch ID"), BatchStatusQuery),
+    responses((status = 200, body = IngestBatchStatus)),
+)]
+#[get("/api/v1/shield/ingest-batches/{id}")]
+pub async fn ingest_batch_status(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    query: ValidQuery<BatchStatusQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let status = ingest_queue::batch_status(pool.get_ref(), path.into_inner(), query.results)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?
+        .ok_or(ApiError::NotFound("Ingest batch"))?;
+    Ok(HttpResponse::Ok().json(status))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FlowListQuery {
+    /// `open` (window still counting or awaiting its evidence), `sealed` or `failed`.
+    pub status: Option<String>,
+    /// Default 100, at most 1000.
+    pub limit: Option<i64>,
+}
+
+impl Validate for FlowListQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("status", self.status.as_deref(), &["open", "sealed", "failed"]);
+        v.range("limit", self.limit, 1, 1000);
+    }
+}
 
+/// Aggregated transfer flows (`FLOW_AGGREGATION_WINDOW_SECS`), newest window first, with their counters,
+/// byte totals and, once sealed, the evidence event and review written for the window.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    params(FlowListQuery),
+    responses((status = 200, body = Vec<TransferFlowRow>)),
+)]
+#[get("/api/v1/shield/flows")]
+pub async fn list_flows(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<FlowListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let flows = transfer_flows::list(pool.get_ref(), query.status.as_deref(), query.limit.unwrap_or(100))
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(flows))
+}
+
+/// The policy the engine evaluates against (taxonomy, country overrides, active SCCs), for
+/// `veridion-api scan-iac --policy` to evaluate infrastructure code without a database.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    responses((status = 200, body = ExportedPolicy)),
+)]
+#[get("/api/v1/shield/policy-snapshot")]
+pub async fn policy_snapshot(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let exported = PolicySnapshot::load(pool.get_ref())
+        .await
+        .map_err(|e| ApiError::internal("POLICY_LOAD_FAILED", e))?
+        .export()
+        .map_err(|e| ApiError::internal("POLICY_EXPORT_FAILED", e))?;
+    Ok(HttpResponse::Ok().json(exported))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttentionItem {
+    pub event_id: Option<String>,
+    pub destination_country: String,
+    pub destination_country_code: String,
+    pub system_name: String,
+    pub occurrence_count: i64,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+    pub decision: Option<String>,
+    pub blocked_reason: Option<String>,
+}
+
+/// Transfers behind a set of evidence events: a sealed flow event stands for its whole window.
+const TRANSFER_COUNT: &str = "SUM(COALESCE((payload->'flow'->>'transfer_count')::bigint, 1))::bigint";
+
+/// Blocked and review-required destinations, most frequent first.
+async fn attention_items(pool: &PgPool) -> Vec<AttentionItem> {
     #[derive(sqlx::FromRow)]
-    struct AttentionRow {
+    struct Row {
         destination_country_code: Option<String>,
         decision: Option<String>,
         occurrence_count: Option<i64>,
@@ -302,63 +965,125 @@ pub async fn shield_stats(pool: web::Data<PgPool>) -> HttpResponse {
         system_name: Option<String>,
     }
 
-    let attention_rows: Vec<AttentionRow> = sqlx::query_as(
+    let rows: Vec<Row> = sqlx::query_as(&format!(
         r#"SELECT
             payload->>'destination_country_code' as destination_country_code,
             payload->>'decision' as decision,
-            COUNT(*) as occurrence_count,
-            MIN(created_at) as first_seen,
-            MAX(created_at) as last_seen,
+            {} as occurrence_count,
+            MIN(occurred_at) as first_seen,
+            MAX(occurred_at) as last_seen,
             MIN(event_id) as event_id,
             MIN(payload->>'source_ip') as system_name
         FROM evidence_events
         WHERE source_system = 'sovereign-shield'
-          AND event_type IN ('DATA_TRANSFER_BLOCKED', 'DATA_TRANSFER_REVIEW')
+          AND event_type IN ('DATA_TRANSFER_BLOCKED', 'DATA_TRANSFER_SANCTIONS_BLOCKED', 'DATA_TRANSFER_REVIEW')
         GROUP BY payload->>'destination_country_code', payload->>'decision'
-        ORDER BY COUNT(*) DESC
-        LIMIT 20"#
-    )
-    .fetch_all(pool.get_ref())
+        ORDER BY 3 DESC
+        LIMIT 20"#,
+        TRANSFER_COUNT
+    ))
+    .fetch_all(pool)
     .await
     .unwrap_or_default();
 
-    let requires_attention: Vec<serde_json::Value> = attention_rows.iter().map(|r| {
-        let code = r.destination_country_code.clone().unwrap_or_default();
-        serde_json::json!({
-            "eventId": r.event_id,
-            "destinationCountry": country_name(&code),
-            "destinationCountryCode": code,
-            "systemName": r.system_name.clone().unwrap_or("unknown".into()),
-            "occurrenceCount": r.occurrence_count.unwrap_or(0),
-            "firstSeen": r.first_seen.map(|t| t.to_rfc3339()),
-            "lastSeen": r.last_seen.map(|t| t.to_rfc3339()),
-            "decision": r.decision,
-        })
-    }).collect();
+    rows.into_iter().map(|r| {
+        let code = r.destination_country_code.unwrap_or_default();
+        AttentionItem {
+            event_id: r.event_id,
+            destination_country: country_name(&code),
+            destination_country_code: code,
+            system_name: r.system_name.unwrap_or("unknown".into()),
+            occurrence_count: r.occurrence_count.unwrap_or(0),
+            first_seen: r.first_seen.map(|t| t.to_rfc3339()),
+            last_seen: r.last_seen.map(|t| t.to_rfc3339()),
+            decision: r.decision,
+            blocked_reason: None,
+        }
+    }).collect()
+}
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "totalTransfers": total_transfers,
-        "activeAdequateCount": 13,
-        "totalAdequateWhitelistCount": 15,
-        "sccCoverage": {
-            "percentage": 0,
-            "trend": 0,
-            "covered": 0,
-            "total": 6
-        },
-        "blockedToday": blocked_today,
-        "pendingApprovals": pending_reviews,
-        "expiringSccs": 0,
-        "dataVolumeToday": 0,
-        "highRiskDestinations": 0,
-        "activeAgents": active_agents,
-        "requiresAttention": requires_attention,
-    }))
+#[derive(Serialize, ToSchema)]
+pub struct SccCoverage {
+    pub percentage: i64,
+    pub trend: i64,
+    pub covered: i64,
+    pub total: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShieldStatsResponse {
+    pub total_transfers: i64,
+    pub active_adequate_count: i64,
+    pub total_adequate_whitelist_count: i64,
+    pub scc_coverage: SccCoverage,
+    pub blocked_today: i64,
+    pub pending_approvals: i64,
+    pub expiring_sccs: i64,
+    pub data_volume_today: i64,
+    pub high_risk_destinations: i64,
+    pub active_agents: i64,
+    pub requires_attention: Vec<AttentionItem>,
+}
+
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    responses((status = 200, body = ShieldStatsResponse)),
+)]
+#[get("/api/v1/lenses/sovereign-shield/stats")]
+pub async fn shield_stats(pool: web::Data<PgPool>) -> HttpResponse {
+    let total_transfers: i64 = sqlx::query_scalar(&format!(
+        "SELECT COALESCE({}, 0) FROM evidence_events WHERE source_system = 'sovereign-shield'",
+        TRANSFER_COUNT
+    ))
+    .fetch_one(pool.get_ref())
+    .await
+    .unwrap_or(0);
+
+    let blocked_today: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM evidence_events WHERE source_system = 'sovereign-shield' AND event_type IN ('DATA_TRANSFER_BLOCKED', 'DATA_TRANSFER_SANCTIONS_BLOCKED') AND occurred_at >= CURRENT_DATE"
+    )
+    .fetch_one(pool.get_ref())
+    .await
+    .unwrap_or(0);
+
+    let pending_reviews: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM human_oversight WHERE status = 'PENDING'"
+    )
+    .fetch_one(pool.get_ref())
+    .await
+    .unwrap_or(0);
+
+    let active_agents: i64 = sqlx::query_scalar(
+        "SELECT COUNT(DISTINCT payload->>'source_ip') FROM evidence_events WHERE source_system = 'sovereign-shield' AND occurred_at >= NOW() - INTERVAL '24 hours'"
+    )
+    .fetch_one(pool.get_ref())
+    .await
+    .unwrap_or(0);
+
+    HttpResponse::Ok().json(ShieldStatsResponse {
+        total_transfers,
+        active_adequate_count: 13,
+        total_adequate_whitelist_count: 15,
+        scc_coverage: SccCoverage { percentage: 0, trend: 0, covered: 0, total: 6 },
+        blocked_today,
+        pending_approvals: pending_reviews,
+        expiring_sccs: 0,
+        data_volume_today: 0,
+        high_risk_destinations: 0,
+        active_agents,
+        requires_attention: attention_items(pool.get_ref()).await,
+    })
 }
 
+/// Effective classification per country, with transfer counts.
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    responses((status = 200, body = Vec<Object>)),
+)]
 #[get("/api/v1/lenses/sovereign-shield/countries")]
 pub async fn shield_countries(pool: web::Data<PgPool>) -> HttpResponse {
-    let mut countries = all_country_classifications();
+    let mut countries = country_policy::effective_classifications(pool.get_ref()).await;
 
     #[derive(sqlx::FromRow)]
     struct CountryTransferCount {
@@ -366,15 +1091,16 @@ pub async fn shield_countries(pool: web::Data<PgPool>) -> HttpResponse {
         transfer_count: Option<i64>,
     }
 
-    let counts: Vec<CountryTransferCount> = sqlx::query_as(
+    let counts: Vec<CountryTransferCount> = sqlx::query_as(&format!(
         r#"SELECT
             payload->>'destination_country_code' as country_code,
-            COUNT(*) as transfer_count
+            {} as transfer_count
         FROM evidence_events
         WHERE source_system = 'sovereign-shield'
           AND payload->>'destination_country_code' IS NOT NULL
-        GROUP BY payload->>'destination_country_code'"#
-    )
+        GROUP BY payload->>'destination_country_code'"#,
+        TRANSFER_COUNT
+    ))
     .fetch_all(pool.get_ref())
     .await
     .unwrap_or_default();
@@ -394,57 +1120,27 @@ pub async fn shield_countries(pool: web::Data<PgPool>) -> HttpResponse {
     HttpResponse::Ok().json(countries)
 }
 
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    responses((status = 200, body = Vec<AttentionItem>)),
+)]
 #[get("/api/v1/lenses/sovereign-shield/requires-attention")]
 pub async fn shield_requires_attention(pool: web::Data<PgPool>) -> HttpResponse {
-    #[derive(sqlx::FromRow)]
-    struct Row {
-        destination_country_code: Option<String>,
-        decision: Option<String>,
-        occurrence_count: Option<i64>,
-        first_seen: Option<chrono::DateTime<chrono::Utc>>,
-        last_seen: Option<chrono::DateTime<chrono::Utc>>,
-        event_id: Option<String>,
-        system_name: Option<String>,
-    }
-
-    let rows: Vec<Row> = sqlx::query_as(
-        r#"SELECT
-            payload->>'destination_country_code' as destination_country_code,
-            payload->>'decision' as decision,
-            COUNT(*) as occurrence_count,
-            MIN(created_at) as first_seen,
-            MAX(created_at) as last_seen,
-            MIN(event_id) as event_id,
-            MIN(payload->>'source_ip') as system_name
-        FROM evidence_events
-        WHERE source_system = 'sovereign-shield'
-          AND event_type IN ('DATA_TRANSFER_BLOCKED', 'DATA_TRANSFER_REVIEW')
-        GROUP BY payload->>'destination_country_code', payload->>'decision'
-        ORDER BY COUNT(*) DESC
-        LIMIT 20"#
-    )
-    .fetch_all(pool.get_ref())
-    .await
-    .unwrap_or_default();
-
-    let items: Vec<serde_json::Value> = rows.iter().map(|r| {
-        let code = r.destination_country_code.clone().unwrap_or_default();
-        serde_json::json!({
-            "eventId": r.event_id,
-            "destinationCountry": country_name(&code),
-            "destinationCountryCode": code,
-            "systemName": r.system_name.clone().unwrap_or("unknown".into()),
-            "occurrenceCount": r.occurrence_count.unwrap_or(0),
-            "firstSeen": r.first_seen.map(|t| t.to_rfc3339()),
-            "lastSeen": r.last_seen.map(|t| t.to_rfc3339()),
-            "decision": r.decision,
-            "blockedReason": null,
-        })
-    }).collect();
+    HttpResponse::Ok().json(attention_items(pool.get_ref()).await)
+}
 
-    HttpResponse::Ok().json(items)
+#[derive(Serialize, ToSchema)]
+pub struct DestinationCount {
+    pub destination: Option<String>,
+    /// `Adequate`, `SCC`, `Blocked` or `Unknown`.
+    pub status: String,
+    pub count: i64,
 }
 
+#[utoipa::path(
+    tag = "Sovereign Shield",
+    responses((status = 200, body = Vec<DestinationCount>)),
+)]
 #[get("/api/v1/lenses/sovereign-shield/transfers/by-destination")]
 pub async fn transfers_by_destination(pool: web::Data<PgPool>) -> HttpResponse {
     #[derive(sqlx::FromRow)]
@@ -454,44 +1150,48 @@ pub async fn transfers_by_destination(pool: web::Data<PgPool>) -> HttpResponse {
         count: Option<i64>,
     }
 
-    let rows: Vec<Row> = sqlx::query_as(
+    let rows: Vec<Row> = sqlx::query_as(&format!(
         r#"SELECT
             payload->>'destination_country' as destination,
             payload->>'country_status' as status,
-            COUNT(*) as count
+            {} as count
         FROM evidence_events
         WHERE source_system = 'sovereign-shield'
           AND payload->>'destination_country' IS NOT NULL
         GROUP BY payload->>'destination_country', payload->>'country_status'
-        ORDER BY COUNT(*) DESC
-        LIMIT 20"#
-    )
+        ORDER BY 3 DESC
+        LIMIT 20"#,
+        TRANSFER_COUNT
+    ))
     .fetch_all(pool.get_ref())
     .await
     .unwrap_or_default();
 
-    let items: Vec<serde_json::Value> = rows.iter().map(|r| {
+    let items: Vec<DestinationCount> = rows.into_iter().map(|r| {
         let status_label = match r.status.as_deref() {
             Some("adequate_protection") | Some("eu_eea") => "Adequate",
             Some("scc_required") => "SCC",
             Some("blocked") => "Blocked",
             _ => "Unknown",
         };
-        serde_json::json!({
-            "destination": r.destination,
-            "status": status_label,
-            "count": r.count.unwrap_or(0),
-        })
+        DestinationCount {
+            destination: r.destination,
+            status: status_label.into(),
+            count: r.count.unwrap_or(0),
+        }
     }).collect();
 
     HttpResponse::Ok().json(items)
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SccRegistryRequest {
+    #[schema(min_length = 1, max_length = 500)]
     pub partner_name: String,
+    #[schema(pattern = "^[A-Za-z]{2}$")]
     pub destination_country_code: String,
+    #[schema(format = DateTime)]
     pub expires_at: Option<String>,
     pub notes: Option<String>,
     #[serde(default)]
@@ -500,7 +1200,16 @@ pub struct SccRegistryRequest {
     pub scc_module: Option<String>,
 }
 
-#[derive(sqlx::FromRow, Serialize)]
+impl Validate for SccRegistryRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("partnerName", &self.partner_name);
+        v.max_len("partnerName", Some(&self.partner_name), 500);
+        v.country_code("destinationCountryCode", Some(&self.destination_country_code));
+        v.rfc3339("expiresAt", self.expires_at.as_deref());
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SccRegistryRow {
     pub id: Uuid,
@@ -521,17 +1230,31 @@ pub struct SccRegistryRow {
     pub scc_module: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SccRegistryPatchRequest {
     pub tia_completed: Option<bool>,
 }
 
+impl Validate for SccRegistryPatchRequest {
+    fn validate(&self, v: &mut Validator) {
+        if self.tia_completed.is_none() {
+            v.error("tiaCompleted", "no updatable fields provided");
+        }
+    }
+}
+
+/// Register an SCC; pending reviews for transfers it covers are approved automatically.
+#[utoipa::path(
+    tag = "SCC registry",
+    request_body = SccRegistryRequest,
+    responses((status = 201, body = SccRegistryRow)),
+)]
 #[post("/api/v1/scc-registries")]
 pub async fn register_scc(
     pool: web::Data<PgPool>,
-    body: web::Json<SccRegistryRequest>,
-) -> HttpResponse {
+    body: ValidJson<SccRegistryRequest>,
+) -> Result<HttpResponse, ApiError> {
     let expires_at = body.expires_at.as_ref().and_then(|s| {
         chrono::DateTime::parse_from_rfc3339(s)
             .ok()
@@ -539,7 +1262,7 @@ pub async fn register_scc(
     });
 
     let dest_upper = body.destination_country_code.to_uppercase();
-    let row = match sqlx::query_as::<_, SccRegistryRow>(
+    let row = sqlx::query_as::<_, SccRegistryRow>(
         r#"INSERT INTO scc_registries 
            (partner_name, destination_country_code, status, expires_at, registered_by, notes, tia_completed, dpa_id, scc_module)
            VALUES ($1, $2, 'active', $3, 'admin', $4, $5, $6, $7)
@@ -553,15 +1276,8 @@ pub async fn register_scc(
     .bind(&body.dpa_id)
     .bind(&body.scc_module)
     .fetch_one(pool.get_ref())
-    .await {
-        Ok(r) => r,
-        Err(e) => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "REGISTRATION_FAILED",
-                "message": format!("Failed to register SCC: {}", e),
-            }));
-        }
-    };
+    .await
+    .map_err(|e| ApiError::internal("REGISTRATION_FAILED", e))?;
 
     // Auto-approve pending review items whose transfer matches this SCC (destination country)
     if let Ok(n) = review_queue::approve_pending_reviews_for_scc(
@@ -576,172 +1292,124 @@ pub async fn register_scc(
         }
     }
 
-    HttpResponse::Created().json(serde_json::json!({
-        "id": row.id.to_string(),
-        "partnerName": row.partner_name,
-        "destinationCountryCode": row.destination_country_code,
-        "status": row.status,
-        "expiresAt": row.expires_at.map(|t| t.to_rfc3339()),
-        "registeredBy": row.registered_by,
-        "registeredAt": row.registered_at.to_rfc3339(),
-        "notes": row.notes,
-        "createdAt": row.created_at.to_rfc3339(),
-        "updatedAt": row.updated_at.to_rfc3339(),
-        "tiaCompleted": row.tia_completed,
-        "dpaId": row.dpa_id,
-        "sccModule": row.scc_module,
-    }))
+    Ok(HttpResponse::Created().json(row))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SccRegistryListResponse {
+    pub registries: Vec<SccRegistryRow>,
+    pub total: usize,
 }
 
+#[utoipa::path(
+    tag = "SCC registry",
+    responses((status = 200, body = SccRegistryListResponse)),
+)]
 #[get("/api/v1/scc-registries")]
 pub async fn list_scc_registries(
     pool: web::Data<PgPool>,
-) -> HttpResponse {
-    let rows: Vec<SccRegistryRow> = match sqlx::query_as::<_, SccRegistryRow>(
+) -> Result<HttpResponse, ApiError> {
+    let registries = sqlx::query_as::<_, SccRegistryRow>(
         "SELECT * FROM scc_registries ORDER BY registered_at DESC"
     )
     .fetch_all(pool.get_ref())
-    .await {
-        Ok(r) => r,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "QUERY_FAILED",
-                "message": format!("Failed to list SCC registries: {}", e),
-            }));
-        }
-    };
-
-    let items: Vec<serde_json::Value> = rows.iter().map(|r| {
-        serde_json::json!({
-            "id": r.id.to_string(),
-            "partnerName": r.partner_name,
-            "destinationCountryCode": r.destination_country_code,
-            "status": r.status,
-            "expiresAt": r.expires_at.map(|t| t.to_rfc3339()),
-            "registeredBy": r.registered_by,
-            "registeredAt": r.registered_at.to_rfc3339(),
-            "notes": r.notes,
-            "createdAt": r.created_at.to_rfc3339(),
-            "updatedAt": r.updated_at.to_rfc3339(),
-            "tiaCompleted": r.tia_completed,
-            "dpaId": r.dpa_id,
-            "sccModule": r.scc_module,
-        })
-    }).collect();
+    .await
+    .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
 
-    HttpResponse::Ok().json(serde_json::json!({
-        "registries": items,
-        "total": items.len(),
-    }))
+    let total = registries.len();
+    Ok(HttpResponse::Ok().json(SccRegistryListResponse { registries, total }))
 }
 
-#[derive(Deserialize)]
-pub struct SccRegistryPath {
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SccPatchResponse {
+    pub success: bool,
     pub id: String,
+    pub tia_completed: bool,
 }
 
+#[utoipa::path(
+    tag = "SCC registry",
+    params(("id" = Uuid, Path, description = "SCC registry ID")),
+    request_body = SccRegistryPatchRequest,
+    responses((status = 200, body = SccPatchResponse)),
+)]
 #[patch("/api/v1/scc-registries/{id}")]
 pub async fn patch_scc_registry(
     pool: web::Data<PgPool>,
-    path: web::Path<SccRegistryPath>,
-    body: web::Json<SccRegistryPatchRequest>,
-) -> HttpResponse {
-    let id = match Uuid::parse_str(&path.id) {
-        Ok(u) => u,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "INVALID_ID",
-                "message": "Invalid UUID format",
-            }));
-        }
-    };
+    path: web::Path<Uuid>,
+    body: ValidJson<SccRegistryPatchRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let tia_completed = body.tia_completed.unwrap_or_default();
 
-    if let Some(tia_completed) = body.tia_completed {
-        let result = sqlx::query(
-            "UPDATE scc_registries SET tia_completed = $1, updated_at = NOW() WHERE id = $2 AND status = 'active'"
-        )
-        .bind(tia_completed)
-        .bind(id)
-        .execute(pool.get_ref())
-        .await;
+    let result = sqlx::query(
+        "UPDATE scc_registries SET tia_completed = $1, updated_at = NOW() WHERE id = $2 AND status = 'active'"
+    )
+    .bind(tia_completed)
+    .bind(id)
+    .execute(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal("PATCH_FAILED", e))?;
 
-        match result {
-            Ok(res) if res.rows_affected() > 0 => {
-                return HttpResponse::Ok().json(serde_json::json!({
-                    "success": true,
-                    "id": path.id,
-                    "tiaCompleted": tia_completed,
-                }));
-            }
-            Ok(_) => {
-                return HttpResponse::NotFound().json(serde_json::json!({
-                    "error": "NOT_FOUND",
-                    "message": "SCC registry not found or already revoked",
-                }));
-            }
-            Err(e) => {
-                return HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "PATCH_FAILED",
-                    "message": format!("Failed to update SCC: {}", e),
-                }));
-            }
-        }
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound("Active SCC registry"));
     }
-
-    HttpResponse::BadRequest().json(serde_json::json!({
-        "error": "INVALID_REQUEST",
-        "message": "No updatable fields provided",
+    Ok(HttpResponse::Ok().json(SccPatchResponse {
+        success: true,
+        id: id.to_string(),
+        tia_completed,
     }))
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct SccRevokeResponse {
+    pub success: bool,
+    pub id: String,
+    pub status: String,
+}
+
+#[utoipa::path(
+    tag = "SCC registry",
+    params(("id" = Uuid, Path, description = "SCC registry ID")),
+    responses((status = 200, body = SccRevokeResponse)),
+)]
 #[delete("/api/v1/scc-registries/{id}")]
 pub async fn revoke_scc(
     pool: web::Data<PgPool>,
-    path: web::Path<SccRegistryPath>,
-) -> HttpResponse {
-    let id = match Uuid::parse_str(&path.id) {
-        Ok(u) => u,
-        Err(_) => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "INVALID_ID",
-                "message": "Invalid UUID format",
-            }));
-        }
-    };
-
-    let result = sqlx::query(
-        "UPDATE scc_registries SET status = 'revoked' WHERE id = $1 AND status = 'active'"
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let revoked: Option<(String, String)> = sqlx::query_as(
+        "UPDATE scc_registries SET status = 'revoked' WHERE id = $1 AND status = 'active' RETURNING partner_name, destination_country_code"
     )
     .bind(id)
-    .execute(pool.get_ref())
-    .await;
-
-    match result {
-        Ok(res) if res.rows_affected() > 0 => {
-            HttpResponse::Ok().json(serde_json::json!({
-                "success": true,
-                "id": path.id,
-                "status": "revoked",
-            }))
-        }
-        Ok(_) => {
-            HttpResponse::NotFound().json(serde_json::json!({
-                "error": "NOT_FOUND",
-                "message": "SCC registry not found or already revoked",
-            }))
-        }
-        Err(e) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "REVOKE_FAILED",
-                "message": format!("Failed to revoke SCC: {}", e),
-            }))
-        }
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(|e| ApiError::internal("REVOKE_FAILED", e))?;
+
+    let Some((partner_name, country_code)) = revoked else {
+        return Err(ApiError::NotFound("Active SCC registry"));
+    };
+    let reason = format!("SCC {} revoked", id);
+    match transfer_permits::revoke_for_scc(pool.get_ref(), &partner_name, &country_code, &reason).await {
+        Ok(n) if n > 0 => log::info!("SCC {} revoked: {} transfer permit(s) revoked", id, n),
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to revoke transfer permits for SCC {}: {}", id, e),
     }
+    Ok(HttpResponse::Ok().json(SccRevokeResponse {
+        success: true,
+        id: id.to_string(),
+        status: "revoked".into(),
+    }))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(evaluate)
        .service(ingest_logs)
+       .service(ingest_batch_status)
+       .service(list_flows)
+       .service(policy_snapshot)
        .service(shield_stats)
        .service(shield_countries)
        .service(shield_requires_attention)
@@ -0,0 +1,78 @@
+use actix_web::{web, HttpRequest, HttpResponse, get};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+
+use crate::api_error::ApiError;
+use crate::live_stream::{self, LiveFilter, LiveHub};
+use crate::validation::{ValidQuery, Validate, Validator};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct StreamQuery {
+    /// Comma-separated: `evidence`, `review.created`, `review.decided`.
+    pub kind: Option<String>,
+    #[serde(alias = "source_system")]
+    pub source_system: Option<String>,
+    #[serde(alias = "event_type")]
+    pub event_type: Option<String>,
+    /// For clients that cannot set the `Last-Event-ID` header on the first connection.
+    #[serde(alias = "last_event_id")]
+    #[param(minimum = 0)]
+    pub last_event_id: Option<i64>,
+}
+
+impl Validate for StreamQuery {
+    fn validate(&self, v: &mut Validator) {
+        for kind in LiveFilter::parse_list(self.kind.as_deref()) {
+            v.one_of("kind", Some(&kind), &["evidence", "review.created", "review.decided"]);
+        }
+        v.range("lastEventId", self.last_event_id, 0, i64::MAX);
+    }
+}
+
+/// Server-Sent Events feed of evidence events, review creations and review decisions.
+/// Reconnecting with `Last-Event-ID` replays what was missed before switching to live events.
+#[utoipa::path(
+    tag = "Stream",
+    params(
+        StreamQuery,
+        ("Last-Event-ID" = Option<i64>, Header, description = "Resume after this event ID; takes priority over `lastEventId`"),
+    ),
+    responses((status = 200, content_type = "text/event-stream", body = crate::live_stream::LiveEvent,
+        description = "One `data:` frame per event, JSON-encoded, with `id:` set for resumption")),
+)]
+#[get("/api/v1/stream/events")]
+pub async fn stream_events(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    hub: web::Data<LiveHub>,
+    query: ValidQuery<StreamQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .or(query.last_event_id);
+
+    let filter = LiveFilter {
+        kinds: LiveFilter::parse_list(query.kind.as_deref()),
+        source_systems: LiveFilter::parse_list(query.source_system.as_deref()),
+        event_types: LiveFilter::parse_list(query.event_type.as_deref()),
+    };
+
+    let body = live_stream::subscribe_stream(pool.get_ref().clone(), hub.get_ref(), filter, last_event_id)
+        .await
+        .map_err(|e| ApiError::internal("QUERY_FAILED", e))?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(stream_events);
+}
@@ -0,0 +1,298 @@
+use actix_web::{web, HttpResponse, get, post, patch, delete};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::validation::{ValidJson, ValidQuery, Validate, Validator};
+use crate::webhooks::{
+    self, CreateSubscriptionParams, UpdateSubscriptionParams, WebhookDeadLetterRow, WebhookDeliveryRow,
+    WebhookSubscriptionRow,
+};
+
+const SEVERITIES: &[&str] = &["L1", "L2", "L3", "L4"];
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSubscriptionRequest {
+    #[schema(min_length = 1, max_length = 255)]
+    pub name: String,
+    #[schema(format = "uri")]
+    pub url: String,
+    /// Signing secret; generated when omitted. Only returned in the create response.
+    pub secret: Option<String>,
+    /// Empty or omitted filters match everything.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub severities: Vec<String>,
+    #[serde(default)]
+    pub source_systems: Vec<String>,
+    pub created_by: Option<String>,
+}
+
+impl Validate for CreateSubscriptionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.required("name", &self.name);
+        v.max_len("name", Some(&self.name), 255);
+        v.http_url("url", Some(&self.url));
+        for s in &self.severities {
+            v.one_of("severities", Some(&s.trim().to_uppercase()), SEVERITIES);
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSubscriptionRequest {
+    #[schema(format = "uri")]
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub severities: Option<Vec<String>>,
+    pub source_systems: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+impl Validate for UpdateSubscriptionRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.http_url("url", self.url.as_deref());
+        for s in self.severities.iter().flatten() {
+            v.one_of("severities", Some(&s.trim().to_uppercase()), SEVERITIES);
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Deliveries only: `pending`, `delivering` or `delivered`.
+    pub status: Option<String>,
+    #[param(minimum = 1, maximum = 1000)]
+    pub limit: Option<i64>,
+}
+
+impl Validate for ListQuery {
+    fn validate(&self, v: &mut Validator) {
+        v.one_of("status", self.status.as_deref(), &["pending", "delivering", "delivered"]);
+        v.range("limit", self.limit, 1, 1000);
+    }
+}
+
+fn query_failed(e: String) -> ApiError {
+    ApiError::internal("QUERY_FAILED", e)
+}
+
+fn normalise(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+}
+
+/// A subscription as returned on creation: the only response that includes the signing secret.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedSubscriptionResponse {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscriptionRow,
+    pub secret: String,
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    request_body = CreateSubscriptionRequest,
+    responses((status = 201, body = CreatedSubscriptionResponse)),
+)]
+#[post("/api/v1/webhooks/subscriptions")]
+pub async fn create_subscription(
+    pool: web::Data<PgPool>,
+    body: ValidJson<CreateSubscriptionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let body = body.into_inner();
+    webhooks::validate_url(&body.url).await.map_err(|e| ApiError::invalid_field("url", e))?;
+    let params = CreateSubscriptionParams {
+        name: body.name,
+        url: body.url,
+        secret: body.secret,
+        event_types: normalise(body.event_types),
+        severities: normalise(body.severities).into_iter().map(|s| s.to_uppercase()).collect(),
+        source_systems: normalise(body.source_systems),
+        created_by: Some(body.created_by.unwrap_or_else(|| "admin".into())),
+    };
+
+    let (subscription, secret) = webhooks::create_subscription(pool.get_ref(), params)
+        .await
+        .map_err(|e| ApiError::internal("CREATE_FAILED", e))?;
+    Ok(HttpResponse::Created().json(CreatedSubscriptionResponse { subscription, secret }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SubscriptionListResponse {
+    pub subscriptions: Vec<WebhookSubscriptionRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    responses((status = 200, body = SubscriptionListResponse)),
+)]
+#[get("/api/v1/webhooks/subscriptions")]
+pub async fn list_subscriptions(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let subscriptions = webhooks::list_subscriptions(pool.get_ref()).await.map_err(query_failed)?;
+    let total = subscriptions.len();
+    Ok(HttpResponse::Ok().json(SubscriptionListResponse { subscriptions, total }))
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    params(("id" = Uuid, Path, description = "Subscription ID")),
+    request_body = UpdateSubscriptionRequest,
+    responses((status = 200, body = WebhookSubscriptionRow)),
+)]
+#[patch("/api/v1/webhooks/subscriptions/{id}")]
+pub async fn update_subscription(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+    body: ValidJson<UpdateSubscriptionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let body = body.into_inner();
+    if let Some(url) = &body.url {
+        webhooks::validate_url(url).await.map_err(|e| ApiError::invalid_field("url", e))?;
+    }
+    let params = UpdateSubscriptionParams {
+        url: body.url,
+        event_types: body.event_types.map(normalise),
+        severities: body.severities.map(|s| normalise(s).into_iter().map(|v| v.to_uppercase()).collect()),
+        source_systems: body.source_systems.map(normalise),
+        active: body.active,
+    };
+
+    let sub = webhooks::update_subscription(pool.get_ref(), id, params)
+        .await
+        .map_err(|e| ApiError::internal("UPDATE_FAILED", e))?
+        .ok_or(ApiError::NotFound("Subscription"))?;
+    Ok(HttpResponse::Ok().json(sub))
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    params(("id" = Uuid, Path, description = "Subscription ID")),
+    responses((status = 204, description = "Deleted")),
+)]
+#[delete("/api/v1/webhooks/subscriptions/{id}")]
+pub async fn delete_subscription(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    if !webhooks::delete_subscription(pool.get_ref(), path.into_inner()).await.map_err(query_failed)? {
+        return Err(ApiError::NotFound("Subscription"));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeliveryListResponse {
+    pub deliveries: Vec<WebhookDeliveryRow>,
+    pub total: usize,
+}
+
+/// Outbox entries: `pending`, `delivering` or `delivered`.
+#[utoipa::path(
+    tag = "Webhooks",
+    params(ListQuery),
+    responses((status = 200, body = DeliveryListResponse)),
+)]
+#[get("/api/v1/webhooks/deliveries")]
+pub async fn list_deliveries(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let limit = query.limit.unwrap_or(100);
+    let deliveries = webhooks::list_deliveries(pool.get_ref(), query.status.as_deref(), limit)
+        .await
+        .map_err(query_failed)?;
+    let total = deliveries.len();
+    Ok(HttpResponse::Ok().json(DeliveryListResponse { deliveries, total }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeliveryResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_letter_id: Option<Uuid>,
+    pub delivery_id: Uuid,
+    pub status: String,
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    params(("id" = Uuid, Path, description = "Delivery ID")),
+    responses((status = 202, body = RedeliveryResponse)),
+)]
+#[post("/api/v1/webhooks/deliveries/{id}/redeliver")]
+pub async fn redeliver(pool: web::Data<PgPool>, path: web::Path<Uuid>) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    if !webhooks::redeliver(pool.get_ref(), id).await.map_err(query_failed)? {
+        return Err(ApiError::NotFound("Delivery"));
+    }
+    Ok(HttpResponse::Accepted().json(RedeliveryResponse {
+        dead_letter_id: None,
+        delivery_id: id,
+        status: "pending".into(),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetterListResponse {
+    pub dead_letters: Vec<WebhookDeadLetterRow>,
+    pub total: usize,
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    params(ListQuery),
+    responses((status = 200, body = DeadLetterListResponse)),
+)]
+#[get("/api/v1/webhooks/dead-letters")]
+pub async fn list_dead_letters(
+    pool: web::Data<PgPool>,
+    query: ValidQuery<ListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let dead_letters = webhooks::list_dead_letters(pool.get_ref(), query.limit.unwrap_or(100))
+        .await
+        .map_err(query_failed)?;
+    let total = dead_letters.len();
+    Ok(HttpResponse::Ok().json(DeadLetterListResponse { dead_letters, total }))
+}
+
+#[utoipa::path(
+    tag = "Webhooks",
+    params(("id" = Uuid, Path, description = "Dead letter ID")),
+    responses((status = 202, body = RedeliveryResponse)),
+)]
+#[post("/api/v1/webhooks/dead-letters/{id}/redeliver")]
+pub async fn redeliver_dead_letter(
+    pool: web::Data<PgPool>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+    let delivery_id = webhooks::redeliver_dead_letter(pool.get_ref(), id)
+        .await
+        .map_err(query_failed)?
+        .ok_or(ApiError::NotFound("Dead letter"))?;
+    Ok(HttpResponse::Accepted().json(RedeliveryResponse {
+        dead_letter_id: Some(id),
+        delivery_id,
+        status: "pending".into(),
+    }))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_subscription)
+       .service(list_subscriptions)
+       .service(update_subscription)
+       .service(delete_subscription)
+       .service(list_deliveries)
+       .service(redeliver)
+       .service(list_dead_letters)
+       .service(redeliver_dead_letter);
+}
@@ -0,0 +1,618 @@
+use serde::Serialize;
+use sha2::{Sha256, Digest};
+use sqlx::PgPool;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::evidence::{self, CreateEventParams};
+use crate::shield::{Decision, TransferDecision};
+
+const SOURCE_SYSTEM: &str = "sanctions-screening";
+
+/// Legal-form tokens ignored when comparing entity names ("Acme Ltd" == "ACME Limited").
+const LEGAL_SUFFIXES: &[&str] = &[
+    "ltd", "limited", "llc", "inc", "incorporated", "corp", "corporation", "co", "company",
+    "plc", "gmbh", "ag", "sa", "sas", "srl", "bv", "nv", "oy", "ab", "as", "jsc", "ojsc", "pjsc",
+    "cjsc", "ooo", "oao", "zao", "llp", "lp", "the", "of",
+];
+
+fn match_threshold() -> f64 {
+    std::env::var("SANCTIONS_MATCH_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.88)
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SanctionsListRow {
+    pub id: Uuid,
+    pub source_name: String,
+    pub format: String,
+    pub content_hash: String,
+    pub entry_count: i32,
+    pub active: bool,
+    pub loaded_by: Option<String>,
+    pub evidence_event_id: Option<String>,
+    pub loaded_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SanctionsEntry {
+    pub entity_ref: String,
+    pub name: String,
+    pub subject_type: Option<String>,
+    pub programme: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SanctionsMatch {
+    pub list_id: String,
+    pub list_source: String,
+    pub entity_ref: String,
+    pub matched_name: String,
+    pub screened_name: String,
+    pub subject_type: Option<String>,
+    pub programme: Option<String>,
+    pub score: f64,
+}
+
+/// Lowercase, strip punctuation and legal suffixes, and sort tokens so word order does not matter.
+pub fn normalise_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let mut tokens: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty() && !LEGAL_SUFFIXES.contains(t))
+        .collect();
+    tokens.sort_unstable();
+    tokens.dedup();
+    tokens.join(" ")
+}
+
+/// Candidate lookup keys: every token plus its 4-character prefix, so a typo late in a word still finds the entry.
+fn name_keys(normalised: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    for token in normalised.split(' ').filter(|t| t.chars().count() >= 3) {
+        keys.push(token.to_string());
+        let prefix: String = token.chars().take(4).collect();
+        keys.push(format!("p:{}", prefix));
+    }
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Similarity in [0, 1] between two normalised names.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / max_len as f64
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn xml_attr(tag: &str, name: &str) -> Option<String> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let end = tag[start..].find('"')? + start;
+    let value = xml_unescape(&tag[start..end]);
+    (!value.trim().is_empty()).then(|| value.trim().to_string())
+}
+
+/// Opening tags named `name` inside `block` (e.g. every `<nameAlias .../>`).
+fn xml_tags<'a>(block: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{}", name);
+    let mut tags = Vec::new();
+    let mut rest = block;
+    while let Some(pos) = rest.find(&open) {
+        let after = &rest[pos + open.len()..];
+        if !after.starts_with([' ', '>', '/', '\n', '\t', '\r']) {
+            rest = after;
+            continue;
+        }
+        let end = after.find('>').map(|e| e + 1).unwrap_or(after.len());
+        tags.push(&rest[pos..pos + open.len() + end]);
+        rest = &after[end..];
+    }
+    tags
+}
+
+/// Parse the EU Financial Sanctions Files (FSF) XML: one entry per `nameAlias` of each `sanctionEntity`.
+pub fn parse_xml(content: &str) -> Vec<SanctionsEntry> {
+    let mut entries = Vec::new();
+    for block in content.split("<sanctionEntity").skip(1) {
+        let block = block.split("</sanctionEntity>").next().unwrap_or(block);
+        let opening = format!("<sanctionEntity{}", &block[..block.find('>').unwrap_or(block.len())]);
+        let entity_ref = xml_attr(&opening, "logicalId")
+            .or_else(|| xml_attr(&opening, "euReferenceNumber"))
+            .unwrap_or_default();
+        let programme = xml_tags(block, "regulation").first().and_then(|t| xml_attr(t, "programme"));
+        let subject_type = xml_tags(block, "subjectType").first().and_then(|t| xml_attr(t, "code"));
+
+        for alias in xml_tags(block, "nameAlias") {
+            if let Some(name) = xml_attr(alias, "wholeName") {
+                entries.push(SanctionsEntry {
+                    entity_ref: entity_ref.clone(),
+                    name,
+                    subject_type: subject_type.clone(),
+                    programme: programme.clone(),
+                });
+            }
+        }
+    }
+    entries
+}
+
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut cols = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => cols.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    cols.push(current);
+    cols.into_iter().map(|c| c.trim().to_string()).collect()
+}
+
+/// Parse the EU consolidated list CSV (semicolon-separated, `NameAlias_WholeName` etc.)
+/// or a simple CSV with `name`, `id`, `subject_type` and `programme` columns.
+pub fn parse_csv(content: &str) -> Result<Vec<SanctionsEntry>, String> {
+    let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().ok_or("CSV file is empty")?;
+    let header = header.trim_start_matches('\u{feff}');
+    let delimiter = if header.contains(';') { ';' } else { ',' };
+    let columns: Vec<String> = split_csv_line(header, delimiter).into_iter().map(|c| c.to_lowercase()).collect();
+    let find = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+
+    let name_idx = find(&["namealias_wholename", "wholename", "whole_name", "name"])
+        .ok_or("CSV must have a NameAlias_WholeName or name column")?;
+    let id_idx = find(&["entity_logicalid", "logicalid", "entity_id", "id", "eu_reference_number"]);
+    let subject_idx = find(&["entity_subjecttype", "subject_type", "subjecttype", "type"]);
+    let programme_idx = find(&["entity_regulation_programme", "programme", "program"]);
+
+    let mut entries = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let cols = split_csv_line(line, delimiter);
+        let get = |idx: Option<usize>| idx.and_then(|i| cols.get(i)).filter(|v| !v.is_empty()).cloned();
+        let Some(name) = get(Some(name_idx)) else {
+            continue;
+        };
+        entries.push(SanctionsEntry {
+            entity_ref: get(id_idx).unwrap_or_else(|| format!("row-{}", i + 2)),
+            name,
+            subject_type: get(subject_idx),
+            programme: get(programme_idx),
+        });
+    }
+    Ok(entries)
+}
+
+/// Parse a list file in `xml` (EU consolidated) or `csv` format; an empty list is an error.
+pub fn parse_entries(content: &str, format: &str) -> Result<Vec<SanctionsEntry>, String> {
+    let entries = match format {
+        "xml" => parse_xml(content),
+        "csv" => parse_csv(content)?,
+        other => return Err(format!("Unsupported format: {}", other)),
+    };
+    if entries.is_empty() {
+        return Err("No sanctions entries found in file".into());
+    }
+    Ok(entries)
+}
+
+pub struct LoadListParams {
+    pub source_name: String,
+    pub format: String,
+    pub content: String,
+    pub loaded_by: Option<String>,
+}
+
+/// Load a sanctions list and make it the active one. The previous list is deactivated in the same
+/// transaction so screening never runs against a half-loaded list.
+pub async fn load_list(pool: &PgPool, params: LoadListParams) -> Result<SanctionsListRow, String> {
+    let format = params.format.to_lowercase();
+    let entries = parse_entries(&params.content, &format)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(params.content.as_bytes());
+    let content_hash = format!("{:x}", hasher.finalize());
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut list = sqlx::query_as::<_, SanctionsListRow>(
+        r#"INSERT INTO sanctions_lists (source_name, format, content_hash, entry_count, active, loaded_by)
+           VALUES ($1, $2, $3, $4, FALSE, $5)
+           RETURNING *"#
+    )
+    .bind(&params.source_name)
+    .bind(&format)
+    .bind(&content_hash)
+    .bind(entries.len() as i32)
+    .bind(&params.loaded_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create sanctions list: {}", e))?;
+
+    for chunk in entries.chunks(500) {
+        let mut refs = Vec::with_capacity(chunk.len());
+        let mut names = Vec::with_capacity(chunk.len());
+        let mut normalised = Vec::with_capacity(chunk.len());
+        let mut keys = Vec::with_capacity(chunk.len());
+        let mut subjects = Vec::with_capacity(chunk.len());
+        let mut programmes = Vec::with_capacity(chunk.len());
+        for e in chunk {
+            let n = normalise_name(&e.name);
+            // Postgres cannot unnest a ragged 2-D array, so keys travel as one space-joined string
+            keys.push(name_keys(&n).join(" "));
+            normalised.push(n);
+            refs.push(e.entity_ref.clone());
+            names.push(e.name.clone());
+            subjects.push(e.subject_type.clone());
+            programmes.push(e.programme.clone());
+        }
+        sqlx::query(
+            r#"INSERT INTO sanctions_entries (list_id, entity_ref, name, normalised_name, name_keys, subject_type, programme)
+               SELECT $1, r, n, nn, string_to_array(NULLIF(k, ''), ' '), s, p
+               FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[]) AS t(r, n, nn, k, s, p)"#
+        )
+        .bind(list.id)
+        .bind(&refs)
+        .bind(&names)
+        .bind(&normalised)
+        .bind(&keys)
+        .bind(&subjects)
+        .bind(&programmes)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to insert sanctions entries: {}", e))?;
+    }
+
+    sqlx::query("UPDATE sanctions_lists SET active = (id = $1)")
+        .bind(list.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to activate sanctions list: {}", e))?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    list.active = true;
+
+    match evidence::create_event(pool, CreateEventParams {
+        event_type: "SANCTIONS_LIST_LOADED".into(),
+        severity: "L1".into(),
+        source_system: SOURCE_SYSTEM.into(),
+        regulatory_tags: vec!["EU_SANCTIONS".into()],
+        articles: vec![],
+        payload: serde_json::json!({
+            "list_id": list.id.to_string(),
+            "source_name": list.source_name,
+            "format": list.format,
+            "content_hash": list.content_hash,
+            "entry_count": list.entry_count,
+        }),
+        correlation_id: Some(list.id.to_string()),
+        causation_id: None,
+        source_ip: None,
+        source_user_agent: None,
+        occurred_at: None,
+        idempotency_key: None,
+    }).await {
+        Ok(event) => {
+            let _ = sqlx::query("UPDATE sanctions_lists SET evidence_event_id = $1 WHERE id = $2")
+                .bind(&event.event_id)
+                .bind(list.id)
+                .execute(pool)
+                .await;
+            list.evidence_event_id = Some(event.event_id);
+        }
+        Err(e) => log::error!("Failed to create evidence for sanctions list {}: {}", list.id, e),
+    }
+
+    Ok(list)
+}
+
+pub async fn list_lists(pool: &PgPool) -> Result<Vec<SanctionsListRow>, String> {
+    sqlx::query_as::<_, SanctionsListRow>("SELECT * FROM sanctions_lists ORDER BY loaded_at DESC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Screen a partner name against the active sanctions list. Returns the best match above the threshold.
+#[derive(sqlx::FromRow)]
+struct Candidate {
+    list_id: Uuid,
+    source_name: String,
+    entity_ref: String,
+    name: String,
+    normalised_name: String,
+    subject_type: Option<String>,
+    programme: Option<String>,
+}
+
+/// Highest-scoring candidate at or above `threshold`; a name that normalises to nothing matches nothing.
+fn best_candidate(normalised: &str, candidates: Vec<Candidate>, threshold: f64) -> Option<(f64, Candidate)> {
+    if normalised.is_empty() {
+        return None;
+    }
+    candidates.into_iter()
+        .map(|c| {
+            let score = similarity(normalised, &c.normalised_name);
+            (score, c)
+        })
+        .filter(|(score, _)| *score >= threshold)
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+pub async fn screen_name(pool: &PgPool, name: &str) -> Result<Option<SanctionsMatch>, String> {
+    let normalised = normalise_name(name);
+    if normalised.is_empty() {
+        return Ok(None);
+    }
+    let keys = name_keys(&normalised);
+
+    let candidates: Vec<Candidate> = sqlx::query_as(
+        r#"SELECT se.list_id, sl.source_name, se.entity_ref, se.name, se.normalised_name, se.subject_type, se.programme
+           FROM sanctions_entries se
+           JOIN sanctions_lists sl ON sl.id = se.list_id
+           WHERE sl.active = TRUE
+             AND (se.normalised_name = $1 OR se.name_keys && $2)"#
+    )
+    .bind(&normalised)
+    .bind(&keys)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to screen sanctions list: {}", e))?;
+
+    let best = best_candidate(&normalised, candidates, match_threshold());
+
+    Ok(best.map(|(score, c)| SanctionsMatch {
+        list_id: c.list_id.to_string(),
+        list_source: c.source_name,
+        entity_ref: c.entity_ref,
+        matched_name: c.name,
+        screened_name: name.to_string(),
+        subject_type: c.subject_type,
+        programme: c.programme,
+        score: (score * 1000.0).round() / 1000.0,
+    }))
+}
+
+/// BLOCK decision for a sanctioned transfer partner. Takes precedence over any country-based outcome.
+pub fn blocked_decision(hit: &SanctionsMatch, country_status: &str) -> TransferDecision {
+    let mut articles = vec!["GDPR Art. 44".to_string()];
+    if let Some(programme) = &hit.programme {
+        articles.push(format!("EU restrictive measures ({})", programme));
+    }
+    TransferDecision {
+        decision: Decision::BLOCK,
+        reason: format!(
+            "Partner '{}' matches sanctioned entity '{}' ({}) — transfer prohibited",
+            hit.screened_name, hit.matched_name, hit.entity_ref
+        ),
+        severity: "L3".into(),
+        articles,
+        event_type: "DATA_TRANSFER_SANCTIONS_BLOCKED".into(),
+        country_status: country_status.to_string(),
+    }
+}
+
+/// `veridion-api load-sanctions-list <file> [--format xml|csv] [--source NAME]`
+pub async fn run_cli(pool: &PgPool, args: &[String]) -> std::io::Result<()> {
+    let mut path: Option<&str> = None;
+    let mut format: Option<String> = None;
+    let mut source: Option<String> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = iter.next().cloned(),
+            "--source" => source = iter.next().cloned(),
+            other => path = Some(other),
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!("Usage: veridion-api load-sanctions-list <file> [--format xml|csv] [--source NAME]");
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing file"));
+    };
+    let content = std::fs::read_to_string(path)?;
+    let format = format.unwrap_or_else(|| {
+        if path.to_lowercase().ends_with(".csv") { "csv".into() } else { "xml".into() }
+    });
+
+    let params = LoadListParams {
+        source_name: source.unwrap_or_else(|| path.to_string()),
+        format,
+        content,
+        loaded_by: Some("cli".into()),
+    };
+    match load_list(pool, params).await {
+        Ok(list) => {
+            println!("Loaded sanctions list {} ({} entries, sha256 {})", list.id, list.entry_count, list.content_hash);
+            Ok(())
+        }
+        Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(entity_ref: &str, name: &str) -> Candidate {
+        Candidate {
+            list_id: Uuid::nil(),
+            source_name: "EU FSF".into(),
+            entity_ref: entity_ref.into(),
+            name: name.into(),
+            normalised_name: normalise_name(name),
+            subject_type: Some("enterprise".into()),
+            programme: None,
+        }
+    }
+
+    #[test]
+    fn names_normalise() {
+        let cases = [
+            ("ACME Limited", "acme"),
+            ("Limited Acme", "acme"),
+            ("Acme Ltd.", "acme"),
+            ("Bank of Moscow, PJSC", "bank moscow"),
+            ("Moscow Bank", "bank moscow"),
+            ("OOO \"Rosneft\" Rosneft", "rosneft"),
+            ("Müller GmbH & Co", "müller"),
+            ("The Company Ltd", ""),
+            ("  --  ", ""),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(normalise_name(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn similarity_scores() {
+        let cases = [
+            ("ACME Limited", "Limited Acme", 1.0, 1.0),
+            // One inserted letter in "oil rosneft"
+            ("Rosneeft Oil Company", "Rosneft Oil Company", 0.88, 0.99),
+            ("Sberbank", "Sberbnak", 0.7, 0.8),
+            ("Apex Trading", "Acme Trading", 0.0, 0.88),
+            ("Acme", "", 0.0, 0.0),
+        ];
+        for (a, b, min, max) in cases {
+            let score = similarity(&normalise_name(a), &normalise_name(b));
+            assert!(score >= min && score <= max, "{} vs {}: {}", a, b, score);
+        }
+        assert!(similarity("oil rosneeft", "oil rosneft") > 0.88);
+        assert!(similarity("acme trading", "apex trading") < 0.88);
+    }
+
+    #[test]
+    fn typos_share_a_lookup_key() {
+        let listed = name_keys(&normalise_name("Rosneft Oil Company"));
+        let screened = name_keys(&normalise_name("Rosneeft Oil"));
+        assert!(listed.contains(&"p:rosn".to_string()));
+        assert!(screened.iter().any(|k| listed.contains(k)));
+        // Tokens under three characters are too common to look up by
+        assert!(name_keys("ab cd").is_empty());
+    }
+
+    #[test]
+    fn screening_picks_the_best_match_above_the_threshold() {
+        let candidates = || vec![
+            candidate("EU.1", "Rosneft Oil Company"),
+            candidate("EU.2", "Rosneeft Oil Trading"),
+            candidate("EU.3", "Apex Trading LLC"),
+            candidate("EU.4", "The Company Ltd"),
+        ];
+        let cases = [
+            ("Rosneft Oil Co", Some("EU.1")),
+            ("OIL ROSNEEFT", Some("EU.1")),
+            ("Acme Trading Ltd", None),
+            ("Ltd.", None),
+            ("", None),
+        ];
+        for (name, expected) in cases {
+            let best = best_candidate(&normalise_name(name), candidates(), 0.88);
+            assert_eq!(best.as_ref().map(|(_, c)| c.entity_ref.as_str()), expected, "{:?}", name);
+        }
+        let (score, _) = best_candidate("oil rosneeft", candidates(), 0.88).unwrap();
+        assert!(score > 0.88 && score < 1.0);
+    }
+
+    #[test]
+    fn xml_entities_and_aliases() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<export generationDate="2026-01-01T00:00:00">
+  <sanctionEntity designationDetails="" euReferenceNumber="EU.27.28" logicalId="13">
+    <regulation regulationType="amendment" programme="UKR" numberTitle="2014/833"/>
+    <subjectType code="enterprise" classificationCode="E"/>
+    <nameAlias firstName="" wholeName="Rosneft Oil Company" nameLanguage="EN"/>
+    <nameAlias firstName="" wholeName="OAO &quot;Rosneft&quot;" nameLanguage="RU"></nameAlias>
+    <nameAliasNote wholeName="not an alias"/>
+    <nameAlias firstName="" wholeName="  " nameLanguage=""/>
+  </sanctionEntity>
+  <sanctionEntity euReferenceNumber="EU.99.1">
+    <subjectType code="person"/>
+    <nameAlias wholeName="Smith &amp; Sons &lt;Trading&gt;"/>
+  </sanctionEntity>
+</export>"#;
+        let entries = parse_xml(xml);
+        let got: Vec<(&str, &str, Option<&str>, Option<&str>)> = entries.iter()
+            .map(|e| (e.entity_ref.as_str(), e.name.as_str(), e.subject_type.as_deref(), e.programme.as_deref()))
+            .collect();
+        assert_eq!(got, [
+            ("13", "Rosneft Oil Company", Some("enterprise"), Some("UKR")),
+            ("13", "OAO \"Rosneft\"", Some("enterprise"), Some("UKR")),
+            ("EU.99.1", "Smith & Sons <Trading>", Some("person"), None),
+        ]);
+        assert_eq!(xml_unescape("&amp;quot;"), "&quot;");
+    }
+
+    #[test]
+    fn eu_csv_with_quoted_fields() {
+        let csv = "\u{feff}Entity_LogicalId;Entity_SubjectType;Entity_Regulation_Programme;NameAlias_WholeName\n\
+                   13;enterprise;UKR;\"Rosneft; Oil Company\"\n\
+                   13;enterprise;UKR;\"OAO \"\"Rosneft\"\"\"\n\
+                   \n\
+                   14;person;;\n\
+                   ;person;RUS; Ivan Petrov \n";
+        let entries = parse_csv(csv).unwrap();
+        let got: Vec<(&str, &str, Option<&str>, Option<&str>)> = entries.iter()
+            .map(|e| (e.entity_ref.as_str(), e.name.as_str(), e.subject_type.as_deref(), e.programme.as_deref()))
+            .collect();
+        assert_eq!(got, [
+            ("13", "Rosneft; Oil Company", Some("enterprise"), Some("UKR")),
+            ("13", "OAO \"Rosneft\"", Some("enterprise"), Some("UKR")),
+            ("row-5", "Ivan Petrov", Some("person"), Some("RUS")),
+        ]);
+    }
+
+    #[test]
+    fn simple_csv_and_errors() {
+        let entries = parse_csv("name,programme\n\"Acme, Ltd\",IRN\n").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].entity_ref.as_str(), entries[0].name.as_str()), ("row-2", "Acme, Ltd"));
+        assert_eq!(entries[0].programme.as_deref(), Some("IRN"));
+
+        assert!(parse_csv("\n\n").is_err());
+        assert!(parse_csv("id,country\n1,IR\n").unwrap_err().contains("name column"));
+        assert!(parse_entries("name\n", "csv").is_err());
+        assert!(parse_entries("<export/>", "xml").is_err());
+        assert!(parse_entries("name\nAcme\n", "json").is_err());
+    }
+}
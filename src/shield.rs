@@ -1,5 +1,13 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+use crate::country_policy;
+use crate::data_categories::{self, DataCategoryRow, NormalisedCategories, Taxonomy};
+use crate::sanctions::{self, SanctionsMatch};
 
 // Country classification per GDPR Art. 44-49
 const EU_EEA: &[&str] = &[
@@ -18,46 +26,8 @@ const SCC_REQUIRED: &[&str] = &[
 
 const BLOCKED: &[&str] = &["CN","RU","KP","IR","SY","BY"];
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum Decision {
-    ALLOW,
-    BLOCK,
-    REVIEW,
-}
-
-impl std::fmt::Display for Decision {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Decision::ALLOW => write!(f, "ALLOW"),
-            Decision::BLOCK => write!(f, "BLOCK"),
-            Decision::REVIEW => write!(f, "REVIEW"),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransferContext {
-    pub destination_country_code: Option<String>,
-    pub destination_country: Option<String>,
-    pub data_categories: Option<Vec<String>>,
-    pub partner_name: Option<String>,
-    pub source_ip: Option<String>,
-    pub dest_ip: Option<String>,
-    pub data_size: Option<u64>,
-    pub protocol: Option<String>,
-    pub user_agent: Option<String>,
-    pub request_path: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct TransferDecision {
-    pub decision: Decision,
-    pub reason: String,
-    pub severity: String,
-    pub articles: Vec<String>,
-    pub event_type: String,
-    pub country_status: String,
-}
+/// Request and decision types are shared with the `veridion-client` crate.
+pub use veridion_client::models::{Decision, TransferContext, TransferDecision};
 
 pub fn country_name(code: &str) -> String {
     match code.to_uppercase().as_str() {
@@ -254,168 +224,398 @@ pub fn evaluate_transfer(ctx: &TransferContext) -> TransferDecision {
     }
 }
 
-pub async fn check_scc_exists(
-    pool: &PgPool,
-    partner_name: &str,
-    destination_country_code: &str,
-) -> Result<bool, String> {
-    let count: i64 = sqlx::query_scalar(
-        r#"SELECT COUNT(*) FROM scc_registries 
-           WHERE partner_name = $1 
-             AND destination_country_code = $2 
-             AND status = 'active' 
-             AND (expires_at IS NULL OR expires_at > NOW())"#
-    )
-    .bind(partner_name)
-    .bind(destination_country_code.to_uppercase())
-    .fetch_one(pool)
-    .await
-    .map_err(|e| format!("Failed to check SCC: {}", e))?;
-
-    Ok(count > 0)
+/// Format version of `ExportedPolicy`; bumped when a field's meaning changes.
+pub const EXPORTED_POLICY_VERSION: u32 = 1;
+
+/// A `PolicySnapshot` written out for evaluation without the database (CI scans of infrastructure
+/// code). Sanctions lists are not included, so partners are not screened against them.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPolicy {
+    pub version: u32,
+    pub exported_at: String,
+    pub data_categories: Vec<DataCategoryRow>,
+    /// Country code to classification, for countries whose status differs from the built-in lists.
+    pub country_overrides: BTreeMap<String, String>,
+    /// Active SCCs by partner and destination country.
+    pub sccs: Vec<ExportedScc>,
 }
 
-pub async fn evaluate_transfer_with_db(
-    pool: &PgPool,
-    ctx: &TransferContext,
-) -> Result<TransferDecision, String> {
-    let code = match &ctx.destination_country_code {
-        Some(c) if !c.is_empty() => c.to_uppercase(),
-        _ => {
-            return Ok(TransferDecision {
-                decision: Decision::REVIEW,
-                reason: "Missing destination country — cannot evaluate transfer".into(),
-                severity: "L2".into(),
-                articles: vec!["GDPR Art. 44".into()],
-                event_type: "DATA_TRANSFER_REVIEW".into(),
-                country_status: "unknown".into(),
-            });
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedScc {
+    pub partner_name: String,
+    pub destination_country_code: String,
+}
+
+/// Active SCCs by (partner_name, country_code), with their expiry (None never expires).
+type SccRegistry = HashMap<(String, String), Option<DateTime<Utc>>>;
+
+/// Everything the engine reads from the database, loaded once so a batch of transfers
+/// can be evaluated without per-entry round-trips.
+pub struct PolicySnapshot {
+    taxonomy: Taxonomy,
+    overrides: HashMap<String, String>,
+    /// None when the registry could not be read.
+    sccs: Option<SccRegistry>,
+}
+
+/// Last complete snapshot and the `policy_version` it was loaded at.
+static SNAPSHOT_CACHE: Mutex<Option<(i64, Arc<PolicySnapshot>)>> = Mutex::new(None);
+
+impl PolicySnapshot {
+    /// The snapshot for the current `policy_version`, reloaded only when the taxonomy, overrides or SCC
+    /// registry changed since it was cached. Degraded snapshots are never cached.
+    pub async fn load(pool: &PgPool) -> Result<Arc<Self>, String> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT version FROM policy_version")
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| log::error!("Policy version lookup error: {}", e))
+            .ok()
+            .flatten();
+        if let Some(version) = version {
+            let cache = SNAPSHOT_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((cached, snapshot)) = cache.as_ref() {
+                if *cached == version {
+                    return Ok(snapshot.clone());
+                }
+            }
         }
-    };
 
-    let has_personal_data = ctx.data_categories
-        .as_ref()
-        .map(|cats| !cats.is_empty())
-        .unwrap_or(false);
+        let (snapshot, complete) = Self::load_uncached(pool).await?;
+        let snapshot = Arc::new(snapshot);
+        if let (Some(version), true) = (version, complete) {
+            *SNAPSHOT_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((version, snapshot.clone()));
+        }
+        Ok(snapshot)
+    }
 
-    if ctx.data_categories.is_none() {
-        return Ok(TransferDecision {
-            decision: Decision::REVIEW,
-            reason: "Missing data categories — cannot determine if personal data is involved".into(),
-            severity: "L2".into(),
-            articles: vec!["GDPR Art. 44".into()],
-            event_type: "DATA_TRANSFER_REVIEW".into(),
-            country_status: classify_country(&code).into(),
-        });
+    /// Load the taxonomy, country overrides and active SCCs, and whether all three were read. Override
+    /// and SCC failures degrade the same way single lookups do: built-in lists for countries, REVIEW for
+    /// SCC-dependent transfers. A taxonomy failure is an error: without it Art. 9 / Art. 10 categories
+    /// would go unrecognised.
+    async fn load_uncached(pool: &PgPool) -> Result<(Self, bool), String> {
+        let taxonomy = data_categories::load_taxonomy(pool)
+            .await
+            .map_err(|e| format!("Taxonomy load error: {}", e))?;
+        let overrides = country_policy::load_overrides(pool)
+            .await
+            .map_err(|e| log::error!("{}", e))
+            .ok();
+        let sccs = sqlx::query_as::<_, (String, String, Option<DateTime<Utc>>)>(
+            r#"SELECT partner_name, destination_country_code, expires_at FROM scc_registries
+               WHERE status = 'active'
+                 AND (expires_at IS NULL OR expires_at > NOW())"#
+        )
+        .fetch_all(pool)
+        .await
+        .map(|rows| {
+            let mut sccs = HashMap::new();
+            for (partner, code, expires_at) in rows {
+                // Several registrations for a pair: the one lasting longest counts
+                let expiry = sccs.entry((partner, code.to_uppercase())).or_insert(expires_at);
+                if expiry.is_some() && expires_at.is_none_or(|e| Some(e) > *expiry) {
+                    *expiry = expires_at;
+                }
+            }
+            sccs
+        })
+        .map_err(|e| log::error!("SCC lookup error: {}", e))
+        .ok();
+
+        let complete = overrides.is_some() && sccs.is_some();
+        Ok((PolicySnapshot { taxonomy, overrides: overrides.unwrap_or_default(), sccs }, complete))
     }
 
-    let classification = classify_country(&code);
+    /// An SCC for the pair that has not expired since the snapshot was loaded.
+    fn has_scc(sccs: &SccRegistry, partner: &str, code: &str) -> bool {
+        sccs.get(&(partner.to_string(), code.to_string()))
+            .is_some_and(|expires_at| expires_at.is_none_or(|e| e > Utc::now()))
+    }
 
-    match classification {
-        "eu_eea" => Ok(TransferDecision {
-            decision: Decision::ALLOW,
-            reason: format!("{} is EU/EEA — no transfer restrictions", country_name(&code)),
-            severity: "L1".into(),
-            articles: vec![],
-            event_type: "DATA_TRANSFER".into(),
-            country_status: "eu_eea".into(),
-        }),
+    /// Portable copy of this snapshot. Fails when the SCC registry could not be read, since an export
+    /// without it would turn every SCC-covered transfer into a REVIEW.
+    pub fn export(&self) -> Result<ExportedPolicy, String> {
+        let sccs = self.sccs.as_ref().ok_or("SCC registry could not be read")?;
+        let mut sccs: Vec<ExportedScc> = sccs.keys()
+            .filter(|(partner, code)| Self::has_scc(sccs, partner, code))
+            .map(|(partner, code)| ExportedScc { partner_name: partner.clone(), destination_country_code: code.clone() })
+            .collect();
+        sccs.sort();
+        Ok(ExportedPolicy {
+            version: EXPORTED_POLICY_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            data_categories: self.taxonomy.entries().to_vec(),
+            country_overrides: self.overrides.iter().map(|(c, s)| (c.clone(), s.clone())).collect(),
+            sccs,
+        })
+    }
 
-        "adequate_protection" => Ok(TransferDecision {
-            decision: Decision::ALLOW,
-            reason: format!("{} has EU adequacy decision", country_name(&code)),
-            severity: "L1".into(),
-            articles: vec!["GDPR Art. 45".into()],
-            event_type: "DATA_TRANSFER".into(),
-            country_status: "adequate_protection".into(),
-        }),
+    pub fn from_export(exported: ExportedPolicy) -> Result<Self, String> {
+        if exported.version != EXPORTED_POLICY_VERSION {
+            return Err(format!("unsupported policy snapshot version {} (expected {})", exported.version, EXPORTED_POLICY_VERSION));
+        }
+        Ok(PolicySnapshot {
+            taxonomy: Taxonomy::new(exported.data_categories),
+            overrides: exported.country_overrides.into_iter().map(|(c, s)| (c.to_uppercase(), s)).collect(),
+            sccs: Some(exported.sccs.into_iter()
+                .map(|s| ((s.partner_name, s.destination_country_code.to_uppercase()), None))
+                .collect()),
+        })
+    }
 
-        "blocked" => Ok(TransferDecision {
-            decision: Decision::BLOCK,
-            reason: format!("{} is blocked — no legal transfer mechanism available", country_name(&code)),
-            severity: "L3".into(),
-            articles: vec!["GDPR Art. 44".into(), "GDPR Art. 46".into()],
-            event_type: "DATA_TRANSFER_BLOCKED".into(),
-            country_status: "blocked".into(),
-        }),
+    /// Classification currently in force for a country: override if present, otherwise the built-in list.
+    pub fn country_status(&self, code: &str) -> String {
+        let upper = code.to_uppercase();
+        self.overrides
+            .get(&upper)
+            .cloned()
+            .unwrap_or_else(|| classify_country(&upper).to_string())
+    }
 
-        "scc_required" => {
-            if !has_personal_data {
-                return Ok(TransferDecision {
-                    decision: Decision::ALLOW,
-                    reason: format!("Transfer to {} — no personal data involved", country_name(&code)),
-                    severity: "L1".into(),
-                    articles: vec![],
-                    event_type: "DATA_TRANSFER".into(),
-                    country_status: "scc_required".into(),
-                });
+    /// Rewrite the context's data categories to canonical taxonomy codes before evaluation and evidence.
+    pub fn normalise_context(&self, ctx: &mut TransferContext) -> Option<NormalisedCategories> {
+        let normalised = self.taxonomy.normalise(ctx.data_categories.as_ref()?);
+        ctx.data_categories = Some(normalised.categories.clone());
+        Some(normalised)
+    }
+
+    /// Evaluate a transfer against the country classification, SCC registry and data-category taxonomy.
+    /// Categories are normalised first so synonyms and Art. 9 / Art. 10 flags are honoured.
+    pub fn evaluate(&self, ctx: &TransferContext) -> TransferDecision {
+        let categories = ctx.data_categories.as_ref().map(|c| self.taxonomy.normalise(c));
+        self.evaluate_normalised(ctx, categories.as_ref())
+    }
+
+    /// `evaluate` for a context already passed through `normalise_context`, with its result.
+    fn evaluate_normalised(&self, ctx: &TransferContext, categories: Option<&NormalisedCategories>) -> TransferDecision {
+        let decision = self.evaluate_country(ctx, categories);
+        match categories {
+            Some(c) => escalate_sensitive_categories(decision, c),
+            None => decision,
+        }
+    }
+
+    /// Combine a sanctions screening outcome with the policy evaluation of a context normalised by
+    /// `normalise_context` (`categories` is what it returned). A hit always blocks; a named partner
+    /// that could not be screened is never allowed without review.
+    pub fn evaluate_screened(
+        &self,
+        ctx: &TransferContext,
+        categories: Option<&NormalisedCategories>,
+        screening: Result<Option<SanctionsMatch>, String>,
+    ) -> (TransferDecision, Option<SanctionsMatch>) {
+        let screening_failed = match screening {
+            Ok(Some(hit)) => {
+                let status = match ctx.destination_country_code.as_deref() {
+                    Some(c) if !c.is_empty() => self.country_status(c),
+                    _ => "unknown".to_string(),
+                };
+                return (sanctions::blocked_decision(&hit, &status), Some(hit));
             }
-            // Check SCC in database
-            let partner = ctx.partner_name.as_deref().unwrap_or("");
-            if partner.is_empty() {
-                return Ok(TransferDecision {
-                    decision: Decision::REVIEW,
-                    reason: format!("{} requires SCC — partner name required to verify SCC", country_name(&code)),
-                    severity: "L2".into(),
-                    articles: vec!["GDPR Art. 46".into()],
-                    event_type: "DATA_TRANSFER_REVIEW".into(),
-                    country_status: "scc_required".into(),
-                });
+            Ok(None) => false,
+            Err(e) => {
+                log::error!("Sanctions screening error: {}", e);
+                true
             }
-            match check_scc_exists(pool, partner, &code).await {
-                Ok(true) => Ok(TransferDecision {
-                    decision: Decision::ALLOW,
-                    reason: format!("Transfer to {} — valid SCC in place for {}", country_name(&code), partner),
-                    severity: "L1".into(),
-                    articles: vec!["GDPR Art. 46".into()],
-                    event_type: "DATA_TRANSFER".into(),
-                    country_status: "scc_required".into(),
-                }),
-                Ok(false) => Ok(TransferDecision {
+        };
+
+        let decision = self.evaluate_normalised(ctx, categories);
+        if screening_failed && decision.decision == Decision::ALLOW {
+            return (TransferDecision {
+                decision: Decision::REVIEW,
+                reason: format!("{} — sanctions screening unavailable, requires human review", decision.reason),
+                severity: "L2".into(),
+                articles: decision.articles,
+                event_type: "DATA_TRANSFER_REVIEW".into(),
+                country_status: decision.country_status,
+            }, None);
+        }
+        (decision, None)
+    }
+
+    fn evaluate_country(
+        &self,
+        ctx: &TransferContext,
+        categories: Option<&NormalisedCategories>,
+    ) -> TransferDecision {
+        let code = match &ctx.destination_country_code {
+            Some(c) if !c.is_empty() => c.to_uppercase(),
+            _ => {
+                return TransferDecision {
                     decision: Decision::REVIEW,
-                    reason: format!("{} requires SCC — no active SCC found for {}", country_name(&code), partner),
+                    reason: "Missing destination country — cannot evaluate transfer".into(),
                     severity: "L2".into(),
-                    articles: vec!["GDPR Art. 46".into()],
+                    articles: vec!["GDPR Art. 44".into()],
                     event_type: "DATA_TRANSFER_REVIEW".into(),
-                    country_status: "scc_required".into(),
-                }),
-                Err(e) => {
-                    log::error!("SCC lookup error: {}", e);
-                    Ok(TransferDecision {
+                    country_status: "unknown".into(),
+                };
+            }
+        };
+
+        let has_personal_data = categories
+            .map(|cats| cats.has_personal_data())
+            .unwrap_or(false);
+
+        // Scheduled changes and approved imports override the built-in lists
+        let classification = self.country_status(&code);
+
+        if ctx.data_categories.is_none() {
+            return TransferDecision {
+                decision: Decision::REVIEW,
+                reason: "Missing data categories — cannot determine if personal data is involved".into(),
+                severity: "L2".into(),
+                articles: vec!["GDPR Art. 44".into()],
+                event_type: "DATA_TRANSFER_REVIEW".into(),
+                country_status: classification,
+            };
+        }
+
+        match classification.as_str() {
+            "eu_eea" => TransferDecision {
+                decision: Decision::ALLOW,
+                reason: format!("{} is EU/EEA — no transfer restrictions", country_name(&code)),
+                severity: "L1".into(),
+                articles: vec![],
+                event_type: "DATA_TRANSFER".into(),
+                country_status: "eu_eea".into(),
+            },
+
+            "adequate_protection" => TransferDecision {
+                decision: Decision::ALLOW,
+                reason: format!("{} has EU adequacy decision", country_name(&code)),
+                severity: "L1".into(),
+                articles: vec!["GDPR Art. 45".into()],
+                event_type: "DATA_TRANSFER".into(),
+                country_status: "adequate_protection".into(),
+            },
+
+            "blocked" => TransferDecision {
+                decision: Decision::BLOCK,
+                reason: format!("{} is blocked — no legal transfer mechanism available", country_name(&code)),
+                severity: "L3".into(),
+                articles: vec!["GDPR Art. 44".into(), "GDPR Art. 46".into()],
+                event_type: "DATA_TRANSFER_BLOCKED".into(),
+                country_status: "blocked".into(),
+            },
+
+            "scc_required" => {
+                if !has_personal_data {
+                    return TransferDecision {
+                        decision: Decision::ALLOW,
+                        reason: format!("Transfer to {} — no personal data involved", country_name(&code)),
+                        severity: "L1".into(),
+                        articles: vec![],
+                        event_type: "DATA_TRANSFER".into(),
+                        country_status: "scc_required".into(),
+                    };
+                }
+                let partner = ctx.partner_name.as_deref().unwrap_or("");
+                if partner.is_empty() {
+                    return TransferDecision {
+                        decision: Decision::REVIEW,
+                        reason: format!("{} requires SCC — partner name required to verify SCC", country_name(&code)),
+                        severity: "L2".into(),
+                        articles: vec!["GDPR Art. 46".into()],
+                        event_type: "DATA_TRANSFER_REVIEW".into(),
+                        country_status: "scc_required".into(),
+                    };
+                }
+                match &self.sccs {
+                    Some(sccs) if Self::has_scc(sccs, partner, &code) => TransferDecision {
+                        decision: Decision::ALLOW,
+                        reason: format!("Transfer to {} — valid SCC in place for {}", country_name(&code), partner),
+                        severity: "L1".into(),
+                        articles: vec!["GDPR Art. 46".into()],
+                        event_type: "DATA_TRANSFER".into(),
+                        country_status: "scc_required".into(),
+                    },
+                    Some(_) => TransferDecision {
+                        decision: Decision::REVIEW,
+                        reason: format!("{} requires SCC — no active SCC found for {}", country_name(&code), partner),
+                        severity: "L2".into(),
+                        articles: vec!["GDPR Art. 46".into()],
+                        event_type: "DATA_TRANSFER_REVIEW".into(),
+                        country_status: "scc_required".into(),
+                    },
+                    None => TransferDecision {
                         decision: Decision::REVIEW,
                         reason: format!("{} requires SCC — unable to verify SCC status", country_name(&code)),
                         severity: "L2".into(),
                         articles: vec!["GDPR Art. 46".into()],
                         event_type: "DATA_TRANSFER_REVIEW".into(),
                         country_status: "scc_required".into(),
-                    })
+                    },
                 }
-            }
-        },
+            },
 
-        _ => {
-            if !has_personal_data {
-                return Ok(TransferDecision {
-                    decision: Decision::ALLOW,
-                    reason: format!("Transfer to {} — no personal data involved", country_name(&code)),
-                    severity: "L1".into(),
-                    articles: vec![],
-                    event_type: "DATA_TRANSFER".into(),
+            _ => {
+                if !has_personal_data {
+                    return TransferDecision {
+                        decision: Decision::ALLOW,
+                        reason: format!("Transfer to {} — no personal data involved", country_name(&code)),
+                        severity: "L1".into(),
+                        articles: vec![],
+                        event_type: "DATA_TRANSFER".into(),
+                        country_status: "unknown".into(),
+                    };
+                }
+                TransferDecision {
+                    decision: Decision::REVIEW,
+                    reason: format!("{} — unknown jurisdiction, requires human review", country_name(&code)),
+                    severity: "L2".into(),
+                    articles: vec!["GDPR Art. 44".into()],
+                    event_type: "DATA_TRANSFER_REVIEW".into(),
                     country_status: "unknown".into(),
-                });
+                }
             }
-            Ok(TransferDecision {
-                decision: Decision::REVIEW,
-                reason: format!("{} — unknown jurisdiction, requires human review", country_name(&code)),
-                severity: "L2".into(),
-                articles: vec!["GDPR Art. 44".into()],
-                event_type: "DATA_TRANSFER_REVIEW".into(),
-                country_status: "unknown".into(),
-            })
         }
     }
 }
 
+/// Screen a partner name; transfers without a named partner have nothing to screen.
+pub async fn screen_partner(pool: &PgPool, partner: Option<&str>) -> Result<Option<SanctionsMatch>, String> {
+    match partner.filter(|p| !p.trim().is_empty()) {
+        Some(p) => sanctions::screen_name(pool, p).await,
+        None => Ok(None),
+    }
+}
+
+/// Special-category (Art. 9) and criminal (Art. 10) data leaving the EU/EEA for a
+/// non-adequate country always goes to human review, even when an SCC is in place.
+pub fn escalate_sensitive_categories(decision: TransferDecision, categories: &NormalisedCategories) -> TransferDecision {
+    if !categories.is_sensitive() || decision.decision == Decision::BLOCK {
+        return decision;
+    }
+    if matches!(decision.country_status.as_str(), "eu_eea" | "adequate_protection") {
+        return decision;
+    }
+
+    let mut articles = decision.articles;
+    let mut kinds = Vec::new();
+    if categories.special_category {
+        kinds.push("special category data (Art. 9)");
+    }
+    if categories.criminal_data {
+        kinds.push("criminal offence data (Art. 10)");
+    }
+    // Keep the decision's article order; skip any it already cites
+    for (flag, article) in [(categories.special_category, "GDPR Art. 9"), (categories.criminal_data, "GDPR Art. 10")] {
+        if flag && !articles.iter().any(|a| a == article) {
+            articles.push(article.into());
+        }
+    }
+
+    TransferDecision {
+        decision: Decision::REVIEW,
+        reason: format!("{} — {} to a non-adequate country requires human review", decision.reason, kinds.join(" and ")),
+        severity: "L3".into(),
+        articles,
+        event_type: "DATA_TRANSFER_REVIEW".into(),
+        country_status: decision.country_status,
+    }
+}
+
 pub fn all_country_classifications() -> Vec<serde_json::Value> {
     let mut countries = Vec::new();
     for &code in EU_EEA {
@@ -448,3 +648,61 @@ pub fn all_country_classifications() -> Vec<serde_json::Value> {
     }
     countries
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(decision: Decision, country_status: &str, articles: &[&str]) -> TransferDecision {
+        TransferDecision {
+            decision,
+            reason: "Partner has an active SCC".into(),
+            severity: "L1".into(),
+            articles: articles.iter().map(|a| a.to_string()).collect(),
+            event_type: "DATA_TRANSFER".into(),
+            country_status: country_status.into(),
+        }
+    }
+
+    fn sensitive(special_category: bool, criminal_data: bool) -> NormalisedCategories {
+        NormalisedCategories { categories: vec!["x".into()], special_category, criminal_data, ..Default::default() }
+    }
+
+    #[test]
+    fn sensitive_data_to_non_adequate_countries_needs_review() {
+        let cases = [
+            (sensitive(true, false), vec!["GDPR Art. 46", "GDPR Art. 9"], "special category data (Art. 9)"),
+            (sensitive(false, true), vec!["GDPR Art. 46", "GDPR Art. 10"], "criminal offence data (Art. 10)"),
+            (sensitive(true, true), vec!["GDPR Art. 46", "GDPR Art. 9", "GDPR Art. 10"], "(Art. 9) and criminal offence data (Art. 10)"),
+        ];
+        for (categories, articles, kinds) in cases {
+            let out = escalate_sensitive_categories(decision(Decision::ALLOW, "scc_required", &["GDPR Art. 46"]), &categories);
+            assert_eq!(out.decision, Decision::REVIEW);
+            assert_eq!(out.severity, "L3");
+            assert_eq!(out.event_type, "DATA_TRANSFER_REVIEW");
+            assert_eq!(out.articles, articles);
+            assert!(out.reason.contains(kinds), "{}", out.reason);
+        }
+    }
+
+    #[test]
+    fn escalation_keeps_articles_unique() {
+        let prior = decision(Decision::REVIEW, "unknown", &["GDPR Art. 9", "GDPR Art. 46", "GDPR Art. 10"]);
+        let out = escalate_sensitive_categories(prior, &sensitive(true, true));
+        assert_eq!(out.articles, ["GDPR Art. 9", "GDPR Art. 46", "GDPR Art. 10"]);
+    }
+
+    #[test]
+    fn escalation_leaves_other_decisions_alone() {
+        let cases = [
+            (decision(Decision::ALLOW, "eu_eea", &[]), sensitive(true, true)),
+            (decision(Decision::ALLOW, "adequate_protection", &[]), sensitive(true, false)),
+            (decision(Decision::BLOCK, "blocked", &["GDPR Art. 44"]), sensitive(false, true)),
+            (decision(Decision::ALLOW, "scc_required", &["GDPR Art. 46"]), sensitive(false, false)),
+        ];
+        for (prior, categories) in cases {
+            let out = escalate_sensitive_categories(prior.clone(), &categories);
+            assert_eq!((out.decision, out.articles), (prior.decision, prior.articles));
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Utc};
+
+/// Default tolerance for source clocks running ahead of ours.
+const DEFAULT_MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+/// Epoch values above this are taken to be milliseconds (1e11 s is the year 5138).
+const EPOCH_MILLIS_THRESHOLD: f64 = 1e11;
+
+/// Timestamp resolved for an ingested log entry.
+#[derive(Debug, Clone)]
+pub struct SourceTime {
+    /// Value to store as `occurred_at`: the source time, or the receive time when it is unusable.
+    pub occurred_at: DateTime<Utc>,
+    /// Seconds the source time was ahead of the receive time, when beyond the tolerance.
+    pub clock_skew_seconds: Option<i64>,
+    pub warning: Option<String>,
+}
+
+/// Tolerance for future-dated source timestamps: `INGEST_MAX_CLOCK_SKEW_SECS` (default 300).
+pub fn max_clock_skew() -> Duration {
+    let secs = std::env::var("INGEST_MAX_CLOCK_SKEW_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_MAX_CLOCK_SKEW_SECS)
+        .max(0);
+    Duration::seconds(secs)
+}
+
+/// Parse a source timestamp in any of the formats log shippers commonly emit:
+/// RFC 3339 / ISO 8601 (naive values are UTC), epoch seconds or milliseconds,
+/// Apache common log (`10/Oct/2024:13:55:36 +0200`) and BSD syslog (`Oct 10 13:55:36`).
+pub fn parse_timestamp(raw: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let s = raw.trim().trim_start_matches('[').trim_end_matches(']').trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%d %H:%M:%S%.f%z"] {
+        if let Ok(dt) = DateTime::parse_from_str(s, fmt) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    for fmt in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(Utc.from_utc_datetime(&naive));
+        }
+    }
+
+    if s.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        let value: f64 = s.parse().ok()?;
+        let millis = if value > EPOCH_MILLIS_THRESHOLD { value } else { value * 1000.0 };
+        return Utc.timestamp_millis_opt(millis as i64).single();
+    }
+
+    if let Ok(dt) = DateTime::parse_from_str(s, "%d/%b/%Y:%H:%M:%S %z") {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    parse_syslog(s, now)
+}
+
+/// BSD syslog timestamps carry no year or zone: assume UTC and the current year,
+/// falling back to last year when that would put the entry in the future (e.g. Dec logs read in Jan).
+fn parse_syslog(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let collapsed = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    for year in [now.year(), now.year() - 1] {
+        let candidate = format!("{} {}", year, collapsed);
+        let naive = NaiveDateTime::parse_from_str(&candidate, "%Y %b %d %H:%M:%S%.f").ok()?;
+        let dt = Utc.from_utc_datetime(&naive);
+        if dt <= now + Duration::days(1) {
+            return Some(dt);
+        }
+    }
+    None
+}
+
+/// Resolve the occurred-at time for an entry received at `received_at`. Missing or unparseable
+/// timestamps fall back to the receive time; timestamps too far in the future are flagged as clock skew
+/// and not trusted.
+pub fn resolve(raw: Option<&str>, received_at: DateTime<Utc>, max_skew: Duration) -> SourceTime {
+    let Some(raw) = raw.filter(|r| !r.trim().is_empty()) else {
+        return SourceTime { occurred_at: received_at, clock_skew_seconds: None, warning: None };
+    };
+
+    match parse_timestamp(raw, received_at) {
+        Some(ts) if ts - received_at > max_skew => {
+            let skew = (ts - received_at).num_seconds();
+            SourceTime {
+                occurred_at: received_at,
+                clock_skew_seconds: Some(skew),
+                warning: Some(format!("Source timestamp is {}s ahead of server time — recorded at receive time", skew)),
+            }
+        }
+        Some(ts) => SourceTime { occurred_at: ts, clock_skew_seconds: None, warning: None },
+        None => SourceTime {
+            occurred_at: received_at,
+            clock_skew_seconds: None,
+            warning: Some(format!("Unrecognised timestamp format: {}", raw)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parses_supported_formats() {
+        let now = at("2026-03-15T12:00:00Z");
+        let cases = [
+            ("2026-03-15T10:30:00Z", "2026-03-15T10:30:00Z"),
+            ("2026-03-15T10:30:00.250+02:00", "2026-03-15T08:30:00.250Z"),
+            ("2026-03-15 10:30:00+02:00", "2026-03-15T08:30:00Z"),
+            ("2026-03-15 10:30:00 +0200", "2026-03-15T08:30:00Z"),
+            ("2026-03-15T10:30:00", "2026-03-15T10:30:00Z"),
+            ("2026-03-15 10:30:00.5", "2026-03-15T10:30:00.500Z"),
+            ("1773570600", "2026-03-15T10:30:00Z"),
+            ("1773570600.5", "2026-03-15T10:30:00.500Z"),
+            ("1773570600123", "2026-03-15T10:30:00.123Z"),
+            ("15/Mar/2026:12:30:00 +0200", "2026-03-15T10:30:00Z"),
+            ("[15/Mar/2026:12:30:00 +0200]", "2026-03-15T10:30:00Z"),
+            ("Mar 15 10:30:00", "2026-03-15T10:30:00Z"),
+            ("Mar  5 10:30:00", "2026-03-05T10:30:00Z"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(parse_timestamp(raw, now), Some(at(expected)), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn syslog_without_year_falls_back_to_last_year() {
+        let now = at("2026-01-02T00:00:00Z");
+        assert_eq!(parse_timestamp("Dec 31 23:59:59", now), Some(at("2025-12-31T23:59:59Z")));
+    }
+
+    #[test]
+    fn rejects_unrecognised_values() {
+        let now = at("2026-03-15T12:00:00Z");
+        for raw in ["", "  ", "yesterday", "2026-13-45", "Foo 15 10:30:00"] {
+            assert_eq!(parse_timestamp(raw, now), None, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn resolve_falls_back_to_receive_time() {
+        let received = at("2026-03-15T12:00:00Z");
+        let skew = Duration::seconds(300);
+
+        let missing = resolve(None, received, skew);
+        assert_eq!(missing.occurred_at, received);
+        assert!(missing.warning.is_none());
+
+        let blank = resolve(Some(" "), received, skew);
+        assert_eq!(blank.occurred_at, received);
+        assert!(blank.warning.is_none());
+
+        let garbage = resolve(Some("not a time"), received, skew);
+        assert_eq!(garbage.occurred_at, received);
+        assert!(garbage.warning.unwrap().contains("Unrecognised"));
+    }
+
+    #[test]
+    fn resolve_flags_clock_skew() {
+        let received = at("2026-03-15T12:00:00Z");
+        let skew = Duration::seconds(300);
+
+        let within = resolve(Some("2026-03-15T12:04:00Z"), received, skew);
+        assert_eq!(within.occurred_at, at("2026-03-15T12:04:00Z"));
+        assert_eq!(within.clock_skew_seconds, None);
+
+        let ahead = resolve(Some("2026-03-15T13:00:00Z"), received, skew);
+        assert_eq!(ahead.occurred_at, received);
+        assert_eq!(ahead.clock_skew_seconds, Some(3600));
+        assert!(ahead.warning.is_some());
+
+        let past = resolve(Some("2026-03-14T12:00:00Z"), received, skew);
+        assert_eq!(past.occurred_at, at("2026-03-14T12:00:00Z"));
+        assert_eq!(past.clock_skew_seconds, None);
+    }
+}
@@ -0,0 +1,450 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::evidence::{self, CreateEventParams};
+use crate::idempotency;
+use crate::review_queue;
+use crate::shield::{Decision, TransferContext, TransferDecision};
+
+/// Flows sealed per pass.
+pub const SEAL_BATCH: i64 = 100;
+/// A flow claimed for sealing whose evidence is still missing after this long is claimed again.
+const SEAL_LEASE_SECS: f64 = 300.0;
+
+/// Payload fields that differ between the transfers of one flow; the sealed event carries totals instead.
+const PER_TRANSFER_FIELDS: &[&str] = &["data_size", "dest_ip", "request_path", "user_agent", "source_timestamp", "clock_skew_seconds"];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferFlowRow {
+    pub id: Uuid,
+    pub flow_key: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub source_system: Option<String>,
+    pub source_ip: Option<String>,
+    pub partner_name: Option<String>,
+    pub destination_country_code: Option<String>,
+    #[schema(value_type = Vec<String>)]
+    pub data_categories: serde_json::Value,
+    pub decision: String,
+    pub transfer_count: i64,
+    pub total_bytes: i64,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub context: serde_json::Value,
+    #[serde(skip)]
+    pub decision_detail: serde_json::Value,
+    #[serde(skip)]
+    pub payload: serde_json::Value,
+    /// Set once the window has closed and the flow's evidence event is written.
+    pub evidence_event_id: Option<String>,
+    pub review_id: Option<String>,
+    pub sealed_at: Option<DateTime<Utc>>,
+    /// Why the flow could not be sealed; such a flow is not retried.
+    pub seal_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One evaluated ALLOW / REVIEW transfer to count towards its flow.
+pub struct FlowSample {
+    pub idempotency_key: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub ctx: TransferContext,
+    pub decision: TransferDecision,
+    /// The evidence payload the transfer would have been recorded with on its own.
+    pub payload: serde_json::Value,
+}
+
+pub struct FlowOutcome {
+    pub flow_id: Option<Uuid>,
+    /// False when the sample's idempotency key had already been counted.
+    pub counted: bool,
+}
+
+/// Aggregation window: `FLOW_AGGREGATION_WINDOW_SECS` (default 0, aggregation off).
+pub fn window_secs() -> i64 {
+    std::env::var("FLOW_AGGREGATION_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(0)
+        .max(0)
+}
+
+/// Source system (the source IP when none is given), partner, destination country, category set
+/// and decision identify a flow, plus the declared flow (or its absence) once the flow register is in use.
+fn flow_key(sample: &FlowSample) -> String {
+    let mut categories = sample.ctx.data_categories.clone().unwrap_or_default();
+    categories.sort();
+    categories.dedup();
+    let source = match sample.ctx.source_system.as_deref() {
+        Some(system) => serde_json::json!({ "system": system }),
+        None => serde_json::json!({ "ip": sample.ctx.source_ip }),
+    };
+    let mut parts = serde_json::json!([
+        source,
+        sample.ctx.partner_name.as_deref().map(|p| p.trim().to_lowercase()),
+        sample.ctx.destination_country_code,
+        categories,
+        sample.decision.decision.to_string(),
+    ]);
+    if let (Some(status), Some(list)) = (sample.payload.get("flow_status"), parts.as_array_mut()) {
+        list.push(status.clone());
+        list.push(sample.payload.get("declared_flow_id").cloned().unwrap_or_default());
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(parts.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn window_start(occurred_at: DateTime<Utc>, window_secs: i64) -> DateTime<Utc> {
+    let secs = occurred_at.timestamp();
+    DateTime::from_timestamp(secs - secs.rem_euclid(window_secs), 0).unwrap_or(occurred_at)
+}
+
+fn flow_payload(payload: &serde_json::Value) -> serde_json::Value {
+    let mut payload = payload.clone();
+    if let Some(obj) = payload.as_object_mut() {
+        for field in PER_TRANSFER_FIELDS {
+            obj.remove(*field);
+        }
+    }
+    payload
+}
+
+struct FlowGroup {
+    key: String,
+    window_start: DateTime<Utc>,
+    first: usize,
+    count: i64,
+    bytes: i64,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+}
+
+/// Count samples towards their open flow for the window they fall in, creating flows as needed.
+/// Samples whose idempotency key was already counted are reported with the flow that counted them.
+/// Returns one outcome per sample, in order.
+pub async fn record(pool: &PgPool, window_secs: i64, samples: &[FlowSample]) -> Result<Vec<FlowOutcome>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    let keys: Vec<String> = samples.iter().filter_map(|s| s.idempotency_key.clone()).collect();
+    let new_keys: HashSet<String> = if keys.is_empty() {
+        HashSet::new()
+    } else {
+        sqlx::query_scalar::<_, String>(
+            r#"INSERT INTO transfer_flow_keys (idempotency_key)
+               SELECT unnest($1::text[])
+               ON CONFLICT DO NOTHING
+               RETURNING idempotency_key"#
+        )
+        .bind(&keys)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record flow idempotency keys: {}", e))?
+        .into_iter()
+        .collect()
+    };
+
+    let mut groups: Vec<FlowGroup> = Vec::new();
+    let mut group_of: HashMap<(String, DateTime<Utc>), usize> = HashMap::new();
+    let mut sample_group: Vec<Option<usize>> = Vec::with_capacity(samples.len());
+    for (i, sample) in samples.iter().enumerate() {
+        if sample.idempotency_key.as_ref().is_some_and(|k| !new_keys.contains(k)) {
+            sample_group.push(None);
+            continue;
+        }
+        let start = window_start(sample.occurred_at, window_secs);
+        let key = flow_key(sample);
+        let g = *group_of.entry((key.clone(), start)).or_insert_with(|| {
+            groups.push(FlowGroup {
+                key,
+                window_start: start,
+                first: i,
+                count: 0,
+                bytes: 0,
+                first_seen: sample.occurred_at,
+                last_seen: sample.occurred_at,
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[g];
+        group.count += 1;
+        group.bytes += sample.ctx.data_size.unwrap_or(0) as i64;
+        group.first_seen = group.first_seen.min(sample.occurred_at);
+        group.last_seen = group.last_seen.max(sample.occurred_at);
+        sample_group.push(Some(g));
+    }
+
+    let mut group_ids = Vec::with_capacity(groups.len());
+    for group in &groups {
+        let first = &samples[group.first];
+        let mut categories = first.ctx.data_categories.clone().unwrap_or_default();
+        categories.sort();
+        categories.dedup();
+        let id: Uuid = sqlx::query_scalar(
+            r#"INSERT INTO transfer_flows
+                   (flow_key, window_start, window_end, source_ip, partner_name, destination_country_code,
+                    data_categories, decision, transfer_count, total_bytes, first_seen_at, last_seen_at,
+                    context, decision_detail, payload, source_system)
+               VALUES ($1, $2, $2 + make_interval(secs => $3), $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+               ON CONFLICT (flow_key, window_start) WHERE sealed_at IS NULL DO UPDATE SET
+                   transfer_count = transfer_flows.transfer_count + EXCLUDED.transfer_count,
+                   total_bytes = transfer_flows.total_bytes + EXCLUDED.total_bytes,
+                   first_seen_at = LEAST(transfer_flows.first_seen_at, EXCLUDED.first_seen_at),
+                   last_seen_at = GREATEST(transfer_flows.last_seen_at, EXCLUDED.last_seen_at),
+                   updated_at = NOW()
+               RETURNING id"#
+        )
+        .bind(&group.key)
+        .bind(group.window_start)
+        .bind(window_secs as f64)
+        .bind(&first.ctx.source_ip)
+        .bind(&first.ctx.partner_name)
+        .bind(&first.ctx.destination_country_code)
+        .bind(serde_json::json!(categories))
+        .bind(first.decision.decision.to_string())
+        .bind(group.count)
+        .bind(group.bytes)
+        .bind(group.first_seen)
+        .bind(group.last_seen)
+        .bind(serde_json::to_value(&first.ctx).unwrap_or_default())
+        .bind(serde_json::to_value(&first.decision).unwrap_or_default())
+        .bind(flow_payload(&first.payload))
+        .bind(&first.ctx.source_system)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record transfer flow: {}", e))?;
+        group_ids.push(id);
+    }
+
+    let (counted_keys, counted_flows): (Vec<String>, Vec<Uuid>) = samples.iter()
+        .zip(&sample_group)
+        .filter_map(|(s, g)| Some((s.idempotency_key.clone()?, group_ids[(*g)?])))
+        .unzip();
+    if !counted_keys.is_empty() {
+        sqlx::query(
+            r#"UPDATE transfer_flow_keys k SET flow_id = t.flow_id
+               FROM unnest($1::text[], $2::uuid[]) AS t(idempotency_key, flow_id)
+               WHERE k.idempotency_key = t.idempotency_key"#
+        )
+        .bind(&counted_keys)
+        .bind(&counted_flows)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record flow idempotency keys: {}", e))?;
+    }
+
+    let replayed: Vec<String> = keys.into_iter().filter(|k| !new_keys.contains(k)).collect();
+    let prior: HashMap<String, Option<Uuid>> = if replayed.is_empty() {
+        HashMap::new()
+    } else {
+        sqlx::query_as::<_, (String, Option<Uuid>)>(
+            "SELECT idempotency_key, flow_id FROM transfer_flow_keys WHERE idempotency_key = ANY($1)"
+        )
+        .bind(&replayed)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to look up flow idempotency keys: {}", e))?
+        .into_iter()
+        .collect()
+    };
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(samples.iter()
+        .zip(sample_group)
+        .map(|(sample, g)| match g {
+            Some(g) => FlowOutcome { flow_id: Some(group_ids[g]), counted: true },
+            None => FlowOutcome {
+                flow_id: sample.idempotency_key.as_ref().and_then(|k| prior.get(k).copied().flatten()),
+                counted: false,
+            },
+        })
+        .collect())
+}
+
+fn evidence_key(flow_id: Uuid) -> String {
+    format!("transfer-flow:{}", flow_id)
+}
+
+fn sealed_event(flow: &TransferFlowRow, decision: &TransferDecision) -> CreateEventParams {
+    let mut payload = flow.payload.clone();
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("flow".into(), serde_json::json!({
+            "flow_id": flow.id,
+            "window_start": flow.window_start.to_rfc3339(),
+            "window_end": flow.window_end.to_rfc3339(),
+            "transfer_count": flow.transfer_count,
+            "total_bytes": flow.total_bytes,
+            "first_seen_at": flow.first_seen_at.to_rfc3339(),
+            "last_seen_at": flow.last_seen_at.to_rfc3339(),
+        }));
+    }
+    CreateEventParams {
+        event_type: decision.event_type.clone(),
+        severity: decision.severity.clone(),
+        source_system: "sovereign-shield".into(),
+        regulatory_tags: vec!["GDPR".into()],
+        articles: decision.articles.clone(),
+        payload,
+        correlation_id: Some(flow.id.to_string()),
+        causation_id: None,
+        source_ip: flow.source_ip.clone(),
+        source_user_agent: None,
+        occurred_at: Some(flow.first_seen_at),
+        idempotency_key: Some(evidence_key(flow.id)),
+    }
+}
+
+/// Open one review for a REVIEW flow, covering every transfer counted in its window.
+async fn create_flow_review(pool: &PgPool, flow: &TransferFlowRow, decision: &TransferDecision, event_id: &str) -> Result<String, String> {
+    let ctx: TransferContext = serde_json::from_value(flow.context.clone()).unwrap_or_default();
+    let dest_code = ctx.destination_country_code.clone().unwrap_or_default();
+    let action = format!("transfer_data_to_{}", dest_code.to_lowercase());
+    review_queue::create_review(
+        pool,
+        "sovereign-shield",
+        &action,
+        "sovereign-shield",
+        &serde_json::json!({
+            "destination": flow.payload.get("destination_country"),
+            "destination_country_code": dest_code,
+            "data_categories": ctx.data_categories,
+            "partner_name": ctx.partner_name,
+            "reason": decision.reason,
+            "transfer_count": flow.transfer_count,
+            "total_bytes": flow.total_bytes,
+            "window_start": flow.window_start.to_rfc3339(),
+            "window_end": flow.window_end.to_rfc3339(),
+        }),
+        event_id,
+    ).await
+}
+
+/// Seal flows whose window ended at least `grace_secs` ago: write one evidence event per flow and open
+/// a review for REVIEW flows. A flow is claimed before its evidence is written, so transfers arriving
+/// afterwards open a follow-up flow; an interrupted seal is retried after a lease and reuses evidence
+/// already written under the flow's idempotency key. Returns the number of flows claimed.
+pub async fn seal_due(pool: &PgPool, grace_secs: i64) -> Result<usize, String> {
+    let flows: Vec<TransferFlowRow> = sqlx::query_as(
+        r#"UPDATE transfer_flows SET sealed_at = NOW(), updated_at = NOW()
+           WHERE id IN (
+               SELECT id FROM transfer_flows
+               WHERE evidence_event_id IS NULL AND seal_error IS NULL
+                 AND ((sealed_at IS NULL AND window_end <= NOW() - make_interval(secs => $1))
+                      OR sealed_at <= NOW() - make_interval(secs => $2))
+               ORDER BY window_end
+               LIMIT $3
+               FOR UPDATE SKIP LOCKED
+           )
+           RETURNING *"#
+    )
+    .bind(grace_secs as f64)
+    .bind(SEAL_LEASE_SECS)
+    .bind(SEAL_BATCH)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to claim transfer flows: {}", e))?;
+
+    let count = flows.len();
+    if count == 0 {
+        return Ok(0);
+    }
+
+    let keys: Vec<String> = flows.iter().map(|f| evidence_key(f.id)).collect();
+    let prior = idempotency::find_recent(pool, "sovereign-shield", &keys).await?;
+
+    let mut event_ids: HashMap<Uuid, String> = HashMap::new();
+    let mut decisions: HashMap<Uuid, TransferDecision> = HashMap::new();
+    let mut to_write: Vec<(Uuid, CreateEventParams)> = Vec::new();
+    for flow in &flows {
+        let decision: TransferDecision = match serde_json::from_value(flow.decision_detail.clone()) {
+            Ok(d) => d,
+            Err(e) => {
+                log::error!("Transfer flow {} has an unreadable decision: {}", flow.id, e);
+                mark_failed(pool, flow.id, &format!("Unreadable decision: {}", e)).await?;
+                continue;
+            }
+        };
+        match prior.get(&evidence_key(flow.id)) {
+            Some(p) => {
+                event_ids.insert(flow.id, p.event_id.clone());
+            }
+            None => to_write.push((flow.id, sealed_event(flow, &decision))),
+        }
+        decisions.insert(flow.id, decision);
+    }
+
+    let (ids, params): (Vec<Uuid>, Vec<CreateEventParams>) = to_write.into_iter().unzip();
+    let rows = evidence::create_events_batch(pool, "sovereign-shield", params).await?;
+    // A flow whose key another sealer claimed first is skipped here and picks its evidence up after the lease
+    event_ids.extend(ids.into_iter().zip(rows).filter_map(|(id, row)| Some((id, row?.event_id))));
+
+    for flow in &flows {
+        let (Some(event_id), Some(decision)) = (event_ids.get(&flow.id), decisions.get(&flow.id)) else { continue };
+        let review_id = if decision.decision == Decision::REVIEW {
+            match create_flow_review(pool, flow, decision, event_id).await {
+                Ok(seal_id) => Some(seal_id),
+                Err(e) => {
+                    log::error!("Failed to create review for transfer flow {}: {}", flow.id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        sqlx::query("UPDATE transfer_flows SET evidence_event_id = $2, review_id = $3, updated_at = NOW() WHERE id = $1")
+            .bind(flow.id)
+            .bind(event_id)
+            .bind(&review_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to seal transfer flow {}: {}", flow.id, e))?;
+    }
+    Ok(count)
+}
+
+/// Stop retrying a flow that cannot be sealed; it stays listed under `failed` with the reason.
+async fn mark_failed(pool: &PgPool, flow_id: Uuid, error: &str) -> Result<(), String> {
+    sqlx::query("UPDATE transfer_flows SET seal_error = $2, updated_at = NOW() WHERE id = $1")
+        .bind(flow_id)
+        .bind(error)
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to mark transfer flow {} failed: {}", flow_id, e))
+}
+
+/// Forget idempotency keys older than the replay window.
+pub async fn prune_keys(pool: &PgPool, window_secs: i64) -> Result<u64, String> {
+    sqlx::query("DELETE FROM transfer_flow_keys WHERE recorded_at < NOW() - make_interval(secs => $1)")
+        .bind(window_secs as f64)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected())
+        .map_err(|e| format!("Failed to prune flow idempotency keys: {}", e))
+}
+
+/// Most recent flows first; `status` is `open` (window still counting or being sealed), `sealed`
+/// or `failed` (could not be sealed, see `seal_error`).
+pub async fn list(pool: &PgPool, status: Option<&str>, limit: i64) -> Result<Vec<TransferFlowRow>, String> {
+    let filter = match status {
+        Some("open") => "WHERE evidence_event_id IS NULL AND seal_error IS NULL",
+        Some("failed") => "WHERE seal_error IS NOT NULL",
+        Some("sealed") => "WHERE evidence_event_id IS NOT NULL",
+        _ => "",
+    };
+    sqlx::query_as(&format!("SELECT * FROM transfer_flows {} ORDER BY window_start DESC, id LIMIT $1", filter))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to list transfer flows: {}", e))
+}
@@ -0,0 +1,481 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::country_policy;
+use crate::crypto_shredder;
+use crate::shield::TransferContext;
+
+/// Upper bound on `PERMIT_TTL_SECS`; retired keys stay in the JWKS this long.
+pub const MAX_TTL_SECS: i64 = 3600;
+const DEFAULT_TTL_SECS: i64 = 300;
+
+#[derive(sqlx::FromRow)]
+struct SigningKeyRow {
+    kid: String,
+    wrapped_private_key: Vec<u8>,
+}
+
+/// Attributes of the transfer a permit covers, as submitted to `evaluate`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PermitTransfer {
+    pub destination_country_code: Option<String>,
+    pub partner_name: Option<String>,
+    /// Sorted; a transfer of any category not listed here is not covered.
+    pub data_categories: Vec<String>,
+    pub source_ip: Option<String>,
+    pub dest_ip: Option<String>,
+    pub protocol: Option<String>,
+    pub request_path: Option<String>,
+    /// Upper bound in bytes when the evaluated transfer stated one.
+    pub data_size: Option<u64>,
+}
+
+/// JWS payload of a transfer permit. Verifiers check the signature against the JWKS, `exp`, that `jti`
+/// is not on the revocation list, and that the transfer about to happen matches `transfer`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PermitClaims {
+    pub iss: String,
+    /// Evidence event recorded for the evaluation.
+    pub sub: String,
+    pub jti: Uuid,
+    pub iat: i64,
+    pub nbf: i64,
+    pub exp: i64,
+    pub decision: String,
+    /// `eu_eea` or `adequate_protection`, or `scc_required` when the ALLOW rests on an active SCC for the partner.
+    pub country_status: String,
+    pub transfer: PermitTransfer,
+}
+
+pub struct IssuedPermit {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    pub kid: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokedPermit {
+    pub jti: Uuid,
+    pub revoked_at: DateTime<Utc>,
+    pub revocation_reason: Option<String>,
+    /// Entries drop off the list once the permit has expired.
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Permit lifetime: `PERMIT_TTL_SECS` (default 300, at most 3600).
+pub fn ttl_secs() -> i64 {
+    std::env::var("PERMIT_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(DEFAULT_TTL_SECS)
+        .clamp(1, MAX_TTL_SECS)
+}
+
+/// `iss` of every permit: `PERMIT_ISSUER` (default `veridion-api`).
+pub fn issuer() -> String {
+    std::env::var("PERMIT_ISSUER").unwrap_or_else(|_| "veridion-api".to_string())
+}
+
+/// RFC 7638 thumbprint of an Ed25519 public key.
+fn thumbprint(x: &str) -> String {
+    let canonical = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#, x);
+    URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes()))
+}
+
+async fn insert_new_key(executor: impl sqlx::PgExecutor<'_>) -> Result<String, String> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| "Failed to generate permit signing key".to_string())?;
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+        .map_err(|_| "Failed to read generated permit signing key".to_string())?;
+    let public_key = pair.public_key().as_ref().to_vec();
+    let kid = thumbprint(&URL_SAFE_NO_PAD.encode(&public_key));
+    let wrapped = crypto_shredder::wrap_with_master_key(pkcs8.as_ref())?;
+
+    // Two instances generating the first key at once: the unique active index keeps one of them
+    sqlx::query(
+        r#"INSERT INTO permit_signing_keys (kid, public_key, wrapped_private_key)
+           VALUES ($1, $2, $3)
+           ON CONFLICT DO NOTHING"#
+    )
+    .bind(&kid)
+    .bind(&public_key)
+    .bind(&wrapped)
+    .execute(executor)
+    .await
+    .map_err(|e| format!("Failed to store permit signing key: {}", e))?;
+    Ok(kid)
+}
+
+async fn fetch_active_key(pool: &PgPool) -> Result<Option<SigningKeyRow>, String> {
+    sqlx::query_as("SELECT kid, wrapped_private_key FROM permit_signing_keys WHERE retired_at IS NULL")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load permit signing key: {}", e))
+}
+
+/// The key new permits are signed with, generated on first use.
+async fn active_key(pool: &PgPool) -> Result<SigningKeyRow, String> {
+    if let Some(key) = fetch_active_key(pool).await? {
+        return Ok(key);
+    }
+    insert_new_key(pool).await?;
+    fetch_active_key(pool).await?.ok_or_else(|| "No active permit signing key".to_string())
+}
+
+/// Retire the active key and start signing with a new one. The old key stays in the JWKS until
+/// every permit it signed has expired. Returns the new key ID.
+pub async fn rotate_key(pool: &PgPool) -> Result<String, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("UPDATE permit_signing_keys SET retired_at = NOW() WHERE retired_at IS NULL")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to retire permit signing key: {}", e))?;
+    let kid = insert_new_key(&mut *tx).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(kid)
+}
+
+/// Claims of an ALLOW permit for `ctx`, valid from `now` until `expires_at`.
+fn permit_claims(
+    ctx: &TransferContext,
+    evidence_id: &str,
+    country_status: &str,
+    now: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> PermitClaims {
+    let mut data_categories = ctx.data_categories.clone().unwrap_or_default();
+    data_categories.sort();
+    data_categories.dedup();
+    PermitClaims {
+        iss: issuer(),
+        sub: evidence_id.to_string(),
+        jti: Uuid::new_v4(),
+        iat: now.timestamp(),
+        nbf: now.timestamp(),
+        exp: expires_at.timestamp(),
+        decision: "ALLOW".into(),
+        country_status: country_status.to_string(),
+        transfer: PermitTransfer {
+            destination_country_code: ctx.destination_country_code.as_deref().map(str::to_uppercase),
+            partner_name: ctx.partner_name.clone(),
+            data_categories,
+            source_ip: ctx.source_ip.clone(),
+            dest_ip: ctx.dest_ip.clone(),
+            protocol: ctx.protocol.clone(),
+            request_path: ctx.request_path.clone(),
+            data_size: ctx.data_size,
+        },
+    }
+}
+
+/// Compact JWS of `claims`, signed with the PKCS#8 Ed25519 key `kid`.
+fn sign(claims: &PermitClaims, kid: &str, pkcs8: &[u8]) -> Result<String, String> {
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.typ = Some("transfer-permit+jwt".into());
+    header.kid = Some(kid.to_string());
+    jsonwebtoken::encode(&header, claims, &EncodingKey::from_ed_der(pkcs8))
+        .map_err(|e| format!("Failed to sign permit: {}", e))
+}
+
+/// Sign a permit for an ALLOW decision and record it so it can be revoked.
+pub async fn issue(
+    pool: &PgPool,
+    ctx: &TransferContext,
+    evidence_id: &str,
+    country_status: &str,
+) -> Result<IssuedPermit, String> {
+    let key = active_key(pool).await?;
+    let pkcs8 = crypto_shredder::unwrap_with_master_key(&key.wrapped_private_key)?;
+
+    let now = Utc::now();
+    let expires_at = now + Duration::seconds(ttl_secs());
+    let claims = permit_claims(ctx, evidence_id, country_status, now, expires_at);
+    let token = sign(&claims, &key.kid, &pkcs8)?;
+
+    sqlx::query(
+        r#"INSERT INTO transfer_permits
+               (jti, kid, evidence_event_id, destination_country_code, partner_name, country_status, claims, issued_at, expires_at, token)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#
+    )
+    .bind(claims.jti)
+    .bind(&key.kid)
+    .bind(evidence_id)
+    .bind(&claims.transfer.destination_country_code)
+    .bind(&claims.transfer.partner_name)
+    .bind(country_status)
+    .bind(serde_json::to_value(&claims).unwrap_or_default())
+    .bind(now)
+    .bind(expires_at)
+    .bind(&token)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to record permit: {}", e))?;
+
+    Ok(IssuedPermit { token, expires_at })
+}
+
+/// The latest permit issued for an evidence event that is still unexpired and unrevoked, so a
+/// replayed evaluation returns it rather than issuing a second permit for the same transfer.
+pub async fn find_for_evidence(pool: &PgPool, evidence_id: &str) -> Result<Option<IssuedPermit>, String> {
+    let row: Option<(String, DateTime<Utc>)> = sqlx::query_as(
+        r#"SELECT token, expires_at FROM transfer_permits
+           WHERE evidence_event_id = $1 AND token IS NOT NULL
+             AND revoked_at IS NULL AND expires_at > NOW()
+           ORDER BY issued_at DESC
+           LIMIT 1"#
+    )
+    .bind(evidence_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to look up permit: {}", e))?;
+    Ok(row.map(|(token, expires_at)| IssuedPermit { token, expires_at }))
+}
+
+/// Public keys permits may be signed with: the active key plus keys retired within the maximum permit lifetime.
+pub async fn jwks(pool: &PgPool) -> Result<JwkSet, String> {
+    active_key(pool).await?;
+    let rows: Vec<(String, Vec<u8>)> = sqlx::query_as(
+        r#"SELECT kid, public_key FROM permit_signing_keys
+           WHERE retired_at IS NULL OR retired_at > NOW() - make_interval(secs => $1)
+           ORDER BY created_at DESC"#
+    )
+    .bind(MAX_TTL_SECS as f64)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list permit signing keys: {}", e))?;
+    Ok(JwkSet {
+        keys: rows.into_iter().map(|(kid, public_key)| jwk(kid, &public_key)).collect(),
+    })
+}
+
+fn jwk(kid: String, public_key: &[u8]) -> Jwk {
+    Jwk {
+        kty: "OKP".into(),
+        crv: "Ed25519".into(),
+        x: URL_SAFE_NO_PAD.encode(public_key),
+        kid,
+        key_use: "sig".into(),
+        alg: "EdDSA".into(),
+    }
+}
+
+/// Revoked permits that have not yet expired.
+pub async fn revoked(pool: &PgPool) -> Result<Vec<RevokedPermit>, String> {
+    sqlx::query_as(
+        r#"SELECT jti, revoked_at, revocation_reason, expires_at FROM transfer_permits
+           WHERE revoked_at IS NOT NULL AND expires_at > NOW()
+           ORDER BY revoked_at DESC"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list revoked permits: {}", e))
+}
+
+/// Revoke one permit; false when it does not exist, is already revoked or has expired.
+pub async fn revoke(pool: &PgPool, jti: Uuid, reason: &str) -> Result<bool, String> {
+    let result = sqlx::query(
+        r#"UPDATE transfer_permits SET revoked_at = NOW(), revocation_reason = $2
+           WHERE jti = $1 AND revoked_at IS NULL AND expires_at > NOW()"#
+    )
+    .bind(jti)
+    .bind(reason)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to revoke permit: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke unexpired permits that relied on an SCC for `partner_name` and `country_code`, unless another
+/// active SCC still covers the pair. Call after the SCC's status has changed.
+pub async fn revoke_for_scc(pool: &PgPool, partner_name: &str, country_code: &str, reason: &str) -> Result<u64, String> {
+    let result = sqlx::query(
+        r#"UPDATE transfer_permits p SET revoked_at = NOW(), revocation_reason = $3
+           WHERE p.revoked_at IS NULL AND p.expires_at > NOW()
+             AND p.country_status = 'scc_required'
+             AND p.partner_name = $1 AND p.destination_country_code = $2
+             AND NOT EXISTS (
+                 SELECT 1 FROM scc_registries s
+                 WHERE s.partner_name = p.partner_name AND s.destination_country_code = p.destination_country_code
+                   AND s.status = 'active' AND (s.expires_at IS NULL OR s.expires_at > NOW())
+             )"#
+    )
+    .bind(partner_name)
+    .bind(country_code.to_uppercase())
+    .bind(reason)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to revoke permits: {}", e))?;
+    Ok(result.rows_affected())
+}
+
+/// Revoke unexpired permits to a country whose status changed away from the one they relied on.
+/// A country becoming EU/EEA or adequate withdraws no basis, so nothing is revoked then.
+pub async fn revoke_for_country(pool: &PgPool, country_code: &str, new_status: &str, reason: &str) -> Result<u64, String> {
+    if country_policy::UNRESTRICTED_STATUSES.contains(&new_status) {
+        return Ok(0);
+    }
+    let result = sqlx::query(
+        r#"UPDATE transfer_permits SET revoked_at = NOW(), revocation_reason = $3
+           WHERE revoked_at IS NULL AND expires_at > NOW()
+             AND destination_country_code = $1 AND country_status <> $2"#
+    )
+    .bind(country_code.to_uppercase())
+    .bind(new_status)
+    .bind(reason)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to revoke permits: {}", e))?;
+    Ok(result.rows_affected())
+}
+
+pub async fn run_cli(pool: &PgPool) -> std::io::Result<()> {
+    match rotate_key(pool).await {
+        Ok(kid) => {
+            println!("Permit signing key rotated; new kid {}", kid);
+            println!("The previous key stays in the JWKS for {}s so permits it signed still verify.", MAX_TTL_SECS);
+            Ok(())
+        }
+        Err(e) => Err(std::io::Error::other(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{DecodingKey, Validation};
+
+    struct TestKey {
+        kid: String,
+        pkcs8: Vec<u8>,
+        public_key: Vec<u8>,
+    }
+
+    fn test_key() -> TestKey {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = pair.public_key().as_ref().to_vec();
+        TestKey { kid: thumbprint(&URL_SAFE_NO_PAD.encode(&public_key)), pkcs8: pkcs8.as_ref().to_vec(), public_key }
+    }
+
+    fn published(keys: &[&TestKey]) -> JwkSet {
+        JwkSet { keys: keys.iter().map(|k| jwk(k.kid.clone(), &k.public_key)).collect() }
+    }
+
+    fn context() -> TransferContext {
+        TransferContext {
+            destination_country_code: Some("jp".into()),
+            partner_name: Some("Acme KK".into()),
+            data_categories: Some(vec!["email".into(), "name".into(), "email".into()]),
+            ..Default::default()
+        }
+    }
+
+    fn revocation(jti: Uuid) -> RevokedPermit {
+        RevokedPermit { jti, revoked_at: Utc::now(), revocation_reason: Some("SCC terminated".into()), expires_at: Utc::now() }
+    }
+
+    /// What a relying party does: find the key by `kid` in the published JWKS, check the signature,
+    /// `iss`, `nbf` and `exp`, then the revocation list.
+    fn verify(token: &str, jwks: &JwkSet, revoked: &[RevokedPermit]) -> Result<PermitClaims, String> {
+        let set: jsonwebtoken::jwk::JwkSet = serde_json::from_value(serde_json::to_value(jwks).unwrap())
+            .map_err(|e| format!("JWKS: {}", e))?;
+        let kid = jsonwebtoken::decode_header(token).map_err(|e| e.to_string())?.kid.ok_or("no kid")?;
+        let jwk = set.find(&kid).ok_or("unknown kid")?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?;
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.set_issuer(&[issuer()]);
+        validation.validate_nbf = true;
+        validation.leeway = 0;
+        let claims = jsonwebtoken::decode::<PermitClaims>(token, &key, &validation).map_err(|e| e.to_string())?.claims;
+        if revoked.iter().any(|r| r.jti == claims.jti) {
+            return Err("revoked".into());
+        }
+        Ok(claims)
+    }
+
+    fn issued(key: &TestKey, now: DateTime<Utc>, ttl: i64) -> (PermitClaims, String) {
+        let claims = permit_claims(&context(), "evt-1", "adequate_protection", now, now + Duration::seconds(ttl));
+        let token = sign(&claims, &key.kid, &key.pkcs8).unwrap();
+        (claims, token)
+    }
+
+    #[test]
+    fn permit_verifies_against_published_jwks() {
+        let key = test_key();
+        let (claims, token) = issued(&key, Utc::now(), 300);
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.typ.as_deref(), Some("transfer-permit+jwt"));
+        assert_eq!(header.alg, Algorithm::EdDSA);
+
+        let verified = verify(&token, &published(&[&key]), &[]).unwrap();
+        assert_eq!(verified.jti, claims.jti);
+        assert_eq!(verified.sub, "evt-1");
+        assert_eq!(verified.decision, "ALLOW");
+        assert_eq!(verified.transfer.destination_country_code.as_deref(), Some("JP"));
+        assert_eq!(verified.transfer.data_categories, ["email", "name"]);
+    }
+
+    #[test]
+    fn retired_keys_verify_until_dropped_from_jwks() {
+        let (old, new) = (test_key(), test_key());
+        let (_, token) = issued(&old, Utc::now(), 300);
+        assert!(verify(&token, &published(&[&new, &old]), &[]).is_ok());
+        assert_eq!(verify(&token, &published(&[&new]), &[]).unwrap_err(), "unknown kid");
+
+        // Signed by another key under a published kid
+        let (claims, _) = issued(&old, Utc::now(), 300);
+        let forged = sign(&claims, &old.kid, &new.pkcs8).unwrap();
+        assert!(verify(&forged, &published(&[&old]), &[]).unwrap_err().contains("InvalidSignature"));
+    }
+
+    #[test]
+    fn revoked_and_expired_permits_are_rejected() {
+        let key = test_key();
+        let jwks = published(&[&key]);
+        let (claims, token) = issued(&key, Utc::now(), 300);
+        assert_eq!(verify(&token, &jwks, &[revocation(Uuid::new_v4())]).unwrap().jti, claims.jti);
+        assert_eq!(verify(&token, &jwks, &[revocation(claims.jti)]).unwrap_err(), "revoked");
+
+        let (_, expired) = issued(&key, Utc::now() - Duration::seconds(600), 300);
+        assert!(verify(&expired, &jwks, &[]).unwrap_err().contains("ExpiredSignature"));
+
+        let (_, not_yet) = issued(&key, Utc::now() + Duration::seconds(600), 300);
+        assert!(verify(&not_yet, &jwks, &[]).unwrap_err().contains("ImmatureSignature"));
+    }
+
+    #[test]
+    fn changed_payload_fails_verification() {
+        let key = test_key();
+        let (mut claims, token) = issued(&key, Utc::now(), 300);
+        let parts: Vec<&str> = token.split('.').collect();
+        claims.transfer.destination_country_code = Some("US".into());
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap());
+        let tampered = format!("{}.{}.{}", parts[0], payload, parts[2]);
+        assert!(verify(&tampered, &published(&[&key]), &[]).unwrap_err().contains("InvalidSignature"));
+    }
+}
@@ -0,0 +1,160 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
+
+use crate::api_error::{ApiError, FieldError};
+
+/// Request checks beyond what deserialisation enforces. The limits here are the ones declared
+/// on the same types' OpenAPI schemas (`#[schema(...)]`), so the document and the server agree.
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+}
+
+/// Collects every field error so a client sees all problems in one response.
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError { field: field.into(), message: message.into() });
+    }
+
+    pub fn required(&mut self, field: &str, value: &str) {
+        if value.trim().is_empty() {
+            self.error(field, "must not be empty");
+        }
+    }
+
+    pub fn max_len(&mut self, field: &str, value: Option<&str>, max: usize) {
+        if let Some(v) = value {
+            if v.chars().count() > max {
+                self.error(field, format!("must be at most {} characters", max));
+            }
+        }
+    }
+
+    pub fn one_of(&mut self, field: &str, value: Option<&str>, allowed: &[&str]) {
+        if let Some(v) = value {
+            if !allowed.contains(&v) {
+                self.error(field, format!("must be one of: {}", allowed.join(", ")));
+            }
+        }
+    }
+
+    /// ISO 3166-1 alpha-2, either case.
+    pub fn country_code(&mut self, field: &str, value: Option<&str>) {
+        if let Some(v) = value {
+            if v.len() != 2 || !v.chars().all(|c| c.is_ascii_alphabetic()) {
+                self.error(field, "must be a two-letter ISO 3166-1 country code");
+            }
+        }
+    }
+
+    pub fn rfc3339(&mut self, field: &str, value: Option<&str>) {
+        if let Some(v) = value {
+            if chrono::DateTime::parse_from_rfc3339(v).is_err() {
+                self.error(field, "must be an RFC 3339 timestamp");
+            }
+        }
+    }
+
+    pub fn range(&mut self, field: &str, value: Option<i64>, min: i64, max: i64) {
+        if let Some(v) = value {
+            if v < min || v > max {
+                self.error(field, format!("must be between {} and {}", min, max));
+            }
+        }
+    }
+
+    pub fn http_url(&mut self, field: &str, value: Option<&str>) {
+        if let Some(v) = value {
+            match reqwest::Url::parse(v) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => self.error(field, "must be an absolute http(s) URL"),
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), ApiError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.errors))
+        }
+    }
+}
+
+pub fn validate<T: Validate>(value: &T) -> Result<(), ApiError> {
+    let mut v = Validator::default();
+    value.validate(&mut v);
+    v.finish()
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self, v: &mut Validator) {
+        for (i, item) in self.iter().enumerate() {
+            let mut inner = Validator::default();
+            item.validate(&mut inner);
+            for e in inner.errors {
+                v.error(&format!("[{}].{}", i, e.field), e.message);
+            }
+        }
+    }
+}
+
+/// `web::Json<T>` that also runs `T::validate`.
+pub struct ValidJson<T>(pub T);
+
+impl<T> ValidJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidJson<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidJson<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let value = json.await?.into_inner();
+            validate(&value)?;
+            Ok(ValidJson(value))
+        })
+    }
+}
+
+/// `web::Query<T>` that also runs `T::validate`.
+pub struct ValidQuery<T>(pub T);
+
+impl<T> Deref for ValidQuery<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for ValidQuery<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let query = web::Query::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let value = query.await?.into_inner();
+            validate(&value)?;
+            Ok(ValidQuery(value))
+        })
+    }
+}
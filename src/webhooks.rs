@@ -0,0 +1,495 @@
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::net::IpAddr;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::crypto_shredder;
+use crate::log_formats::is_internal;
+use crate::models::EvidenceEventRow;
+
+pub const DISPATCH_BATCH: i64 = 50;
+const DISPATCH_CONCURRENCY: usize = 8;
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+const MAX_BACKOFF_SECS: i64 = 3600;
+const DNS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSubscriptionRow {
+    pub id: Uuid,
+    pub name: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub severities: Vec<String>,
+    pub source_systems: Vec<String>,
+    pub active: bool,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryRow {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeadLetterRow {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub outbox_id: Uuid,
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub failed_at: DateTime<Utc>,
+    pub redelivered_at: Option<DateTime<Utc>>,
+}
+
+fn env_i64(key: &str, default: i64) -> i64 {
+    std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Deliveries that fail this many times move to the dead-letter table: `WEBHOOK_MAX_ATTEMPTS` (default 8).
+fn max_attempts() -> i32 {
+    env_i64("WEBHOOK_MAX_ATTEMPTS", 8).max(1) as i32
+}
+
+/// Exponential backoff from `WEBHOOK_RETRY_BASE_SECS` (default 30), capped at one hour.
+fn backoff_secs(attempts: i32) -> i64 {
+    let base = env_i64("WEBHOOK_RETRY_BASE_SECS", 30).max(1);
+    let exp = (attempts - 1).clamp(0, 16) as u32;
+    base.saturating_mul(2i64.pow(exp)).min(MAX_BACKOFF_SECS)
+}
+
+/// Body delivered to subscribers for one evidence event.
+fn envelope(row: &EvidenceEventRow) -> serde_json::Value {
+    serde_json::json!({
+        "eventId": row.event_id,
+        "eventType": row.event_type,
+        "severity": row.severity,
+        "sourceSystem": row.source_system,
+        "correlationId": row.correlation_id,
+        "occurredAt": row.occurred_at.to_rfc3339(),
+        "recordedAt": row.recorded_at.to_rfc3339(),
+        "regulatoryTags": row.regulatory_tags,
+        "articles": row.articles,
+        "payloadHash": row.payload_hash,
+        "payload": row.payload,
+    })
+}
+
+/// Queue deliveries for every active subscription whose filters match the events, in the transaction
+/// that writes them: an event commits together with its deliveries or not at all.
+pub async fn enqueue(tx: &mut Transaction<'_, Postgres>, rows: &[EvidenceEventRow]) -> Result<(), String> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let event_ids: Vec<String> = rows.iter().map(|r| r.event_id.clone()).collect();
+    let event_types: Vec<String> = rows.iter().map(|r| r.event_type.clone()).collect();
+    let severities: Vec<String> = rows.iter().map(|r| r.severity.clone()).collect();
+    let sources: Vec<String> = rows.iter().map(|r| r.source_system.clone()).collect();
+    let payloads: Vec<serde_json::Value> = rows.iter().map(envelope).collect();
+
+    sqlx::query(
+        r#"INSERT INTO webhook_outbox (subscription_id, event_id, event_type, payload)
+           SELECT s.id, e.event_id, e.event_type, e.payload
+           FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[], $5::jsonb[])
+                AS e(event_id, event_type, severity, source_system, payload)
+           JOIN webhook_subscriptions s ON s.active
+            AND (cardinality(s.event_types) = 0 OR e.event_type = ANY(s.event_types))
+            AND (cardinality(s.severities) = 0 OR e.severity = ANY(s.severities))
+            AND (cardinality(s.source_systems) = 0 OR e.source_system = ANY(s.source_systems))"#
+    )
+    .bind(&event_ids)
+    .bind(&event_types)
+    .bind(&severities)
+    .bind(&sources)
+    .bind(&payloads)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to enqueue webhooks for {} event(s): {}", rows.len(), e))?;
+    Ok(())
+}
+
+/// `t=<unix seconds>,v1=<hex HMAC-SHA256(secret, "<t>.<body>")>`
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("t={},v1={:x}", timestamp, mac.finalize().into_bytes())
+}
+
+fn generate_secret() -> String {
+    let mut buf = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut buf);
+    format!("whsec_{}", buf.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+pub struct CreateSubscriptionParams {
+    pub name: String,
+    pub url: String,
+    pub secret: Option<String>,
+    pub event_types: Vec<String>,
+    pub severities: Vec<String>,
+    pub source_systems: Vec<String>,
+    pub created_by: Option<String>,
+}
+
+/// `WEBHOOK_ALLOW_PRIVATE_URLS=true` lets subscriptions reach loopback, private and link-local addresses
+/// (receivers on the same network); off by default so a subscription cannot probe internal services.
+fn allow_private_urls() -> bool {
+    std::env::var("WEBHOOK_ALLOW_PRIVATE_URLS").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+fn is_forbidden(ip: &IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*ip),
+        IpAddr::V4(_) => *ip,
+    };
+    is_internal(&ip) || ip.is_multicast()
+}
+
+/// An http(s) URL whose host resolves only to public addresses. Checked when a subscription is saved
+/// and again before every delivery, since DNS can change in between.
+pub async fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "https" | "http") {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+    let host = parsed.host_str().ok_or("URL has no host")?.trim_start_matches('[').trim_end_matches(']');
+    if allow_private_urls() {
+        return Ok(());
+    }
+    let port = parsed.port_or_known_default().unwrap_or(443);
+    let addrs: Vec<IpAddr> = match tokio::time::timeout(DNS_TIMEOUT, tokio::net::lookup_host((host, port))).await {
+        Ok(Ok(addrs)) => addrs.map(|a| a.ip()).collect(),
+        Ok(Err(e)) => return Err(format!("Cannot resolve {}: {}", host, e)),
+        Err(_) => return Err(format!("Timed out resolving {}", host)),
+    };
+    match addrs.iter().find(|ip| is_forbidden(ip)) {
+        Some(ip) => Err(format!("{} resolves to non-public address {}", host, ip)),
+        None if addrs.is_empty() => Err(format!("{} has no address", host)),
+        None => Ok(()),
+    }
+}
+
+/// Wrap plaintext secrets stored by earlier versions and clear them. Run at startup.
+pub async fn wrap_plaintext_secrets(pool: &PgPool) -> Result<usize, String> {
+    let plain: Vec<(Uuid, String)> = sqlx::query_as(
+        "SELECT id, secret FROM webhook_subscriptions WHERE secret IS NOT NULL AND wrapped_secret IS NULL"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    for (id, secret) in &plain {
+        let wrapped = crypto_shredder::wrap_with_master_key(secret.as_bytes())?;
+        sqlx::query("UPDATE webhook_subscriptions SET wrapped_secret = $2, secret = NULL WHERE id = $1")
+            .bind(id)
+            .bind(&wrapped)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(plain.len())
+}
+
+/// Create a subscription; returns it with the signing secret, which is only ever shown here.
+pub async fn create_subscription(
+    pool: &PgPool,
+    params: CreateSubscriptionParams,
+) -> Result<(WebhookSubscriptionRow, String), String> {
+    let secret = params.secret.filter(|s| !s.is_empty()).unwrap_or_else(generate_secret);
+    let wrapped = crypto_shredder::wrap_with_master_key(secret.as_bytes())?;
+    let row = sqlx::query_as::<_, WebhookSubscriptionRow>(
+        r#"INSERT INTO webhook_subscriptions (name, url, wrapped_secret, event_types, severities, source_systems, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7)
+           RETURNING *"#
+    )
+    .bind(&params.name)
+    .bind(&params.url)
+    .bind(&wrapped)
+    .bind(&params.event_types)
+    .bind(&params.severities)
+    .bind(&params.source_systems)
+    .bind(&params.created_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to create webhook subscription: {}", e))?;
+    Ok((row, secret))
+}
+
+pub async fn list_subscriptions(pool: &PgPool) -> Result<Vec<WebhookSubscriptionRow>, String> {
+    sqlx::query_as::<_, WebhookSubscriptionRow>("SELECT * FROM webhook_subscriptions ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+pub struct UpdateSubscriptionParams {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub severities: Option<Vec<String>>,
+    pub source_systems: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+pub async fn update_subscription(
+    pool: &PgPool,
+    id: Uuid,
+    params: UpdateSubscriptionParams,
+) -> Result<Option<WebhookSubscriptionRow>, String> {
+    sqlx::query_as::<_, WebhookSubscriptionRow>(
+        r#"UPDATE webhook_subscriptions SET
+             url = COALESCE($2, url),
+             event_types = COALESCE($3, event_types),
+             severities = COALESCE($4, severities),
+             source_systems = COALESCE($5, source_systems),
+             active = COALESCE($6, active),
+             updated_at = NOW()
+           WHERE id = $1
+           RETURNING *"#
+    )
+    .bind(id)
+    .bind(&params.url)
+    .bind(&params.event_types)
+    .bind(&params.severities)
+    .bind(&params.source_systems)
+    .bind(params.active)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub async fn delete_subscription(pool: &PgPool, id: Uuid) -> Result<bool, String> {
+    let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_deliveries(
+    pool: &PgPool,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<Vec<WebhookDeliveryRow>, String> {
+    sqlx::query_as::<_, WebhookDeliveryRow>(
+        r#"SELECT * FROM webhook_outbox
+           WHERE ($1::text IS NULL OR status = $1)
+           ORDER BY created_at DESC LIMIT $2"#
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+pub async fn list_dead_letters(pool: &PgPool, limit: i64) -> Result<Vec<WebhookDeadLetterRow>, String> {
+    sqlx::query_as::<_, WebhookDeadLetterRow>(
+        "SELECT * FROM webhook_dead_letters ORDER BY failed_at DESC LIMIT $1"
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Put a delivery back in the queue for an immediate attempt, whatever its current state.
+pub async fn redeliver(pool: &PgPool, outbox_id: Uuid) -> Result<bool, String> {
+    let result = sqlx::query(
+        r#"UPDATE webhook_outbox
+           SET status = 'pending', attempts = 0, next_attempt_at = NOW(), delivered_at = NULL, last_error = NULL
+           WHERE id = $1"#
+    )
+    .bind(outbox_id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Requeue a dead letter as a fresh delivery. Returns the new outbox ID.
+pub async fn redeliver_dead_letter(pool: &PgPool, dead_letter_id: Uuid) -> Result<Option<Uuid>, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let outbox_id: Option<Uuid> = sqlx::query_scalar(
+        r#"INSERT INTO webhook_outbox (subscription_id, event_id, event_type, payload)
+           SELECT subscription_id, event_id, event_type, payload FROM webhook_dead_letters WHERE id = $1
+           RETURNING id"#
+    )
+    .bind(dead_letter_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if outbox_id.is_some() {
+        sqlx::query("UPDATE webhook_dead_letters SET redelivered_at = NOW() WHERE id = $1")
+            .bind(dead_letter_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(outbox_id)
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedDelivery {
+    id: Uuid,
+    event_id: String,
+    event_type: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    url: String,
+    wrapped_secret: Option<Vec<u8>>,
+}
+
+/// Claim due deliveries (leased for five minutes so a crashed dispatcher's rows are retried) and send them.
+pub async fn dispatch_due(pool: &PgPool, http: &reqwest::Client) -> Result<usize, String> {
+    let claimed: Vec<ClaimedDelivery> = sqlx::query_as(
+        r#"WITH due AS (
+               SELECT o.id FROM webhook_outbox o
+               JOIN webhook_subscriptions s ON s.id = o.subscription_id
+               WHERE s.active AND o.status IN ('pending', 'delivering') AND o.next_attempt_at <= NOW()
+               ORDER BY o.next_attempt_at
+               LIMIT $1
+               FOR UPDATE OF o SKIP LOCKED
+           )
+           UPDATE webhook_outbox o
+           SET status = 'delivering', next_attempt_at = NOW() + INTERVAL '5 minutes'
+           FROM due, webhook_subscriptions s
+           WHERE o.id = due.id AND s.id = o.subscription_id
+           RETURNING o.id, o.event_id, o.event_type, o.payload, o.attempts, s.url, s.wrapped_secret"#
+    )
+    .bind(DISPATCH_BATCH)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to claim webhook deliveries: {}", e))?;
+
+    let count = claimed.len();
+    stream::iter(claimed)
+        .for_each_concurrent(DISPATCH_CONCURRENCY, |delivery| async move {
+            let outcome = send(http, &delivery).await;
+            if let Err(e) = record_attempt(pool, &delivery, outcome).await {
+                log::error!("Failed to record webhook delivery {}: {}", delivery.id, e);
+            }
+        })
+        .await;
+    Ok(count)
+}
+
+async fn send(http: &reqwest::Client, delivery: &ClaimedDelivery) -> Result<u16, (Option<u16>, String)> {
+    validate_url(&delivery.url).await.map_err(|e| (None, e))?;
+    let secret = delivery.wrapped_secret.as_deref()
+        .ok_or_else(|| "Subscription has no signing secret".to_string())
+        .and_then(crypto_shredder::unwrap_with_master_key)
+        .and_then(|s| String::from_utf8(s).map_err(|_| "Signing secret is not UTF-8".to_string()))
+        .map_err(|e| (None, e))?;
+    let body = serde_json::to_string(&delivery.payload).unwrap_or_default();
+    let timestamp = Utc::now().timestamp();
+    let resp = http
+        .post(&delivery.url)
+        .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+        .header("Content-Type", "application/json")
+        .header("X-Veridion-Event", &delivery.event_type)
+        .header("X-Veridion-Event-Id", &delivery.event_id)
+        .header("X-Veridion-Delivery", delivery.id.to_string())
+        .header("X-Veridion-Signature", sign(&secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = resp.status().as_u16();
+    if resp.status().is_success() {
+        Ok(status)
+    } else {
+        Err((Some(status), format!("HTTP {}", status)))
+    }
+}
+
+async fn record_attempt(
+    pool: &PgPool,
+    delivery: &ClaimedDelivery,
+    outcome: Result<u16, (Option<u16>, String)>,
+) -> Result<(), String> {
+    let attempts = delivery.attempts + 1;
+    match outcome {
+        Ok(status) => {
+            sqlx::query(
+                r#"UPDATE webhook_outbox
+                   SET status = 'delivered', attempts = $2, last_status_code = $3, last_error = NULL, delivered_at = NOW()
+                   WHERE id = $1"#
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(status as i32)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        Err((status, error)) if attempts >= max_attempts() => {
+            log::warn!("Webhook delivery {} dead-lettered after {} attempts: {}", delivery.id, attempts, error);
+            let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+            sqlx::query(
+                r#"INSERT INTO webhook_dead_letters
+                     (subscription_id, outbox_id, event_id, event_type, payload, attempts, last_status_code, last_error)
+                   SELECT subscription_id, id, event_id, event_type, payload, $2, $3, $4
+                   FROM webhook_outbox WHERE id = $1"#
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(status.map(i32::from))
+            .bind(&error)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            sqlx::query("DELETE FROM webhook_outbox WHERE id = $1")
+                .bind(delivery.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            tx.commit().await.map_err(|e| e.to_string())?;
+        }
+        Err((status, error)) => {
+            sqlx::query(
+                r#"UPDATE webhook_outbox
+                   SET status = 'pending', attempts = $2, last_status_code = $3, last_error = $4,
+                       next_attempt_at = NOW() + make_interval(secs => $5)
+                   WHERE id = $1"#
+            )
+            .bind(delivery.id)
+            .bind(attempts)
+            .bind(status.map(i32::from))
+            .bind(&error)
+            .bind(backoff_secs(attempts) as f64)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}